@@ -5,6 +5,7 @@ loom_mcp_wrapper.py
 MCP stdio wrapper around `loom proxy` that:
 1) Adds curated prompt "recipes" (MCP Prompts) for Zed Agent UX.
 2) Polls `tools/list` and emits `notifications/tools/list_changed` when the tool set changes.
+3) Exposes Loom status, memory entries, and exported configs as MCP Resources.
 
 This is intentionally dependency-free (stdlib only) and should run anywhere Python 3 is available.
 """
@@ -180,6 +181,12 @@ BASE_RESOURCES = [
         "description": "Sync status (from `loom sync status`).",
         "mimeType": "text/plain",
     },
+    {
+        "uri": f"{RESOURCE_PREFIX}config",
+        "name": "Loom Config",
+        "description": "Effective Loom configuration (from `loom config show`).",
+        "mimeType": "text/plain",
+    },
     {
         "uri": f"{RESOURCE_PREFIX}wrapper-stderr",
         "name": "Loom Wrapper Stderr",
@@ -204,6 +211,102 @@ OPTIONAL_RESOURCES = [
 ]
 
 
+MEMORY_URI_PREFIX = f"{RESOURCE_PREFIX}memory/"
+CONFIG_URI_PREFIX = f"{RESOURCE_PREFIX}config/"
+
+# Platforms `loom config export` can render (matches /loom-sync targets).
+CONFIG_EXPORT_PLATFORMS = ["zed", "vscode", "claude", "gemini", "codex", "antigravity", "kilocode"]
+
+
+def _config_resources() -> list[Dict[str, Any]]:
+    return [
+        {
+            "uri": f"{CONFIG_URI_PREFIX}{platform}",
+            "name": f"Loom Exported Config: {platform}",
+            "description": f"MCP config Loom generates for {platform} (from `loom config export {platform}`).",
+            "mimeType": "text/plain",
+        }
+        for platform in CONFIG_EXPORT_PLATFORMS
+    ]
+
+
+def _tool_call_json(ns: argparse.Namespace, tool: str, payload: Dict[str, Any], timeout_secs: int) -> Any:
+    """Run `loom tools call <tool> -- <json>` and decode its JSON result (best-effort)."""
+    out = subprocess.run(
+        [ns.loom, "tools", "call", tool, "--", json.dumps(payload)],
+        text=True,
+        capture_output=True,
+        timeout=timeout_secs,
+    )
+    data: Any = json.loads(out.stdout or "null")
+    # Unwrap MCP-style results: {"content": [{"type": "text", "text": "<json>"}]}.
+    if isinstance(data, dict) and isinstance(data.get("content"), list):
+        for block in data["content"]:
+            if isinstance(block, dict) and block.get("type") == "text":
+                try:
+                    return json.loads(block.get("text") or "")
+                except ValueError:
+                    return block.get("text")
+    return data
+
+
+def _memory_resources(ns: argparse.Namespace, limit: int) -> list[Dict[str, Any]]:
+    """List recent agent memory entries as resources (empty on any failure)."""
+    if limit <= 0:
+        return []
+    try:
+        data = _tool_call_json(ns, "agent_memory_list", {"limit": limit}, timeout_secs=5)
+    except (subprocess.TimeoutExpired, ValueError, OSError) as e:
+        _eprint(f"memory resources unavailable: {e}")
+        return []
+    entries = data.get("entries") if isinstance(data, dict) else data
+    if not isinstance(entries, list):
+        return []
+
+    out: list[Dict[str, Any]] = []
+    for entry in entries[:limit]:
+        if not isinstance(entry, dict):
+            continue
+        entry_id = entry.get("id")
+        if not isinstance(entry_id, (str, int)):
+            continue
+        title = entry.get("title") or entry.get("key") or entry.get("content") or str(entry_id)
+        title = str(title).strip().splitlines()[0][:80] if str(title).strip() else str(entry_id)
+        namespace = entry.get("namespace")
+        out.append(
+            {
+                "uri": f"{MEMORY_URI_PREFIX}{entry_id}",
+                "name": f"Loom Memory: {title}",
+                "description": f"Agent memory entry {entry_id}"
+                + (f" in namespace {namespace}" if isinstance(namespace, str) and namespace else ""),
+                "mimeType": "text/markdown",
+            }
+        )
+    return out
+
+
+def _read_memory(ns: argparse.Namespace, entry_id: str) -> str:
+    data = _tool_call_json(ns, "agent_memory_get", {"id": entry_id}, timeout_secs=10)
+    if isinstance(data, dict):
+        content = data.get("content") or data.get("text")
+        if isinstance(content, str):
+            meta = [f"- {k}: {data[k]}" for k in ("namespace", "created_at", "tags") if data.get(k)]
+            header = f"# Memory {entry_id}\n\n" + ("\n".join(meta) + "\n\n" if meta else "")
+            return header + content
+        return json.dumps(data, indent=2)
+    if isinstance(data, str):
+        return data
+    return f"Memory entry {entry_id} not found."
+
+
+def _resource_result(rid: Any, uri: str, text: str, mime: str = "text/plain") -> Dict[str, Any]:
+    return {
+        "jsonrpc": "2.0",
+        "id": rid,
+        "result": {"contents": [{"uri": uri, "mimeType": mime, "text": text}]},
+    }
+
+
 def _prompt_list() -> Dict[str, Any]:
     return {
         "prompts": [
@@ -249,10 +352,14 @@ def _prompt_get(name: str, arguments: Optional[Dict[str, Any]] = None) -> Dict[s
             }
     raise KeyError(name)
 
-def _resources_list(include_diagnostics: bool) -> Dict[str, Any]:
+def _resources_list(
+    include_diagnostics: bool, dynamic: Optional[list[Dict[str, Any]]] = None
+) -> Dict[str, Any]:
     resources = list(BASE_RESOURCES)
+    resources.extend(_config_resources())
     if include_diagnostics:
         resources.extend(OPTIONAL_RESOURCES)
+    resources.extend(dynamic or [])
     return {"resources": resources}
 
 
@@ -367,6 +474,12 @@ def main() -> int:
         default=None,
         help="Optional path to a JSON file containing additional prompt recipes.",
     )
+    ap.add_argument(
+        "--list-prompt-recipes",
+        action="store_true",
+        default=False,
+        help="Print the prompt recipes (including templates) as JSON and exit.",
+    )
     ap.add_argument(
         "--disable-zed-resources",
         action="store_true",
@@ -379,12 +492,19 @@ def main() -> int:
         default=False,
         help="Expose an additional diagnostics resource (runs `loom check`).",
     )
+    ap.add_argument(
+        "--resources-memory-limit",
+        type=int,
+        default=20,
+        help="Expose up to N recent agent memory entries as resources; 0 disables.",
+    )
     ap.add_argument("child_args", nargs=argparse.REMAINDER)
     ns = ap.parse_args()
 
     enable_prompts = not ns.disable_prompt_recipes
     enable_resources = not ns.disable_zed_resources
     include_diagnostics = bool(ns.resources_include_diagnostics)
+    memory_limit = max(0, int(ns.resources_memory_limit or 0))
 
     # Load optional additional prompt recipes.
     if enable_prompts and isinstance(ns.prompts_recipes_file, str) and ns.prompts_recipes_file.strip():
@@ -394,6 +514,11 @@ def main() -> int:
         except Exception as e:
             _eprint(f"failed to load prompt recipes file {ns.prompts_recipes_file!r}: {e}")
 
+    # Used by the `/loom-prompts` slash command; never starts the child.
+    if ns.list_prompt_recipes:
+        print(json.dumps({"prompts": PROMPT_RECIPES if enable_prompts else []}))
+        return 0
+
     child_cmd = [ns.loom]
     if ns.child_args and ns.child_args[0] == "--":
         child_cmd.extend(ns.child_args[1:])
@@ -406,6 +531,8 @@ def main() -> int:
     intercept_prompts_list_ids: set[Any] = set()
     intercept_tools_list_ids: set[Any] = set()
     intercept_resources_list_ids: set[Any] = set()
+    # Memory resources fetched when a resources/list request arrives, merged into its response.
+    pending_dynamic_resources: Dict[Any, list[Dict[str, Any]]] = {}
     initialize_id: Any = None
 
     # Tool set change detection state.
@@ -485,15 +612,16 @@ def main() -> int:
 
             if msg_id in intercept_resources_list_ids:
                 intercept_resources_list_ids.discard(msg_id)
+                dynamic = pending_dynamic_resources.pop(msg_id, [])
                 if "error" in msg:
                     msg.pop("error", None)
-                    msg["result"] = _resources_list(include_diagnostics)
+                    msg["result"] = _resources_list(include_diagnostics, dynamic)
                     forward_to_client(msg)
                     continue
 
                 # Merge child resources with ours (ours first).
                 result = msg.get("result") if isinstance(msg.get("result"), dict) else {}
-                merged = _resources_list(include_diagnostics)
+                merged = _resources_list(include_diagnostics, dynamic)
                 child_resources = []
                 if isinstance(result, dict):
                     child_resources = result.get("resources") or []
@@ -616,6 +744,7 @@ def main() -> int:
                 initialize_id = msg.get("id")
 
             if enable_resources and method == "resources/list":
+                pending_dynamic_resources[msg.get("id")] = _memory_resources(ns, memory_limit)
                 intercept_resources_list_ids.add(msg.get("id"))
                 child.send(msg)
                 continue
@@ -773,6 +902,23 @@ def main() -> int:
                                         },
                                     }
                                 )
+                        elif uri == f"{RESOURCE_PREFIX}config":
+                            text = _truncate_lines(
+                                _run_loom(ns, ["config", "show"], timeout_secs=10)
+                            )
+                            forward_to_client(_resource_result(rid, uri, text))
+                        elif uri.startswith(CONFIG_URI_PREFIX):
+                            platform = uri[len(CONFIG_URI_PREFIX):]
+                            if platform not in CONFIG_EXPORT_PLATFORMS:
+                                raise KeyError(uri)
+                            text = _truncate_lines(
+                                _run_loom(ns, ["config", "export", platform], timeout_secs=20)
+                            )
+                            forward_to_client(_resource_result(rid, uri, text))
+                        elif uri.startswith(MEMORY_URI_PREFIX):
+                            entry_id = uri[len(MEMORY_URI_PREFIX):]
+                            text = _truncate_lines(_read_memory(ns, entry_id))
+                            forward_to_client(_resource_result(rid, uri, text, "text/markdown"))
                         elif uri == f"{RESOURCE_PREFIX}wrapper-stderr":
                             with stderr_lock:
                                 lines = list(stderr_tail)
@@ -801,11 +947,12 @@ def main() -> int:
                                 "This process wraps `loom proxy` over stdio and adds:\n"
                                 "- Prompt recipes (MCP Prompts)\n"
                                 "- Tool hot reload notifications (tools/list_changed)\n"
-                                "- Zed-friendly resources for 'Add Context'\n\n"
+                                "- Zed-friendly resources for 'Add Context' (status, memory, exported configs)\n\n"
                                 "Wrapper options:\n"
                                 f"- prompts enabled: {bool(enable_prompts)}\n"
                                 f"- resources enabled: {bool(enable_resources)}\n"
                                 f"- diagnostics resource enabled: {bool(include_diagnostics)}\n"
+                                f"- memory resources limit: {memory_limit}\n"
                             )
                             forward_to_client(
                                 {
@@ -831,6 +978,7 @@ def main() -> int:
                                     "prompts_enabled": bool(enable_prompts),
                                     "resources_enabled": bool(enable_resources),
                                     "include_diagnostics": bool(include_diagnostics),
+                                    "memory_limit": memory_limit,
                                     "prompts_recipes_file": ns.prompts_recipes_file,
                                     "stderr_tail_lines": int(len(stderr_tail)),
                                 }
@@ -866,6 +1014,14 @@ def main() -> int:
                                 "error": {"code": -32000, "message": f"timeout reading resource: {uri}"},
                             }
                         )
+                    except (KeyError, ValueError, OSError) as e:
+                        forward_to_client(
+                            {
+                                "jsonrpc": "2.0",
+                                "id": rid,
+                                "error": {"code": -32602, "message": f"failed to read resource {uri}: {e}"},
+                            }
+                        )
                     continue
 
             # Default: proxy through to loom.
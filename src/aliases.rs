@@ -0,0 +1,228 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+use zed_extension_api as zed;
+
+use crate::args::redact_args;
+
+/// Aliases saved with `/loom-alias <name> = <command>`, relative to the extension work dir.
+///
+/// Zed doesn't let extensions write user settings, so these live next to the managed installs.
+const ALIASES_FILE: &str = "aliases.json";
+
+/// `/loom-alias` sub-commands, which can't double as alias names.
+const RESERVED: &[&str] = &["list", "remove"];
+
+/// Where an alias was defined. `settings.commands.aliases` wins over a saved alias of the same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AliasSource {
+    Settings,
+    Saved,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Alias {
+    pub(crate) name: String,
+    /// Command and leading args, e.g. `status verbose`.
+    pub(crate) target: String,
+    pub(crate) source: AliasSource,
+}
+
+/// Aliases saved in the work dir (empty when none were saved or the file is unreadable).
+pub(crate) fn load_saved() -> BTreeMap<String, String> {
+    fs::read_to_string(ALIASES_FILE)
+        .ok()
+        .and_then(|text| zed::serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save(aliases: &BTreeMap<String, String>) -> Result<(), String> {
+    let text = zed::serde_json::to_string_pretty(aliases).map_err(|e| e.to_string())?;
+    fs::write(ALIASES_FILE, text).map_err(|e| format!("failed to save aliases: {}", e))
+}
+
+/// Settings and saved aliases together, sorted by name.
+pub(crate) fn merged(
+    settings: &HashMap<String, String>,
+    saved: &BTreeMap<String, String>,
+) -> Vec<Alias> {
+    let mut all: BTreeMap<String, Alias> = BTreeMap::new();
+    let sources = [
+        (AliasSource::Saved, saved.iter().collect::<Vec<_>>()),
+        (AliasSource::Settings, settings.iter().collect()),
+    ];
+    for (source, entries) in sources {
+        for (name, target) in entries {
+            let name = normalize_name(name);
+            all.insert(
+                name.clone(),
+                Alias {
+                    name,
+                    target: target.trim().to_string(),
+                    source,
+                },
+            );
+        }
+    }
+    all.into_values().collect()
+}
+
+/// The alias `/loom-alias <name> [args...]` runs, plus the extra args to append.
+///
+/// Definitions (`st = status`) and sub-commands are not invocations.
+pub(crate) fn invocation<'a, 'b>(
+    args: &'b [String],
+    aliases: &'a [Alias],
+) -> Option<(&'a Alias, &'b [String])> {
+    let (name, rest) = args.split_first()?;
+    if name.contains('=') || rest.first().is_some_and(|a| a.starts_with('=')) {
+        return None;
+    }
+    let name = normalize_name(name);
+    aliases
+        .iter()
+        .find(|a| a.name == name)
+        .map(|alias| (alias, rest))
+}
+
+/// Parse `st = status verbose` (spacing around `=` optional) into a name and target.
+pub(crate) fn parse_definition(args: &[String]) -> Result<(String, String), String> {
+    let joined = args.join(" ");
+    let (name, target) = joined
+        .split_once('=')
+        .ok_or("usage: /loom-alias <name> = <command> [args...]")?;
+    let name = normalize_name(name.trim());
+    let target = target.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "invalid alias name {:?}: use letters, digits, `-`, and `_`",
+            name
+        ));
+    }
+    if RESERVED.contains(&name.as_str()) {
+        return Err(format!(
+            "`{}` is a /loom-alias sub-command and can't be an alias name",
+            name
+        ));
+    }
+    if target.is_empty() {
+        return Err("usage: /loom-alias <name> = <command> [args...]".into());
+    }
+    let (command, args) = expand(target, &[])?;
+    let mut words = vec![command.trim_start_matches("loom-").to_string()];
+    words.extend(args);
+    Ok((name, words.join(" ")))
+}
+
+/// Slash command name and args an alias target runs, with `extra` appended.
+///
+/// Targets may be written `status`, `loom-status`, or `/loom-status`.
+pub(crate) fn expand(target: &str, extra: &[String]) -> Result<(String, Vec<String>), String> {
+    let mut words = target.split_whitespace();
+    let command = words
+        .next()
+        .map(normalize_name)
+        .ok_or("alias target is empty")?;
+    if command == "alias" {
+        return Err("an alias must run a command other than /loom-alias".into());
+    }
+    let args = words
+        .map(str::to_string)
+        .chain(extra.iter().cloned())
+        .collect();
+    Ok((format!("loom-{}", command), args))
+}
+
+/// `/loom-alias` args for logs and history, with secret values masked as in the command the
+/// alias runs (`/loom-alias ss KEY value` where `ss = secrets set`).
+pub(crate) fn redact_invocation(args: &[String], aliases: &[Alias]) -> Vec<String> {
+    let Some((alias, rest)) = invocation(args, aliases) else {
+        return args.to_vec();
+    };
+    let Ok((target, target_args)) = expand(&alias.target, rest) else {
+        return args.to_vec();
+    };
+    let redacted = redact_args(&target, &target_args);
+    if redacted == target_args {
+        return args.to_vec();
+    }
+    // The alias's own words come first; what's left after them was typed.
+    let typed = target_args.len() - rest.len();
+    let mut logged = vec![args[0].clone()];
+    logged.extend(redacted.into_iter().skip(typed));
+    logged
+}
+
+fn normalize_name(name: &str) -> String {
+    let name = name.trim().trim_start_matches('/');
+    name.strip_prefix("loom-").unwrap_or(name).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn definitions_and_invocations() {
+        assert_eq!(
+            parse_definition(&args("st = /loom-status verbose")),
+            Ok(("st".into(), "status verbose".into()))
+        );
+        assert_eq!(
+            parse_definition(&args("mem=memory")),
+            Ok(("mem".into(), "memory".into()))
+        );
+        assert!(parse_definition(&args("list = status")).is_err());
+        assert!(parse_definition(&args("a b = status")).is_err());
+        assert!(parse_definition(&args("x = alias list")).is_err());
+        assert!(parse_definition(&args("st")).is_err());
+
+        let saved = BTreeMap::from([
+            ("st".to_string(), "status".to_string()),
+            ("h".to_string(), "health".to_string()),
+        ]);
+        let settings = HashMap::from([("st".to_string(), "status verbose".to_string())]);
+        let all = merged(&settings, &saved);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].source, AliasSource::Settings);
+
+        let typed = args("st --raw");
+        let (alias, rest) = invocation(&typed, &all).unwrap();
+        assert_eq!(
+            expand(&alias.target, rest),
+            Ok(("loom-status".into(), args("verbose --raw")))
+        );
+        assert!(invocation(&args("st = sync"), &all).is_none());
+        assert!(invocation(&args("nope"), &all).is_none());
+    }
+
+    #[test]
+    fn alias_to_secrets_set_is_redacted() {
+        let saved = BTreeMap::from([
+            ("ss".to_string(), "secrets set".to_string()),
+            ("gh".to_string(), "secrets set GITHUB_TOKEN".to_string()),
+        ]);
+        let all = merged(&HashMap::new(), &saved);
+        assert_eq!(
+            redact_invocation(&args("ss API_KEY hunter2"), &all),
+            args("ss API_KEY [redacted]")
+        );
+        assert_eq!(
+            redact_invocation(&args("gh ghp_abc"), &all),
+            args("gh [redacted]")
+        );
+        assert_eq!(
+            redact_invocation(&args("ss = secrets list"), &all),
+            args("ss = secrets list")
+        );
+        assert_eq!(redact_invocation(&args("ss"), &all), args("ss"));
+    }
+}
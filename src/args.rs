@@ -0,0 +1,417 @@
+use zed_extension_api::{
+    self as zed,
+    serde_json::{Map, Value},
+};
+
+/// Re-tokenize slash command arguments shell-style.
+///
+/// Zed splits slash command input on whitespace before handing it to us, which mangles quoted
+/// phrases (`"fix the flaky test"`) and inline JSON (`{"a": "b c"}`). We rejoin the pieces and
+/// split again, honoring double quotes and backslash escapes, and keeping `{...}` / `[...]`
+/// literals intact as a single token.
+pub(crate) fn tokenize_args(args: &[String]) -> Vec<String> {
+    tokenize(&args.join(" "))
+}
+
+/// Stands in for secret values wherever a command line is logged or kept.
+pub(crate) const REDACTED: &str = "[redacted]";
+
+/// `args` with secret values masked, for logs, history, and error reports.
+///
+/// Only `/loom-secrets set <KEY> <value>` carries one; other commands come back unchanged.
+pub(crate) fn redact_args(command: &str, args: &[String]) -> Vec<String> {
+    match (command, args) {
+        ("loom-secrets", [sub, key, value, ..]) if sub == "set" && !value.is_empty() => {
+            vec![sub.clone(), key.clone(), REDACTED.to_string()]
+        }
+        _ => args.to_vec(),
+    }
+}
+
+/// Split a single line into tokens (see [`tokenize_args`]).
+pub(crate) fn tokenize(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if chars[i] == '{' || chars[i] == '[' {
+            let end = json_literal_end(&chars, i);
+            tokens.push(chars[i..end].iter().collect());
+            i = end;
+            continue;
+        }
+
+        let mut token = String::new();
+        let mut in_quotes = false;
+        while i < chars.len() {
+            let c = chars[i];
+            match c {
+                '\\' if i + 1 < chars.len() => {
+                    token.push(chars[i + 1]);
+                    i += 2;
+                    continue;
+                }
+                '"' => in_quotes = !in_quotes,
+                c if c.is_whitespace() && !in_quotes => break,
+                c => token.push(c),
+            }
+            i += 1;
+        }
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+/// Return the index just past the balanced JSON object/array starting at `start`.
+///
+/// Strings may use either quote style (see [`normalize_json`]). Unbalanced input consumes the
+/// rest of the line so nothing is silently dropped.
+fn json_literal_end(chars: &[char], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut in_string: Option<char> = None;
+    let mut i = start;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if let Some(quote) = in_string {
+            match c {
+                '\\' => i += 1,
+                c if c == quote => in_string = None,
+                _ => {}
+            }
+        } else {
+            match c {
+                '"' | '\'' => in_string = Some(c),
+                '{' | '[' => depth += 1,
+                '}' | ']' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+
+    chars.len()
+}
+
+/// Parse relaxed JSON (JSON5-style) and re-serialize it as strict JSON.
+///
+/// Accepts single-quoted strings, unquoted object keys, trailing commas, and `//` / `/* */`
+/// comments, which is what people actually type into a chat argument.
+pub(crate) fn normalize_json(input: &str) -> Result<String, String> {
+    let mut parser = RelaxedJson {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_trivia();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(value.to_string())
+}
+
+struct RelaxedJson {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl RelaxedJson {
+    fn error(&self, msg: &str) -> String {
+        format!("{} at column {}", msg, self.pos + 1)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            match (self.peek(), self.chars.get(self.pos + 1)) {
+                (Some(c), _) if c.is_whitespace() => self.pos += 1,
+                (Some('/'), Some('/')) => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    self.pos += 2;
+                    while self.pos < self.chars.len()
+                        && !(self.chars[self.pos] == '*'
+                            && self.chars.get(self.pos + 1) == Some(&'/'))
+                    {
+                        self.pos += 1;
+                    }
+                    self.pos = (self.pos + 2).min(self.chars.len());
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_trivia();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some(q @ ('"' | '\'')) => self.string(q).map(Value::String),
+            Some(c) if c == '-' || c == '+' || c == '.' || c.is_ascii_digit() => self.number(),
+            Some(c) if is_ident_char(c) => match self.ident().as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "null" => Ok(Value::Null),
+                other => Err(self.error(&format!(
+                    "unquoted value `{}` (wrap strings in quotes)",
+                    other
+                ))),
+            },
+            Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut map = Map::new();
+        loop {
+            self.skip_trivia();
+            let key = match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(map));
+                }
+                Some(q @ ('"' | '\'')) => self.string(q)?,
+                Some(c) if is_ident_char(c) => self.ident(),
+                Some(c) => return Err(self.error(&format!("expected a key, found `{}`", c))),
+                None => return Err(self.error("unterminated object")),
+            };
+            self.skip_trivia();
+            if self.peek() != Some(':') {
+                return Err(self.error(&format!("expected `:` after key `{}`", key)));
+            }
+            self.pos += 1;
+            let value = self.value()?;
+            map.insert(key, value);
+            self.skip_trivia();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {}
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            if self.peek().is_none() {
+                return Err(self.error("unterminated array"));
+            }
+            items.push(self.value()?);
+            self.skip_trivia();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                c if c == quote => return Ok(out),
+                '\\' => {
+                    let Some(esc) = self.peek() else { break };
+                    self.pos += 1;
+                    match esc {
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        'b' => out.push('\u{8}'),
+                        'f' => out.push('\u{c}'),
+                        'u' => {
+                            let hex: String = self.chars.iter().skip(self.pos).take(4).collect();
+                            let code = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid \\u escape"))?;
+                            out.push(code);
+                            self.pos += 4;
+                        }
+                        other => out.push(other),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        {
+            self.pos += 1;
+        }
+        let raw: String = self.chars[start..self.pos].iter().collect();
+        let mut text = raw.trim_start_matches('+').to_string();
+        if text.starts_with('.') {
+            text.insert(0, '0');
+        } else if text.starts_with("-.") {
+            text.insert(1, '0');
+        }
+        if text.ends_with('.') {
+            text.push('0');
+        }
+        zed::serde_json::from_str::<Value>(&text)
+            .ok()
+            .filter(Value::is_number)
+            .ok_or_else(|| format!("invalid number `{}` at column {}", raw, start + 1))
+    }
+
+    fn ident(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_ident_char) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(args: &str) -> Vec<String> {
+        args.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn secret_values_are_redacted() {
+        assert_eq!(
+            redact_args("loom-secrets", &split("set API_KEY hunter2 --raw")),
+            split("set API_KEY [redacted]")
+        );
+        assert_eq!(
+            redact_args("loom-secrets", &split("set API_KEY")),
+            split("set API_KEY")
+        );
+        assert_eq!(
+            redact_args("loom-recall", &split("set a b")),
+            split("set a b")
+        );
+    }
+
+    #[test]
+    fn plain_words() {
+        assert_eq!(tokenize("list all"), vec!["list", "all"]);
+        assert!(tokenize("   ").is_empty());
+    }
+
+    #[test]
+    fn quoted_phrase_is_one_token() {
+        let tokens = tokenize_args(&split(r#"add "fix the flaky test""#));
+        assert_eq!(tokens, vec!["add", "fix the flaky test"]);
+    }
+
+    #[test]
+    fn escapes_are_honored() {
+        assert_eq!(
+            tokenize(r#"say \"hi\" a\ b"#),
+            vec![r#"say"#, r#""hi""#, "a b"]
+        );
+    }
+
+    #[test]
+    fn empty_quotes_produce_empty_token() {
+        assert_eq!(tokenize(r#"set "" x"#), vec!["set", "", "x"]);
+    }
+
+    #[test]
+    fn json_object_survives_intact() {
+        let tokens = tokenize_args(&split(r#"tool {"a": "b c"}"#));
+        assert_eq!(tokens, vec!["tool", r#"{"a": "b c"}"#]);
+    }
+
+    #[test]
+    fn nested_json_with_brackets_in_strings() {
+        let tokens = tokenize(r#"tool {"a": ["x", "}"], "b": {"c": 1}} tail"#);
+        assert_eq!(
+            tokens,
+            vec!["tool", r#"{"a": ["x", "}"], "b": {"c": 1}}"#, "tail"]
+        );
+    }
+
+    #[test]
+    fn single_quoted_json_survives_intact() {
+        let tokens = tokenize_args(&split("tool {q: 'a } b', n: 1}"));
+        assert_eq!(tokens, vec!["tool", "{q: 'a } b', n: 1}"]);
+    }
+
+    #[test]
+    fn normalize_relaxed_json() {
+        assert_eq!(
+            normalize_json("{query: 'auth flow', limit: 5,}").unwrap(),
+            r#"{"limit":5,"query":"auth flow"}"#
+        );
+        assert_eq!(
+            normalize_json("['it\\'s', \"x\", [1, 2,],] // trailing comment").unwrap(),
+            r#"["it's","x",[1,2]]"#
+        );
+        assert_eq!(
+            normalize_json("{/* note */ 'a-b': {c: null, d: true}, e: +.5}").unwrap(),
+            r#"{"a-b":{"c":null,"d":true},"e":0.5}"#
+        );
+        assert_eq!(
+            normalize_json(r#"{"strict": "json", "n": -1.5e3}"#).unwrap(),
+            r#"{"n":-1500.0,"strict":"json"}"#
+        );
+    }
+
+    #[test]
+    fn normalize_reports_position() {
+        let err = normalize_json("{a: 1 b: 2}").unwrap_err();
+        assert!(err.contains("expected `,` or `}`"), "{}", err);
+        assert!(err.contains("column 7"), "{}", err);
+        assert!(normalize_json("{a: hello}")
+            .unwrap_err()
+            .contains("`hello`"));
+        assert!(normalize_json("{a: 'x")
+            .unwrap_err()
+            .contains("unterminated"));
+        assert!(normalize_json("{} extra").is_err());
+    }
+
+    #[test]
+    fn unbalanced_json_consumes_rest() {
+        let tokens = tokenize(r#"tool {"a": 1"#);
+        assert_eq!(tokens, vec!["tool", r#"{"a": 1"#]);
+    }
+}
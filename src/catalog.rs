@@ -0,0 +1,363 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+use crate::commands::{run_command_capture, RecordedRun};
+use crate::format::parse_name_list;
+use crate::state::diff_names;
+use zed_extension_api as zed;
+
+/// How long a fetched tool list is trusted before another command re-polls `loom tools list`.
+pub(crate) const TOOL_CATALOG_TTL: Duration = Duration::from_secs(60);
+
+/// Commands whose output gets a "Tool set changed" notice when the tool list moved.
+const TOOL_NOTICE_COMMANDS: &[&str] = &[
+    "loom-status",
+    "loom-tools",
+    "loom-servers",
+    "loom-call",
+    "loom-search",
+    "loom-skills",
+    "loom-profile",
+    "loom-sync",
+    "loom-restart",
+    "loom-start",
+    "loom-kill",
+];
+
+/// Snapshot of the aggregated tool names exposed by Loom.
+#[derive(Clone, Debug)]
+pub(crate) struct ToolCatalog {
+    pub(crate) names: BTreeSet<String>,
+    /// Tool counts per upstream server, when the listing said which server owns each tool.
+    pub(crate) servers: BTreeMap<String, ServerTools>,
+    /// Input schemas by tool name, when the listing was `tools list --json` and included them.
+    pub(crate) schemas: BTreeMap<String, zed::serde_json::Value>,
+    pub(crate) hash: u64,
+    pub(crate) fetched_at_unix_secs: u64,
+}
+
+/// How many tools one server contributes, and how many of those are enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ServerTools {
+    pub(crate) total: usize,
+    pub(crate) enabled: usize,
+}
+
+impl ToolCatalog {
+    pub(crate) fn new(names: BTreeSet<String>, fetched_at_unix_secs: u64) -> Self {
+        let mut hasher = DefaultHasher::new();
+        names.hash(&mut hasher);
+        Self {
+            names,
+            servers: BTreeMap::new(),
+            schemas: BTreeMap::new(),
+            hash: hasher.finish(),
+            fetched_at_unix_secs,
+        }
+    }
+
+    /// Catalog from `loom tools list` output, with per-server counts when available.
+    pub(crate) fn from_listing(stdout: &str, fetched_at_unix_secs: u64) -> Self {
+        Self {
+            servers: parse_server_tool_counts(stdout),
+            schemas: parse_input_schemas(stdout),
+            ..Self::new(parse_tool_names(stdout), fetched_at_unix_secs)
+        }
+    }
+
+    pub(crate) fn is_fresh(&self, now: u64) -> bool {
+        now.saturating_sub(self.fetched_at_unix_secs) < TOOL_CATALOG_TTL.as_secs()
+    }
+
+    /// Tools added and removed going from `self` to `next` (`None` when the sets match).
+    pub(crate) fn diff(&self, next: &ToolCatalog) -> Option<ToolSetDiff> {
+        if self.hash == next.hash && self.names == next.names {
+            return None;
+        }
+        let (added, removed) = diff_names(&self.names, &next.names);
+        Some(ToolSetDiff { added, removed })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ToolSetDiff {
+    pub(crate) added: Vec<String>,
+    pub(crate) removed: Vec<String>,
+}
+
+impl ToolSetDiff {
+    /// Markdown notice listing the changed tool names.
+    pub(crate) fn notice(&self) -> String {
+        let mut text = format!(
+            "> 🔄 **Tool set changed: +{} / -{} tools**\n",
+            self.added.len(),
+            self.removed.len()
+        );
+        for name in &self.added {
+            text.push_str(&format!("> - ➕ `{}`\n", name));
+        }
+        for name in &self.removed {
+            text.push_str(&format!("> - ➖ `{}`\n", name));
+        }
+        text.push('\n');
+        text
+    }
+}
+
+pub(crate) fn wants_tool_notice(command_name: &str) -> bool {
+    TOOL_NOTICE_COMMANDS.contains(&command_name)
+}
+
+/// Catalog from a `tools list` run the command already made, if any succeeded.
+pub(crate) fn catalog_from_runs(runs: &[RecordedRun], now: u64) -> Option<ToolCatalog> {
+    runs.iter()
+        .rev()
+        .find(|run| {
+            run.exit_code == "0"
+                && run.args.first().map(String::as_str) == Some("tools")
+                && run.args.get(1).map(String::as_str) == Some("list")
+        })
+        .map(|run| ToolCatalog::from_listing(&run.stdout, now))
+}
+
+/// Run `loom tools list --json` (plain `tools list` on CLIs without `--json`) and build a
+/// catalog (`None` if the CLI failed).
+pub(crate) fn fetch_catalog(
+    program: &str,
+    base_env: &[(String, String)],
+    now: u64,
+) -> Option<ToolCatalog> {
+    let mut args: Vec<String> = vec!["tools".into(), "list".into(), "--json".into()];
+    let mut result = run_command_capture(program, &args, base_env, &[]).ok()?;
+    if !result.success() {
+        args.pop();
+        result = run_command_capture(program, &args, base_env, &[]).ok()?;
+    }
+    result
+        .success()
+        .then(|| ToolCatalog::from_listing(&result.stdout, now))
+}
+
+/// Run `loom tools list` and parse the tool names (`None` if the CLI failed).
+pub(crate) fn fetch_tool_names(
+    program: &str,
+    base_env: &[(String, String)],
+) -> Option<BTreeSet<String>> {
+    let result =
+        run_command_capture(program, &["tools".into(), "list".into()], base_env, &[]).ok()?;
+    result.success().then(|| parse_tool_names(&result.stdout))
+}
+
+/// Extract tool names from `loom tools list` output (see [`parse_name_list`]).
+pub(crate) fn parse_tool_names(stdout: &str) -> BTreeSet<String> {
+    parse_name_list(stdout, "tools")
+}
+
+/// Per-server tool counts from `loom tools list` output.
+///
+/// JSON tools are attributed by `server` (tools flagged `enabled: false` / `disabled: true` count
+/// as disabled); tables need a `SERVER` column, with an optional `ENABLED`/`STATUS` column.
+/// Listings without server information yield an empty map.
+pub(crate) fn parse_server_tool_counts(stdout: &str) -> BTreeMap<String, ServerTools> {
+    let mut counts: BTreeMap<String, ServerTools> = BTreeMap::new();
+    let mut add = |server: &str, enabled: bool| {
+        let entry = counts.entry(server.to_string()).or_default();
+        entry.total += 1;
+        if enabled {
+            entry.enabled += 1;
+        }
+    };
+    let is_enabled = |flag: &str| {
+        !matches!(
+            flag.to_ascii_lowercase().as_str(),
+            "false" | "no" | "disabled" | "off"
+        )
+    };
+
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) {
+        let tools = value
+            .get("tools")
+            .and_then(|t| t.as_array())
+            .or_else(|| value.as_array());
+        for tool in tools.into_iter().flatten() {
+            let Some(server) = tool.get("server").and_then(|s| s.as_str()) else {
+                continue;
+            };
+            let enabled = tool
+                .get("enabled")
+                .and_then(|e| e.as_bool())
+                .or_else(|| tool.get("disabled")?.as_bool().map(|d| !d))
+                .unwrap_or(true);
+            add(server, enabled);
+        }
+        return counts;
+    }
+
+    let mut lines = stdout.lines().filter(|l| !l.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return counts;
+    };
+    let columns: Vec<String> = header
+        .split_whitespace()
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let Some(server_col) = columns.iter().position(|c| c == "SERVER") else {
+        return counts;
+    };
+    let enabled_col = columns.iter().position(|c| c == "ENABLED" || c == "STATUS");
+    for line in lines.filter(|l| !l.trim_start().starts_with(['-', '='])) {
+        let cells: Vec<&str> = line.split_whitespace().collect();
+        if let Some(server) = cells.get(server_col) {
+            let enabled = enabled_col
+                .and_then(|i| cells.get(i))
+                .is_none_or(|flag| is_enabled(flag));
+            add(server, enabled);
+        }
+    }
+    counts
+}
+
+/// Input schemas keyed by tool name from `loom tools list --json` (empty for text listings).
+pub(crate) fn parse_input_schemas(stdout: &str) -> BTreeMap<String, zed::serde_json::Value> {
+    let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) else {
+        return BTreeMap::new();
+    };
+    let tools = value
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .or_else(|| value.as_array());
+    tools
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| {
+            let name = tool.get("name")?.as_str()?;
+            let schema = ["inputSchema", "input_schema", "schema"]
+                .iter()
+                .find_map(|k| tool.get(*k))
+                .filter(|s| s.is_object())?;
+            Some((name.to_string(), schema.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> BTreeSet<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_tool_names_formats() {
+        let json = r#"{"tools": [{"name": "github_search"}, {"name": "k8s_pods"}]}"#;
+        assert_eq!(
+            parse_tool_names(json),
+            names(&["github_search", "k8s_pods"])
+        );
+        assert_eq!(parse_tool_names(r#"["a", "b"]"#), names(&["a", "b"]));
+
+        let table = "NAME           SERVER\ngithub_search  github\nk8s_pods       k8s\n";
+        assert_eq!(
+            parse_tool_names(table),
+            names(&["github_search", "k8s_pods"])
+        );
+        assert_eq!(parse_tool_names("one\ntwo\n"), names(&["one", "two"]));
+    }
+
+    #[test]
+    fn diff_reports_added_and_removed() {
+        let before = ToolCatalog::new(names(&["a", "b", "c"]), 0);
+        let same = ToolCatalog::new(names(&["c", "b", "a"]), 10);
+        assert!(before.diff(&same).is_none());
+
+        let after = ToolCatalog::new(names(&["a", "d", "e"]), 10);
+        let diff = before.diff(&after).unwrap();
+        assert_eq!(diff.added, vec!["d", "e"]);
+        assert_eq!(diff.removed, vec!["b", "c"]);
+        let notice = diff.notice();
+        assert!(notice.contains("+2 / -2"));
+        assert!(notice.contains("`d`"));
+    }
+
+    #[test]
+    fn freshness_uses_ttl() {
+        let cat = ToolCatalog::new(BTreeSet::new(), 100);
+        assert!(cat.is_fresh(100 + TOOL_CATALOG_TTL.as_secs() - 1));
+        assert!(!cat.is_fresh(100 + TOOL_CATALOG_TTL.as_secs()));
+    }
+
+    #[test]
+    fn names_from_recorded_runs() {
+        let run = RecordedRun {
+            args: vec!["tools".into(), "list".into()],
+            exit_code: "0".into(),
+            stdout: "NAME\nalpha\n".into(),
+            stderr: String::new(),
+        };
+        let catalog = catalog_from_runs(&[run], 5).unwrap();
+        assert_eq!(catalog.names, names(&["alpha"]));
+        assert_eq!(catalog.fetched_at_unix_secs, 5);
+        assert!(catalog_from_runs(&[], 5).is_none());
+    }
+
+    #[test]
+    fn input_schemas_from_json_listing() {
+        let json = r#"{"tools": [
+            {"name": "a", "inputSchema": {"type": "object"}},
+            {"name": "b", "input_schema": {"type": "object", "required": ["q"]}},
+            {"name": "c"}
+        ]}"#;
+        let catalog = ToolCatalog::from_listing(json, 0);
+        assert_eq!(catalog.schemas.len(), 2);
+        assert_eq!(catalog.schemas["b"]["required"][0], "q");
+        assert!(parse_input_schemas("NAME\na\n").is_empty());
+    }
+
+    #[test]
+    fn server_tool_counts() {
+        let json = r#"{"tools": [
+            {"name": "a", "server": "github"},
+            {"name": "b", "server": "github", "enabled": false},
+            {"name": "c", "server": "k8s", "disabled": true},
+            {"name": "d"}
+        ]}"#;
+        let counts = parse_server_tool_counts(json);
+        assert_eq!(
+            counts["github"],
+            ServerTools {
+                total: 2,
+                enabled: 1
+            }
+        );
+        assert_eq!(
+            counts["k8s"],
+            ServerTools {
+                total: 1,
+                enabled: 0
+            }
+        );
+        assert_eq!(counts.len(), 2);
+
+        let table =
+            "NAME   SERVER  STATUS\n----\na      github  enabled\nb      k8s     disabled\n";
+        let counts = parse_server_tool_counts(table);
+        assert_eq!(
+            counts["github"],
+            ServerTools {
+                total: 1,
+                enabled: 1
+            }
+        );
+        assert_eq!(
+            counts["k8s"],
+            ServerTools {
+                total: 1,
+                enabled: 0
+            }
+        );
+        assert!(parse_server_tool_counts("NAME\nalpha\n").is_empty());
+    }
+}
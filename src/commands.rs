@@ -1,6 +1,32 @@
+use std::cell::RefCell;
+use std::time::Instant;
 use zed_extension_api as zed;
 
-use crate::format::CommandResult;
+use crate::format::{strip_ansi, CommandResult};
+
+/// A CLI invocation captured before truncation (for `/loom-last`).
+#[derive(Clone, Debug)]
+pub(crate) struct RecordedRun {
+    pub(crate) args: Vec<String>,
+    pub(crate) exit_code: String,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+}
+
+thread_local! {
+    // Zed runs extensions single-threaded; this collects every run of the current slash command.
+    static RECORDED_RUNS: RefCell<Vec<RecordedRun>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Copy the runs recorded so far without draining them.
+pub(crate) fn peek_recorded_runs() -> Vec<RecordedRun> {
+    RECORDED_RUNS.with(|runs| runs.borrow().clone())
+}
+
+/// Drain the runs recorded since the last call.
+pub(crate) fn take_recorded_runs() -> Vec<RecordedRun> {
+    RECORDED_RUNS.with(|runs| std::mem::take(&mut *runs.borrow_mut()))
+}
 
 /// Execute a command and capture its output as a structured `CommandResult`.
 pub(crate) fn run_command_capture(
@@ -14,19 +40,105 @@ pub(crate) fn run_command_capture(
         cmd = cmd.env(k, v);
     }
     let output = cmd.output()?;
+    Ok(capture_output(args, output))
+}
+
+/// Env var the `sh` wrapper in [`run_command_with_stdin`] reads the input from.
+const STDIN_ENV: &str = "LOOM_ZED_STDIN";
+
+/// Run a command like [`run_command_capture`], feeding `stdin` to it.
+///
+/// Zed's process API can't write to a child's stdin, so the command runs under `sh`, which pipes
+/// the input from an env var and unsets it first. The input never appears in argv (visible to
+/// every user in `ps`) or in the recorded run, which only keeps `args`.
+pub(crate) fn run_command_with_stdin(
+    program: &str,
+    args: &[String],
+    base_env: &[(String, String)],
+    stdin: &str,
+) -> Result<CommandResult, String> {
+    if matches!(zed::current_platform().0, zed::Os::Windows) {
+        return Err("piping input to loom needs `sh`, which isn't available on Windows".into());
+    }
+    let script = format!(
+        "input=\"${0}\"; unset {0}; printf '%s' \"$input\" | \"$0\" \"$@\"",
+        STDIN_ENV
+    );
+    let mut cmd = zed::process::Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .arg(program)
+        .args(args.iter().cloned());
+    for (k, v) in base_env {
+        cmd = cmd.env(k, v);
+    }
+    let output = cmd.env(STDIN_ENV, stdin).output()?;
+    Ok(capture_output(args, output))
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+/// Record a finished run and truncate its output into a `CommandResult`.
+fn capture_output(args: &[String], output: zed::process::Output) -> CommandResult {
+    let stdout = strip_ansi(&String::from_utf8_lossy(&output.stdout));
+    let stderr = strip_ansi(&String::from_utf8_lossy(&output.stderr));
     let exit_code = output
         .status
         .map(|s| s.to_string())
         .unwrap_or_else(|| "unknown".into());
 
-    Ok(CommandResult {
+    RECORDED_RUNS.with(|runs| {
+        runs.borrow_mut().push(RecordedRun {
+            args: args.to_vec(),
+            exit_code: exit_code.clone(),
+            stdout: stdout.clone(),
+            stderr: stderr.clone(),
+        })
+    });
+
+    CommandResult {
         exit_code,
         stdout: truncate_output(&stdout, 40_000),
         stderr: truncate_output(&stderr, 40_000),
-    })
+    }
+}
+
+/// Run a command like [`run_command_capture`] and also return its wall-clock time in ms.
+///
+/// The time covers process startup, so it is what a slash command or agent actually waits.
+pub(crate) fn run_timed(
+    program: &str,
+    args: &[String],
+    base_env: &[(String, String)],
+) -> Result<(CommandResult, u64), String> {
+    let started = Instant::now();
+    let result = run_command_capture(program, args, base_env, &[])?;
+    Ok((result, started.elapsed().as_millis() as u64))
+}
+
+/// Min/median/max of a set of latency samples (ms).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct LatencyStats {
+    pub(crate) min_ms: u64,
+    pub(crate) median_ms: u64,
+    pub(crate) max_ms: u64,
+}
+
+impl LatencyStats {
+    /// `None` for no samples; an even count's median is the mean of the middle two.
+    pub(crate) fn from_samples(samples: &[u64]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let mid = sorted.len() / 2;
+        let median_ms = if sorted.len().is_multiple_of(2) {
+            (sorted.get(mid.checked_sub(1)?)? + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        Some(LatencyStats {
+            min_ms: *sorted.first()?,
+            median_ms,
+            max_ms: *sorted.last()?,
+        })
+    }
 }
 
 pub(crate) fn truncate_output(s: &str, max_chars: usize) -> String {
@@ -55,6 +167,15 @@ mod tests {
         assert_eq!(join_args(&args), "");
     }
 
+    #[test]
+    fn latency_stats() {
+        assert_eq!(LatencyStats::from_samples(&[]), None);
+        let odd = LatencyStats::from_samples(&[30, 10, 20]).unwrap();
+        assert_eq!((odd.min_ms, odd.median_ms, odd.max_ms), (10, 20, 30));
+        let even = LatencyStats::from_samples(&[40, 10, 20, 30]).unwrap();
+        assert_eq!(even.median_ms, 25);
+    }
+
     #[test]
     fn truncate_within_limit() {
         let s = "hello world";
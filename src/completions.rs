@@ -1,8 +1,11 @@
 use zed_extension_api as zed;
 
+use crate::aliases::{Alias, AliasSource};
+
 /// Known sync platforms (matches loom CLI targets).
 const SYNC_PLATFORMS: &[(&str, &str)] = &[
     ("status", "Show sync status across all platforms"),
+    ("diff", "Preview pending changes (--dry-run)"),
     ("zed", "Sync Zed editor config (--regen)"),
     ("vscode", "Sync VS Code config (--regen)"),
     ("claude", "Sync Claude Code config (--regen)"),
@@ -10,18 +13,64 @@ const SYNC_PLATFORMS: &[(&str, &str)] = &[
     ("codex", "Sync Codex config (--regen)"),
     ("antigravity", "Sync Antigravity config (--regen)"),
     ("kilocode", "Sync Kilocode config (--regen)"),
+    ("all", "Sync every platform (--regen)"),
 ];
 
 /// Known sub-commands for /loom-tools.
 const TOOLS_SUBS: &[(&str, &str)] = &[
     ("list", "List all available tools"),
     ("search", "Search tools by name or description"),
+    ("describe", "Show a tool's parameters"),
+    (
+        "schema-export",
+        "Write every tool's JSON schema to .loom/tools/",
+    ),
+];
+
+/// Options accepted by `/loom-tools list`.
+const TOOLS_LIST_OPTIONS: &[(&str, &str)] = &[
+    ("sort:name", "Sort by tool name"),
+    ("sort:server", "Group by upstream server"),
+    ("sort:recent", "Most recently used first"),
+    ("limit:20", "Show at most 20 tools"),
+    ("limit:50", "Show at most 50 tools"),
+];
+
+/// Known sub-commands for /loom-forget.
+const FORGET_SUBS: &[(&str, &str)] = &[
+    ("id", "Delete one memory entry by ID"),
+    ("query", "Delete every memory entry matching a query"),
+];
+
+/// Known sub-commands for /loom-inbox.
+const INBOX_SUBS: &[(&str, &str)] = &[
+    ("list", "List messages addressed to this agent"),
+    ("read", "Show one message in full"),
+];
+
+/// Reports /loom-export can write.
+const EXPORT_SUBS: &[(&str, &str)] = &[
+    ("dashboard", "Overview dashboard"),
+    ("status", "Daemon and server status"),
+    ("check", "Config and health diagnostics"),
+];
+
+/// Known sub-commands for /loom-namespace.
+const NAMESPACE_SUBS: &[(&str, &str)] = &[("list", "Namespaces with memory/session counts")];
+
+/// Known sub-commands for /loom-servers.
+const SERVERS_SUBS: &[(&str, &str)] = &[
+    ("list", "List registered servers"),
+    ("enable", "Enable a server"),
+    ("disable", "Disable a server"),
+    ("restart", "Restart a server"),
 ];
 
 /// Known sub-commands for /loom-secrets.
 const SECRETS_SUBS: &[(&str, &str)] = &[
     ("list", "List secret names with set/missing status"),
     ("validate", "Validate all secrets are properly configured"),
+    ("set", "Set a secret (value is passed on stdin)"),
 ];
 
 /// Known sub-commands for /loom-session.
@@ -30,6 +79,23 @@ const SESSION_SUBS: &[(&str, &str)] = &[
     ("start", "Start a new agent session"),
     ("end", "End the current agent session"),
     ("list", "List recent sessions"),
+    (
+        "resume",
+        "Reattach to a previous session (provide session ID after)",
+    ),
+];
+
+/// Known sub-commands for /loom-heartbeat.
+const HEARTBEAT_SUBS: &[(&str, &str)] = &[
+    ("send", "Send a heartbeat now"),
+    (
+        "status",
+        "Show the last heartbeat and auto-heartbeat cadence",
+    ),
+    (
+        "interval",
+        "Set the auto-heartbeat cadence in seconds (0 disables)",
+    ),
 ];
 
 /// Known sub-commands for /loom-task.
@@ -37,6 +103,8 @@ const TASK_SUBS: &[(&str, &str)] = &[
     ("list", "List agent tasks"),
     ("add", "Add a new task (provide description after)"),
     ("update", "Update a task (provide task ID and status after)"),
+    ("done", "Mark a task completed (provide task ID after)"),
+    ("remove", "Delete a task (provide task ID, then `confirm`)"),
 ];
 
 /// Task status completions (for second arg of /loom-task update).
@@ -50,6 +118,7 @@ const TASK_STATUSES: &[(&str, &str)] = &[
 const SKILLS_SUBS: &[(&str, &str)] = &[
     ("list", "List all available skills"),
     ("search", "Search skills by keyword"),
+    ("show", "Show one skill's steps and examples"),
     ("categories", "Show skill categories"),
 ];
 
@@ -57,31 +126,338 @@ const SKILLS_SUBS: &[(&str, &str)] = &[
 const PROFILE_SUBS: &[(&str, &str)] = &[
     ("current", "Show the active profile"),
     ("list", "List all profiles"),
+    ("show", "Inspect a profile without switching"),
     ("switch", "Switch to a different profile"),
+    ("create", "Create a new, empty profile"),
+    ("delete", "Delete a profile (provide name, then `confirm`)"),
+];
+
+/// Known sub-commands for /loom-status.
+const STATUS_SUBS: &[(&str, &str)] = &[(
+    "verbose",
+    "Include daemon PID, memory, CPU, and server processes",
+)];
+
+/// Known sub-commands for /loom-daemon.
+const DAEMON_SUBS: &[(&str, &str)] = &[(
+    "env",
+    "Diff the daemon's environment against the extension's",
+)];
+
+/// Known sub-commands for /loom-workflows.
+const WORKFLOWS_SUBS: &[(&str, &str)] = &[
+    ("list", "List defined workflows"),
+    ("run", "Run a workflow by name"),
+];
+
+/// Known sub-commands for /loom-permissions.
+const PERMISSIONS_SUBS: &[(&str, &str)] = &[
+    ("list", "Allowed and denied tools"),
+    ("allow", "Allow a tool"),
+    ("deny", "Deny a tool"),
+];
+
+/// Known sub-commands for /loom-alias (defined alias names are offered alongside).
+const ALIAS_SUBS: &[(&str, &str)] = &[
+    ("list", "Show defined aliases"),
+    ("remove", "Delete a saved alias"),
+];
+
+/// Known sub-commands for /loom-favorites.
+const FAVORITES_SUBS: &[(&str, &str)] = &[
+    ("list", "Pinned tools"),
+    ("add", "Pin a tool"),
+    ("remove", "Unpin a tool"),
 ];
 
+/// Flags accepted anywhere in a /loom-recall query.
+const RECALL_FLAGS: &[(&str, &str)] = &[
+    ("--namespace", "Only recall from one namespace"),
+    ("--limit", "Maximum number of results"),
+    ("--save", "Pin the results to .loom/context/"),
+];
+
+/// Flags accepted anywhere in a /loom-search query.
+const SEARCH_FLAGS: &[(&str, &str)] = &[
+    ("--source", "Only search these sources (comma-separated)"),
+    ("--limit", "Maximum number of results"),
+];
+
+/// Source names `deep_search` knows about, offered after `--source`.
+const SEARCH_SOURCES: &[(&str, &str)] = &[
+    ("web", "Public web"),
+    ("github", "GitHub repositories, issues, and PRs"),
+    ("docs", "Indexed documentation"),
+    ("code", "Indexed code"),
+    ("memory", "Agent memory"),
+];
+
+/// Known sub-commands for /loom-queue.
+const QUEUE_SUBS: &[(&str, &str)] = &[
+    ("list", "List queued and running daemon jobs"),
+    ("cancel", "Cancel a job (provide job ID, then `confirm`)"),
+];
+
+/// Name lists that completions pull live from the CLI (cached per session).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum NameSource {
+    Profiles,
+    Servers,
+    Tools,
+    Workflows,
+    Skills,
+    Namespaces,
+    Sessions,
+}
+
+impl NameSource {
+    const ALL: &'static [NameSource] = &[
+        NameSource::Profiles,
+        NameSource::Servers,
+        NameSource::Tools,
+        NameSource::Workflows,
+        NameSource::Skills,
+        NameSource::Namespaces,
+        NameSource::Sessions,
+    ];
+
+    /// CLI arguments that list the names.
+    pub(crate) fn list_args(self) -> &'static [&'static str] {
+        match self {
+            NameSource::Profiles => &["profile", "list"],
+            NameSource::Servers => &["servers", "list"],
+            NameSource::Tools => &["tools", "list"],
+            NameSource::Workflows => &["workflows", "list"],
+            NameSource::Skills => &["tools", "call", "skills_list"],
+            NameSource::Namespaces => &["agent", "namespace-list"],
+            NameSource::Sessions => &["agent", "session-list"],
+        }
+    }
+
+    /// JSON key holding the list when the CLI emits an object.
+    pub(crate) fn json_key(self) -> &'static str {
+        match self {
+            NameSource::Profiles => "profiles",
+            NameSource::Servers => "servers",
+            NameSource::Tools => "tools",
+            NameSource::Workflows => "workflows",
+            NameSource::Skills => "skills",
+            NameSource::Namespaces => "namespaces",
+            NameSource::Sessions => "sessions",
+        }
+    }
+
+    /// The source whose list command produced `args` (so any listing refreshes the cache).
+    pub(crate) fn from_list_args(args: &[String]) -> Option<NameSource> {
+        NameSource::ALL.iter().copied().find(|source| {
+            let list = source.list_args();
+            args.len() >= list.len() && args.iter().zip(list).all(|(a, b)| a == b)
+        })
+    }
+}
+
+/// Which live name list (if any) the argument being typed should complete from.
+pub(crate) fn name_source(command: &str, args: &[String]) -> Option<NameSource> {
+    match (command, args) {
+        ("loom-profile", [sub, _]) if matches!(sub.as_str(), "show" | "switch" | "delete") => {
+            Some(NameSource::Profiles)
+        }
+        ("loom-kill" | "loom-server", [_]) => Some(NameSource::Servers),
+        ("loom-trace" | "loom-benchmark", [_]) => Some(NameSource::Tools),
+        ("loom-favorites", [sub, _]) if sub == "add" => Some(NameSource::Tools),
+        ("loom-tools", [sub, _]) if sub == "describe" => Some(NameSource::Tools),
+        ("loom-permissions", [sub, _]) if matches!(sub.as_str(), "allow" | "deny") => {
+            Some(NameSource::Tools)
+        }
+        ("loom-workflows", [sub, _]) if sub == "run" => Some(NameSource::Workflows),
+        ("loom-skills", [sub, _]) if sub == "show" => Some(NameSource::Skills),
+        ("loom-session", [sub, _]) if sub == "resume" => Some(NameSource::Sessions),
+        ("loom-recall", [.., flag, _]) if flag == "--namespace" => Some(NameSource::Namespaces),
+        ("loom-servers", [sub, _]) if matches!(sub.as_str(), "enable" | "disable" | "restart") => {
+            Some(NameSource::Servers)
+        }
+        _ => None,
+    }
+}
+
+/// Complete the argument being typed from a live name list.
+pub(crate) fn complete_names(
+    names: &[String],
+    args: &[String],
+) -> Vec<zed::SlashCommandArgumentCompletion> {
+    let options: Vec<(&str, &str)> = names.iter().map(|n| (n.as_str(), "")).collect();
+    filter_completions(&options, query_from_args(args))
+}
+
 /// Dispatch argument completions for any slash command.
 pub(crate) fn complete_argument(
     command: &str,
     args: &[String],
 ) -> Vec<zed::SlashCommandArgumentCompletion> {
     match command {
-        "loom-sync" => filter_completions(SYNC_PLATFORMS, query_from_args(args)),
+        "loom-status" => filter_completions(STATUS_SUBS, query_from_args(args)),
+        "loom-daemon" => filter_completions(DAEMON_SUBS, query_from_args(args)),
+        "loom-sync" => complete_sync(args),
         "loom-tools" => complete_tools(args),
         "loom-secrets" => filter_completions(SECRETS_SUBS, query_from_args(args)),
         "loom-session" => filter_completions(SESSION_SUBS, query_from_args(args)),
+        "loom-summary" => filter_completions(
+            &[(
+                "restart",
+                "End and restart the session to summarize it (older CLIs)",
+            )],
+            query_from_args(args),
+        ),
+        "loom-heartbeat" => filter_completions(HEARTBEAT_SUBS, query_from_args(args)),
         "loom-task" => complete_task(args),
         "loom-skills" => filter_completions(SKILLS_SUBS, query_from_args(args)),
+        "loom-profile" if args.len() == 3 && args[0] == "delete" => filter_completions(
+            &[("confirm", "Confirm deleting the profile")],
+            query_from_args(args),
+        ),
         "loom-profile" => filter_completions(PROFILE_SUBS, query_from_args(args)),
-        "loom-help" => complete_help(args),
+        "loom-queue" => complete_queue(args),
+        "loom-permissions" if args.len() <= 1 => {
+            filter_completions(PERMISSIONS_SUBS, query_from_args(args))
+        }
+        "loom-workflows" if args.len() <= 1 => {
+            filter_completions(WORKFLOWS_SUBS, query_from_args(args))
+        }
+        "loom-kill" if args.len() == 2 => filter_completions(
+            &[("confirm", "Confirm force-terminating the server")],
+            query_from_args(args),
+        ),
+        "loom-forget" => complete_forget(args),
+        "loom-search" if args.len() >= 2 && args[args.len() - 2] == "--source" => {
+            let typed = query_from_args(args);
+            // Complete the last name in a comma-separated list.
+            let (done, partial) = typed.rsplit_once(',').unwrap_or(("", typed));
+            filter_completions(SEARCH_SOURCES, partial)
+                .into_iter()
+                .map(|mut c| {
+                    if !done.is_empty() {
+                        c.new_text = format!("{},{}", done, c.new_text);
+                    }
+                    c
+                })
+                .collect()
+        }
+        "loom-search" if query_from_args(args).starts_with("--") => {
+            complete_flags(SEARCH_FLAGS, args)
+        }
+        "loom-recall" if query_from_args(args).starts_with("--") => {
+            complete_flags(RECALL_FLAGS, args)
+        }
+        "loom-restore" if args.len() == 2 => filter_completions(
+            &[("confirm", "Confirm replacing the current configuration")],
+            query_from_args(args),
+        ),
+        "loom-favorites" if args.len() <= 1 => {
+            filter_completions(FAVORITES_SUBS, query_from_args(args))
+        }
+        "loom-favorites" if args.len() == 2 && args[0] == "remove" => {
+            let pinned = crate::favorites::load();
+            let names: Vec<(&str, &str)> = pinned.iter().map(|t| (t.as_str(), "")).collect();
+            filter_completions(&names, query_from_args(args))
+        }
+        "loom-export" if args.len() <= 1 => filter_completions(EXPORT_SUBS, query_from_args(args)),
+        "loom-servers" if args.len() <= 1 => {
+            filter_completions(SERVERS_SUBS, query_from_args(args))
+        }
+        "loom-namespace" => filter_completions(NAMESPACE_SUBS, query_from_args(args)),
+        "loom-inbox" if args.len() <= 1 => filter_completions(INBOX_SUBS, query_from_args(args)),
+        "loom-update" => filter_completions(
+            &[("confirm", "Download and install the newer release")],
+            query_from_args(args),
+        ),
+        "loom-cleanup" => filter_completions(
+            &[("confirm", "Delete the unused releases")],
+            query_from_args(args),
+        ),
+        "loom-help" | "loom-last" => complete_help(args),
+        _ => Vec::new(),
+    }
+}
+
+/// Alias: first arg is a sub-command or any defined alias (settings and saved, as dispatch sees
+/// them); `remove` only offers saved ones, since settings aliases can't be removed from here.
+pub(crate) fn complete_alias(
+    args: &[String],
+    defined: &[Alias],
+) -> Vec<zed::SlashCommandArgumentCompletion> {
+    let names = |saved_only: bool| -> Vec<(&str, &str)> {
+        defined
+            .iter()
+            .filter(|a| !saved_only || a.source == AliasSource::Saved)
+            .map(|a| (a.name.as_str(), a.target.as_str()))
+            .collect()
+    };
+    match args {
+        [_] => {
+            let mut options = ALIAS_SUBS.to_vec();
+            options.extend(names(false));
+            filter_completions(&options, query_from_args(args))
+        }
+        [sub, _] if sub == "remove" => filter_completions(&names(true), query_from_args(args)),
         _ => Vec::new(),
     }
 }
 
-/// Tools: first arg is sub-command, second arg after "search" is free-form.
+/// Forget: first arg is `id`/`query`; once a target is typed, offer the `confirm` token.
+fn complete_forget(args: &[String]) -> Vec<zed::SlashCommandArgumentCompletion> {
+    match args.len() {
+        0 | 1 => filter_completions(FORGET_SUBS, query_from_args(args)),
+        2 => Vec::new(), // entry ID or first query word
+        _ => filter_completions(
+            &[("confirm", "Confirm permanently deleting the entries")],
+            query_from_args(args),
+        ),
+    }
+}
+
+/// Sync: first arg is any target; later args offer the platforms not already chosen.
+fn complete_sync(args: &[String]) -> Vec<zed::SlashCommandArgumentCompletion> {
+    if args.len() <= 1 {
+        return filter_completions(SYNC_PLATFORMS, query_from_args(args));
+    }
+    let chosen = &args[..args.len() - 1];
+    if chosen.len() == 1 && (chosen[0] == "status" || chosen[0] == "diff") {
+        let platforms: Vec<(&str, &str)> = SYNC_PLATFORMS
+            .iter()
+            .filter(|(label, _)| {
+                !is_sync_keyword(label) || (chosen[0] == "diff" && *label == "all")
+            })
+            .copied()
+            .collect();
+        return filter_completions(&platforms, query_from_args(args));
+    }
+    if chosen.iter().any(|a| is_sync_keyword(a)) {
+        return Vec::new();
+    }
+    let remaining: Vec<(&str, &str)> = SYNC_PLATFORMS
+        .iter()
+        .filter(|(label, _)| !is_sync_keyword(label) && !chosen.iter().any(|c| c == label))
+        .copied()
+        .collect();
+    filter_completions(&remaining, query_from_args(args))
+}
+
+/// Tools: first arg is sub-command, then `list` takes sort/limit options and "search" is
+/// free-form.
 fn complete_tools(args: &[String]) -> Vec<zed::SlashCommandArgumentCompletion> {
     if args.len() <= 1 {
         filter_completions(TOOLS_SUBS, query_from_args(args))
+    } else if args[0] == "list" {
+        let chosen = &args[1..args.len() - 1];
+        let remaining: Vec<(&str, &str)> = TOOLS_LIST_OPTIONS
+            .iter()
+            .filter(|(label, _)| {
+                let key = label.split(':').next().unwrap_or(label);
+                !chosen.iter().any(|c| c.starts_with(key))
+            })
+            .copied()
+            .collect();
+        filter_completions(&remaining, query_from_args(args))
     } else {
         Vec::new() // free-form search query
     }
@@ -92,42 +468,94 @@ fn complete_tools(args: &[String]) -> Vec<zed::SlashCommandArgumentCompletion> {
 fn complete_task(args: &[String]) -> Vec<zed::SlashCommandArgumentCompletion> {
     match args.len() {
         0 | 1 => filter_completions(TASK_SUBS, query_from_args(args)),
-        3 => {
-            if args.first().map(|s| s.as_str()) == Some("update") {
-                filter_completions(TASK_STATUSES, query_from_args(&args[2..]))
-            } else {
-                Vec::new()
-            }
-        }
+        3 => match args.first().map(|s| s.as_str()) {
+            Some("update") => filter_completions(TASK_STATUSES, query_from_args(&args[2..])),
+            Some("remove") => filter_completions(
+                &[("confirm", "Confirm deleting the task")],
+                query_from_args(args),
+            ),
+            _ => Vec::new(),
+        },
         _ => Vec::new(),
     }
 }
 
+/// Queue: first arg is sub-command, second is a job ID (free-form), third is `confirm`.
+fn complete_queue(args: &[String]) -> Vec<zed::SlashCommandArgumentCompletion> {
+    match args.len() {
+        0 | 1 => filter_completions(QUEUE_SUBS, query_from_args(args)),
+        3 if args.first().map(|s| s.as_str()) == Some("cancel") => filter_completions(
+            &[("confirm", "Confirm cancelling the job")],
+            query_from_args(args),
+        ),
+        _ => Vec::new(),
+    }
+}
+
+/// Known slash commands (without the `loom-` prefix), for /loom-help and /loom-last.
+const COMMAND_NAMES: &[(&str, &str)] = &[
+    ("check", "Run diagnostics"),
+    ("status", "Show daemon status"),
+    ("sync", "Config sync"),
+    ("restart", "Restart daemon"),
+    ("daemon", "Daemon environment"),
+    ("start", "Start daemon"),
+    ("stop", "Stop daemon"),
+    ("tools", "List/search tools"),
+    ("servers", "List servers"),
+    ("server", "Server details"),
+    ("ping", "Health check"),
+    ("health", "Ping every server"),
+    ("benchmark", "Tool-call latency"),
+    ("secrets", "Manage secrets"),
+    ("session", "Agent sessions"),
+    ("summary", "Session digest"),
+    ("heartbeat", "Agent heartbeat"),
+    ("agents", "List registered agents"),
+    ("task", "Agent tasks"),
+    ("recall", "Context recall"),
+    ("context", "Inject project context"),
+    ("memory", "Store agent memory"),
+    ("forget", "Delete agent memory"),
+    ("handoff", "Hand off to another agent"),
+    ("inbox", "Messages from other agents"),
+    ("namespace", "Agent memory namespaces"),
+    ("skills", "Browse skills"),
+    ("search", "Deep search"),
+    ("profile", "Profile management"),
+    ("call", "Invoke MCP tool"),
+    ("trace", "Traced tool call"),
+    ("permissions", "Tool allow/deny"),
+    ("dashboard", "Overview dashboard"),
+    ("info", "Binary/version info"),
+    ("config", "Show effective configuration"),
+    ("backup", "Snapshot loom configuration"),
+    ("restore", "Restore a configuration backup"),
+    ("prompts", "Prompt recipes"),
+    ("events", "Daemon event timeline"),
+    ("metrics", "Daemon request/latency metrics"),
+    ("quota", "Provider usage and rate limits"),
+    ("doctor", "Diagnose problems with next steps"),
+    ("update", "Upgrade managed loom-core"),
+    ("install", "Reinstall loom-core"),
+    ("cleanup", "Remove old loom-core releases"),
+    ("paths", "Show extension file locations"),
+    ("env", "Resolved binary and environment"),
+    ("queue", "Daemon job queue"),
+    ("last", "Raw output of a previous command"),
+    ("history", "Recent slash commands"),
+    ("workflows", "List/run workflows"),
+    ("audit", "Daemon audit log"),
+    ("retry", "Fix and re-run the last failure"),
+    ("export", "Write a report to a file"),
+    ("kill", "Force-terminate an upstream server"),
+    ("alias", "Command shortcuts"),
+    ("favorites", "Pinned tools"),
+];
+
 /// Help: complete with known command names.
 fn complete_help(args: &[String]) -> Vec<zed::SlashCommandArgumentCompletion> {
-    let commands: &[(&str, &str)] = &[
-        ("check", "Run diagnostics"),
-        ("status", "Show daemon status"),
-        ("sync", "Config sync"),
-        ("restart", "Restart daemon"),
-        ("start", "Start daemon"),
-        ("stop", "Stop daemon"),
-        ("tools", "List/search tools"),
-        ("servers", "List servers"),
-        ("ping", "Health check"),
-        ("secrets", "Manage secrets"),
-        ("session", "Agent sessions"),
-        ("heartbeat", "Agent heartbeat"),
-        ("task", "Agent tasks"),
-        ("recall", "Context recall"),
-        ("skills", "Browse skills"),
-        ("search", "Deep search"),
-        ("profile", "Profile management"),
-        ("call", "Invoke MCP tool"),
-        ("dashboard", "Overview dashboard"),
-        ("info", "Binary/version info"),
-    ];
-    filter_completions(commands, query_from_args(args))
+    filter_completions(COMMAND_NAMES, query_from_args(args))
 }
 
 /// Extract the query string from the args (the last partial arg being typed).
@@ -135,6 +563,21 @@ fn query_from_args(args: &[String]) -> &str {
     args.last().map(|s| s.as_str()).unwrap_or("")
 }
 
+/// Complete a `--flag` inside free text; picking one doesn't run the command, since the query
+/// (and usually the flag's value) still has to be typed.
+fn complete_flags(
+    flags: &[(&str, &str)],
+    args: &[String],
+) -> Vec<zed::SlashCommandArgumentCompletion> {
+    filter_completions(flags, query_from_args(args))
+        .into_iter()
+        .map(|c| zed::SlashCommandArgumentCompletion {
+            run_command: false,
+            ..c
+        })
+        .collect()
+}
+
 /// Filter a static list of (label, description) pairs by query prefix.
 fn filter_completions(
     options: &[(&str, &str)],
@@ -152,6 +595,25 @@ fn filter_completions(
         .collect()
 }
 
+/// Valid statuses for `/loom-task update`.
+pub(crate) fn task_statuses() -> Vec<&'static str> {
+    TASK_STATUSES.iter().map(|(label, _)| *label).collect()
+}
+
+/// Concrete sync platforms (everything except the `status` and `all` pseudo-targets).
+pub(crate) fn sync_platforms() -> Vec<&'static str> {
+    SYNC_PLATFORMS
+        .iter()
+        .map(|(label, _)| *label)
+        .filter(|label| !is_sync_keyword(label))
+        .collect()
+}
+
+/// `/loom-sync` arguments that are sub-commands rather than platforms.
+pub(crate) fn is_sync_keyword(arg: &str) -> bool {
+    matches!(arg, "status" | "diff" | "all")
+}
+
 /// Validate that a platform name is known for sync operations.
 pub(crate) fn is_valid_sync_platform(platform: &str) -> bool {
     SYNC_PLATFORMS
@@ -163,6 +625,28 @@ pub(crate) fn is_valid_sync_platform(platform: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn alias_completions_include_settings_aliases() {
+        let alias = |name: &str, source| Alias {
+            name: name.into(),
+            target: "status".into(),
+            source,
+        };
+        let defined = [
+            alias("st", AliasSource::Settings),
+            alias("sv", AliasSource::Saved),
+        ];
+        let labels = |args: &[&str]| -> Vec<String> {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            complete_alias(&args, &defined)
+                .into_iter()
+                .map(|c| c.label)
+                .collect()
+        };
+        assert_eq!(labels(&["s"]), vec!["st", "sv"]);
+        assert_eq!(labels(&["remove", ""]), vec!["sv"]);
+    }
+
     #[test]
     fn sync_completions_no_query() {
         let results = complete_argument("loom-sync", &[]);
@@ -170,6 +654,16 @@ mod tests {
         assert_eq!(results[0].label, "status");
     }
 
+    #[test]
+    fn sync_diff_completes_platforms() {
+        let results = complete_argument("loom-sync", &["diff".to_string(), String::new()]);
+        let labels: Vec<&str> = results.iter().map(|c| c.label.as_str()).collect();
+        assert!(labels.contains(&"all"));
+        assert!(labels.contains(&"zed"));
+        assert!(!labels.contains(&"status") && !labels.contains(&"diff"));
+        assert!(!sync_platforms().contains(&"diff"));
+    }
+
     #[test]
     fn sync_completions_partial_query() {
         let results = complete_argument("loom-sync", &["cl".to_string()]);
@@ -183,12 +677,131 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn sync_second_arg_offers_remaining_platforms() {
+        let results = complete_argument("loom-sync", &["zed".to_string(), "".to_string()]);
+        let labels: Vec<&str> = results.iter().map(|c| c.label.as_str()).collect();
+        assert!(!labels.contains(&"zed"));
+        assert!(!labels.contains(&"status"));
+        assert!(!labels.contains(&"all"));
+        assert!(labels.contains(&"vscode"));
+        assert_eq!(labels.len(), sync_platforms().len() - 1);
+
+        let results = complete_argument(
+            "loom-sync",
+            &["zed".to_string(), "vscode".to_string(), "c".to_string()],
+        );
+        let labels: Vec<&str> = results.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["claude", "codex"]);
+    }
+
+    #[test]
+    fn sync_status_offers_single_platform() {
+        let results = complete_argument("loom-sync", &["status".to_string(), "".to_string()]);
+        let labels: Vec<&str> = results.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels.len(), sync_platforms().len());
+        assert!(!labels.contains(&"all"));
+
+        let results = complete_argument(
+            "loom-sync",
+            &["status".to_string(), "zed".to_string(), "".to_string()],
+        );
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn profile_names_complete_after_show_and_switch() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            name_source("loom-profile", &args(&["show", "w"])),
+            Some(NameSource::Profiles)
+        );
+        assert_eq!(
+            name_source("loom-profile", &args(&["switch", ""])),
+            Some(NameSource::Profiles)
+        );
+        assert_eq!(name_source("loom-profile", &args(&["list"])), None);
+
+        let names = vec!["default".to_string(), "work".to_string()];
+        let results = complete_names(&names, &args(&["show", "w"]));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].label, "work");
+
+        assert_eq!(
+            NameSource::from_list_args(&args(&["profile", "list", "--json"])),
+            Some(NameSource::Profiles)
+        );
+        assert_eq!(
+            NameSource::from_list_args(&args(&["profile", "show"])),
+            None
+        );
+    }
+
+    #[test]
+    fn kill_completes_server_then_confirm() {
+        assert_eq!(
+            name_source("loom-kill", &["gi".to_string()]),
+            Some(NameSource::Servers)
+        );
+        assert_eq!(
+            name_source("loom-server", &["gi".to_string()]),
+            Some(NameSource::Servers)
+        );
+        assert_eq!(
+            name_source("loom-servers", &["restart".to_string(), "".to_string()]),
+            Some(NameSource::Servers)
+        );
+        assert_eq!(
+            name_source("loom-tools", &["describe".to_string(), "gh".to_string()]),
+            Some(NameSource::Tools)
+        );
+        assert_eq!(
+            name_source("loom-servers", &["list".to_string(), "".to_string()]),
+            None
+        );
+        let results = complete_argument("loom-kill", &["github".to_string(), "c".to_string()]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].label, "confirm");
+    }
+
+    #[test]
+    fn sync_all_stops_further_completions() {
+        let results = complete_argument("loom-sync", &["all".to_string(), "".to_string()]);
+        assert!(results.is_empty());
+    }
+
     #[test]
     fn tools_first_arg_completions() {
         let results = complete_argument("loom-tools", &[]);
         assert_eq!(results.len(), TOOLS_SUBS.len());
     }
 
+    #[test]
+    fn tools_list_option_completions() {
+        let results = complete_argument("loom-tools", &["list".into(), "so".into()]);
+        assert_eq!(results.len(), 3);
+        let results = complete_argument(
+            "loom-tools",
+            &["list".into(), "sort:name".into(), "".into()],
+        );
+        let labels: Vec<&str> = results.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["limit:20", "limit:50"]);
+    }
+
+    #[test]
+    fn forget_completions() {
+        assert_eq!(
+            complete_argument("loom-forget", &[]).len(),
+            FORGET_SUBS.len()
+        );
+        assert!(complete_argument("loom-forget", &["id".into(), "m".into()]).is_empty());
+        let results = complete_argument("loom-forget", &["id".into(), "m1".into(), "c".into()]);
+        assert_eq!(results[0].label, "confirm");
+        let results = complete_argument("loom-inbox", &["r".into()]);
+        assert_eq!(results[0].label, "read");
+        assert!(complete_argument("loom-inbox", &["read".into(), "".into()]).is_empty());
+    }
+
     #[test]
     fn tools_search_no_further_completions() {
         let results = complete_argument("loom-tools", &["search".to_string(), "foo".to_string()]);
@@ -213,6 +826,14 @@ mod tests {
         assert_eq!(results.len(), TASK_SUBS.len());
     }
 
+    #[test]
+    fn task_remove_offers_confirm() {
+        let args = ["remove".to_string(), "t-1".to_string(), String::new()];
+        let results = complete_argument("loom-task", &args);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].label, "confirm");
+    }
+
     #[test]
     fn task_update_status_completions() {
         let results = complete_argument(
@@ -233,6 +854,7 @@ mod tests {
         assert!(is_valid_sync_platform("zed"));
         assert!(is_valid_sync_platform("claude"));
         assert!(is_valid_sync_platform("status"));
+        assert!(is_valid_sync_platform("all"));
         assert!(!is_valid_sync_platform("invalid"));
     }
 
@@ -245,15 +867,71 @@ mod tests {
         assert!(labels.contains(&"sync"));
     }
 
+    #[test]
+    fn last_completes_command_names() {
+        let results = complete_argument("loom-last", &["sta".to_string()]);
+        let labels: Vec<&str> = results.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["status", "start"]);
+    }
+
     #[test]
     fn skills_completions() {
         let results = complete_argument("loom-skills", &[]);
         assert_eq!(results.len(), SKILLS_SUBS.len());
     }
 
+    #[test]
+    fn recall_completes_flags_and_namespaces() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let results = complete_argument("loom-recall", &args(&["auth", "--n"]));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].label, "--namespace");
+        assert_eq!(
+            name_source("loom-recall", &args(&["auth", "--namespace", "w"])),
+            Some(NameSource::Namespaces)
+        );
+        assert_eq!(name_source("loom-recall", &args(&["auth", "w"])), None);
+    }
+
+    #[test]
+    fn search_completes_flags_and_sources() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let results = complete_argument("loom-search", &args(&["rate", "--s"]));
+        assert_eq!(results[0].label, "--source");
+        let results = complete_argument("loom-search", &args(&["--source", "github,d"]));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].new_text, "github,docs");
+    }
+
+    #[test]
+    fn queue_completions() {
+        let results = complete_argument("loom-queue", &[]);
+        assert_eq!(results.len(), QUEUE_SUBS.len());
+
+        let results = complete_argument(
+            "loom-queue",
+            &["cancel".to_string(), "job-1".to_string(), "c".to_string()],
+        );
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].label, "confirm");
+    }
+
     #[test]
     fn profile_completions() {
         let results = complete_argument("loom-profile", &[]);
         assert_eq!(results.len(), PROFILE_SUBS.len());
+
+        let args = ["delete".to_string(), "work".to_string(), String::new()];
+        let results = complete_argument("loom-profile", &args);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].label, "confirm");
+        assert_eq!(
+            name_source("loom-profile", &args[..2]),
+            Some(NameSource::Profiles)
+        );
+        assert_eq!(
+            name_source("loom-profile", &["create".into(), "x".into()]),
+            None
+        );
     }
 }
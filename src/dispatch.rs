@@ -1,142 +1,231 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 use std::sync::Mutex;
+use std::time::Instant;
 
-use crate::commands::run_command_capture;
+use crate::aliases;
+use crate::args;
+use crate::catalog;
+use crate::checksum;
+use crate::commands::{
+    join_args, peek_recorded_runs, run_command_capture, run_command_with_stdin, run_timed,
+};
 use crate::completions;
+use crate::doctor::{self, Probe};
 use crate::download::{self, LoomInstall};
-use crate::env::{current_path_sep, shell_env_to_vec, upsert_env, with_path_prefix};
+use crate::favorites;
 use crate::format::{
     self, format_daemon_action, format_diagnostic_report, format_generic, format_status_report,
-    format_sync_report, FormattedOutput,
+    format_sync_report, CleanupAction, CleanupRow, FormattedOutput, PathEntry, ToolListOptions,
+    ToolSort, WizardSlot,
 };
 use crate::help::dispatch_help;
 use crate::log::{log_msg, LogLevel};
+use crate::redact;
+use crate::resolver;
+use crate::schema;
+use crate::secrets;
 use crate::settings::LoomRuntimeSettings;
+use crate::state::{SessionState, HISTORY_CAPACITY};
+use crate::version;
+use crate::worktree;
+use crate::WRAPPER_SCRIPT;
 use zed_extension_api as zed;
 
 // ---------------------------------------------------------------------------
-// Binary resolution (shared between context server + slash commands)
+// Command dispatch and formatting
 // ---------------------------------------------------------------------------
 
-fn resolve_loom_path_from_host() -> String {
-    // Try to locate `loom` through the host (POSIX: `which`, Windows: `where`).
-    for locator in ["which", "where"] {
-        if let Ok(output) = zed::process::Command::new(locator).arg("loom").output() {
-            if output.status == Some(0) {
-                let first_line = String::from_utf8_lossy(&output.stdout)
-                    .lines()
-                    .next()
-                    .unwrap_or("")
-                    .trim()
-                    .to_string();
-                if !first_line.is_empty() {
-                    return first_line;
-                }
-            }
-        }
-    }
-
-    // Check well-known locations.
-    let home = std::env::var("HOME").unwrap_or_default();
-    let candidates = [
-        format!("{home}/.local/bin/loom"),
-        "/usr/local/bin/loom".to_string(),
-        "/opt/homebrew/bin/loom".to_string(),
+/// Record a slash command invocation as a session event (best-effort, `agent.log_commands`).
+pub(crate) fn record_command_event(
+    program: &str,
+    base_env: &[(String, String)],
+    agent_id: &str,
+    command_name: &str,
+    args: &[String],
+    outcome: &str,
+) {
+    let args_summary: String = join_args(args).chars().take(80).collect();
+    let summary = format!("/{} {} -> {}", command_name, args_summary, outcome);
+    let event_args: Vec<String> = vec![
+        "agent".into(),
+        "session-event".into(),
+        "--agent-id".into(),
+        agent_id.to_string(),
+        "--kind".into(),
+        "slash_command".into(),
+        "--summary".into(),
+        summary.trim().to_string(),
     ];
-    for candidate in &candidates {
-        if std::path::Path::new(candidate).exists() {
-            return candidate.clone();
-        }
+    match run_command_capture(program, &event_args, base_env, &[]) {
+        Ok(r) if r.success() => {}
+        Ok(r) => log_msg(
+            LogLevel::Debug,
+            &format!("session event not recorded (exit {})", r.exit_code),
+        ),
+        Err(e) => log_msg(LogLevel::Debug, &format!("session event not recorded: {e}")),
     }
+}
 
-    "loom".to_string()
+/// Extension state a slash command may need beyond the resolved binary.
+pub(crate) struct DispatchContext<'a> {
+    pub(crate) session: &'a Mutex<SessionState>,
+    pub(crate) installs: &'a Mutex<HashMap<String, LoomInstall>>,
+    pub(crate) runtime_settings: Option<&'a LoomRuntimeSettings>,
+    /// Absolute path of the worktree the command was run in, if any.
+    pub(crate) worktree_root: Option<&'a str>,
+    pub(crate) worktree: Option<&'a zed::Worktree>,
 }
 
-/// Resolve the loom binary path and build the base environment.
-pub(crate) fn resolve_binary(
-    installs: &Mutex<HashMap<String, LoomInstall>>,
-    worktree: Option<&zed_extension_api::Worktree>,
-    runtime_settings: Option<&LoomRuntimeSettings>,
-) -> Result<(String, Vec<(String, String)>), String> {
-    let mut base_env = worktree
-        .map(|wt| shell_env_to_vec(&wt.shell_env()))
-        .unwrap_or_default();
-    if base_env.is_empty() {
-        if let Ok(path) = std::env::var("PATH") {
-            base_env.push(("PATH".to_string(), path));
-        }
+impl DispatchContext<'_> {
+    /// Agent identifier from `settings.agent.agent_id` (default `zed-loom`).
+    fn agent_id(&self) -> &str {
+        self.runtime_settings
+            .map(|s| s.extension.agent.agent_id())
+            .unwrap_or("zed-loom")
     }
+}
 
-    if let Some(rt) = runtime_settings {
-        for (k, v) in &rt.command_env {
-            upsert_env(&mut base_env, k, v);
+/// Map a slash command name + args to CLI args, run it, and format the output.
+///
+/// A trailing `--raw` argument is handled here for every command: the command runs as usual,
+/// but the CLI output is returned verbatim instead of going through the formatter. A trailing
+/// `--format json` (or `--format=json`) likewise returns the runs as one JSON document.
+///
+/// `/loom-alias <name> [args...]` is resolved to the aliased command first, so the flags above
+/// and the target command's policy apply as if it had been typed out.
+pub(crate) fn dispatch_command(
+    command_name: &str,
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    ctx: &DispatchContext,
+) -> Result<FormattedOutput, String> {
+    if command_name == "loom-alias" {
+        let commands = ctx.runtime_settings.map(|rt| &rt.extension.commands);
+        if let Some(reason) = commands.and_then(|p| p.policy_block(command_name)) {
+            return Err(reason);
+        }
+        let defined = aliases::merged(
+            &commands.map(|c| c.aliases.clone()).unwrap_or_default(),
+            &aliases::load_saved(),
+        );
+        if let Some((alias, rest)) = aliases::invocation(args, &defined) {
+            let (target, target_args) = aliases::expand(&alias.target, rest)?;
+            log_msg(
+                LogLevel::Debug,
+                &format!(
+                    "alias {} -> {} {}",
+                    alias.name,
+                    target,
+                    join_args(&args::redact_args(&target, &target_args))
+                ),
+            );
+            return dispatch_command(&target, &target_args, program, base_env, ctx);
         }
     }
-
-    // Resolve local binary candidate.
-    let explicit = runtime_settings
-        .and_then(|rt| rt.command_path.as_ref())
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty());
-    if let Some(path) = explicit {
-        return Ok((path, base_env));
+    if let Some(rest) = json_format_args(args) {
+        let before = peek_recorded_runs().len();
+        let started = Instant::now();
+        let dispatched = dispatch_named(command_name, rest, program, base_env, ctx);
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let runs = peek_recorded_runs().split_off(before);
+        return Ok(format::format_json_result(
+            command_name,
+            &args::redact_args(command_name, rest),
+            &runs,
+            duration_ms,
+            dispatched.err().as_deref(),
+        ));
     }
-
-    if let Some(wt) = worktree {
-        if let Some(path) = wt.which("loom") {
-            return Ok((path, base_env));
+    if let Some(("--raw", rest)) = args.split_last().map(|(last, rest)| (last.as_str(), rest)) {
+        let before = peek_recorded_runs().len();
+        let formatted = dispatch_named(command_name, rest, program, base_env, ctx)?;
+        let runs = peek_recorded_runs().split_off(before);
+        if runs.is_empty() {
+            return Ok(formatted);
         }
+        return Ok(format::format_raw_runs(command_name, &runs));
     }
-
-    let local_path = resolve_loom_path_from_host();
-    let have_local = local_path != "loom";
-
-    let download_settings = runtime_settings
-        .map(|rt| rt.extension.download.clone())
-        .unwrap_or_default();
-
-    if have_local {
-        Ok((local_path, base_env))
-    } else if download_settings.enabled() {
-        log_msg(
-            LogLevel::Info,
-            &format!(
-                "slash command: downloading loom-core from {}",
-                download_settings.repo()
-            ),
-        );
-        let install = download::ensure_loom_install(installs, &download_settings)?;
-        Ok((
-            install.loom_path,
-            with_path_prefix(base_env, &install.bin_dir, current_path_sep()),
-        ))
-    } else {
-        Ok(("loom".to_string(), base_env))
+    let before = peek_recorded_runs().len();
+    let mut formatted = dispatch_named(command_name, args, program, base_env, ctx)?;
+    // These already explain failures in full.
+    if !matches!(command_name, "loom-doctor" | "loom-retry") {
+        let last = peek_recorded_runs().split_off(before).pop();
+        let finding = last.and_then(|run| {
+            doctor::classify_error(&format::CommandResult {
+                exit_code: run.exit_code,
+                stdout: run.stdout,
+                stderr: run.stderr,
+            })
+        });
+        if let Some(finding) = finding {
+            formatted.append_section("Hint", &format::format_hint(&finding));
+        }
     }
+    Ok(formatted)
 }
 
-// ---------------------------------------------------------------------------
-// Command dispatch and formatting
-// ---------------------------------------------------------------------------
+/// Whether a dispatch error is about the user's input (shown as-is), as opposed to something
+/// unexpected inside the extension that gets an extension error report.
+///
+/// Only the extension's own phrasings count, so CLI and hub failures passed through
+/// (`invalid token`, `unknown server foo`) keep the full report.
+pub(crate) fn is_usage_error(error: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "usage:",
+        "`",
+        "invalid json",
+        "unknown alias",
+        "invalid alias name",
+        "an alias must",
+        "limit must",
+        "--limit must",
+        "interval must",
+        "unknown sync platform",
+        "unknown sort",
+        "unknown prompt recipe",
+    ];
+    const MARKERS: &[&str] = &[
+        "(usage: /loom-",
+        "; usage: /loom-",
+        "needs an open worktree",
+        "disabled by policy",
+    ];
+    let lower = error.to_ascii_lowercase();
+    PREFIXES.iter().any(|p| lower.starts_with(p)) || MARKERS.iter().any(|m| lower.contains(m))
+}
 
-/// Map a slash command name + args to CLI args, run it, and format the output.
-pub(crate) fn dispatch_command(
+fn dispatch_named(
     command_name: &str,
     args: &[String],
     program: &str,
     base_env: &[(String, String)],
+    ctx: &DispatchContext,
 ) -> Result<FormattedOutput, String> {
+    let session = ctx.session;
+    let worktree_root = ctx.worktree_root;
+    let policy = ctx.runtime_settings.map(|rt| &rt.extension.commands);
+    if let Some(reason) = policy.and_then(|p| p.policy_block(command_name)) {
+        return Err(reason);
+    }
     match command_name {
         "loom-info" => dispatch_info(program, base_env),
         "loom-check" => {
-            let result = run_command_capture(program, &["check".into()], base_env, &[])?;
+            let result = run_json_or_plain(program, &["check".into()], base_env)?;
             Ok(format_diagnostic_report(&result))
         }
-        "loom-status" => {
-            let result = run_command_capture(program, &["status".into()], base_env, &[])?;
-            Ok(format_status_report(&result))
-        }
+        "loom-status" => match args.first().map(|s| s.as_str()) {
+            Some("verbose" | "-v" | "--verbose") => dispatch_status_verbose(program, base_env),
+            _ => {
+                let result = run_command_capture(program, &["status".into()], base_env, &[])?;
+                let mut formatted = format_status_report(&result);
+                prepend_crash_loop_warning(&mut formatted, &result.stdout);
+                Ok(formatted)
+            }
+        },
         "loom-sync" => dispatch_sync(args, program, base_env),
+        "loom-daemon" => dispatch_daemon(args, program, base_env),
         "loom-restart" => {
             let result = run_command_capture(program, &["restart".into()], base_env, &[])?;
             Ok(format_daemon_action(&result, "restart"))
@@ -149,46 +238,168 @@ pub(crate) fn dispatch_command(
             let result = run_command_capture(program, &["stop".into()], base_env, &[])?;
             Ok(format_daemon_action(&result, "stop"))
         }
-        "loom-tools" => dispatch_tools(args, program, base_env),
+        "loom-tools" => dispatch_tools(args, program, base_env, worktree_root),
+        "loom-servers" if !args.is_empty() && args[0] != "list" => {
+            dispatch_server_action(args, program, base_env)
+        }
         "loom-servers" => {
             let result =
                 run_command_capture(program, &["servers".into(), "list".into()], base_env, &[])?;
-            Ok(format::format_servers_list(&result))
+            let changes = if result.success() {
+                session
+                    .lock()
+                    .map_err(|_| "session state mutex poisoned")?
+                    .update_servers_snapshot(format::parse_name_list(&result.stdout, "servers"))
+            } else {
+                None
+            };
+            let mut formatted = format::format_servers_list(
+                &result,
+                changes.as_ref().map(|(a, r)| (a.as_slice(), r.as_slice())),
+            );
+            if result.success() {
+                let servers = format::parse_name_list(&result.stdout, "servers");
+                let counts = server_tool_counts(program, base_env, session)?;
+                if !counts.is_empty() {
+                    formatted.append_section(
+                        "Tools per server",
+                        &format::server_tool_counts_table(&servers, &counts),
+                    );
+                }
+            }
+            Ok(formatted)
+        }
+        "loom-server" => {
+            let name = args.first().ok_or("usage: /loom-server <name>")?;
+            let result = run_json_or_plain(
+                program,
+                &["servers".into(), "show".into(), name.clone()],
+                base_env,
+            )?;
+            Ok(format::format_server_detail(&result, name))
         }
         "loom-ping" => {
             let result = run_command_capture(program, &["status".into()], base_env, &[])?;
             Ok(format::format_ping(&result))
         }
-        "loom-secrets" => dispatch_secrets(args, program, base_env),
-        "loom-session" => dispatch_session(args, program, base_env),
-        "loom-heartbeat" => {
+        "loom-health" => dispatch_health(program, base_env),
+        "loom-benchmark" => {
+            let default_tool = policy.map_or("agent_memory_stats", |p| p.benchmark_tool());
+            dispatch_benchmark(args, default_tool, program, base_env)
+        }
+        "loom-secrets" => dispatch_secrets(args, program, base_env, ctx.worktree),
+        "loom-session" => dispatch_session(args, program, base_env, ctx.agent_id()),
+        "loom-heartbeat" => dispatch_heartbeat(args, program, base_env, ctx.agent_id()),
+        "loom-agents" => {
+            let result = run_json_or_plain(program, &["agent".into(), "list".into()], base_env)?;
+            Ok(format::format_agents(&result, ctx.agent_id()))
+        }
+        "loom-task" => dispatch_task(args, program, base_env),
+        "loom-recall" => dispatch_recall(args, program, base_env, worktree_root),
+        "loom-memory" => dispatch_memory(args, program, base_env, ctx.agent_id()),
+        "loom-forget" => dispatch_forget(args, program, base_env, ctx.agent_id()),
+        "loom-handoff" => dispatch_handoff(args, program, base_env, ctx.agent_id()),
+        "loom-inbox" => dispatch_inbox(args, program, base_env, ctx.agent_id()),
+        "loom-skills" => dispatch_skills(args, program, base_env),
+        "loom-search" => dispatch_search(args, program, base_env),
+        "loom-profile" => dispatch_profile(args, program, base_env, session),
+        "loom-call" => dispatch_call(args, program, base_env, session, ctx.worktree),
+        "loom-trace" => dispatch_trace(args, program, base_env, ctx.worktree),
+        "loom-dashboard" => dispatch_dashboard(program, base_env),
+        "loom-queue" => dispatch_queue(args, program, base_env),
+        "loom-kill" => dispatch_kill(args, program, base_env),
+        "loom-config" => dispatch_config(program, base_env),
+        "loom-quota" => {
+            let payload = match args.first() {
+                Some(provider) => zed::serde_json::json!({ "provider": provider }).to_string(),
+                None => "{}".to_string(),
+            };
             let result = run_command_capture(
                 program,
                 &[
-                    "agent".into(),
-                    "heartbeat".into(),
-                    "--agent-id".into(),
-                    "zed-loom".into(),
-                    "--status".into(),
-                    "active".into(),
+                    "tools".into(),
+                    "call".into(),
+                    "provider_usage".into(),
+                    "--".into(),
+                    payload,
                 ],
                 base_env,
                 &[],
             )?;
-            Ok(format_generic(&result, "Heartbeat"))
+            Ok(format::format_quota(&result, download::unix_now_secs()))
         }
-        "loom-task" => dispatch_task(args, program, base_env),
-        "loom-recall" => dispatch_recall(args, program, base_env),
-        "loom-skills" => dispatch_skills(args, program, base_env),
-        "loom-search" => dispatch_search(args, program, base_env),
-        "loom-profile" => dispatch_profile(args, program, base_env),
-        "loom-call" => dispatch_call(args, program, base_env),
-        "loom-dashboard" => dispatch_dashboard(program, base_env),
-        "loom-help" => Ok(dispatch_help(args)),
+        "loom-backup" => dispatch_backup(args, program, base_env, worktree_root),
+        "loom-restore" => dispatch_restore(args, program, base_env, worktree_root),
+        "loom-doctor" => dispatch_doctor(program, base_env),
+        "loom-metrics" => dispatch_metrics(program, base_env),
+        "loom-events" => dispatch_events(args, program, base_env),
+        "loom-audit" => dispatch_audit(args, program, base_env),
+        "loom-prompts" => dispatch_prompts(args, base_env, ctx),
+        "loom-update" => dispatch_update(args, program, ctx),
+        "loom-install" => dispatch_install(args, program, ctx),
+        "loom-cleanup" => dispatch_cleanup(args, program, ctx),
+        "loom-paths" => dispatch_paths(program, ctx),
+        "loom-env" => {
+            let resolved =
+                resolver::resolve_binary(ctx.installs, ctx.worktree, ctx.runtime_settings)?;
+            let candidates =
+                resolver::binary_candidates(ctx.installs, ctx.worktree, ctx.runtime_settings);
+            let version = version::probe_version(&resolved.program, &resolved.env);
+            let overrides = ctx
+                .runtime_settings
+                .map(|rt| rt.command_env.as_slice())
+                .unwrap_or_default();
+            Ok(format::format_env_report(
+                &resolved,
+                &candidates,
+                version.as_deref(),
+                overrides,
+                crate::env::current_path_sep(),
+            ))
+        }
+        "loom-last" => dispatch_last(args, session),
+        "loom-history" => dispatch_history(args, session),
+        "loom-permissions" => dispatch_permissions(args, program, base_env),
+        "loom-workflows" => dispatch_workflows(args, program, base_env),
+        "loom-retry" => dispatch_retry(program, base_env, ctx),
+        "loom-export" => dispatch_export(args, program, base_env, ctx),
+        "loom-namespace" => dispatch_namespace(args, program, base_env, ctx.agent_id()),
+        "loom-context" => dispatch_context(args, program, base_env, ctx),
+        "loom-summary" => dispatch_summary(args, program, base_env, ctx.agent_id()),
+        "loom-alias" => dispatch_alias(args, ctx),
+        "loom-favorites" => dispatch_favorites(args, program, base_env),
+        "loom-help" => Ok(dispatch_help(args, policy)),
         other => Err(format!("unknown slash command {:?}", other)),
     }
 }
 
+/// The remaining args when `args` ends in `--format json` / `--format=json`.
+fn json_format_args(args: &[String]) -> Option<&[String]> {
+    match args {
+        [rest @ .., flag, value] if flag == "--format" && value == "json" => Some(rest),
+        [rest @ .., flag] if flag == "--format=json" => Some(rest),
+        _ => None,
+    }
+}
+
+/// Run a CLI command with `--json`, retrying without it if this loom build rejects the flag.
+fn run_json_or_plain(
+    program: &str,
+    args: &[String],
+    base_env: &[(String, String)],
+) -> Result<format::CommandResult, String> {
+    let mut json_args = args.to_vec();
+    json_args.push("--json".into());
+    let result = run_command_capture(program, &json_args, base_env, &[])?;
+    let stderr = result.stderr.to_ascii_lowercase();
+    let flag_rejected = !result.success()
+        && (stderr.contains("unknown flag") || stderr.contains("flag provided but not defined"));
+    if flag_rejected {
+        return run_command_capture(program, args, base_env, &[]);
+    }
+    Ok(result)
+}
+
 // ---------------------------------------------------------------------------
 // Sub-command dispatchers
 // ---------------------------------------------------------------------------
@@ -206,10 +417,10 @@ fn dispatch_info(program: &str, base_env: &[(String, String)]) -> Result<Formatt
         Ok(v) => {
             if !v.stdout.trim().is_empty() {
                 text.push_str("### Version\n\n");
-                text.push_str(&format!("```\n{}\n```\n\n", v.stdout.trim()));
+                text.push_str(&format::fenced("", v.stdout.trim()));
             } else if !v.stderr.trim().is_empty() {
                 text.push_str("### Version (stderr)\n\n");
-                text.push_str(&format!("```\n{}\n```\n\n", v.stderr.trim()));
+                text.push_str(&format::fenced("", v.stderr.trim()));
             }
         }
         Err(e) => {
@@ -218,9 +429,135 @@ fn dispatch_info(program: &str, base_env: &[(String, String)]) -> Result<Formatt
         }
     }
 
+    let managed = download::managed_version(program);
+    let manifest = managed.as_deref().and_then(download::InstallManifest::read);
+    let current_sha = manifest
+        .as_ref()
+        .and_then(|_| checksum::file_sha256(Path::new(program)));
+    text.push_str(&format::provenance_markdown(
+        managed.as_deref(),
+        manifest.as_ref(),
+        current_sha.as_deref(),
+    ));
+
     Ok(FormattedOutput::plain(text))
 }
 
+fn dispatch_status_verbose(
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let mut result = run_json_or_plain(program, &["status".into(), "--verbose".into()], base_env)?;
+    let stderr = result.stderr.to_ascii_lowercase();
+    if !result.success()
+        && (stderr.contains("unknown flag") || stderr.contains("flag provided but not defined"))
+    {
+        result = run_command_capture(program, &["status".into()], base_env, &[])?;
+    }
+
+    let mut resources = format::parse_daemon_resources(&result.stdout);
+    if let (true, Some(pid)) = (resources.needs_probe(), resources.pid) {
+        probe_process_usage(pid, &mut resources, base_env);
+    }
+    let mut formatted = format::format_status_verbose(&result, &resources);
+    prepend_crash_loop_warning(&mut formatted, &result.stdout);
+    Ok(formatted)
+}
+
+/// Put a crash-loop warning above `/loom-status` output when the restart history shows one.
+fn prepend_crash_loop_warning(formatted: &mut FormattedOutput, status_stdout: &str) {
+    let restarts = format::parse_daemon_restarts(status_stdout);
+    if let Some(warning) = format::crash_loop_warning(&restarts, download::unix_now_secs()) {
+        formatted.prepend_section("Crash Loop", &warning);
+    }
+}
+
+/// `/loom-daemon env`: diff the running daemon's environment against `base_env`.
+///
+/// Asks `loom daemon env` first; older CLIs without it fall back to reading
+/// `/proc/<pid>/environ` for the PID `loom status` reports (Linux only).
+fn dispatch_daemon(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    match args.first().map(|s| s.as_str()).unwrap_or("env") {
+        "env" => {}
+        other => {
+            return Err(format!(
+                "unknown subcommand '{}'; usage: /loom-daemon env",
+                other
+            ))
+        }
+    }
+
+    let result = run_json_or_plain(program, &["daemon".into(), "env".into()], base_env)?;
+    if result.success() {
+        if let Some(daemon_env) = crate::env::parse_env_listing(&result.stdout) {
+            let diff = crate::env::diff_env(&daemon_env, base_env);
+            return Ok(format::format_daemon_env("`loom daemon env`", &diff));
+        }
+    }
+
+    let status = run_json_or_plain(program, &["status".into()], base_env)?;
+    let pid = format::parse_daemon_resources(&status.stdout)
+        .pid
+        .ok_or("the daemon PID was not reported by `loom status`; is the daemon running?")?;
+    let environ = format!("/proc/{}/environ", pid);
+    let read = run_command_capture("cat", std::slice::from_ref(&environ), base_env, &[])?;
+    let daemon_env = read
+        .success()
+        .then(|| crate::env::parse_env_listing(&read.stdout))
+        .flatten()
+        .ok_or_else(|| {
+            format!(
+                "this loom CLI has no `daemon env` subcommand and {} could not be read; \
+                 upgrade loom-core with `/loom-update`",
+                environ
+            )
+        })?;
+    let diff = crate::env::diff_env(&daemon_env, base_env);
+    Ok(format::format_daemon_env(&format!("`{}`", environ), &diff))
+}
+
+/// Fill missing daemon resource numbers from `ps` / `pgrep` (best-effort, POSIX hosts only).
+fn probe_process_usage(
+    pid: u32,
+    resources: &mut format::DaemonResources,
+    base_env: &[(String, String)],
+) {
+    if resources.rss_kb.is_none() || resources.cpu_percent.is_none() {
+        let args = [
+            "-o".into(),
+            "rss=,%cpu=".into(),
+            "-p".into(),
+            pid.to_string(),
+        ];
+        if let Ok(ps) = run_command_capture("ps", &args, base_env, &[]) {
+            if let Some((rss_kb, cpu)) = format::parse_ps_usage(&ps.stdout) {
+                resources.rss_kb.get_or_insert(rss_kb);
+                resources.cpu_percent.get_or_insert(cpu);
+            }
+        }
+    }
+    if resources.server_processes.is_none() {
+        if let Ok(children) =
+            run_command_capture("pgrep", &["-P".into(), pid.to_string()], base_env, &[])
+        {
+            // pgrep exits 1 when nothing matches, which still means zero children.
+            if children.success() || children.exit_code == "1" {
+                resources.server_processes = Some(
+                    children
+                        .stdout
+                        .lines()
+                        .filter(|l| !l.trim().is_empty())
+                        .count(),
+                );
+            }
+        }
+    }
+}
+
 fn dispatch_sync(
     args: &[String],
     program: &str,
@@ -229,33 +566,147 @@ fn dispatch_sync(
     let sub = args.first().map(|s| s.as_str()).unwrap_or("status");
 
     if sub == "status" || sub.is_empty() {
+        if let Some(platform) = args.get(1) {
+            if completions::is_sync_keyword(platform)
+                || !completions::is_valid_sync_platform(platform)
+            {
+                return Err(format!(
+                    "unknown sync platform {:?}. Valid: zed, vscode, claude, gemini, codex, antigravity, kilocode",
+                    platform
+                ));
+            }
+            let result = run_json_or_plain(
+                program,
+                &["sync".into(), "status".into(), platform.clone()],
+                base_env,
+            )?;
+            return Ok(format::format_sync_platform_status(&result, platform));
+        }
         let result =
             run_command_capture(program, &["sync".into(), "status".into()], base_env, &[])?;
-        Ok(format_sync_report(&result, None))
-    } else {
-        if !completions::is_valid_sync_platform(sub) {
+        return Ok(format_sync_report(&result, None));
+    }
+
+    if sub == "diff" {
+        let platforms = match args.get(1).map(|s| s.as_str()) {
+            None | Some("all") => completions::sync_platforms(),
+            Some(p) if !completions::is_sync_keyword(p) && completions::is_valid_sync_platform(p) => {
+                vec![p]
+            }
+            Some(p) => {
+                return Err(format!(
+                    "unknown sync platform {:?}. Valid: all, zed, vscode, claude, gemini, codex, antigravity, kilocode",
+                    p
+                ))
+            }
+        };
+        let mut results = Vec::with_capacity(platforms.len());
+        for platform in platforms {
+            let result = run_command_capture(
+                program,
+                &["sync".into(), platform.to_string(), "--dry-run".into()],
+                base_env,
+                &[],
+            )?;
+            results.push((platform, result));
+        }
+        let parts: Vec<(&str, &format::CommandResult)> =
+            results.iter().map(|(p, r)| (*p, r)).collect();
+        return Ok(format::format_sync_diff(&parts));
+    }
+
+    // Validate every requested platform up front so a typo doesn't leave a partial sync.
+    let mut platforms: Vec<&str> = Vec::new();
+    for p in args.iter().map(|s| s.as_str()) {
+        if (p != "all" && completions::is_sync_keyword(p))
+            || !completions::is_valid_sync_platform(p)
+        {
             return Err(format!(
-                "unknown sync platform {:?}. Valid: status, zed, vscode, claude, gemini, codex, antigravity, kilocode",
-                sub
+                "unknown sync platform {:?}. Valid: status, diff, all, zed, vscode, claude, gemini, codex, antigravity, kilocode",
+                p
             ));
         }
+        let expanded = if p == "all" {
+            completions::sync_platforms()
+        } else {
+            vec![p]
+        };
+        for platform in expanded {
+            if !platforms.contains(&platform) {
+                platforms.push(platform);
+            }
+        }
+    }
+
+    let mut results = Vec::with_capacity(platforms.len());
+    for platform in &platforms {
         let result = run_command_capture(
             program,
-            &["sync".into(), sub.to_string(), "--regen".into()],
+            &["sync".into(), platform.to_string(), "--regen".into()],
             base_env,
             &[],
         )?;
-        Ok(format_sync_report(&result, Some(sub)))
+        results.push((*platform, result));
+    }
+
+    if let [(platform, result)] = results.as_slice() {
+        return Ok(format_sync_report(result, Some(platform)));
     }
+    let parts: Vec<(&str, &format::CommandResult)> = results.iter().map(|(p, r)| (*p, r)).collect();
+    Ok(format::format_sync_multi(&parts))
 }
 
+/// Worktree-relative directory `/loom-tools schema-export` writes into.
+const TOOL_SCHEMA_DIR: &str = ".loom/tools";
+
 fn dispatch_tools(
     args: &[String],
     program: &str,
     base_env: &[(String, String)],
+    worktree_root: Option<&str>,
 ) -> Result<FormattedOutput, String> {
     let sub = args.first().map(|s| s.as_str()).unwrap_or("list");
     match sub {
+        "schema-export" => {
+            let root = worktree_root.ok_or("/loom-tools schema-export needs an open worktree")?;
+            let result = run_command_capture(
+                program,
+                &["tools".into(), "list".into(), "--json".into()],
+                base_env,
+                &[],
+            )?;
+            if !result.success() {
+                return Ok(format::format_tools_table(&result));
+            }
+            let schemas = format::parse_tool_schemas(&result.stdout)?;
+            let mut written = Vec::with_capacity(schemas.len());
+            for (name, schema) in &schemas {
+                let rel = format!("{}/{}.json", TOOL_SCHEMA_DIR, worktree::file_stem(name));
+                worktree::write_file(root, &rel, schema)?;
+                written.push((name.clone(), rel));
+            }
+            Ok(format::format_schema_export(TOOL_SCHEMA_DIR, &written))
+        }
+        "describe" => {
+            let name = args.get(1).ok_or("usage: /loom-tools describe <tool>")?;
+            let result = run_json_or_plain(
+                program,
+                &["tools".into(), "describe".into(), name.clone()],
+                base_env,
+            )?;
+            let output = format!("{}\n{}", result.stdout, result.stderr).to_ascii_lowercase();
+            if result.success() || !output.contains("unknown command") {
+                return Ok(format::format_tool_describe(&result, name));
+            }
+            // Older CLIs have no `tools describe`; the schema is also in `tools list --json`.
+            let listing = run_command_capture(
+                program,
+                &["tools".into(), "list".into(), "--json".into()],
+                base_env,
+                &[],
+            )?;
+            Ok(format::format_tool_describe(&listing, name))
+        }
         "search" => {
             let query = args.get(1).map(|s| s.as_str()).unwrap_or("");
             if query.is_empty() {
@@ -270,31 +721,121 @@ fn dispatch_tools(
             Ok(format::format_tools_table(&result))
         }
         _ => {
-            let result =
-                run_command_capture(program, &["tools".into(), "list".into()], base_env, &[])?;
-            Ok(format::format_tools_table(&result))
+            let option_args = match args.first() {
+                Some(first) if first == "list" => &args[1..],
+                _ => args,
+            };
+            let pinned = favorites::load();
+            let options = parse_tool_list_options(option_args)?;
+            // Build the table from `--json` fields so it doesn't depend on the CLI's text layout;
+            // older CLIs fall back to parsing the plain table.
+            let result = run_json_or_plain(program, &["tools".into(), "list".into()], base_env)?;
+            Ok(format::format_tools_listing(&result, &options, &pinned))
+        }
+    }
+}
+
+/// Parse `sort:name|server|recent` and `limit:<n>` tokens for `/loom-tools list`.
+fn parse_tool_list_options(args: &[String]) -> Result<ToolListOptions, String> {
+    let mut options = ToolListOptions::default();
+    for arg in args {
+        match arg.split_once(':') {
+            Some(("sort", key)) => {
+                options.sort = Some(ToolSort::parse(key).ok_or_else(|| {
+                    format!("unknown sort `{}` (expected name, server, or recent)", key)
+                })?);
+            }
+            Some(("limit", n)) => {
+                let n = n
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| format!("limit must be a positive number, got `{}`", n))?;
+                options.limit = Some(n);
+            }
+            _ => {
+                return Err(format!(
+                    "unknown option `{}` (usage: /loom-tools list [sort:name|server|recent] [limit:<n>])",
+                    arg
+                ))
+            }
         }
     }
+    Ok(options)
 }
 
 fn dispatch_secrets(
     args: &[String],
     program: &str,
     base_env: &[(String, String)],
+    worktree: Option<&zed::Worktree>,
 ) -> Result<FormattedOutput, String> {
     let sub = args.first().map(|s| s.as_str()).unwrap_or("list");
+    if sub == "set" {
+        return dispatch_secrets_set(&args[1..], program, base_env);
+    }
     let cmd_args: Vec<String> = match sub {
         "validate" => vec!["secrets".into(), "validate".into()],
         _ => vec!["secrets".into(), "list".into()],
     };
     let result = run_command_capture(program, &cmd_args, base_env, &[])?;
-    Ok(format::format_secrets(&result, sub))
+    if sub != "validate" {
+        return Ok(format::format_secrets(&result, sub));
+    }
+
+    // Turn "missing" into where to add the variable: worktree env files, then the shell env.
+    let missing = secrets::missing_env_secrets(&result);
+    let env_files: Vec<(&'static str, String)> = worktree
+        .map(|wt| {
+            secrets::ENV_FILES
+                .iter()
+                .filter_map(|file| wt.read_text_file(file).ok().map(|text| (*file, text)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let hints = secrets::hints(&missing, &env_files, base_env);
+    let shell = base_env
+        .iter()
+        .find(|(k, _)| k == "SHELL")
+        .map(|(_, v)| v.as_str());
+    Ok(format::format_secrets_validation(&result, &hints, shell))
+}
+
+/// `/loom-secrets set <KEY> <value>`: the value reaches loom on stdin, never on its command line.
+fn dispatch_secrets_set(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let (key, value) = match args {
+        [key, value] if !key.starts_with('-') && !value.is_empty() => (key, value),
+        _ => return Err("usage: /loom-secrets set <KEY> <value>".into()),
+    };
+    if value == args::REDACTED {
+        // History and `/loom-retry` only keep the redacted form; don't store the placeholder.
+        return Err(
+            "usage: /loom-secrets set <KEY> <value> (values aren't kept, so type it again)".into(),
+        );
+    }
+    let cmd_args: Vec<String> = vec![
+        "secrets".into(),
+        "set".into(),
+        key.clone(),
+        "--stdin".into(),
+    ];
+    let mut result = run_command_with_stdin(program, &cmd_args, base_env, value)?;
+    // In case loom echoes what it read.
+    for out in [&mut result.stdout, &mut result.stderr] {
+        *out = out.replace(value.as_str(), args::REDACTED);
+    }
+    Ok(format::format_secrets(&result, "set"))
 }
 
 fn dispatch_session(
     args: &[String],
     program: &str,
     base_env: &[(String, String)],
+    agent_id: &str,
 ) -> Result<FormattedOutput, String> {
     let sub = args.first().map(|s| s.as_str()).unwrap_or("status");
     let cmd_args: Vec<String> = match sub {
@@ -303,7 +844,7 @@ fn dispatch_session(
                 "agent".into(),
                 "session-start".into(),
                 "--agent-id".into(),
-                "zed-loom".into(),
+                agent_id.into(),
             ];
             if let Some(ns) = args.get(1) {
                 a.push("--namespace".into());
@@ -316,21 +857,186 @@ fn dispatch_session(
             "agent".into(),
             "session-end".into(),
             "--agent-id".into(),
-            "zed-loom".into(),
+            agent_id.into(),
             "--summarize".into(),
         ],
         "list" => vec!["agent".into(), "session-list".into()],
+        "resume" => {
+            let session_id = args
+                .get(1)
+                .ok_or("usage: /loom-session resume <session-id>")?;
+            vec![
+                "agent".into(),
+                "session-resume".into(),
+                "--agent-id".into(),
+                agent_id.into(),
+                "--session-id".into(),
+                session_id.clone(),
+            ]
+        }
         _ => vec![
             "agent".into(),
             "session".into(),
             "--agent-id".into(),
-            "zed-loom".into(),
+            agent_id.into(),
         ],
     };
     let result = run_command_capture(program, &cmd_args, base_env, &[])?;
     Ok(format::format_session(&result, sub))
 }
 
+/// `/loom-summary [restart]`: a digest of the current session without ending it.
+///
+/// Uses `agent session-summary`; CLIs without it can only summarize on `session-end`, so
+/// `restart` ends the session with `--summarize` and starts a new one in the same namespace.
+fn dispatch_summary(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    agent_id: &str,
+) -> Result<FormattedOutput, String> {
+    let agent = |sub: &str| -> Vec<String> {
+        vec![
+            "agent".into(),
+            sub.into(),
+            "--agent-id".into(),
+            agent_id.into(),
+        ]
+    };
+    let restart = match args.first().map(|s| s.as_str()) {
+        None => false,
+        Some("restart") => true,
+        Some(other) => {
+            return Err(format!(
+                "unknown /loom-summary argument `{}` (usage: /loom-summary [restart])",
+                other
+            ))
+        }
+    };
+    let now = download::unix_now_secs();
+    if !restart {
+        let result = run_json_or_plain(program, &agent("session-summary"), base_env)?;
+        return Ok(format::format_session_summary(
+            &result,
+            is_unsupported(&result),
+            false,
+            now,
+        ));
+    }
+
+    let session = run_json_or_plain(program, &agent("session"), base_env)?;
+    let namespace = session
+        .success()
+        .then(|| format::parse_session_snapshot(&session.stdout).namespace)
+        .flatten();
+    let mut end = agent("session-end");
+    end.push("--summarize".into());
+    let ended = run_json_or_plain(program, &end, base_env)?;
+    if ended.success() {
+        let mut start = agent("session-start");
+        if let Some(ns) = namespace {
+            start.push("--namespace".into());
+            start.push(ns);
+        }
+        start.push("--auto-recall".into());
+        let started = run_command_capture(program, &start, base_env, &[])?;
+        if !started.success() {
+            let mut formatted = format::format_session_summary(&ended, false, true, now);
+            formatted.append_output(format::format_session(&started, "start"));
+            return Ok(formatted);
+        }
+    }
+    Ok(format::format_session_summary(
+        &ended,
+        false,
+        ended.success(),
+        now,
+    ))
+}
+
+/// Shortest auto-heartbeat cadence accepted by `/loom-heartbeat interval` (0 disables).
+const MIN_HEARTBEAT_INTERVAL_SECS: u64 = 10;
+
+fn dispatch_heartbeat(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    agent_id: &str,
+) -> Result<FormattedOutput, String> {
+    let sub = args.first().map(|s| s.as_str()).unwrap_or("send");
+    match sub {
+        "status" => {
+            let result = run_json_or_plain(
+                program,
+                &[
+                    "agent".into(),
+                    "heartbeat-status".into(),
+                    "--agent-id".into(),
+                    agent_id.into(),
+                ],
+                base_env,
+            )?;
+            Ok(format::format_heartbeat_status(&result))
+        }
+        "interval" => {
+            let secs = match args.get(1).map(|s| s.parse::<u64>()) {
+                Some(Ok(secs)) if secs == 0 || secs >= MIN_HEARTBEAT_INTERVAL_SECS => secs,
+                Some(_) => {
+                    return Err(format!(
+                        "interval must be 0 (disable) or at least {} seconds",
+                        MIN_HEARTBEAT_INTERVAL_SECS
+                    ))
+                }
+                None => {
+                    return Ok(format::format_argument_wizard(
+                        "/loom-heartbeat interval",
+                        &[WizardSlot {
+                            name: "<secs>",
+                            hint: "Auto-heartbeat cadence in seconds (0 disables)",
+                            candidates: vec!["30".into(), "60".into(), "300".into(), "0".into()],
+                        }],
+                    ))
+                }
+            };
+            let result = run_command_capture(
+                program,
+                &[
+                    "agent".into(),
+                    "heartbeat-config".into(),
+                    "--agent-id".into(),
+                    agent_id.into(),
+                    "--interval".into(),
+                    secs.to_string(),
+                ],
+                base_env,
+                &[],
+            )?;
+            let title = if secs == 0 {
+                "Auto-heartbeat Disabled".to_string()
+            } else {
+                format!("Auto-heartbeat Every {}s", secs)
+            };
+            Ok(format_generic(&result, &title))
+        }
+        _ => {
+            let result = run_command_capture(
+                program,
+                &[
+                    "agent".into(),
+                    "heartbeat".into(),
+                    "--agent-id".into(),
+                    agent_id.into(),
+                    "--status".into(),
+                    "active".into(),
+                ],
+                base_env,
+                &[],
+            )?;
+            Ok(format_generic(&result, "Heartbeat"))
+        }
+    }
+}
+
 fn dispatch_task(
     args: &[String],
     program: &str,
@@ -341,23 +1047,50 @@ fn dispatch_task(
         "add" => {
             let desc = args.get(1..).map(|a| a.join(" ")).unwrap_or_default();
             if desc.is_empty() {
-                return Err("usage: /loom-task add <description>".to_string());
+                return Ok(format::format_argument_wizard(
+                    "/loom-task add",
+                    &[WizardSlot {
+                        name: "<description>",
+                        hint: "What needs doing (quotes optional)",
+                        candidates: Vec::new(),
+                    }],
+                ));
             }
             vec![
                 "tools".into(),
                 "call".into(),
                 "agent_task_add".into(),
                 "--".into(),
-                format!(r#"{{"description":"{}"}}"#, desc),
+                zed::serde_json::json!({ "description": desc }).to_string(),
             ]
         }
         "update" => {
-            let task_id = args
-                .get(1)
-                .ok_or("usage: /loom-task update <id> <status>")?;
-            let status = args
-                .get(2)
-                .ok_or("usage: /loom-task update <id> <status>")?;
+            let statuses = completions::task_statuses();
+            let status_slot = || WizardSlot {
+                name: "<status>",
+                hint: "New task status",
+                candidates: statuses.iter().map(|s| s.to_string()).collect(),
+            };
+            let Some(task_id) = args.get(1) else {
+                let id_slot = WizardSlot {
+                    name: "<id>",
+                    hint: "Task ID (see `/loom-task list`)",
+                    candidates: Vec::new(),
+                };
+                return Ok(format::format_argument_wizard(
+                    "/loom-task update",
+                    &[id_slot, status_slot()],
+                ));
+            };
+            let status = match args.get(2) {
+                Some(status) if statuses.contains(&status.as_str()) => status,
+                _ => {
+                    return Ok(format::format_argument_wizard(
+                        &format!("/loom-task update {}", task_id),
+                        &[status_slot()],
+                    ))
+                }
+            };
             vec![
                 "agent".into(),
                 "task-update".into(),
@@ -367,20 +1100,203 @@ fn dispatch_task(
                 status.clone(),
             ]
         }
-        _ => vec!["tools".into(), "call".into(), "agent_task_list".into()],
-    };
+        "done" => {
+            let task_id = args.get(1).ok_or("usage: /loom-task done <id>")?;
+            vec![
+                "agent".into(),
+                "task-update".into(),
+                "--task-id".into(),
+                task_id.clone(),
+                "--status".into(),
+                "completed".into(),
+            ]
+        }
+        "remove" => {
+            let task_id = args.get(1).ok_or("usage: /loom-task remove <id> confirm")?;
+            if !is_confirmed(&args[2..]) {
+                return Ok(format::format_confirmation_required(
+                    &format!("permanently delete task `{}`", task_id),
+                    &format!("/loom-task remove {} confirm", task_id),
+                ));
+            }
+            vec![
+                "tools".into(),
+                "call".into(),
+                "agent_task_delete".into(),
+                "--".into(),
+                zed::serde_json::json!({ "task_id": task_id }).to_string(),
+            ]
+        }
+        _ => vec!["tools".into(), "call".into(), "agent_task_list".into()],
+    };
     let result = run_command_capture(program, &cmd_args, base_env, &[])?;
     Ok(format::format_task(&result, sub))
 }
 
+/// Worktree-relative directory `/loom-recall --save` writes into.
+const RECALL_CONTEXT_DIR: &str = ".loom/context";
+
+/// Most results `/loom-recall --limit` may ask for.
+const MAX_RECALL_LIMIT: usize = 100;
+
+/// Pull `--flag <value>` / `--flag=<value>` options named in `flags` out of free-text args.
+///
+/// Returns the values by flag and the remaining words (including any other `--` options).
+fn split_value_flags<'a>(
+    args: &[String],
+    flags: &[&'a str],
+) -> Result<(HashMap<&'a str, String>, Vec<String>), String> {
+    let mut values = HashMap::new();
+    let mut words = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let Some(flag) = flags.iter().find(|f| **f == name) else {
+            words.push(arg.clone());
+            continue;
+        };
+        let value = inline
+            .or_else(|| iter.next().cloned())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        values.insert(*flag, value);
+    }
+    Ok((values, words))
+}
+
+/// Parse a `--limit` value in `1..=max`.
+fn parse_limit(value: &str, max: usize) -> Result<usize, String> {
+    value
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=max).contains(n))
+        .ok_or_else(|| format!("--limit must be between 1 and {}, got `{}`", max, value))
+}
+
+/// `agent_context_recall_enhanced` payload and `--save` choice for `/loom-recall` args.
+///
+/// `--namespace <ns>`, `--limit <n>`, and `--save[=<slug>]` may appear anywhere; everything else
+/// is the query.
+fn recall_request(
+    args: &[String],
+) -> Result<(String, zed::serde_json::Value, Option<Option<String>>), String> {
+    let usage = "usage: /loom-recall <query> [--namespace <ns>] [--limit <n>] [--save[=<slug>]]";
+    let (flags, rest) = split_value_flags(args, &["--namespace", "--limit"])
+        .map_err(|e| format!("{} ({})", e, usage))?;
+    let mut save: Option<Option<String>> = None;
+    let mut words = Vec::new();
+    for arg in &rest {
+        match arg.strip_prefix("--save") {
+            Some("") => save = Some(None),
+            Some(rest) if rest.starts_with('=') => save = Some(Some(rest[1..].to_string())),
+            _ => words.push(arg.as_str()),
+        }
+    }
+    let query = words.join(" ");
+    if query.trim().is_empty() {
+        return Err(usage.to_string());
+    }
+    let mut payload = zed::serde_json::json!({ "query": query });
+    if let Some(namespace) = flags.get("--namespace") {
+        payload["namespace"] = namespace.as_str().into();
+    }
+    if let Some(limit) = flags.get("--limit") {
+        payload["limit"] = parse_limit(limit, MAX_RECALL_LIMIT)?.into();
+    }
+    Ok((query, payload, save))
+}
+
 fn dispatch_recall(
     args: &[String],
     program: &str,
     base_env: &[(String, String)],
+    worktree_root: Option<&str>,
 ) -> Result<FormattedOutput, String> {
-    let query = args.join(" ");
-    if query.trim().is_empty() {
-        return Err("usage: /loom-recall <query>".to_string());
+    let (query, payload, save) = recall_request(args)?;
+    let save_root = match save {
+        Some(_) => Some(worktree_root.ok_or("/loom-recall --save needs an open worktree")?),
+        None => None,
+    };
+    let result = run_command_capture(
+        program,
+        &[
+            "tools".into(),
+            "call".into(),
+            "agent_context_recall_enhanced".into(),
+            "--".into(),
+            payload.to_string(),
+        ],
+        base_env,
+        &[],
+    )?;
+
+    let saved_to = match (save_root, save) {
+        (Some(root), Some(slug)) if result.success() => {
+            let slug = worktree::slugify(slug.as_deref().unwrap_or(&query));
+            let rel = format!("{}/{}.md", RECALL_CONTEXT_DIR, slug);
+            let date = worktree::utc_date(download::unix_now_secs());
+            // Saved files get committed and pasted around; mask secrets as the rendered output does.
+            let contents = redact::mask(&format::recall_markdown(&query, &result.stdout, &date));
+            worktree::write_file(root, &rel, &contents)?;
+            Some(rel)
+        }
+        _ => None,
+    };
+    Ok(format::format_recall(
+        &result,
+        saved_to.as_deref(),
+        download::unix_now_secs(),
+    ))
+}
+
+/// `/loom-context [query]`: recall memories for the current worktree and namespace, formatted
+/// for the model to read directly.
+///
+/// The namespace is `settings.agent.default_namespace`, else the active session's namespace.
+fn dispatch_context(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    ctx: &DispatchContext,
+) -> Result<FormattedOutput, String> {
+    let project = ctx
+        .worktree_root
+        .and_then(|root| Path::new(root).file_name())
+        .map(|name| name.to_string_lossy().into_owned());
+    let query = match (args.join(" "), &project) {
+        (q, _) if !q.trim().is_empty() => q,
+        (_, Some(project)) => format!("{} project context", project),
+        (_, None) => return Err("usage: /loom-context <query> (no worktree to scope to)".into()),
+    };
+
+    let configured = ctx
+        .runtime_settings
+        .and_then(|rt| rt.extension.agent.default_namespace.clone())
+        .filter(|ns| !ns.trim().is_empty());
+    let namespace = configured.or_else(|| {
+        let session = run_json_or_plain(
+            program,
+            &[
+                "agent".into(),
+                "session".into(),
+                "--agent-id".into(),
+                ctx.agent_id().into(),
+            ],
+            base_env,
+        )
+        .ok()?;
+        session
+            .success()
+            .then(|| format::parse_session_snapshot(&session.stdout).namespace)
+            .flatten()
+    });
+
+    let mut payload = zed::serde_json::json!({ "query": query });
+    if let Some(ns) = &namespace {
+        payload["namespace"] = ns.clone().into();
     }
     let result = run_command_capture(
         program,
@@ -389,12 +1305,268 @@ fn dispatch_recall(
             "call".into(),
             "agent_context_recall_enhanced".into(),
             "--".into(),
-            format!(r#"{{"query":"{}"}}"#, query),
+            payload.to_string(),
+        ],
+        base_env,
+        &[],
+    )?;
+    Ok(format::format_context(
+        &result,
+        project.as_deref(),
+        namespace.as_deref(),
+    ))
+}
+
+fn dispatch_memory(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    agent_id: &str,
+) -> Result<FormattedOutput, String> {
+    let content = args.join(" ");
+    if content.trim().is_empty() {
+        return Err("usage: /loom-memory <text>".to_string());
+    }
+    let result = run_command_capture(
+        program,
+        &[
+            "tools".into(),
+            "call".into(),
+            "agent_memory_store".into(),
+            "--".into(),
+            zed::serde_json::json!({ "content": content, "agent_id": agent_id }).to_string(),
+        ],
+        base_env,
+        &[],
+    )?;
+    Ok(format::format_memory_store(&result, &content))
+}
+
+/// Post a handoff note for another agent with this session's namespace and summary.
+///
+/// `/loom-handoff <agent-id> [end] [note...]`: `end` also closes this editor's session and uses
+/// its summary. Uses the `agent_handoff` tool, falling back to an `agent_memory_store` entry
+/// owned by the target agent on hubs without it.
+fn dispatch_handoff(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    agent_id: &str,
+) -> Result<FormattedOutput, String> {
+    let target = args
+        .first()
+        .ok_or("usage: /loom-handoff <agent-id> [end] [note...]")?;
+    if target == agent_id {
+        return Err(format!(
+            "`{}` is this editor's agent; hand off to another agent ID (see /loom-agents)",
+            target
+        ));
+    }
+    let end = args.get(1).map(String::as_str) == Some("end");
+    let note = args[if end { 2 } else { 1 }..].join(" ");
+
+    let session_args: Vec<String> = if end {
+        vec![
+            "agent".into(),
+            "session-end".into(),
+            "--agent-id".into(),
+            agent_id.into(),
+            "--summarize".into(),
+        ]
+    } else {
+        vec![
+            "agent".into(),
+            "session".into(),
+            "--agent-id".into(),
+            agent_id.into(),
+        ]
+    };
+    let session = run_json_or_plain(program, &session_args, base_env)?;
+    let ended = end && session.success();
+    let mut snapshot = format::parse_session_snapshot(&session.stdout);
+    if !note.trim().is_empty() {
+        snapshot.summary = Some(match snapshot.summary.take() {
+            Some(summary) => format!("{}\n\n{}", note.trim(), summary),
+            None => note.trim().to_string(),
+        });
+    }
+    let summary = snapshot
+        .summary
+        .clone()
+        .unwrap_or_else(|| format!("Handoff from {} (no session summary).", agent_id));
+
+    let call = |tool: &str, payload: zed::serde_json::Value| {
+        run_command_capture(
+            program,
+            &[
+                "tools".into(),
+                "call".into(),
+                tool.into(),
+                "--".into(),
+                payload.to_string(),
+            ],
+            base_env,
+            &[],
+        )
+    };
+    let result = call(
+        "agent_handoff",
+        zed::serde_json::json!({
+            "from_agent": agent_id,
+            "to_agent": target,
+            "summary": summary,
+            "namespace": snapshot.namespace,
+        }),
+    )?;
+    if !format::is_missing_tool(&result) {
+        return Ok(format::format_handoff(
+            &result,
+            target,
+            &snapshot,
+            "agent_handoff",
+            ended,
+        ));
+    }
+
+    // Older hubs: leave the note in the target agent's memory, where its recall finds it.
+    let mut content = format!("Handoff from {}: {}", agent_id, summary);
+    if let Some(ns) = &snapshot.namespace {
+        content.push_str(&format!("\nNamespace: {}", ns));
+    }
+    let result = call(
+        "agent_memory_store",
+        zed::serde_json::json!({
+            "content": content,
+            "agent_id": target,
+            "tags": ["handoff", format!("from:{}", agent_id)],
+        }),
+    )?;
+    Ok(format::format_handoff(
+        &result,
+        target,
+        &snapshot,
+        "agent_memory_store",
+        ended,
+    ))
+}
+
+fn dispatch_inbox(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    agent_id: &str,
+) -> Result<FormattedOutput, String> {
+    let sub = args.first().map(String::as_str).unwrap_or("list");
+    let (tool, payload) = match sub {
+        "list" => (
+            "agent_inbox",
+            zed::serde_json::json!({ "agent_id": agent_id }),
+        ),
+        "read" => {
+            let id = args.get(1).ok_or("usage: /loom-inbox read <id>")?;
+            (
+                "agent_inbox_read",
+                zed::serde_json::json!({ "agent_id": agent_id, "id": id }),
+            )
+        }
+        other => {
+            return Err(format!(
+                "unknown /loom-inbox sub-command `{}` (usage: /loom-inbox [list|read <id>])",
+                other
+            ))
+        }
+    };
+    let call = |tool: &str, payload: zed::serde_json::Value| {
+        run_command_capture(
+            program,
+            &[
+                "tools".into(),
+                "call".into(),
+                tool.into(),
+                "--".into(),
+                payload.to_string(),
+            ],
+            base_env,
+            &[],
+        )
+    };
+    let result = call(tool, payload)?;
+    let now = download::unix_now_secs();
+    if let ("read", Some(id)) = (sub, args.get(1)) {
+        return Ok(format::format_inbox_message(&result, id, now));
+    }
+    if !format::is_missing_tool(&result) {
+        return Ok(format::format_inbox(&result, agent_id, now));
+    }
+
+    // Older hubs: `/loom-handoff` left the note in our memory instead, so recall it from there.
+    let result = call(
+        "agent_context_recall_enhanced",
+        zed::serde_json::json!({ "query": "Handoff", "agent_id": agent_id }),
+    )?;
+    Ok(format::format_generic(
+        &result,
+        "Loom Inbox (handoff memories)",
+    ))
+}
+
+fn dispatch_forget(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    agent_id: &str,
+) -> Result<FormattedOutput, String> {
+    const USAGE: &str =
+        "usage: /loom-forget id <entry_id> confirm | /loom-forget query <text> confirm";
+    let (sub, rest) = args.split_first().ok_or(USAGE)?;
+    let confirmed = is_confirmed(rest);
+    let target = if confirmed {
+        &rest[..rest.len() - 1]
+    } else {
+        rest
+    };
+    let (payload, description) = match sub.as_str() {
+        "id" => {
+            let [id] = target else {
+                return Err(USAGE.to_string());
+            };
+            (
+                zed::serde_json::json!({ "id": id, "agent_id": agent_id }),
+                format!("permanently delete memory entry `{}`", id),
+            )
+        }
+        "query" if !target.is_empty() => {
+            let query = target.join(" ");
+            let description = format!(
+                "permanently delete every memory entry matching \"{}\"",
+                query
+            );
+            (
+                zed::serde_json::json!({ "query": query, "agent_id": agent_id }),
+                description,
+            )
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+    if !confirmed {
+        return Ok(format::format_confirmation_required(
+            &description,
+            &format!("/loom-forget {} {} confirm", sub, target.join(" ")),
+        ));
+    }
+    let result = run_command_capture(
+        program,
+        &[
+            "tools".into(),
+            "call".into(),
+            "agent_memory_delete".into(),
+            "--".into(),
+            payload.to_string(),
         ],
         base_env,
         &[],
     )?;
-    Ok(format::format_recall(&result))
+    Ok(format::format_memory_delete(&result, &target.join(" ")))
 }
 
 fn dispatch_skills(
@@ -414,9 +1586,25 @@ fn dispatch_skills(
                 "call".into(),
                 "skills_search".into(),
                 "--".into(),
-                format!(r#"{{"query":"{}"}}"#, query),
+                zed::serde_json::json!({ "query": query }).to_string(),
             ]
         }
+        "show" => {
+            let name = args.get(1).ok_or("usage: /loom-skills show <name>")?;
+            let result = run_command_capture(
+                program,
+                &[
+                    "tools".into(),
+                    "call".into(),
+                    "skills_get".into(),
+                    "--".into(),
+                    zed::serde_json::json!({ "name": name }).to_string(),
+                ],
+                base_env,
+                &[],
+            )?;
+            return Ok(format::format_skill_detail(&result, name));
+        }
         "categories" => {
             vec!["tools".into(), "call".into(), "skills_categories".into()]
         }
@@ -428,15 +1616,40 @@ fn dispatch_skills(
     Ok(format::format_skills(&result))
 }
 
+/// Most results `/loom-search --limit` may ask for.
+const MAX_SEARCH_LIMIT: usize = 50;
+
+/// `deep_search` payload for `/loom-search` args: `--source <a[,b]>` and `--limit <n>` may
+/// appear anywhere; everything else is the query.
+fn search_payload(args: &[String]) -> Result<zed::serde_json::Value, String> {
+    let usage = "usage: /loom-search <query> [--source <name>[,<name>...]] [--limit <n>]";
+    let (flags, words) = split_value_flags(args, &["--source", "--limit"])
+        .map_err(|e| format!("{} ({})", e, usage))?;
+    let query = words.join(" ");
+    if query.trim().is_empty() {
+        return Err(usage.to_string());
+    }
+    let mut payload = zed::serde_json::json!({ "query": query });
+    if let Some(sources) = flags.get("--source") {
+        let sources: Vec<&str> = sources
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect();
+        payload["sources"] = sources.into();
+    }
+    if let Some(limit) = flags.get("--limit") {
+        payload["limit"] = parse_limit(limit, MAX_SEARCH_LIMIT)?.into();
+    }
+    Ok(payload)
+}
+
 fn dispatch_search(
     args: &[String],
     program: &str,
     base_env: &[(String, String)],
 ) -> Result<FormattedOutput, String> {
-    let query = args.join(" ");
-    if query.trim().is_empty() {
-        return Err("usage: /loom-search <query>".to_string());
-    }
+    let payload = search_payload(args)?;
     let result = run_command_capture(
         program,
         &[
@@ -444,7 +1657,7 @@ fn dispatch_search(
             "call".into(),
             "deep_search".into(),
             "--".into(),
-            format!(r#"{{"query":"{}"}}"#, query),
+            payload.to_string(),
         ],
         base_env,
         &[],
@@ -456,37 +1669,279 @@ fn dispatch_profile(
     args: &[String],
     program: &str,
     base_env: &[(String, String)],
+    session: &Mutex<SessionState>,
 ) -> Result<FormattedOutput, String> {
     let sub = args.first().map(|s| s.as_str()).unwrap_or("current");
     let cmd_args: Vec<String> = match sub {
         "list" => vec!["profile".into(), "list".into()],
+        "show" => {
+            let name = args.get(1).ok_or("usage: /loom-profile show <name>")?;
+            let result = run_json_or_plain(
+                program,
+                &["profile".into(), "show".into(), name.clone()],
+                base_env,
+            )?;
+            return Ok(format::format_profile_show(&result, name));
+        }
         "switch" => {
             let name = args.get(1).ok_or("usage: /loom-profile switch <name>")?;
             vec!["profile".into(), "switch".into(), name.clone()]
         }
+        "create" => {
+            let name = args.get(1).ok_or("usage: /loom-profile create <name>")?;
+            vec!["profile".into(), "create".into(), name.clone()]
+        }
+        "delete" => {
+            let name = args
+                .get(1)
+                .ok_or("usage: /loom-profile delete <name> confirm")?;
+            if !is_confirmed(&args[2..]) {
+                return Ok(format::format_confirmation_required(
+                    &format!("delete the `{}` profile and its server selection", name),
+                    &format!("/loom-profile delete {} confirm", name),
+                ));
+            }
+            vec!["profile".into(), "delete".into(), name.clone()]
+        }
         _ => vec!["profile".into(), "current".into()],
     };
     let result = run_command_capture(program, &cmd_args, base_env, &[])?;
-    Ok(format::format_profile(&result, sub))
+    if matches!(sub, "create" | "delete") && result.success() {
+        // Don't offer a deleted profile (or hide a new one) until the name cache expires.
+        session
+            .lock()
+            .map_err(|_| "session state mutex poisoned")?
+            .names
+            .remove(&completions::NameSource::Profiles);
+    }
+    let mut formatted = format::format_profile(&result, sub);
+    if sub == "switch" && result.success() {
+        // Switching profiles changes the servers every platform config should list; check for
+        // drift right away since users rarely remember to resync.
+        let status = run_json_or_plain(program, &["sync".into(), "status".into()], base_env)?;
+        if status.success() {
+            let drifted = format::drifted_platforms(&status.stdout, &completions::sync_platforms());
+            if !drifted.is_empty() {
+                formatted.append_section("Sync", &format::sync_suggestions(&args[1], &drifted));
+            }
+        }
+    }
+    Ok(formatted)
 }
 
 fn dispatch_call(
     args: &[String],
     program: &str,
     base_env: &[(String, String)],
+    session: &Mutex<SessionState>,
+    worktree: Option<&zed::Worktree>,
 ) -> Result<FormattedOutput, String> {
-    let tool_name = args
-        .first()
-        .ok_or("usage: /loom-call <tool_name> [json_args]")?;
+    let Some(tool_name) = args.first() else {
+        let cached = session
+            .lock()
+            .map_err(|_| "session state mutex poisoned")?
+            .tool_catalog
+            .as_ref()
+            .map(|catalog| catalog.names.clone());
+        let known_tools = cached
+            .or_else(|| catalog::fetch_tool_names(program, base_env))
+            .map(|names| names.into_iter().collect())
+            .unwrap_or_default();
+        return Ok(format::format_argument_wizard(
+            "/loom-call",
+            &[
+                WizardSlot {
+                    name: "<tool_name>",
+                    hint: "MCP tool to invoke (see `/loom-tools`)",
+                    candidates: known_tools,
+                },
+                WizardSlot {
+                    name: "[json_args]",
+                    hint: "Optional JSON object, e.g. `{query: 'auth'}` (relaxed JSON is fine), or `@file.json`",
+                    candidates: Vec::new(),
+                },
+            ],
+        ));
+    };
     let mut cmd_args = vec!["tools".into(), "call".into(), tool_name.clone()];
-    if args.len() > 1 {
-        cmd_args.push("--".into());
-        cmd_args.push(args[1..].join(" "));
+    cmd_args.extend(call_payload_args("/loom-call", args, worktree)?);
+    if let Some(violations) = preflight_call(&cmd_args[3..], tool_name, session)? {
+        return Ok(format::format_schema_violations(tool_name, &violations));
     }
     let result = run_command_capture(program, &cmd_args, base_env, &[])?;
     Ok(format::format_tool_call(&result, tool_name))
 }
 
+/// The `-- <payload>` that follows `tools call <tool>` for `/loom-call`-style arguments
+/// (`<tool> [json...]` or `<tool> @args.json`); empty when no payload was given.
+///
+/// Files are read through the worktree: Zed's sandbox doesn't expose the host path to `std::fs`.
+fn call_payload_args(
+    command: &str,
+    args: &[String],
+    worktree: Option<&zed::Worktree>,
+) -> Result<Vec<String>, String> {
+    let Some(tool_name) = args.first() else {
+        return Ok(Vec::new());
+    };
+    let file_arg = match args {
+        [_, file] => file.strip_prefix('@'),
+        _ => None,
+    };
+    let payload = if let Some(rel) = file_arg {
+        let wt = worktree.ok_or_else(|| {
+            format!(
+                "{} @<file> needs an open worktree to resolve the path",
+                command
+            )
+        })?;
+        let rel = worktree::relative_path(rel)?;
+        let raw = wt
+            .read_text_file(&rel)
+            .map_err(|e| format!("failed to read {}: {}", rel, e))?;
+        let payload = args::normalize_json(&raw)
+            .map_err(|e| format!("invalid JSON in `{}` for `{}`: {}", rel, tool_name, e))?;
+        expand_payload_file_refs(payload, Some(wt))?
+    } else if args.len() > 1 {
+        let raw = args[1..].join(" ");
+        if raw.starts_with(['{', '[']) {
+            let normalized = args::normalize_json(&raw)
+                .map_err(|e| format!("invalid JSON arguments for `{}`: {}", tool_name, e))?;
+            expand_payload_file_refs(normalized, worktree)?
+        } else {
+            raw
+        }
+    } else {
+        return Ok(Vec::new());
+    };
+    Ok(vec!["--".into(), payload])
+}
+
+/// `/loom-trace <tool> [json]`: `/loom-call` with `--trace`, rendered as a hop-by-hop tree.
+fn dispatch_trace(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    worktree: Option<&zed::Worktree>,
+) -> Result<FormattedOutput, String> {
+    let tool_name = args
+        .first()
+        .ok_or("usage: /loom-trace <tool> [json_args]")?;
+    let mut cmd_args = vec![
+        "tools".into(),
+        "call".into(),
+        tool_name.clone(),
+        "--trace".into(),
+    ];
+    cmd_args.extend(call_payload_args("/loom-trace", args, worktree)?);
+    let result = run_command_capture(program, &cmd_args, base_env, &[])?;
+    Ok(format::format_trace(&result, tool_name))
+}
+
+/// Inline the worktree files named by `"@file:path"` values in a normalized `/loom-call` payload.
+fn expand_payload_file_refs(
+    payload: String,
+    worktree: Option<&zed::Worktree>,
+) -> Result<String, String> {
+    let mut value: zed::serde_json::Value =
+        zed::serde_json::from_str(&payload).map_err(|e| e.to_string())?;
+    let mut read = |rel: &str| {
+        let wt = worktree.ok_or_else(|| {
+            format!(
+                "`@file:{}` in the payload needs an open worktree to resolve the path",
+                rel
+            )
+        })?;
+        wt.read_text_file(rel)
+            .map_err(|e| format!("failed to read {}: {}", rel, e))
+    };
+    if worktree::expand_file_refs(&mut value, &mut read)?.is_empty() {
+        return Ok(payload);
+    }
+    Ok(value.to_string())
+}
+
+/// Check a `/loom-call` payload against the tool's cached input schema before calling the CLI.
+///
+/// `payload_args` is what follows `tools call <tool>` (`-- <json>`, or nothing). Returns `None`
+/// when there's no cached schema, the payload isn't JSON, or nothing is wrong.
+fn preflight_call(
+    payload_args: &[String],
+    tool_name: &str,
+    session: &Mutex<SessionState>,
+) -> Result<Option<Vec<schema::SchemaViolation>>, String> {
+    let Some(tool_schema) = session
+        .lock()
+        .map_err(|_| "session state mutex poisoned")?
+        .tool_catalog
+        .as_ref()
+        .and_then(|catalog| catalog.schemas.get(tool_name).cloned())
+    else {
+        return Ok(None);
+    };
+    let payload = match payload_args.last() {
+        None => zed::serde_json::Value::Object(Default::default()),
+        Some(raw) => match zed::serde_json::from_str(raw) {
+            Ok(value) => value,
+            Err(_) => return Ok(None),
+        },
+    };
+    let violations = schema::validate(&payload, &tool_schema);
+    Ok((!violations.is_empty()).then_some(violations))
+}
+
+fn dispatch_health(
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let started = Instant::now();
+    let daemon = run_command_capture(program, &["status".into()], base_env, &[])?;
+    let daemon_ms = started.elapsed().as_millis() as u64;
+    if !daemon.success() {
+        return Ok(format::format_health(&daemon, daemon_ms, None, &[]));
+    }
+
+    let listing = run_json_or_plain(program, &["servers".into(), "list".into()], base_env)?;
+    let mut probes = Vec::new();
+    if listing.success() {
+        for name in format::parse_name_list(&listing.stdout, "servers") {
+            let started = Instant::now();
+            let result = run_command_capture(
+                program,
+                &["servers".into(), "ping".into(), name.clone()],
+                base_env,
+                &[],
+            );
+            let latency_ms = started.elapsed().as_millis() as u64;
+            let error = match &result {
+                Ok(r) if r.success() => None,
+                Ok(r) => Some(
+                    r.stderr
+                        .lines()
+                        .chain(r.stdout.lines())
+                        .find(|l| !l.trim().is_empty())
+                        .unwrap_or("exited with an error")
+                        .trim()
+                        .to_string(),
+                ),
+                Err(e) => Some(e.clone()),
+            };
+            probes.push(format::ServerProbe {
+                name,
+                reachable: error.is_none(),
+                latency_ms,
+                error,
+            });
+        }
+    }
+    Ok(format::format_health(
+        &daemon,
+        daemon_ms,
+        Some(&listing),
+        &probes,
+    ))
+}
+
 fn dispatch_dashboard(
     program: &str,
     base_env: &[(String, String)],
@@ -516,3 +1971,1370 @@ fn dispatch_dashboard(
     ];
     Ok(format::format_dashboard(&parts))
 }
+
+fn dispatch_queue(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let sub = args.first().map(|s| s.as_str()).unwrap_or("list");
+    match sub {
+        "cancel" => {
+            let job_id = args
+                .get(1)
+                .filter(|id| *id != "confirm")
+                .ok_or("usage: /loom-queue cancel <id> confirm")?;
+            if !is_confirmed(&args[2..]) {
+                return Ok(format::format_confirmation_required(
+                    &format!("cancel daemon job `{}`", job_id),
+                    &format!("/loom-queue cancel {} confirm", job_id),
+                ));
+            }
+            let result = run_command_capture(
+                program,
+                &["queue".into(), "cancel".into(), job_id.clone()],
+                base_env,
+                &[],
+            )?;
+            Ok(format::format_queue(&result, sub))
+        }
+        _ => {
+            let result =
+                run_command_capture(program, &["queue".into(), "list".into()], base_env, &[])?;
+            Ok(format::format_queue(&result, "list"))
+        }
+    }
+}
+
+fn dispatch_config(
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let show = run_command_capture(program, &["config".into(), "show".into()], base_env, &[])?;
+    let path = config_path(program, base_env);
+    Ok(format::format_config(&show, path.as_deref()))
+}
+
+/// The config file `loom config path` reports, if it reports one.
+fn config_path(program: &str, base_env: &[(String, String)]) -> Option<String> {
+    run_command_capture(program, &["config".into(), "path".into()], base_env, &[])
+        .ok()
+        .filter(|r| r.success())
+        .and_then(|r| {
+            r.stdout
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty())
+                .map(str::to_string)
+        })
+}
+
+/// Whether a failed run means this CLI doesn't have the sub-command at all.
+fn is_unsupported(result: &format::CommandResult) -> bool {
+    let output = format!("{}\n{}", result.stdout, result.stderr).to_ascii_lowercase();
+    !result.success()
+        && ["unknown command", "unknown subcommand", "no such command"]
+            .iter()
+            .any(|m| output.contains(m))
+}
+
+/// Where `/loom-backup` writes config snapshots, relative to the worktree root.
+const BACKUP_DIR: &str = ".loom/backups";
+
+/// Keeps everything in [`BACKUP_DIR`] out of git: backups can hold tokens from the config.
+const BACKUP_GITIGNORE: &str = "# Written by loom-zed: config backups can contain secrets.\n*\n";
+
+/// Run a POSIX `sh` snippet on the host, with `args` as `$1`, `$2`, ...
+///
+/// Backups live in the worktree and the loom config dir, which Zed's WASI sandbox can't see, so
+/// checks and listings there go through the host like `loom` and `tar` do.
+fn host_sh(
+    script: &str,
+    args: &[&str],
+    base_env: &[(String, String)],
+) -> Result<format::CommandResult, String> {
+    let mut sh_args = vec!["-c".to_string(), script.to_string(), "sh".to_string()];
+    sh_args.extend(args.iter().map(|a| a.to_string()));
+    run_command_capture("sh", &sh_args, base_env, &[])
+}
+
+/// Whether `test <flag> <path>` holds on the host (`-f` file, `-d` directory).
+fn host_test(flag: &str, path: &str, base_env: &[(String, String)]) -> bool {
+    host_sh(r#"test "$1" "$2""#, &[flag, path], base_env).is_ok_and(|r| r.success())
+}
+
+/// Create `dir` and its parents on the host.
+fn host_mkdir(dir: &str, base_env: &[(String, String)]) -> Result<(), String> {
+    let result = host_sh(r#"mkdir -p "$1""#, &[dir], base_env)?;
+    if result.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "failed to create {}: {}",
+            dir,
+            result.stderr.trim()
+        ))
+    }
+}
+
+/// Size of a host file in bytes.
+fn host_file_size(path: &str, base_env: &[(String, String)]) -> Option<u64> {
+    host_sh(r#"wc -c < "$1""#, &[path], base_env)
+        .ok()
+        .filter(|r| r.success())
+        .and_then(|r| r.stdout.trim().parse().ok())
+}
+
+/// Config directory and the entries in it a tarball backup holds: the config file and
+/// `profiles/` when present.
+fn config_backup_entries(
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<(String, Vec<String>), String> {
+    let file = config_path(program, base_env)
+        .ok_or("`loom config path` failed, so the config directory is unknown")?;
+    let file = Path::new(&file);
+    let dir = file
+        .parent()
+        .ok_or_else(|| format!("`{}` has no parent directory", file.display()))?;
+    let mut entries = vec![file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()];
+    if host_test("-d", &dir.join("profiles").to_string_lossy(), base_env) {
+        entries.push("profiles".into());
+    }
+    Ok((dir.to_string_lossy().to_string(), entries))
+}
+
+/// `/loom-backup [path]`: snapshot the hub configuration into the worktree with
+/// `loom config export`, or as a tarball of the config file and profiles on CLIs without it.
+fn dispatch_backup(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    worktree_root: Option<&str>,
+) -> Result<FormattedOutput, String> {
+    let root = worktree_root.ok_or("/loom-backup needs an open worktree to write into")?;
+    // The default name follows what was written: `loom config export` YAML or a tarball.
+    let (export_rel, tarball_rel) = match args.first() {
+        Some(path) => {
+            let rel = worktree::relative_path(path)?;
+            (rel.clone(), rel)
+        }
+        None => {
+            let stem = format!(
+                "{}/loom-config-{}",
+                BACKUP_DIR,
+                worktree::utc_stamp(download::unix_now_secs())
+            );
+            (format!("{}.yaml", stem), format!("{}.tar.gz", stem))
+        }
+    };
+    let gitignored = Path::new(&export_rel).starts_with(BACKUP_DIR);
+    if gitignored {
+        let dir = Path::new(root)
+            .join(BACKUP_DIR)
+            .to_string_lossy()
+            .to_string();
+        let result = host_sh(
+            r#"mkdir -p "$1" && { [ -f "$1/.gitignore" ] || printf '%s' "$2" > "$1/.gitignore"; }"#,
+            &[&dir, BACKUP_GITIGNORE],
+            base_env,
+        )?;
+        if !result.success() {
+            return Err(format!(
+                "failed to write {}/.gitignore: {}",
+                BACKUP_DIR,
+                result.stderr.trim()
+            ));
+        }
+    }
+    let dest_of = |rel: &str| -> Result<String, String> {
+        let path = Path::new(root).join(rel);
+        if let Some(parent) = path.parent() {
+            host_mkdir(&parent.to_string_lossy(), base_env)?;
+        }
+        Ok(path.to_string_lossy().to_string())
+    };
+    let dest = dest_of(&export_rel)?;
+    let exported = run_command_capture(
+        program,
+        &[
+            "config".into(),
+            "export".into(),
+            "--output".into(),
+            dest.clone(),
+        ],
+        base_env,
+        &[],
+    )?;
+    let (result, method, rel, dest) = if is_unsupported(&exported) {
+        let dest = dest_of(&tarball_rel)?;
+        let (dir, entries) = config_backup_entries(program, base_env)?;
+        let mut tar_args = vec!["-czf".to_string(), dest.clone(), "-C".into(), dir];
+        tar_args.extend(entries);
+        let result = run_command_capture("tar", &tar_args, base_env, &[])?;
+        (result, format::BackupMethod::Tarball, tarball_rel, dest)
+    } else {
+        (exported, format::BackupMethod::Export, export_rel, dest)
+    };
+    let bytes = host_file_size(&dest, base_env);
+    Ok(format::format_backup(
+        &result, &rel, &dest, bytes, method, gitignored,
+    ))
+}
+
+/// `/loom-restore [file] [confirm]`: list backups, or replace the configuration with one after
+/// confirmation.
+fn dispatch_restore(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    worktree_root: Option<&str>,
+) -> Result<FormattedOutput, String> {
+    let root = worktree_root.ok_or("/loom-restore needs an open worktree to read backups from")?;
+    let Some(file) = args.first().filter(|a| *a != "confirm") else {
+        return Ok(format::format_backup_list(
+            &list_backups(root, base_env),
+            BACKUP_DIR,
+        ));
+    };
+    let rel = worktree::relative_path(file)?;
+    let path = Path::new(root).join(&rel);
+    let src = path.to_string_lossy().to_string();
+    if !host_test("-f", &src, base_env) {
+        return Err(format!(
+            "backup `{}` not found in the worktree (`/loom-restore` lists {})",
+            rel, BACKUP_DIR
+        ));
+    }
+    if !is_confirmed(&args[1..]) {
+        return Ok(format::format_confirmation_required(
+            &format!(
+                "replace the current loom configuration and profiles with `{}`",
+                rel
+            ),
+            &format!("/loom-restore {} confirm", rel),
+        ));
+    }
+    let imported = run_command_capture(
+        program,
+        &["config".into(), "import".into(), src.clone()],
+        base_env,
+        &[],
+    )?;
+    let (result, method) = if is_unsupported(&imported) {
+        let (dir, _) = config_backup_entries(program, base_env)?;
+        let result = run_command_capture(
+            "tar",
+            &["-xzf".into(), src, "-C".into(), dir],
+            base_env,
+            &[],
+        )?;
+        (result, format::BackupMethod::Tarball)
+    } else {
+        (imported, format::BackupMethod::Export)
+    };
+    Ok(format::format_restore(&result, &rel, method))
+}
+
+/// Backups under [`BACKUP_DIR`] as `(relative path, bytes)`, newest first by name.
+///
+/// Listed on the host (see [`host_sh`]); `*` skips dotfiles such as the `.gitignore`.
+fn list_backups(root: &str, base_env: &[(String, String)]) -> Vec<(String, u64)> {
+    let dir = Path::new(root)
+        .join(BACKUP_DIR)
+        .to_string_lossy()
+        .to_string();
+    let script = r#"cd "$1" 2>/dev/null || exit 0
+for f in *; do
+  [ -f "$f" ] && printf '%s %s\n' "$(wc -c < "$f" | tr -d ' ')" "$f"
+done
+exit 0"#;
+    host_sh(script, &[&dir], base_env)
+        .map(|r| parse_backup_listing(&r.stdout))
+        .unwrap_or_default()
+}
+
+/// Parse `<bytes> <name>` lines from [`list_backups`], newest first by name.
+fn parse_backup_listing(stdout: &str) -> Vec<(String, u64)> {
+    let mut backups: Vec<(String, u64)> = stdout
+        .lines()
+        .filter_map(|line| {
+            let (size, name) = line.split_once(' ')?;
+            Some((format!("{}/{}", BACKUP_DIR, name), size.parse().ok()?))
+        })
+        .collect();
+    backups.sort_by(|a, b| b.0.cmp(&a.0));
+    backups
+}
+
+fn dispatch_metrics(
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let result = run_json_or_plain(program, &["metrics".into()], base_env)?;
+    let stderr = result.stderr.to_ascii_lowercase();
+    if result.success() || !stderr.contains("unknown command") {
+        return Ok(format::format_metrics(&result));
+    }
+    // Older CLIs have no `loom metrics`; the hub exposes the same data as a tool.
+    let result = run_command_capture(
+        program,
+        &[
+            "tools".into(),
+            "call".into(),
+            "hub_metrics".into(),
+            "--".into(),
+            "{}".into(),
+        ],
+        base_env,
+        &[],
+    )?;
+    Ok(format::format_metrics(&result))
+}
+
+/// List prompt recipes (or preview one) by asking the MCP wrapper, which owns the recipe set.
+fn dispatch_prompts(
+    args: &[String],
+    base_env: &[(String, String)],
+    ctx: &DispatchContext,
+) -> Result<FormattedOutput, String> {
+    let mcp = ctx
+        .runtime_settings
+        .map(|rt| rt.extension.mcp.clone())
+        .unwrap_or_default();
+    if !mcp.prompts.enabled() {
+        return Ok(FormattedOutput::plain(
+            "Prompt recipes are disabled (`settings.mcp.prompts.enabled = false`).\n".to_string(),
+        ));
+    }
+    let script = resolver::wrapper_script_path().ok_or_else(|| {
+        format!(
+            "prompt recipes are served by the MCP wrapper, but {} is missing",
+            WRAPPER_SCRIPT
+        )
+    })?;
+    let python = resolver::resolve_python(&mcp.wrapper).ok_or(
+        "prompt recipes are served by the python MCP wrapper, but no python3 was found \
+         (set settings.mcp.wrapper.python)",
+    )?;
+
+    let mut wrapper_args = vec![script, "--list-prompt-recipes".to_string()];
+    if let Some(path) = mcp.prompts.recipes_file() {
+        wrapper_args.push("--prompts-recipes-file".to_string());
+        wrapper_args.push(path.to_string());
+    }
+    let result = run_command_capture(&python, &wrapper_args, base_env, &[])?;
+    if !result.success() {
+        return Ok(format::format_generic(&result, "Loom Prompts"));
+    }
+    let recipes = format::parse_prompt_recipes(&result.stdout)?;
+
+    let Some(wanted) = args.first() else {
+        return Ok(format::format_prompts_list(&recipes, Some(&result.stderr)));
+    };
+    let wanted = wanted.strip_prefix(format::PROMPT_PREFIX).unwrap_or(wanted);
+    match recipes.iter().find(|r| r.short_name() == wanted) {
+        Some(recipe) => Ok(format::format_prompt_preview(recipe)),
+        None => Err(format!(
+            "unknown prompt recipe `{}` (available: {})",
+            wanted,
+            recipes
+                .iter()
+                .map(|r| r.short_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Events shown by `/loom-events` without an explicit count.
+const DEFAULT_EVENT_LIMIT: u32 = 20;
+
+fn dispatch_events(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let limit = match args.first() {
+        None => DEFAULT_EVENT_LIMIT,
+        Some(n) => n
+            .parse::<u32>()
+            .ok()
+            .filter(|n| (1..=500).contains(n))
+            .ok_or_else(|| {
+                format!(
+                    "invalid event count `{}` (usage: /loom-events [n], 1-500)",
+                    n
+                )
+            })?,
+    };
+    let result = run_json_or_plain(
+        program,
+        &["events".into(), "--limit".into(), limit.to_string()],
+        base_env,
+    )?;
+    Ok(format::format_events(&result, download::unix_now_secs()))
+}
+
+/// Audit entries fetched by `/loom-audit` without an explicit count.
+const DEFAULT_AUDIT_LIMIT: u32 = 50;
+
+/// `/loom-audit [n] [keyword...]`: the daemon's audit log, optionally filtered by keyword.
+fn dispatch_audit(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let (limit, keywords) = match args.first().map(|n| n.parse::<u32>()) {
+        Some(Ok(n)) if (1..=1000).contains(&n) => (n, &args[1..]),
+        Some(Ok(n)) => {
+            return Err(format!(
+                "invalid audit entry count `{}` (usage: /loom-audit [n] [keyword], 1-1000)",
+                n
+            ))
+        }
+        _ => (DEFAULT_AUDIT_LIMIT, args),
+    };
+    let result = run_json_or_plain(
+        program,
+        &["audit".into(), "--limit".into(), limit.to_string()],
+        base_env,
+    )?;
+    let keyword = keywords.join(" ");
+    Ok(format::format_audit(
+        &result,
+        Some(keyword.as_str()).filter(|k| !k.is_empty()),
+        download::unix_now_secs(),
+    ))
+}
+
+/// Calls made by `/loom-benchmark` without an explicit count, and the most it will make.
+const DEFAULT_BENCHMARK_RUNS: u32 = 5;
+const MAX_BENCHMARK_RUNS: u32 = 50;
+
+/// `/loom-benchmark [tool] [runs]`: call a tool repeatedly and report min/median/max latency.
+///
+/// Stops at the first failed call so a wrong tool name doesn't cost every run.
+fn dispatch_benchmark(
+    args: &[String],
+    default_tool: &str,
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let mut runs = DEFAULT_BENCHMARK_RUNS;
+    let mut tool = default_tool.to_string();
+    for arg in args {
+        match arg.parse::<u32>() {
+            Ok(n) if (1..=MAX_BENCHMARK_RUNS).contains(&n) => runs = n,
+            Ok(n) => {
+                return Err(format!(
+                    "invalid run count `{}` (usage: /loom-benchmark [tool] [runs], 1-{})",
+                    n, MAX_BENCHMARK_RUNS
+                ))
+            }
+            Err(_) => tool = arg.clone(),
+        }
+    }
+
+    let call = vec!["tools".into(), "call".into(), tool.clone()];
+    let mut samples = Vec::new();
+    let mut failure = None;
+    for _ in 0..runs {
+        let (result, ms) = run_timed(program, &call, base_env)?;
+        if !result.success() {
+            failure = Some(result);
+            break;
+        }
+        samples.push(ms);
+    }
+    Ok(format::format_benchmark(
+        &tool,
+        runs,
+        &samples,
+        failure.as_ref(),
+    ))
+}
+
+fn dispatch_doctor(
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let raw_version = version::probe_version(program, base_env);
+    let probes: Vec<Probe> = [["check"], ["status"]]
+        .iter()
+        .map(|args| {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            Probe {
+                label: format!("loom {}", join_args(&args)),
+                outcome: run_command_capture(program, &args, base_env, &[]),
+            }
+        })
+        .collect();
+    let findings = doctor::analyze(&probes, &version::check_version(raw_version.as_deref()));
+    Ok(format::format_doctor(
+        program,
+        raw_version.as_deref(),
+        &probes,
+        &findings,
+    ))
+}
+
+fn dispatch_paths(program: &str, ctx: &DispatchContext) -> Result<FormattedOutput, String> {
+    let work_dir = std::env::current_dir().map_err(|e| format!("work dir unavailable: {}", e))?;
+    let shown = |rel: &str| work_dir.join(rel).to_string_lossy().to_string();
+    let exists = |rel: &str| work_dir.join(rel).exists();
+
+    let active: Vec<String> = ctx
+        .installs
+        .lock()
+        .map_err(|_| "loom install cache mutex poisoned")?
+        .values()
+        .map(|install| install.release_version.clone())
+        .collect();
+
+    let mut entries = vec![PathEntry {
+        label: "Extension work dir".into(),
+        path: Some(work_dir.to_string_lossy().to_string()),
+        note: "Zed-managed; everything below lives here".into(),
+    }];
+    entries.push(PathEntry {
+        label: "Managed installs".into(),
+        path: Some(shown(download::INSTALL_ROOT)),
+        note: if exists(download::INSTALL_ROOT) {
+            String::new()
+        } else {
+            "not created yet (no download so far)".into()
+        },
+    });
+    for version in download::installed_versions() {
+        let rel = format!("{}/{}", download::INSTALL_ROOT, version);
+        entries.push(PathEntry {
+            label: format!("loom-core {}", version),
+            path: Some(shown(&rel)),
+            note: if active.contains(&version) {
+                "in use this session".into()
+            } else {
+                "older release".into()
+            },
+        });
+    }
+    entries.push(PathEntry {
+        label: "Slash command binary".into(),
+        path: Some(if Path::new(program).starts_with(download::INSTALL_ROOT) {
+            shown(program)
+        } else {
+            program.to_string()
+        }),
+        note: String::new(),
+    });
+    entries.push(PathEntry {
+        label: "MCP wrapper script".into(),
+        path: Some(shown(WRAPPER_SCRIPT)),
+        note: if exists(WRAPPER_SCRIPT) {
+            String::new()
+        } else {
+            "missing; the context server runs `loom proxy` directly".into()
+        },
+    });
+    entries.push(PathEntry {
+        label: "Log file".into(),
+        path: None,
+        note: "extension logs go to Zed's log (`zed: open log`)".into(),
+    });
+    entries.push(PathEntry {
+        label: "Install manifests".into(),
+        path: Some(shown(&format!(
+            "{}/<version>/{}",
+            download::INSTALL_ROOT,
+            download::MANIFEST_FILE
+        ))),
+        note: "asset, source URL, and SHA-256 per managed release".into(),
+    });
+    if let Some(root) = ctx.worktree_root {
+        for (label, rel) in [
+            ("Pinned recalls", RECALL_CONTEXT_DIR),
+            ("Tool schemas", TOOL_SCHEMA_DIR),
+        ] {
+            entries.push(PathEntry {
+                label: label.into(),
+                path: Some(Path::new(root).join(rel).to_string_lossy().to_string()),
+                note: "in the worktree".into(),
+            });
+        }
+    }
+    Ok(format::format_paths(&entries))
+}
+
+fn dispatch_install(
+    args: &[String],
+    program: &str,
+    ctx: &DispatchContext,
+) -> Result<FormattedOutput, String> {
+    let settings = ctx
+        .runtime_settings
+        .map(|rt| rt.extension.download.clone())
+        .unwrap_or_default();
+    if !settings.enabled() {
+        return Err(
+            "managed downloads are disabled (settings.download.enabled = false); \
+             install loom-core with your package manager"
+                .to_string(),
+        );
+    }
+    let requested = args.first().map(String::as_str).filter(|t| *t != "latest");
+    let install = download::force_install(ctx.installs, &settings, requested)?;
+
+    // Commands only pick the new install up if it's the release the settings resolve to.
+    let configured = settings
+        .tag
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+    let selected = requested == configured;
+    let managed_in_use = Path::new(program).starts_with(download::INSTALL_ROOT);
+    Ok(format::format_install(
+        &install,
+        program,
+        configured,
+        selected,
+        managed_in_use,
+    ))
+}
+
+fn dispatch_cleanup(
+    args: &[String],
+    program: &str,
+    ctx: &DispatchContext,
+) -> Result<FormattedOutput, String> {
+    let mut active: Vec<String> = ctx
+        .installs
+        .lock()
+        .map_err(|_| "loom install cache mutex poisoned")?
+        .values()
+        .map(|install| install.release_version.clone())
+        .collect();
+    active.extend(download::managed_version(program));
+
+    let installed = download::installed_versions();
+    let stale = download::stale_versions(&installed, &active);
+    let confirmed = is_confirmed(args);
+    let rows: Vec<CleanupRow> = installed
+        .iter()
+        .map(|version| {
+            let bytes = download::installed_version_size(version);
+            let action = if !stale.contains(version) {
+                CleanupAction::Keep(if active.contains(version) {
+                    "in use"
+                } else {
+                    "newest"
+                })
+            } else if !confirmed {
+                CleanupAction::Remove
+            } else {
+                match download::remove_installed_version(version) {
+                    Ok(_) => CleanupAction::Removed,
+                    Err(e) => CleanupAction::Failed(e),
+                }
+            };
+            CleanupRow {
+                version: version.clone(),
+                bytes,
+                action,
+            }
+        })
+        .collect();
+    Ok(format::format_cleanup(&rows))
+}
+
+/// `/loom-update [confirm]`: show what the latest release would bring, then install it.
+fn dispatch_update(
+    args: &[String],
+    program: &str,
+    ctx: &DispatchContext,
+) -> Result<FormattedOutput, String> {
+    let settings = ctx
+        .runtime_settings
+        .map(|rt| rt.extension.download.clone())
+        .unwrap_or_default();
+    if !settings.enabled() {
+        return Err(
+            "managed downloads are disabled (settings.download.enabled = false); \
+             upgrade your loom binary with your package manager"
+                .to_string(),
+        );
+    }
+    let outcome = download::update_loom_install(ctx.installs, &settings, is_confirmed(args))?;
+    let notes = match &outcome {
+        download::UpdateOutcome::Available { version, .. } => {
+            download::release_notes(settings.repo(), version)
+        }
+        _ => None,
+    };
+    // Managed installs live under the extension's relative install root.
+    let managed_in_use = Path::new(program).starts_with(download::INSTALL_ROOT);
+    Ok(format::format_update(
+        &outcome,
+        program,
+        managed_in_use,
+        notes.as_deref(),
+    ))
+}
+
+/// Per-server tool counts from the cached tool catalog, re-listing tools once it goes stale.
+///
+/// The fresh listing is a recorded run, so the catalog refresh after the command picks it up.
+fn server_tool_counts(
+    program: &str,
+    base_env: &[(String, String)],
+    session: &Mutex<SessionState>,
+) -> Result<BTreeMap<String, catalog::ServerTools>, String> {
+    let now = download::unix_now_secs();
+    {
+        let session = session.lock().map_err(|_| "session state mutex poisoned")?;
+        if let Some(catalog) = session.tool_catalog.as_ref().filter(|c| c.is_fresh(now)) {
+            if !catalog.servers.is_empty() {
+                return Ok(catalog.servers.clone());
+            }
+        }
+    }
+    Ok(catalog::fetch_catalog(program, base_env, now)
+        .map(|catalog| catalog.servers)
+        .unwrap_or_default())
+}
+
+/// `/loom-servers enable|disable|restart <name>`, followed by a `servers show` for the new state.
+fn dispatch_server_action(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let action = args[0].as_str();
+    if !matches!(action, "enable" | "disable" | "restart") {
+        return Err(format!(
+            "unknown /loom-servers sub-command `{}` (usage: /loom-servers [list|enable|disable|restart <name>])",
+            action
+        ));
+    }
+    let name = args
+        .get(1)
+        .ok_or_else(|| format!("usage: /loom-servers {} <name>", action))?;
+    let result = run_command_capture(
+        program,
+        &["servers".into(), action.into(), name.clone()],
+        base_env,
+        &[],
+    )?;
+    let state = if result.success() {
+        run_json_or_plain(
+            program,
+            &["servers".into(), "show".into(), name.clone()],
+            base_env,
+        )
+        .ok()
+        .filter(|shown| shown.success())
+        .and_then(|shown| format::parse_server_state(&shown.stdout))
+    } else {
+        None
+    };
+    Ok(format::format_server_action(
+        &result,
+        action,
+        name,
+        state.as_ref(),
+    ))
+}
+
+fn dispatch_kill(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let server = args.first().ok_or("usage: /loom-kill <server> confirm")?;
+    if !is_confirmed(&args[1..]) {
+        return Ok(format::format_confirmation_required(
+            &format!(
+                "force-terminate the `{}` server process (the daemon may respawn it)",
+                server
+            ),
+            &format!("/loom-kill {} confirm", server),
+        ));
+    }
+    let result = run_command_capture(
+        program,
+        &["servers".into(), "kill".into(), server.clone()],
+        base_env,
+        &[],
+    )?;
+    let listing = if result.success() {
+        run_command_capture(program, &["servers".into(), "list".into()], base_env, &[]).ok()
+    } else {
+        None
+    };
+    Ok(format::format_kill(&result, server, listing.as_ref()))
+}
+
+fn dispatch_last(
+    args: &[String],
+    session: &Mutex<SessionState>,
+) -> Result<FormattedOutput, String> {
+    let session = session.lock().map_err(|_| "session state mutex poisoned")?;
+    let name = match args.first() {
+        Some(cmd) if cmd.starts_with("loom-") => cmd.clone(),
+        Some(cmd) => format!("loom-{}", cmd),
+        None => match session.last_command.clone() {
+            Some(name) => name,
+            None => {
+                return Ok(FormattedOutput::plain(
+                    "No Loom commands have run yet in this session.\n".to_string(),
+                ))
+            }
+        },
+    };
+    match session.last_runs.get(&name) {
+        Some(runs) => Ok(format::format_raw_runs(&name, runs)),
+        None => Ok(FormattedOutput::plain(format!(
+            "No recorded output for `/{}` in this session.\n",
+            name
+        ))),
+    }
+}
+
+/// `/loom-workflows [list|run <name>]`: list loom workflows or run one and show its steps.
+fn dispatch_workflows(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    match args.first().map(String::as_str).unwrap_or("list") {
+        "list" => {
+            let result =
+                run_json_or_plain(program, &["workflows".into(), "list".into()], base_env)?;
+            Ok(format::format_workflows(&result))
+        }
+        "run" => {
+            let name = args.get(1).ok_or("usage: /loom-workflows run <name>")?;
+            let result = run_json_or_plain(
+                program,
+                &["workflows".into(), "run".into(), name.clone()],
+                base_env,
+            )?;
+            Ok(format::format_workflow_run(&result, name))
+        }
+        other => Err(format!(
+            "unknown /loom-workflows sub-command `{}` (usage: /loom-workflows [list|run <name>])",
+            other
+        )),
+    }
+}
+
+/// `/loom-alias [list|<name> = <command>|remove <name>]`. Running an alias is handled by
+/// [`dispatch_command`] before this is reached.
+fn dispatch_alias(args: &[String], ctx: &DispatchContext) -> Result<FormattedOutput, String> {
+    let settings = ctx
+        .runtime_settings
+        .map(|rt| rt.extension.commands.aliases.clone())
+        .unwrap_or_default();
+    let mut saved = aliases::load_saved();
+    let changed = match args.first().map(String::as_str) {
+        None | Some("list") => None,
+        Some("remove") => {
+            let name = args.get(1).ok_or("usage: /loom-alias remove <name>")?;
+            let name = name.trim_start_matches('/');
+            if saved.remove(name).is_none() {
+                return Err(if settings.contains_key(name) {
+                    format!(
+                        "alias `{}` is defined in settings.commands.aliases; remove it there",
+                        name
+                    )
+                } else {
+                    format!("unknown alias `{}`; `/loom-alias list` shows them", name)
+                });
+            }
+            aliases::save(&saved)?;
+            Some(format::AliasChange::Removed(name.to_string()))
+        }
+        Some(name) if !args.join(" ").contains('=') => {
+            return Err(format!(
+                "unknown alias `{}`; define it with `/loom-alias {} = <command>`",
+                name, name
+            ));
+        }
+        Some(_) => {
+            let (name, target) = aliases::parse_definition(args)?;
+            saved.insert(name.clone(), target);
+            aliases::save(&saved)?;
+            let shadowed = settings.contains_key(&name);
+            Some(format::AliasChange::Saved { name, shadowed })
+        }
+    };
+    Ok(format::format_aliases(
+        &aliases::merged(&settings, &saved),
+        changed.as_ref(),
+    ))
+}
+
+/// `/loom-favorites [list|add <tool>|remove <tool>]`. Pins are checked against `loom tools list`
+/// when it parses, so a typo isn't pinned silently.
+fn dispatch_favorites(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let mut pinned = favorites::load();
+    let sub = args.first().map(String::as_str).unwrap_or("list");
+    let listing = run_json_or_plain(program, &["tools".into(), "list".into()], base_env)?;
+    let changed = match sub {
+        "list" => None,
+        "add" | "remove" => {
+            let tool = args
+                .get(1)
+                .ok_or_else(|| format!("usage: /loom-favorites {} <tool>", sub))?;
+            if sub == "add" {
+                let known = format::parse_tool_rows(&listing.stdout);
+                if listing.success() && !known.is_empty() && !known.iter().any(|r| &r.name == tool)
+                {
+                    return Err(format!(
+                        "unknown tool `{}`; `/loom-tools search <query>` finds tool names",
+                        tool
+                    ));
+                }
+                if !favorites::add(&mut pinned, tool) {
+                    return Err(format!("`{}` is already pinned", tool));
+                }
+            } else if !favorites::remove(&mut pinned, tool) {
+                return Err(format!("`{}` is not pinned", tool));
+            }
+            favorites::save(&pinned)?;
+            Some((
+                if sub == "add" { "added" } else { "removed" },
+                tool.as_str(),
+            ))
+        }
+        other => {
+            return Err(format!(
+                "unknown /loom-favorites sub-command `{}` (expected list, add, or remove)",
+                other
+            ))
+        }
+    };
+    Ok(format::format_favorites(&pinned, Some(&listing), changed))
+}
+
+/// `/loom-permissions [list|allow <tool>|deny <tool>]`, re-listing after a change.
+fn dispatch_permissions(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+) -> Result<FormattedOutput, String> {
+    let list = || run_json_or_plain(program, &["permissions".into(), "list".into()], base_env);
+    match args.first().map(String::as_str).unwrap_or("list") {
+        "list" => Ok(format::format_permissions(&list()?, None, None)),
+        action @ ("allow" | "deny") => {
+            let tool = args
+                .get(1)
+                .ok_or_else(|| format!("usage: /loom-permissions {} <tool>", action))?;
+            let result = run_command_capture(
+                program,
+                &["permissions".into(), action.into(), tool.clone()],
+                base_env,
+                &[],
+            )?;
+            let listing = if result.success() { Some(list()?) } else { None };
+            Ok(format::format_permissions(
+                &result,
+                Some((action, tool)),
+                listing.as_ref(),
+            ))
+        }
+        other => Err(format!(
+            "unknown /loom-permissions sub-command `{}` (usage: /loom-permissions [list|allow <tool>|deny <tool>])",
+            other
+        )),
+    }
+}
+
+/// `/loom-retry`: apply the automatic fix for the last failure, then re-run that command.
+///
+/// The failure is classified with [`doctor::analyze_failure`]; when the first finding with an
+/// [`doctor::AutoFix`] can't be applied, the command is not re-run.
+fn dispatch_retry(
+    program: &str,
+    base_env: &[(String, String)],
+    ctx: &DispatchContext,
+) -> Result<FormattedOutput, String> {
+    let failure = ctx
+        .session
+        .lock()
+        .map_err(|_| "session state mutex poisoned")?
+        .last_failure
+        .clone();
+    let Some(failure) = failure else {
+        return Ok(FormattedOutput::plain(
+            "No failed Loom command to retry in this session.\n".to_string(),
+        ));
+    };
+
+    let findings = doctor::analyze_failure(&failure.runs, failure.error.as_deref());
+    let fix = findings
+        .iter()
+        .find_map(|f| doctor::AutoFix::for_finding(f.id).map(|fix| (f, fix)));
+    let remediation = match fix {
+        Some((finding, fix)) => format::RetryRemediation::Applied {
+            finding,
+            action: fix.describe(),
+            outcome: apply_auto_fix(fix, program, base_env, ctx),
+        },
+        None => match findings.iter().find(|f| f.id != "probe-failed") {
+            Some(finding) => format::RetryRemediation::Manual(finding),
+            None => format::RetryRemediation::None,
+        },
+    };
+    let fixed = !matches!(
+        &remediation,
+        format::RetryRemediation::Applied {
+            outcome: Err(_),
+            ..
+        }
+    );
+    let retried = fixed.then(|| {
+        let before = peek_recorded_runs().len();
+        let result = dispatch_command(&failure.command, &failure.args, program, base_env, ctx);
+        let runs = peek_recorded_runs().split_off(before);
+        if let Ok(mut session) = ctx.session.lock() {
+            session.note_outcome(
+                &failure.command,
+                &failure.args,
+                &runs,
+                result.as_ref().err().map(String::as_str),
+            );
+        }
+        result
+    });
+    Ok(format::format_retry(
+        &failure.command,
+        &failure.args,
+        &remediation,
+        retried,
+    ))
+}
+
+/// Run one automatic fix, returning a one-line summary of what happened.
+fn apply_auto_fix(
+    fix: doctor::AutoFix,
+    program: &str,
+    base_env: &[(String, String)],
+    ctx: &DispatchContext,
+) -> Result<String, String> {
+    let args: Vec<String> = match fix {
+        doctor::AutoFix::StartDaemon => vec!["start".into()],
+        doctor::AutoFix::RestartDaemon => vec!["restart".into()],
+        doctor::AutoFix::Reinstall => {
+            dispatch_install(&[], program, ctx)?;
+            return Ok("Re-downloaded the managed loom-core install.".into());
+        }
+    };
+    let result = run_command_capture(program, &args, base_env, &[])?;
+    let summary = result
+        .stdout
+        .lines()
+        .chain(result.stderr.lines())
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or("done")
+        .to_string();
+    if result.success() {
+        Ok(format!("`loom {}`: {}", join_args(&args), summary))
+    } else {
+        Err(format!(
+            "`loom {}` failed (exit {}): {}",
+            join_args(&args),
+            result.exit_code,
+            summary
+        ))
+    }
+}
+
+/// `/loom-namespace [list]`: namespaces with memory/session counts and the active one marked.
+///
+/// Counts come from `agent_memory_stats`; when it has no session counts, sessions are tallied
+/// from `agent session-list`. Missing stats leave the counts blank rather than failing.
+fn dispatch_namespace(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    agent_id: &str,
+) -> Result<FormattedOutput, String> {
+    match args.first().map(String::as_str) {
+        None | Some("list") => {}
+        Some(other) => {
+            return Err(format!(
+                "unknown /loom-namespace sub-command `{}` (usage: /loom-namespace [list])",
+                other
+            ))
+        }
+    }
+    let list = run_json_or_plain(
+        program,
+        &["agent".into(), "namespace-list".into()],
+        base_env,
+    )?;
+    let names = if list.success() {
+        format::parse_name_list(&list.stdout, "namespaces")
+    } else {
+        Default::default()
+    };
+
+    let stats = run_command_capture(
+        program,
+        &[
+            "tools".into(),
+            "call".into(),
+            "agent_memory_stats".into(),
+            "--".into(),
+            "{}".into(),
+        ],
+        base_env,
+        &[],
+    )?;
+    let mut counts = if stats.success() {
+        format::parse_namespace_counts(&stats.stdout)
+    } else {
+        Default::default()
+    };
+    if counts.values().all(|c| c.sessions.is_none()) {
+        let sessions =
+            run_json_or_plain(program, &["agent".into(), "session-list".into()], base_env)?;
+        if sessions.success() {
+            for (ns, n) in format::count_sessions_by_namespace(&sessions.stdout) {
+                counts.entry(ns).or_default().sessions = Some(n);
+            }
+        }
+    }
+
+    let session = run_json_or_plain(
+        program,
+        &[
+            "agent".into(),
+            "session".into(),
+            "--agent-id".into(),
+            agent_id.into(),
+        ],
+        base_env,
+    )?;
+    let active = session
+        .success()
+        .then(|| format::parse_session_snapshot(&session.stdout).namespace)
+        .flatten();
+    Ok(format::format_namespaces(
+        &list,
+        &names,
+        &counts,
+        active.as_deref(),
+    ))
+}
+
+/// Reports `/loom-export` can write, by command name without the `loom-` prefix.
+const EXPORT_REPORTS: &[&str] = &["dashboard", "status", "check"];
+
+/// Worktree-relative file `/loom-export` writes when no path is given.
+const DEFAULT_EXPORT_PATH: &str = "loom-report.md";
+
+fn dispatch_export(
+    args: &[String],
+    program: &str,
+    base_env: &[(String, String)],
+    ctx: &DispatchContext,
+) -> Result<FormattedOutput, String> {
+    let usage = "usage: /loom-export <dashboard|status|check> [path]";
+    let report = args.first().ok_or(usage)?.trim_start_matches("loom-");
+    if !EXPORT_REPORTS.contains(&report) {
+        return Err(format!("unknown report `{}` ({})", report, usage));
+    }
+    let root = ctx
+        .worktree_root
+        .ok_or("/loom-export needs an open worktree to write into")?;
+    let rel = worktree::relative_path(args.get(1).map_or(DEFAULT_EXPORT_PATH, |p| p.as_str()))?;
+
+    let exported = dispatch_named(&format!("loom-{}", report), &[], program, base_env, ctx)?;
+    let date = worktree::utc_date(download::unix_now_secs());
+    let contents = redact::mask(&format::export_markdown(report, &date, &exported.text));
+    let path = worktree::write_file(root, &rel, &contents)?;
+    Ok(format::format_export(
+        report,
+        &rel,
+        &path.to_string_lossy(),
+        contents.len(),
+        &exported,
+    ))
+}
+
+/// Invocations shown by `/loom-history` without an explicit count.
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+fn dispatch_history(
+    args: &[String],
+    session: &Mutex<SessionState>,
+) -> Result<FormattedOutput, String> {
+    let limit = match args.first() {
+        None => DEFAULT_HISTORY_LIMIT,
+        Some(n) => n
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=HISTORY_CAPACITY).contains(n))
+            .ok_or_else(|| {
+                format!(
+                    "invalid history count `{}` (usage: /loom-history [n], 1-{})",
+                    n, HISTORY_CAPACITY
+                )
+            })?,
+    };
+    let session = session.lock().map_err(|_| "session state mutex poisoned")?;
+    let total = session.history.len();
+    let entries: Vec<_> = session
+        .history
+        .iter()
+        .skip(total.saturating_sub(limit))
+        .collect();
+    Ok(format::format_history(
+        &entries,
+        total,
+        download::unix_now_secs(),
+    ))
+}
+
+/// Destructive sub-commands require a trailing `confirm` token.
+fn is_confirmed(rest: &[String]) -> bool {
+    rest.last().map(|s| s.as_str()) == Some("confirm")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_list_options_parse() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            parse_tool_list_options(&args(&["sort:server", "limit:20"])).unwrap(),
+            ToolListOptions {
+                sort: Some(ToolSort::Server),
+                limit: Some(20)
+            }
+        );
+        assert!(parse_tool_list_options(&args(&["sort:size"])).is_err());
+        assert!(parse_tool_list_options(&args(&["limit:0"])).is_err());
+        assert!(parse_tool_list_options(&args(&["github"])).is_err());
+    }
+
+    #[test]
+    fn recall_flags_parse_anywhere() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let (query, payload, save) = recall_request(&args(&[
+            "auth",
+            "--namespace",
+            "work",
+            "flow",
+            "--limit=5",
+            "--save",
+        ]))
+        .unwrap();
+        assert_eq!(query, "auth flow");
+        assert_eq!(
+            payload,
+            zed::serde_json::json!({"query": "auth flow", "namespace": "work", "limit": 5})
+        );
+        assert_eq!(save, Some(None));
+        assert!(recall_request(&args(&["auth", "--limit", "0"])).is_err());
+        assert!(recall_request(&args(&["auth", "--namespace"])).is_err());
+        assert!(recall_request(&args(&["--limit", "3"])).is_err());
+    }
+
+    #[test]
+    fn search_flags_build_payload() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            search_payload(&args(&[
+                "--source",
+                "github,docs",
+                "rate",
+                "limits",
+                "--limit=10"
+            ]))
+            .unwrap(),
+            zed::serde_json::json!({
+                "query": "rate limits",
+                "sources": ["github", "docs"],
+                "limit": 10
+            })
+        );
+        assert_eq!(
+            search_payload(&args(&["rate"])).unwrap(),
+            zed::serde_json::json!({"query": "rate"})
+        );
+        assert!(search_payload(&args(&["rate", "--limit", "51"])).is_err());
+        assert!(search_payload(&args(&["--source", "web"])).is_err());
+    }
+
+    #[test]
+    fn backup_listing_parses_host_output() {
+        let listing = parse_backup_listing(
+            "3000 loom-config-20261016-120000.tar.gz\n\
+             812 loom-config-20261017-090000.yaml\n\
+             garbage\n",
+        );
+        assert_eq!(
+            listing,
+            vec![
+                (
+                    ".loom/backups/loom-config-20261017-090000.yaml".to_string(),
+                    812
+                ),
+                (
+                    ".loom/backups/loom-config-20261016-120000.tar.gz".to_string(),
+                    3000
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn queue_cancel_needs_a_job_id_before_confirm() {
+        let args = ["cancel".to_string(), "confirm".to_string()];
+        let err = dispatch_queue(&args, "loom", &[]).err();
+        assert_eq!(
+            err.as_deref(),
+            Some("usage: /loom-queue cancel <id> confirm")
+        );
+    }
+
+    #[test]
+    fn usage_errors_are_told_apart() {
+        assert!(is_usage_error("usage: /loom-server <name>"));
+        assert!(is_usage_error("invalid JSON arguments for `x`: eof"));
+        assert!(is_usage_error(
+            "`../x` must be a relative path inside the worktree"
+        ));
+        assert!(is_usage_error(
+            "`/loom-secrets` is disabled by policy: it is not listed in `settings.commands.enabled`."
+        ));
+        assert!(is_usage_error(
+            "/loom-call @<file> needs an open worktree to resolve the path"
+        ));
+        assert!(is_usage_error(
+            "invalid event count `x` (usage: /loom-events [n], 1-500)"
+        ));
+        assert!(!is_usage_error("session state mutex poisoned"));
+        assert!(!is_usage_error("invalid token"));
+        assert!(!is_usage_error("unknown server foo"));
+        assert!(!is_usage_error("hub: limit must be positive"));
+        assert!(!is_usage_error(
+            "failed to reach /loom-hub: connection refused"
+        ));
+        assert!(!is_usage_error(
+            "failed to spawn process: No such file or directory"
+        ));
+    }
+}
@@ -0,0 +1,471 @@
+use crate::commands::{join_args, RecordedRun};
+use crate::format::CommandResult;
+use crate::version::{VersionStatus, MIN_LOOM_VERSION};
+
+/// How urgently a finding needs attention.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Critical,
+    Warning,
+}
+
+/// A known failure pattern in CLI output and the next step that fixes it.
+struct Signature {
+    id: &'static str,
+    title: &'static str,
+    severity: Severity,
+    /// A line matches when it contains any of these (lowercase) ...
+    any: &'static [&'static str],
+    /// ... and all of these.
+    all: &'static [&'static str],
+    remedy: &'static str,
+}
+
+const SIGNATURES: &[Signature] = &[
+    Signature {
+        id: "socket-permission",
+        title: "Daemon socket permission denied",
+        severity: Severity::Critical,
+        any: &["permission denied", "operation not permitted"],
+        all: &[],
+        remedy: "The daemon socket belongs to another user. Run `/loom-restart` so the daemon is \
+                 recreated under your account, or fix the socket's ownership.",
+    },
+    Signature {
+        id: "daemon-down",
+        title: "Daemon not running",
+        severity: Severity::Critical,
+        any: &[
+            "daemon not running",
+            "daemon is not running",
+            "is the daemon running",
+            "connection refused",
+            "failed to connect",
+            "socket not found",
+        ],
+        all: &[],
+        remedy: "Run `/loom-start` to start the daemon.",
+    },
+    Signature {
+        id: "daemon-down",
+        title: "Daemon not running",
+        severity: Severity::Critical,
+        any: &["no such file or directory"],
+        all: &["sock"],
+        remedy: "Run `/loom-start` to start the daemon.",
+    },
+    Signature {
+        id: "missing-secrets",
+        title: "Missing secrets",
+        severity: Severity::Critical,
+        any: &["not set", "missing", "not found", "unset", "empty"],
+        all: &["secret"],
+        remedy: "Run `/loom-secrets validate` to see which secrets are missing, then set them \
+                 and `/loom-restart`.",
+    },
+    Signature {
+        id: "missing-token",
+        title: "Missing API token",
+        severity: Severity::Critical,
+        any: &["not set", "missing", "unset", "empty"],
+        all: &["token"],
+        remedy: "Run `/loom-secrets validate` to see which tokens are missing, then set them \
+                 and `/loom-restart`.",
+    },
+    Signature {
+        id: "auth-expired",
+        title: "Credentials rejected or expired",
+        severity: Severity::Critical,
+        any: &[
+            "token expired",
+            "token has expired",
+            "expired token",
+            "unauthorized",
+            "authentication failed",
+            "bad credentials",
+            "invalid token",
+        ],
+        all: &[],
+        remedy: "Update the token with `/loom-secrets set <KEY> <value>` (or in your env file), \
+                 then `/loom-restart` so the daemon picks it up.",
+    },
+    Signature {
+        id: "unknown-subcommand",
+        title: "Command not supported by this loom-core",
+        severity: Severity::Warning,
+        any: &[
+            "unknown command",
+            "unknown subcommand",
+            "unrecognized subcommand",
+            "no such command",
+            "unknown flag",
+            "flag provided but not defined",
+        ],
+        all: &[],
+        remedy: "Run `/loom-update` to upgrade loom-core, or `/loom-env` to check which binary \
+                 slash commands run.",
+    },
+    Signature {
+        id: "server-failed",
+        title: "Upstream MCP server unhealthy",
+        severity: Severity::Warning,
+        any: &["crashed", "unhealthy", "failed to start", "exited"],
+        all: &[],
+        remedy: "Run `/loom-servers` to find the failing server; `/loom-kill <server> confirm` \
+                 forces a respawn.",
+    },
+    Signature {
+        id: "config-invalid",
+        title: "Invalid configuration",
+        severity: Severity::Warning,
+        any: &["invalid", "parse error", "failed to parse", "unmarshal"],
+        all: &["config"],
+        remedy: "Fix the reported config error, then run `/loom-sync status` to confirm the \
+                 platforms are in sync.",
+    },
+];
+
+/// One probe `/loom-doctor` ran and what it returned.
+pub(crate) struct Probe {
+    pub(crate) label: String,
+    pub(crate) outcome: Result<CommandResult, String>,
+}
+
+/// A diagnosed problem with the line that triggered it and a concrete next step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Finding {
+    pub(crate) id: &'static str,
+    pub(crate) title: String,
+    pub(crate) severity: Severity,
+    pub(crate) evidence: Option<String>,
+    pub(crate) remedy: String,
+}
+
+/// Map probe output (and the version check) to findings, most severe first.
+///
+/// Each signature is reported once, with the first line that matched as evidence. A probe that
+/// failed without matching any signature still gets a generic finding pointing at the raw output.
+pub(crate) fn analyze(probes: &[Probe], version: &VersionStatus) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+
+    if let VersionStatus::Outdated(found) = version {
+        findings.push(Finding {
+            id: "outdated",
+            title: format!("loom-core v{} is older than v{}", found, MIN_LOOM_VERSION),
+            severity: Severity::Warning,
+            evidence: None,
+            remedy: "Run `/loom-update` to upgrade the managed install.".into(),
+        });
+    }
+
+    for probe in probes {
+        let result = match &probe.outcome {
+            Ok(result) => result,
+            Err(err) => {
+                push_unique(
+                    &mut findings,
+                    Finding {
+                        id: "binary-missing",
+                        title: "Loom binary could not be run".into(),
+                        severity: Severity::Critical,
+                        evidence: Some(err.clone()),
+                        remedy: "Run `/loom-info` to see which binary was resolved; install \
+                                 loom-core or set `context_servers.loom.command.path`."
+                            .into(),
+                    },
+                );
+                continue;
+            }
+        };
+
+        let before = findings.len();
+        for line in result.stderr.lines().chain(result.stdout.lines()) {
+            if let Some(sig) = match_line(line) {
+                push_unique(
+                    &mut findings,
+                    Finding {
+                        id: sig.id,
+                        title: sig.title.into(),
+                        severity: sig.severity,
+                        evidence: Some(line.trim().to_string()),
+                        remedy: sig.remedy.into(),
+                    },
+                );
+            }
+        }
+        if !result.success() && findings.len() == before {
+            findings.push(Finding {
+                id: "probe-failed",
+                title: format!("`{}` failed (exit {})", probe.label, result.exit_code),
+                severity: Severity::Warning,
+                evidence: first_line(result),
+                remedy: format!(
+                    "Run `/loom-doctor --raw` for the full `{}` output.",
+                    probe.label
+                ),
+            });
+        }
+    }
+
+    findings.sort_by_key(|f| f.severity);
+    findings
+}
+
+/// A fix `/loom-retry` applies on its own before re-running a failed command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AutoFix {
+    /// `loom start`.
+    StartDaemon,
+    /// `loom restart`: recreates the socket and re-reads credentials from the environment.
+    RestartDaemon,
+    /// Force a fresh managed download, as `/loom-install` does.
+    Reinstall,
+}
+
+impl AutoFix {
+    /// The automatic fix for a finding, if it has one (config errors and the like need a human).
+    pub(crate) fn for_finding(id: &str) -> Option<Self> {
+        match id {
+            "daemon-down" => Some(Self::StartDaemon),
+            "socket-permission" | "missing-secrets" | "missing-token" => Some(Self::RestartDaemon),
+            "binary-missing" => Some(Self::Reinstall),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn describe(self) -> &'static str {
+        match self {
+            Self::StartDaemon => "start the daemon (`loom start`)",
+            Self::RestartDaemon => {
+                "restart the daemon so it picks up the current environment (`loom restart`)"
+            }
+            Self::Reinstall => "re-download the managed loom-core install",
+        }
+    }
+}
+
+/// Findings for a failed slash command, from its failing CLI runs and dispatch error.
+///
+/// Successful runs are ignored so fallbacks and healthy output can't trigger a signature. An
+/// error with no runs at all that reads like a spawn failure counts as a missing binary.
+pub(crate) fn analyze_failure(runs: &[RecordedRun], error: Option<&str>) -> Vec<Finding> {
+    let mut probes: Vec<Probe> = runs
+        .iter()
+        .filter(|run| run.exit_code != "0")
+        .map(|run| Probe {
+            label: format!("loom {}", join_args(&run.args)),
+            outcome: Ok(CommandResult {
+                exit_code: run.exit_code.clone(),
+                stdout: run.stdout.clone(),
+                stderr: run.stderr.clone(),
+            }),
+        })
+        .collect();
+    if let Some(error) = error {
+        let lower = error.to_ascii_lowercase();
+        let spawn_failed = runs.is_empty()
+            && ["os error", "failed to spawn", "no such file or directory"]
+                .iter()
+                .any(|m| lower.contains(m));
+        probes.push(Probe {
+            label: "slash command".into(),
+            outcome: if spawn_failed {
+                Err(error.to_string())
+            } else {
+                Ok(CommandResult {
+                    exit_code: "1".into(),
+                    stdout: String::new(),
+                    stderr: error.to_string(),
+                })
+            },
+        });
+    }
+    analyze(&probes, &VersionStatus::Unknown)
+}
+
+/// The known failure a failed CLI run's output points at, if any (stderr is checked first).
+///
+/// Used to add a hint to any command's output; successful runs are never classified.
+pub(crate) fn classify_error(result: &CommandResult) -> Option<Finding> {
+    if result.success() {
+        return None;
+    }
+    result
+        .stderr
+        .lines()
+        .chain(result.stdout.lines())
+        .find_map(|line| {
+            match_line(line).map(|sig| Finding {
+                id: sig.id,
+                title: sig.title.into(),
+                severity: sig.severity,
+                evidence: Some(line.trim().to_string()),
+                remedy: sig.remedy.into(),
+            })
+        })
+}
+
+/// The first signature matching `line`, skipping lines that report success.
+fn match_line(line: &str) -> Option<&'static Signature> {
+    let lower = line.to_ascii_lowercase();
+    let trimmed = lower.trim_start();
+    if trimmed.starts_with('✓') || trimmed.starts_with("[ok]") || trimmed.starts_with("ok ") {
+        return None;
+    }
+    SIGNATURES.iter().find(|sig| {
+        sig.any.iter().any(|needle| lower.contains(needle))
+            && sig.all.iter().all(|needle| lower.contains(needle))
+    })
+}
+
+fn push_unique(findings: &mut Vec<Finding>, finding: Finding) {
+    if !findings.iter().any(|f| f.id == finding.id) {
+        findings.push(finding);
+    }
+}
+
+fn first_line(result: &CommandResult) -> Option<String> {
+    result
+        .stderr
+        .lines()
+        .chain(result.stdout.lines())
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(label: &str, exit: &str, stdout: &str, stderr: &str) -> Probe {
+        Probe {
+            label: label.into(),
+            outcome: Ok(CommandResult {
+                exit_code: exit.into(),
+                stdout: stdout.into(),
+                stderr: stderr.into(),
+            }),
+        }
+    }
+
+    #[test]
+    fn classifies_failed_runs_only() {
+        let result = |exit: &str, stderr: &str| CommandResult {
+            exit_code: exit.into(),
+            stdout: String::new(),
+            stderr: stderr.into(),
+        };
+        let cases = [
+            (
+                "dial unix /run/loom.sock: connect: connection refused",
+                "daemon-down",
+            ),
+            ("error: socket not found at /run/loom.sock", "daemon-down"),
+            ("github: 401 Unauthorized", "auth-expired"),
+            (
+                "Error: unknown command \"quota\" for \"loom\"",
+                "unknown-subcommand",
+            ),
+        ];
+        for (stderr, id) in cases {
+            assert_eq!(classify_error(&result("1", stderr)).map(|f| f.id), Some(id));
+        }
+        assert!(classify_error(&result("0", "connection refused, retrying")).is_none());
+        assert!(classify_error(&result("1", "something odd")).is_none());
+    }
+
+    #[test]
+    fn maps_known_signatures_to_remedies() {
+        let probes = [
+            probe(
+                "loom check",
+                "1",
+                "✓ config loaded\n⚠ GITHUB_TOKEN secret: not set\n",
+                "",
+            ),
+            probe(
+                "loom status",
+                "1",
+                "",
+                "error: dial unix /tmp/loom.sock: connect: connection refused",
+            ),
+        ];
+        let findings = analyze(&probes, &VersionStatus::Supported);
+        let ids: Vec<&str> = findings.iter().map(|f| f.id).collect();
+        assert_eq!(ids, vec!["missing-secrets", "daemon-down"]);
+        assert!(findings[1].remedy.contains("/loom-start"));
+        assert!(findings[0].remedy.contains("/loom-secrets validate"));
+        assert!(findings[1]
+            .evidence
+            .as_deref()
+            .unwrap()
+            .contains("connection refused"));
+    }
+
+    #[test]
+    fn permission_denied_wins_over_daemon_down() {
+        let probes = [probe(
+            "loom status",
+            "1",
+            "",
+            "dial unix /run/loom.sock: connect: permission denied",
+        )];
+        let findings = analyze(&probes, &VersionStatus::Supported);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "socket-permission");
+    }
+
+    #[test]
+    fn unmatched_failure_gets_generic_finding() {
+        let probes = [probe("loom status", "2", "", "something odd happened")];
+        let findings = analyze(&probes, &VersionStatus::Supported);
+        assert_eq!(findings[0].id, "probe-failed");
+        assert!(findings[0].remedy.contains("--raw"));
+    }
+
+    #[test]
+    fn healthy_run_has_no_findings() {
+        let probes = [probe("loom check", "0", "✓ secrets present\n", "")];
+        assert!(analyze(&probes, &VersionStatus::Supported).is_empty());
+    }
+
+    #[test]
+    fn failure_analysis_ignores_successful_runs() {
+        let run = |exit: &str, stdout: &str, stderr: &str| RecordedRun {
+            args: vec!["status".into()],
+            exit_code: exit.into(),
+            stdout: stdout.into(),
+            stderr: stderr.into(),
+        };
+        let runs = [
+            run("0", "GITHUB_TOKEN secret: missing", ""),
+            run("1", "", "daemon is not running"),
+        ];
+        let findings = analyze_failure(&runs, None);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            AutoFix::for_finding(findings[0].id),
+            Some(AutoFix::StartDaemon)
+        );
+
+        let spawn = analyze_failure(&[], Some("failed to spawn loom: No such file or directory"));
+        assert_eq!(AutoFix::for_finding(spawn[0].id), Some(AutoFix::Reinstall));
+
+        let usage = analyze_failure(&[], Some("usage: /loom-server <name>"));
+        assert_eq!(usage[0].id, "probe-failed");
+        assert_eq!(AutoFix::for_finding(usage[0].id), None);
+    }
+
+    #[test]
+    fn spawn_error_and_old_version() {
+        let probes = [Probe {
+            label: "loom check".into(),
+            outcome: Err("No such file".into()),
+        }];
+        let findings = analyze(&probes, &VersionStatus::Outdated("0.6.0".into()));
+        assert_eq!(findings[0].id, "binary-missing");
+        assert_eq!(findings[1].id, "outdated");
+        assert!(findings[1].remedy.contains("/loom-update"));
+    }
+}
@@ -8,8 +8,14 @@ use std::{
 };
 use zed_extension_api as zed;
 
-use crate::env::install_key;
+use serde::{Deserialize, Serialize};
+
+use crate::checksum::file_sha256;
+use crate::commands::run_command_capture;
+use crate::env::{current_path_sep, install_key, with_path_prefix};
+use crate::log::{log_msg, LogLevel};
 use crate::settings::LoomDownloadSettings;
+use crate::version::parse_version;
 
 const LATEST_RELEASE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
 
@@ -21,27 +27,148 @@ pub(crate) struct LoomInstall {
     pub(crate) loomd_path: Option<String>,
     pub(crate) bin_dir: String,
     pub(crate) resolved_at_unix_secs: Option<u64>,
+    /// Release asset the binaries were unpacked from, and where it was downloaded.
+    pub(crate) asset_name: String,
+    pub(crate) download_url: String,
+    /// SHA-256 of the `loom` binary right after install (`None` if it couldn't be read).
+    pub(crate) checksum: Option<String>,
+    /// Markdown report of the post-download daemon start (only set on the fresh install).
+    pub(crate) startup_report: Option<String>,
+}
+
+impl LoomInstall {
+    /// Cheap check that the cached binary can still run: a non-empty regular file that is
+    /// executable where the platform exposes mode bits. Catches deleted install dirs and OS
+    /// temp cleanup before a command fails with "No such file or directory".
+    pub(crate) fn is_usable(&self) -> bool {
+        binary_usable(Path::new(&self.loom_path))
+    }
+}
+
+fn binary_usable(path: &Path) -> bool {
+    match fs::metadata(path) {
+        Ok(meta) => meta.is_file() && meta.len() > 0 && is_executable(&meta),
+        Err(_) => false,
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+/// WASI (where the extension runs) has no mode bits; `make_file_executable` ran at install.
+#[cfg(not(unix))]
+fn is_executable(_meta: &fs::Metadata) -> bool {
+    true
+}
+
+/// Directory (relative to the extension work dir) that managed releases are unpacked into.
+pub(crate) const INSTALL_ROOT: &str = "loom-core";
+
+/// Provenance file written next to each managed release (`loom-core/<version>/manifest.json`).
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+
+/// What was installed into a release directory, persisted so later sessions (and `/loom-info`)
+/// can answer where the running binary came from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct InstallManifest {
+    pub(crate) release_version: String,
+    pub(crate) asset_name: String,
+    pub(crate) download_url: String,
+    pub(crate) sha256: Option<String>,
+    pub(crate) installed_at_unix_secs: u64,
+}
+
+impl InstallManifest {
+    fn path(version: &str) -> PathBuf {
+        PathBuf::from(INSTALL_ROOT)
+            .join(version)
+            .join(MANIFEST_FILE)
+    }
+
+    /// The manifest for a managed release, if one was written.
+    pub(crate) fn read(version: &str) -> Option<InstallManifest> {
+        let text = fs::read_to_string(Self::path(version)).ok()?;
+        zed::serde_json::from_str(&text).ok()
+    }
+
+    fn write(&self) -> Result<(), String> {
+        let text = zed::serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(Self::path(&self.release_version), text).map_err(|e| e.to_string())
+    }
+}
+
+/// Release version of a binary under [`INSTALL_ROOT`] (`loom-core/<version>/...`), if managed.
+pub(crate) fn managed_version(program: &str) -> Option<String> {
+    let rest = Path::new(program).strip_prefix(INSTALL_ROOT).ok()?;
+    rest.components()
+        .next()
+        .and_then(|c| c.as_os_str().to_str())
+        .map(str::to_string)
 }
 
 const RETRY_BACKOFF_MS: &[u64] = &[500, 1000, 2000];
 
-fn retry_with_backoff<T, F>(mut f: F) -> Result<T, String>
+/// Error substrings that won't go away by retrying (missing tags/assets, auth, local disk).
+const PERMANENT_ERROR_MARKERS: &[&str] = &[
+    "404",
+    "not found",
+    "401",
+    "403",
+    "unauthorized",
+    "forbidden",
+    "permission denied",
+    "no space left",
+    "read-only file system",
+    "invalid archive",
+    "unsupported",
+    // The asset extracted cleanly but holds no loom binary; downloading it again won't help.
+    "could not find loom",
+];
+
+/// Whether an operation failure is worth retrying; unknown errors are assumed transient.
+fn is_retryable(err: &str) -> bool {
+    let lower = err.to_ascii_lowercase();
+    !PERMANENT_ERROR_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+fn retry_with_backoff<T, F>(f: F) -> Result<T, String>
 where
     F: FnMut() -> Result<T, String>,
 {
-    // First attempt without backoff, then retry with each backoff delay
-    let mut last_err = match f() {
-        Ok(val) => return Ok(val),
-        Err(e) => e,
-    };
-    for &delay_ms in RETRY_BACKOFF_MS {
-        thread::sleep(Duration::from_millis(delay_ms));
-        match f() {
+    retry_with_delays(RETRY_BACKOFF_MS, f)
+}
+
+/// Run `f`, retrying transient failures after each delay in `delays_ms`.
+///
+/// Permanent errors (see [`is_retryable`]) fail immediately. The final error says which kind it
+/// was so users know whether trying again later can help.
+fn retry_with_delays<T, F>(delays_ms: &[u64], mut f: F) -> Result<T, String>
+where
+    F: FnMut() -> Result<T, String>,
+{
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let err = match f() {
             Ok(val) => return Ok(val),
-            Err(e) => last_err = e,
+            Err(e) => e,
+        };
+        if !is_retryable(&err) {
+            return Err(format!("{} (permanent error, not retried)", err));
+        }
+        match delays_ms.get(attempts - 1) {
+            Some(&delay_ms) => thread::sleep(Duration::from_millis(delay_ms)),
+            None => {
+                return Err(format!(
+                    "{} (transient error; gave up after {} attempts)",
+                    err, attempts
+                ))
+            }
         }
     }
-    Err(last_err)
 }
 
 pub(crate) fn ensure_loom_install(
@@ -58,11 +185,20 @@ pub(crate) fn ensure_loom_install(
         .unwrap_or(true);
 
     {
-        let installs = installs
+        let mut installs = installs
             .lock()
             .map_err(|_| "install cache mutex poisoned")?;
         if let Some(found) = installs.get(&key) {
-            if Path::new(&found.loom_path).exists() {
+            if !found.is_usable() {
+                log_msg(
+                    LogLevel::Warn,
+                    &format!(
+                        "cached loom-core {} at {} is missing or not executable; reinstalling",
+                        found.release_version, found.loom_path
+                    ),
+                );
+                installs.remove(&key);
+            } else {
                 // Avoid spamming GitHub for latest unless TTL elapsed.
                 if !is_latest {
                     return Ok(found.clone());
@@ -78,6 +214,12 @@ pub(crate) fn ensure_loom_install(
         }
     }
 
+    let release = fetch_release(settings)?;
+    install_release(installs, settings, &key, release, is_latest, now)
+}
+
+/// Fetch the pinned (`settings.tag`) or latest release, with retries.
+fn fetch_release(settings: &LoomDownloadSettings) -> Result<zed::GithubRelease, String> {
     let repo = settings.repo().to_string();
     let release = if let Some(tag) = settings.tag.as_ref().filter(|t| !t.trim().is_empty()) {
         let tag = tag.trim().to_string();
@@ -101,7 +243,20 @@ pub(crate) fn ensure_loom_install(
             e
         )
     })?;
+    Ok(release)
+}
 
+/// Download `release`, unpack it under `loom-core/<version>`, and cache it as `key`.
+fn install_release(
+    installs: &Mutex<HashMap<String, LoomInstall>>,
+    settings: &LoomDownloadSettings,
+    key: &str,
+    release: zed::GithubRelease,
+    is_latest: bool,
+    now: u64,
+) -> Result<LoomInstall, String> {
+    let (os, arch) = zed::current_platform();
+    let repo = settings.repo();
     let asset = select_release_asset(
         &release.assets,
         &release.version,
@@ -118,24 +273,32 @@ pub(crate) fn ensure_loom_install(
         )
     })?;
 
-    let install_dir = PathBuf::from("loom-core").join(&release.version);
+    let install_dir = PathBuf::from(INSTALL_ROOT).join(&release.version);
     fs::create_dir_all(&install_dir).map_err(|e| e.to_string())?;
 
-    let file_type = infer_downloaded_file_type(&asset.name);
     let dest_file = install_dir.join(&asset.name);
     let dest_file_str = dest_file.to_string_lossy().to_string();
-    zed::download_file(&asset.download_url, &dest_file_str, file_type)?;
-
     let (loom_name, loomd_name) = match os {
         zed::Os::Windows => ("loom.exe", "loomd.exe"),
         _ => ("loom", "loomd"),
     };
 
-    let loom_path = find_file_named(&install_dir, &[loom_name, "loom"]).ok_or_else(|| {
-        format!(
-            "download succeeded but could not find {} under {:?}",
-            loom_name, install_dir
+    // Download + extract as one retried step, so a flaky connection that breaks extraction is
+    // retried. An archive that extracts but has no loom binary is a permanent error.
+    let loom_path = retry_with_backoff(|| {
+        remove_partial_download(&dest_file);
+        zed::download_file(
+            &asset.download_url,
+            &dest_file_str,
+            infer_downloaded_file_type(&asset.name),
         )
+        .map_err(|e| format!("downloading {}: {}", asset.name, e))?;
+        find_file_named(&install_dir, &[loom_name, "loom"]).ok_or_else(|| {
+            format!(
+                "download succeeded but could not find {} under {:?}",
+                loom_name, install_dir
+            )
+        })
     })?;
     let loomd_path = find_file_named(&install_dir, &[loomd_name, "loomd"])
         .map(|p| p.to_string_lossy().to_string());
@@ -161,15 +324,359 @@ pub(crate) fn ensure_loom_install(
         loomd_path,
         bin_dir,
         resolved_at_unix_secs: if is_latest { Some(now) } else { None },
+        asset_name: asset.name.clone(),
+        download_url: asset.download_url.clone(),
+        checksum: file_sha256(&loom_path),
+        startup_report: None,
     };
 
-    let mut installs = installs
-        .lock()
-        .map_err(|_| "install cache mutex poisoned")?;
-    installs.insert(key, install.clone());
+    let manifest = InstallManifest {
+        release_version: install.release_version.clone(),
+        asset_name: install.asset_name.clone(),
+        download_url: install.download_url.clone(),
+        sha256: install.checksum.clone(),
+        installed_at_unix_secs: now,
+    };
+    if let Err(e) = manifest.write() {
+        // Provenance is nice to have; a read-only work dir shouldn't fail the install.
+        log_msg(
+            LogLevel::Warn,
+            &format!("could not write install manifest: {}", e),
+        );
+    }
+
+    {
+        let mut installs = installs
+            .lock()
+            .map_err(|_| "install cache mutex poisoned")?;
+        installs.insert(key.to_string(), install.clone());
+    }
+
+    // The report belongs to this fresh install only; cached copies stay quiet.
+    let mut install = install;
+    if settings.auto_start_daemon() && install.loomd_path.is_some() {
+        install.startup_report = start_daemon_if_stopped(&install);
+    }
     Ok(install)
 }
 
+/// Remove what a failed download attempt left behind (archive file or extracted dir).
+fn remove_partial_download(dest: &Path) {
+    let _ = if dest.is_dir() {
+        fs::remove_dir_all(dest)
+    } else {
+        fs::remove_file(dest)
+    };
+}
+
+/// Result of `/loom-update`.
+pub(crate) enum UpdateOutcome {
+    /// A newer release was downloaded (`from` is `None` when nothing was installed before).
+    Updated {
+        from: Option<String>,
+        install: Box<LoomInstall>,
+    },
+    /// A newer release exists but was not downloaded (`/loom-update` without `confirm`).
+    Available {
+        from: Option<String>,
+        version: String,
+    },
+    UpToDate(String),
+    /// `settings.download.tag` pins a release, so "latest" is never installed.
+    Pinned(String),
+}
+
+/// Check the latest release against the managed install and, with `apply`, download it if newer.
+///
+/// Always hits GitHub (ignoring the "latest" TTL), since the user asked explicitly.
+pub(crate) fn update_loom_install(
+    installs: &Mutex<HashMap<String, LoomInstall>>,
+    settings: &LoomDownloadSettings,
+    apply: bool,
+) -> Result<UpdateOutcome, String> {
+    if let Some(tag) = settings
+        .tag
+        .as_ref()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+    {
+        return Ok(UpdateOutcome::Pinned(tag.to_string()));
+    }
+
+    let (os, arch) = zed::current_platform();
+    let key = install_key(settings, os, arch);
+    let current = {
+        let installs = installs
+            .lock()
+            .map_err(|_| "install cache mutex poisoned")?;
+        installs
+            .get(&key)
+            .filter(|i| i.is_usable())
+            .map(|i| i.release_version.clone())
+    }
+    .or_else(newest_installed_version);
+
+    let release = fetch_release(settings)?;
+    if let Some(current) = &current {
+        if !is_newer_version(&release.version, current) {
+            return Ok(UpdateOutcome::UpToDate(current.clone()));
+        }
+    }
+    if !apply {
+        return Ok(UpdateOutcome::Available {
+            from: current,
+            version: release.version,
+        });
+    }
+
+    log_msg(
+        LogLevel::Info,
+        &format!(
+            "updating loom-core {} -> {}",
+            current.as_deref().unwrap_or("(none)"),
+            release.version
+        ),
+    );
+    let install = install_release(installs, settings, &key, release, true, unix_now_secs())?;
+    Ok(UpdateOutcome::Updated {
+        from: current,
+        install: Box::new(install),
+    })
+}
+
+/// Lines of release notes shown before the rest is left to the release page.
+const MAX_RELEASE_NOTES_LINES: usize = 40;
+
+/// The changelog part of a release's notes, as Markdown, from the GitHub release API.
+///
+/// Best effort: `None` when the request fails or the release has no body, so an update
+/// check never fails just because the notes couldn't be fetched.
+pub(crate) fn release_notes(repo: &str, tag: &str) -> Option<String> {
+    let request = zed::http_client::HttpRequest::builder()
+        .method(zed::http_client::HttpMethod::Get)
+        .url(format!(
+            "https://api.github.com/repos/{}/releases/tags/{}",
+            repo, tag
+        ))
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "loom-zed")
+        .build()
+        .ok()?;
+    match request.fetch() {
+        Ok(response) => release_notes_markdown(&String::from_utf8_lossy(&response.body)),
+        Err(e) => {
+            log_msg(
+                LogLevel::Warn,
+                &format!("release notes for {} {} unavailable: {}", repo, tag, e),
+            );
+            None
+        }
+    }
+}
+
+/// Pick the changelog section out of a release API response's `body`.
+///
+/// Uses the section under a "Changelog" / "What's Changed" / "Changes" heading when there is
+/// one (the whole body otherwise), capped at [`MAX_RELEASE_NOTES_LINES`] with a link to the
+/// release page for the rest.
+pub(crate) fn release_notes_markdown(response: &str) -> Option<String> {
+    let release: zed::serde_json::Value = zed::serde_json::from_str(response).ok()?;
+    let body = release.get("body")?.as_str()?.replace("\r\n", "\n");
+    let url = release.get("html_url").and_then(|u| u.as_str());
+
+    let lines: Vec<&str> = body.lines().collect();
+    let heading_level = |line: &str| {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        (level > 0 && line[level..].starts_with(' ')).then_some(level)
+    };
+    let section = lines.iter().position(|line| {
+        heading_level(line).is_some() && {
+            let title = line.trim_start_matches('#').trim().to_ascii_lowercase();
+            ["changelog", "what's changed", "changes"]
+                .iter()
+                .any(|t| title.starts_with(t))
+        }
+    });
+    let selected: Vec<&str> = match section {
+        Some(start) => {
+            let level = heading_level(lines[start]).unwrap_or(1);
+            lines[start + 1..]
+                .iter()
+                .take_while(|line| heading_level(line).is_none_or(|l| l > level))
+                .copied()
+                .collect()
+        }
+        None => lines,
+    };
+    let text = selected.join("\n");
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut kept: Vec<&str> = text.lines().take(MAX_RELEASE_NOTES_LINES).collect();
+    let truncated = text.lines().count() > kept.len();
+    if truncated {
+        kept.push("");
+        kept.push("…");
+    }
+    let mut notes = kept.join("\n");
+    if let Some(url) = url.filter(|_| truncated) {
+        notes.push_str(&format!("\n\nFull release notes: {}", url));
+    }
+    Some(notes)
+}
+
+/// Download `tag` (or the latest release) from scratch for `/loom-install`.
+///
+/// Skips the in-memory cache and deletes any existing copy of the release first, so a corrupted
+/// download is replaced rather than reused. The install is cached under the key for `tag`, so
+/// it only becomes the active binary when `settings.download.tag` selects the same release.
+pub(crate) fn force_install(
+    installs: &Mutex<HashMap<String, LoomInstall>>,
+    settings: &LoomDownloadSettings,
+    tag: Option<&str>,
+) -> Result<LoomInstall, String> {
+    let mut settings = settings.clone();
+    if let Some(tag) = tag {
+        settings.tag = Some(tag.to_string());
+    }
+    let is_latest = settings
+        .tag
+        .as_ref()
+        .map(|t| t.trim().is_empty())
+        .unwrap_or(true);
+    let (os, arch) = zed::current_platform();
+    let key = install_key(&settings, os, arch);
+
+    let release = fetch_release(&settings)?;
+    installs
+        .lock()
+        .map_err(|_| "install cache mutex poisoned")?
+        .remove(&key);
+    let release_dir = PathBuf::from(INSTALL_ROOT).join(&release.version);
+    if release_dir.exists() {
+        fs::remove_dir_all(&release_dir)
+            .map_err(|e| format!("could not remove old {:?}: {}", release_dir, e))?;
+    }
+
+    log_msg(
+        LogLevel::Info,
+        &format!("reinstalling loom-core {}", release.version),
+    );
+    install_release(
+        installs,
+        &settings,
+        &key,
+        release,
+        is_latest,
+        unix_now_secs(),
+    )
+}
+
+/// Release directories unpacked under [`INSTALL_ROOT`], oldest first.
+pub(crate) fn installed_versions() -> Vec<String> {
+    let mut versions: Vec<((u64, u64, u64), String)> = fs::read_dir(INSTALL_ROOT)
+        .map(|dir| {
+            dir.flatten()
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                .filter_map(|name| parse_version(&name).map(|v| (v, name)))
+                .collect()
+        })
+        .unwrap_or_default();
+    versions.sort();
+    versions.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Installed releases `/loom-cleanup` may delete: everything not in `active`.
+///
+/// With no active managed install (e.g. `command.path` is set), the newest release is kept so
+/// re-enabling downloads doesn't force a fresh download. `installed` is oldest first.
+pub(crate) fn stale_versions(installed: &[String], active: &[String]) -> Vec<String> {
+    let newest = installed
+        .last()
+        .filter(|_| !active.iter().any(|v| installed.contains(v)));
+    installed
+        .iter()
+        .filter(|v| !active.contains(v) && Some(*v) != newest)
+        .cloned()
+        .collect()
+}
+
+/// Disk usage of an installed release directory, in bytes.
+pub(crate) fn installed_version_size(version: &str) -> u64 {
+    dir_size(&PathBuf::from(INSTALL_ROOT).join(version))
+}
+
+/// Delete an installed release directory, returning the bytes reclaimed.
+pub(crate) fn remove_installed_version(version: &str) -> Result<u64, String> {
+    let dir = PathBuf::from(INSTALL_ROOT).join(version);
+    let bytes = dir_size(&dir);
+    fs::remove_dir_all(&dir).map_err(|e| format!("removing {:?}: {}", dir, e))?;
+    Ok(bytes)
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Newest release directory already unpacked (from earlier sessions).
+fn newest_installed_version() -> Option<String> {
+    installed_versions().pop()
+}
+
+/// Whether release `candidate` is strictly newer than `current` (unparseable tags never are).
+fn is_newer_version(candidate: &str, current: &str) -> bool {
+    match (parse_version(candidate), parse_version(current)) {
+        (Some(new), Some(old)) => new > old,
+        _ => false,
+    }
+}
+
+/// Run `loom start` with a freshly installed binary when `loom status` shows no daemon.
+///
+/// Returns a Markdown report when a start was attempted, `None` if a daemon was already up.
+fn start_daemon_if_stopped(install: &LoomInstall) -> Option<String> {
+    let env = with_path_prefix(Vec::new(), &install.bin_dir, current_path_sep());
+    let status = run_command_capture(&install.loom_path, &["status".into()], &env, &[]).ok()?;
+    if status.success() {
+        return None;
+    }
+
+    log_msg(
+        LogLevel::Info,
+        "no loom daemon running after fresh install; running `loom start`",
+    );
+    let report = match run_command_capture(&install.loom_path, &["start".into()], &env, &[]) {
+        Ok(r) if r.success() => format!(
+            "### ✅ Daemon started\n\nInstalled loom-core {} and started `loomd` (no daemon was running).\n\n",
+            install.release_version
+        ),
+        Ok(r) => format!(
+            "### ❌ Daemon start failed\n\nInstalled loom-core {}, but `loom start` exited with `{}`:\n\n```\n{}\n```\n\nRun `/loom-start` to retry.\n\n",
+            install.release_version,
+            r.exit_code,
+            if r.stderr.trim().is_empty() { r.stdout.trim() } else { r.stderr.trim() }
+        ),
+        Err(e) => format!(
+            "### ❌ Daemon start failed\n\nInstalled loom-core {}, but `loom start` could not run: `{}`\n\n",
+            install.release_version, e
+        ),
+    };
+    Some(report)
+}
+
 fn select_release_asset<'a>(
     assets: &'a [zed::GithubReleaseAsset],
     version: &str,
@@ -227,7 +734,7 @@ fn select_release_asset<'a>(
         .collect();
 
     // Choose the most specific-looking candidate.
-    matches.sort_by(|a, b| a.name.len().cmp(&b.name.len()));
+    matches.sort_by_key(|a| a.name.len());
     matches.into_iter().next()
 }
 
@@ -258,7 +765,7 @@ fn find_file_named(root: &Path, names: &[&str]) -> Option<PathBuf> {
     walk(root, names, 0)
 }
 
-fn unix_now_secs() -> u64 {
+pub(crate) fn unix_now_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_else(|_| Duration::from_secs(0))
@@ -294,6 +801,32 @@ fn infer_downloaded_file_type(asset_name: &str) -> zed::DownloadedFileType {
 mod tests {
     use super::*;
 
+    #[test]
+    fn release_notes_pick_changelog_section() {
+        let response = r###"{
+            "html_url": "https://github.com/crb2nu/loom-core/releases/tag/v0.9.1",
+            "body": "Intro text\r\n\r\n## Changelog\r\n- Fix proxy reconnects\r\n### Internal\r\n- Bump deps\r\n## Checksums\r\nabc123"
+        }"###;
+        assert_eq!(
+            release_notes_markdown(response).as_deref(),
+            Some("- Fix proxy reconnects\n### Internal\n- Bump deps")
+        );
+
+        let long: Vec<String> = (0..50).map(|i| format!("- change {}", i)).collect();
+        let response = zed::serde_json::json!({
+            "html_url": "https://example.test/r",
+            "body": long.join("\n"),
+        })
+        .to_string();
+        let notes = release_notes_markdown(&response).unwrap();
+        assert!(notes.contains("- change 39\n\n…"));
+        assert!(!notes.contains("change 40"));
+        assert!(notes.ends_with("Full release notes: https://example.test/r"));
+
+        assert_eq!(release_notes_markdown(r#"{"body": "  "}"#), None);
+        assert_eq!(release_notes_markdown("not json"), None);
+    }
+
     #[test]
     fn infer_file_type_tar_gz() {
         assert!(matches!(
@@ -306,6 +839,146 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn stale_versions_keep_active_or_newest() {
+        let installed: Vec<String> = ["v0.8.0", "v0.9.0", "v0.9.1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            stale_versions(&installed, &["v0.9.0".into()]),
+            vec!["v0.8.0", "v0.9.1"]
+        );
+        assert_eq!(stale_versions(&installed, &[]), vec!["v0.8.0", "v0.9.0"]);
+        assert_eq!(
+            stale_versions(&installed, &["v1.0.0".into()]),
+            vec!["v0.8.0", "v0.9.0"]
+        );
+        assert!(stale_versions(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn dir_size_sums_nested_files() {
+        let dir = std::env::temp_dir().join(format!("loom-zed-size-{}", std::process::id()));
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("a"), [0u8; 10]).unwrap();
+        fs::write(dir.join("bin").join("b"), [0u8; 5]).unwrap();
+        assert_eq!(dir_size(&dir), 15);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(dir_size(&dir), 0);
+    }
+
+    #[test]
+    fn usable_binary_checks() {
+        let dir = std::env::temp_dir().join(format!("loom-zed-usable-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let bin = dir.join("loom");
+        assert!(!binary_usable(&bin));
+        fs::write(&bin, "").unwrap();
+        assert!(!binary_usable(&bin));
+        fs::write(&bin, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&bin, fs::Permissions::from_mode(0o644)).unwrap();
+            assert!(!binary_usable(&bin));
+            fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        assert!(binary_usable(&bin));
+        assert!(!binary_usable(&dir));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn managed_version_from_program_path() {
+        assert_eq!(
+            managed_version("loom-core/v0.9.1/loom_linux_amd64/loom"),
+            Some("v0.9.1".into())
+        );
+        assert_eq!(managed_version("/usr/local/bin/loom"), None);
+        assert_eq!(managed_version("loom"), None);
+    }
+
+    #[test]
+    fn manifest_round_trips() {
+        let manifest = InstallManifest {
+            release_version: "v0.9.1".into(),
+            asset_name: "loom-core_v0.9.1_linux_amd64.tar.gz".into(),
+            download_url: "https://example.com/loom.tar.gz".into(),
+            sha256: Some("ab".repeat(32)),
+            installed_at_unix_secs: 1_767_225_600,
+        };
+        let text = zed::serde_json::to_string(&manifest).unwrap();
+        assert_eq!(
+            zed::serde_json::from_str::<InstallManifest>(&text).unwrap(),
+            manifest
+        );
+    }
+
+    #[test]
+    fn classifies_retryable_errors() {
+        assert!(is_retryable("connection reset by peer"));
+        assert!(is_retryable("request timed out"));
+        assert!(is_retryable("HTTP 503 Service Unavailable"));
+        assert!(!is_retryable("HTTP 404 Not Found"));
+        assert!(!is_retryable("failed to create file: Permission denied"));
+        assert!(!is_retryable(
+            "download succeeded but could not find loom under \"/x/loom-v1\""
+        ));
+    }
+
+    #[test]
+    fn retry_stops_on_permanent_error() {
+        let mut calls = 0;
+        let result: Result<(), String> = retry_with_delays(&[0, 0, 0], || {
+            calls += 1;
+            Err("release not found".into())
+        });
+        assert_eq!(calls, 1);
+        assert!(result.unwrap_err().contains("permanent error, not retried"));
+
+        let mut calls = 0;
+        let result: Result<(), String> = retry_with_delays(&[0, 0, 0], || {
+            calls += 1;
+            Err("download succeeded but could not find loom.exe under \"C:\\x\"".into())
+        });
+        assert_eq!(calls, 1);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn retry_gives_up_after_transient_errors() {
+        let mut calls = 0;
+        let result: Result<(), String> = retry_with_delays(&[0, 0], || {
+            calls += 1;
+            Err("connection reset".into())
+        });
+        assert_eq!(calls, 3);
+        assert!(result
+            .unwrap_err()
+            .contains("transient error; gave up after 3 attempts"));
+
+        let mut calls = 0;
+        let result = retry_with_delays(&[0, 0], || {
+            calls += 1;
+            if calls < 2 {
+                Err("timeout".to_string())
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn newer_version_comparison() {
+        assert!(is_newer_version("v0.9.1", "v0.9.0"));
+        assert!(is_newer_version("v1.0.0", "0.9.9"));
+        assert!(!is_newer_version("v0.9.0", "v0.9.0"));
+        assert!(!is_newer_version("v0.8.0", "v0.9.0"));
+        assert!(!is_newer_version("nightly", "v0.9.0"));
+    }
+
     #[test]
     fn infer_file_type_zip() {
         assert!(matches!(
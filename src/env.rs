@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use zed_extension_api as zed;
 
 use crate::settings::LoomDownloadSettings;
@@ -68,6 +68,132 @@ pub(crate) fn install_key(
     )
 }
 
+/// Substrings of variable names whose values are never shown (case-insensitive).
+const SECRET_KEY_MARKERS: &[&str] = &[
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "KEY",
+    "CREDENTIAL",
+    "AUTH",
+    "COOKIE",
+    "SESSION",
+];
+
+/// Longest value shown verbatim before it is shortened.
+const MAX_ENV_VALUE_CHARS: usize = 80;
+
+/// Whether a variable name looks like it holds a secret (`GITHUB_TOKEN`, `AWS_SECRET_ACCESS_KEY`).
+pub(crate) fn is_secret_key(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    SECRET_KEY_MARKERS.iter().any(|m| upper.contains(m))
+}
+
+/// Value safe to print: secrets become `<redacted, N chars>`, long values are shortened.
+pub(crate) fn redact_env_value(key: &str, value: &str) -> String {
+    if is_secret_key(key) {
+        return format!("<redacted, {} chars>", value.chars().count());
+    }
+    if value.chars().count() > MAX_ENV_VALUE_CHARS {
+        let head: String = value.chars().take(MAX_ENV_VALUE_CHARS).collect();
+        return format!("{}…", head);
+    }
+    value.to_string()
+}
+
+/// Parse an environment listing: a JSON object (optionally under `env`/`environment`),
+/// `KEY=VALUE` lines, or NUL-separated `/proc/<pid>/environ` contents.
+///
+/// Returns `None` when nothing in the input looks like an environment.
+pub(crate) fn parse_env_listing(text: &str) -> Option<Vec<(String, String)>> {
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(text.trim()) {
+        let obj = ["env", "environment"]
+            .iter()
+            .find_map(|k| value.get(*k))
+            .unwrap_or(&value)
+            .as_object()?;
+        let mut pairs: Vec<(String, String)> = obj
+            .iter()
+            .map(|(k, v)| {
+                let v = v
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| v.to_string());
+                (k.clone(), v)
+            })
+            .collect();
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        return Some(pairs);
+    }
+
+    let mut pairs: Vec<(String, String)> = text
+        .split(['\0', '\n'])
+        .filter_map(|entry| {
+            let (key, value) = entry.trim_end_matches('\r').split_once('=')?;
+            let key = key.trim().strip_prefix("export ").unwrap_or(key.trim());
+            let valid =
+                !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            valid.then(|| (key.to_string(), value.to_string()))
+        })
+        .collect();
+    if pairs.is_empty() {
+        return None;
+    }
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    pairs.dedup_by(|a, b| a.0 == b.0);
+    Some(pairs)
+}
+
+/// How the daemon's environment differs from the one the extension passes to commands.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct EnvDiff {
+    /// Set for the daemon but not for the extension.
+    pub(crate) only_daemon: Vec<(String, String)>,
+    /// Set for the extension but missing from the daemon (the usual "can't see my secret").
+    pub(crate) only_extension: Vec<(String, String)>,
+    /// `(key, daemon value, extension value)`.
+    pub(crate) changed: Vec<(String, String, String)>,
+    pub(crate) same: usize,
+}
+
+impl EnvDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.only_daemon.is_empty() && self.only_extension.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare two environments by key; output lists are sorted by name.
+pub(crate) fn diff_env(daemon: &[(String, String)], extension: &[(String, String)]) -> EnvDiff {
+    let daemon: BTreeMap<&str, &str> = daemon
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let extension: BTreeMap<&str, &str> = extension
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    let mut diff = EnvDiff::default();
+    for (key, value) in &daemon {
+        match extension.get(key) {
+            None => diff.only_daemon.push((key.to_string(), value.to_string())),
+            Some(ext) if ext != value => {
+                diff.changed
+                    .push((key.to_string(), value.to_string(), ext.to_string()))
+            }
+            Some(_) => diff.same += 1,
+        }
+    }
+    for (key, value) in &extension {
+        if !daemon.contains_key(key) {
+            diff.only_extension
+                .push((key.to_string(), value.to_string()));
+        }
+    }
+    diff
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,4 +219,54 @@ mod tests {
         let editor_val = env.iter().find(|(k, _)| k == "EDITOR").unwrap();
         assert_eq!(editor_val.1, "vim");
     }
+
+    #[test]
+    fn parse_env_listing_formats() {
+        let json = parse_env_listing(r#"{"env": {"B": "2", "A": "1", "N": 3}}"#).unwrap();
+        assert_eq!(
+            json,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "2".to_string()),
+                ("N".to_string(), "3".to_string()),
+            ]
+        );
+        let text = parse_env_listing("HOME=/home/u\nexport PATH=/bin\nnot a var\n").unwrap();
+        assert_eq!(text.len(), 2);
+        assert_eq!(text[1], ("PATH".to_string(), "/bin".to_string()));
+        let proc = parse_env_listing("A=1\0B=x=y\0").unwrap();
+        assert_eq!(proc[1], ("B".to_string(), "x=y".to_string()));
+        assert!(parse_env_listing("daemon not running").is_none());
+    }
+
+    #[test]
+    fn diff_env_classifies_keys() {
+        let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+        let daemon = vec![pair("HOME", "/h"), pair("PATH", "/bin"), pair("OLD", "1")];
+        let ext = vec![
+            pair("HOME", "/h"),
+            pair("PATH", "/usr/bin"),
+            pair("GH_TOKEN", "x"),
+        ];
+        let diff = diff_env(&daemon, &ext);
+        assert_eq!(diff.only_daemon, vec![pair("OLD", "1")]);
+        assert_eq!(diff.only_extension, vec![pair("GH_TOKEN", "x")]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.same, 1);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn redact_env_value_hides_secrets() {
+        assert_eq!(
+            redact_env_value("GITHUB_TOKEN", "abcd"),
+            "<redacted, 4 chars>"
+        );
+        assert_eq!(
+            redact_env_value("aws_secret_access_key", ""),
+            "<redacted, 0 chars>"
+        );
+        assert_eq!(redact_env_value("HOME", "/home/u"), "/home/u");
+        assert!(redact_env_value("PATH", &"x".repeat(200)).ends_with('…'));
+    }
 }
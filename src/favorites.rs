@@ -0,0 +1,51 @@
+use std::fs;
+
+use zed_extension_api as zed;
+
+/// Tools pinned with `/loom-favorites add`, relative to the extension work dir.
+const FAVORITES_FILE: &str = "favorites.json";
+
+/// Pinned tool names in the order they were added (empty when nothing is pinned).
+pub(crate) fn load() -> Vec<String> {
+    fs::read_to_string(FAVORITES_FILE)
+        .ok()
+        .and_then(|text| zed::serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save(tools: &[String]) -> Result<(), String> {
+    let text = zed::serde_json::to_string_pretty(tools).map_err(|e| e.to_string())?;
+    fs::write(FAVORITES_FILE, text).map_err(|e| format!("failed to save favorites: {}", e))
+}
+
+/// Append `tool` unless it's already pinned. Returns whether the list changed.
+pub(crate) fn add(tools: &mut Vec<String>, tool: &str) -> bool {
+    if tools.iter().any(|t| t == tool) {
+        return false;
+    }
+    tools.push(tool.to_string());
+    true
+}
+
+/// Drop `tool` from the list. Returns whether it was pinned.
+pub(crate) fn remove(tools: &mut Vec<String>, tool: &str) -> bool {
+    let before = tools.len();
+    tools.retain(|t| t != tool);
+    tools.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_remove_keep_order_without_duplicates() {
+        let mut tools = vec!["github__search".to_string()];
+        assert!(add(&mut tools, "agent_memory_stats"));
+        assert!(!add(&mut tools, "github__search"));
+        assert_eq!(tools, vec!["github__search", "agent_memory_stats"]);
+        assert!(remove(&mut tools, "github__search"));
+        assert!(!remove(&mut tools, "github__search"));
+        assert_eq!(tools, vec!["agent_memory_stats"]);
+    }
+}
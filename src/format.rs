@@ -1,5 +1,20 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Deserialize;
 use zed_extension_api as zed;
 
+use crate::aliases::{Alias, AliasSource};
+use crate::catalog::ServerTools;
+use crate::commands::{LatencyStats, RecordedRun};
+use crate::doctor::{Finding, Probe, Severity};
+use crate::download::{InstallManifest, LoomInstall, UpdateOutcome};
+use crate::env::EnvDiff;
+use crate::i18n::tr;
+use crate::resolver::{BinaryCandidate, BinarySource, ResolvedBinary};
+use crate::schema::SchemaViolation;
+use crate::secrets::SecretHint;
+use crate::state::HistoryEntry;
+
 /// Structured result from running a CLI command.
 pub(crate) struct CommandResult {
     pub(crate) exit_code: String,
@@ -13,7 +28,53 @@ impl CommandResult {
     }
 }
 
+/// Remove ANSI escape sequences (colors, cursor moves, hyperlinks) from CLI output.
+///
+/// `loom` colors its output even when piped; Zed renders the escapes as garbage. CSI sequences
+/// (`ESC [ ... final`) and OSC sequences (`ESC ] ... BEL` or `ESC ] ... ESC \`) are dropped
+/// whole, as are charset selections (`ESC ( B`) and other `ESC x` pairs. OSC 8 hyperlinks keep
+/// their visible text.
+pub(crate) fn strip_ansi(s: &str) -> String {
+    if !s.contains('\u{1b}') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' {
+                        break;
+                    }
+                    if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            Some('(' | ')' | '*' | '+') => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
 /// Formatted output ready for Zed's slash command response.
+#[derive(Clone)]
 pub(crate) struct FormattedOutput {
     pub(crate) text: String,
     pub(crate) sections: Vec<zed::SlashCommandOutputSection>,
@@ -27,6 +88,87 @@ impl FormattedOutput {
             sections: Vec::new(),
         }
     }
+
+    /// Insert a labeled section before the existing output, shifting existing section ranges.
+    pub(crate) fn prepend_section(&mut self, label: &str, content: &str) {
+        let shift = content.len() as u32;
+        for section in &mut self.sections {
+            section.range.start += shift;
+            section.range.end += shift;
+        }
+        self.text.insert_str(0, content);
+        self.sections.insert(
+            0,
+            zed::SlashCommandOutputSection {
+                range: zed::Range {
+                    start: 0,
+                    end: shift,
+                },
+                label: tr(label),
+            },
+        );
+    }
+}
+
+impl FormattedOutput {
+    /// The same output with icons replaced by text (`display.icons: false`).
+    pub(crate) fn without_icons(self) -> Self {
+        self.map_text(strip_icons)
+    }
+
+    /// The same output with secret-looking values masked (see [`crate::redact`]).
+    pub(crate) fn redacted(self) -> Self {
+        self.map_text(crate::redact::mask)
+    }
+
+    /// Rewrite the text with `rewrite`, keeping sections in place.
+    ///
+    /// Section boundaries never split an icon or a secret, so each span between boundaries is
+    /// rewritten on its own and the ranges are moved to match.
+    fn map_text(self, rewrite: impl Fn(&str) -> String) -> Self {
+        let mut bounds: Vec<u32> = self
+            .sections
+            .iter()
+            .flat_map(|s| [s.range.start, s.range.end])
+            .chain([0, self.text.len() as u32])
+            .collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let mut text = String::with_capacity(self.text.len());
+        let mut moved = HashMap::from([(0, 0)]);
+        for pair in bounds.windows(2) {
+            text.push_str(&rewrite(&self.text[pair[0] as usize..pair[1] as usize]));
+            moved.insert(pair[1], text.len() as u32);
+        }
+        let sections = self
+            .sections
+            .into_iter()
+            .map(|mut section| {
+                section.range.start = moved[&section.range.start];
+                section.range.end = moved[&section.range.end];
+                section
+            })
+            .collect();
+        FormattedOutput { text, sections }
+    }
+
+    /// Append a labeled section after the existing output.
+    pub(crate) fn append_section(&mut self, label: &str, content: &str) {
+        push_section(&mut self.text, &mut self.sections, label, content);
+    }
+
+    /// Append another command's output, keeping its sections (ranges shifted to fit).
+    pub(crate) fn append_output(&mut self, other: FormattedOutput) {
+        let shift = self.text.len() as u32;
+        self.text.push_str(&other.text);
+        self.sections
+            .extend(other.sections.into_iter().map(|mut section| {
+                section.range.start += shift;
+                section.range.end += shift;
+                section
+            }));
+    }
 }
 
 /// Helper: append a labeled section and return the byte range.
@@ -41,10 +183,75 @@ fn push_section(
     let end = buf.len() as u32;
     sections.push(zed::SlashCommandOutputSection {
         range: zed::Range { start, end },
-        label: label.to_string(),
+        label: tr(label),
     });
 }
 
+/// Outputs longer than this get an "Output Summary" section ahead of the full text.
+const LONG_OUTPUT_LINES: usize = 200;
+
+/// Lines of the head and tail of a long output to show in its summary.
+const SUMMARY_EDGE_LINES: usize = 5;
+
+/// Push `body` as a fenced `label` section, preceded by a summary when it's long.
+///
+/// The summary (line count, lines mentioning errors, first and last lines) is its own section, so
+/// Zed's section navigation can jump past a huge body.
+fn push_long_output(
+    buf: &mut String,
+    sections: &mut Vec<zed::SlashCommandOutputSection>,
+    label: &str,
+    body: &str,
+    lang: &str,
+) {
+    let body = body.trim();
+    let lines: Vec<&str> = body.lines().collect();
+    if lines.len() > LONG_OUTPUT_LINES {
+        let errors = lines
+            .iter()
+            .filter(|line| {
+                let line = line.to_ascii_lowercase();
+                ["error", "fatal", "panic"].iter().any(|m| line.contains(m))
+            })
+            .count();
+        let mut summary = format!("> 📜 {} lines", lines.len());
+        if errors > 0 {
+            summary.push_str(&format!(
+                " · ⚠️ {} mention{} errors",
+                errors,
+                if errors == 1 { "s" } else { "" }
+            ));
+        }
+        summary.push_str(&format!(
+            " — full text in the \"{}\" section.\n\n**First lines**\n\n{}**Last lines**\n\n{}",
+            tr(label),
+            fenced(lang, &lines[..SUMMARY_EDGE_LINES].join("\n")),
+            fenced(lang, &lines[lines.len() - SUMMARY_EDGE_LINES..].join("\n"))
+        ));
+        push_section(buf, sections, "Output Summary", &summary);
+    }
+    push_section(buf, sections, label, &fenced(lang, body));
+}
+
+/// `body` in a code fence its own backticks can't close, followed by a blank line.
+///
+/// The fence is one backtick longer than the longest run inside `body` (and at least three), so
+/// CLI output that prints Markdown of its own stays inside the block.
+pub(crate) fn fenced(lang: &str, body: &str) -> String {
+    let longest = body.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{body}\n{fence}\n\n")
+}
+
+/// CLI text made safe for a Markdown table cell (outside backticks): pipes are escaped, line
+/// breaks become spaces, and `<` is written as an entity so `</...>` can't open or close HTML.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace("\r\n", " ")
+        .replace('\n', " ")
+        .replace('<', "&lt;")
+}
+
 /// Status indicator emoji.
 fn status_icon(ok: bool) -> &'static str {
     if ok {
@@ -54,11 +261,81 @@ fn status_icon(ok: bool) -> &'static str {
     }
 }
 
+/// Text stand-ins for icons that carry a status, used when `display.icons` is off.
+///
+/// Other pictographs are decoration and are dropped (see [`strip_icons`]).
+const PLAIN_ICONS: &[(char, &str)] = &[
+    ('✅', "[OK]"),
+    ('🟢', "[OK]"),
+    ('❌', "[FAIL]"),
+    ('🔴', "[FAIL]"),
+    ('⚠', "[WARN]"),
+    ('🟠', "[WARN]"),
+    ('⚪', "[--]"),
+    ('🔵', "[NEW]"),
+    ('📝', "[CHANGED]"),
+    ('⭐', "[*]"),
+    ('➕', "[+]"),
+    ('➖', "[-]"),
+    ('🚫', "[DENY]"),
+    ('⏭', "[SKIP]"),
+    ('⏳', "[..]"),
+    ('❔', "[?]"),
+];
+
+/// Replace status icons with [`PLAIN_ICONS`] text and drop decorative emoji (with the space
+/// after them), for fonts that render emoji poorly.
+fn strip_icons(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let plain = PLAIN_ICONS.iter().find(|(icon, _)| *icon == c);
+        let decorative = matches!(c, '\u{1F300}'..='\u{1FAFF}' | '✉' | '⬆' | '⏱' | '✔' | '✘');
+        if plain.is_none() && !decorative {
+            out.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'\u{FE0F}') {
+            chars.next();
+        }
+        match plain {
+            Some((_, text)) => out.push_str(text),
+            None => {
+                if chars.peek() == Some(&' ') {
+                    chars.next();
+                }
+            }
+        }
+    }
+    out
+}
+
 // ---------------------------------------------------------------------------
 // Per-command formatters
 // ---------------------------------------------------------------------------
 
+/// Severity of a single `loom check` item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One parsed `loom check` result line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CheckItem {
+    pub(crate) category: String,
+    pub(crate) name: String,
+    pub(crate) status: CheckStatus,
+    pub(crate) message: String,
+}
+
 /// Format `loom check` output as a diagnostic report.
+///
+/// Parsed checks (JSON or marker-prefixed text) are counted in the header and split into
+/// Failed / Warnings / Passed sections: problems get a table with their category, passing checks
+/// are collapsed to one line per category. Output we can't parse falls back to a plain code block.
 pub(crate) fn format_diagnostic_report(result: &CommandResult) -> FormattedOutput {
     let icon = status_icon(result.success());
     let mut text = String::new();
@@ -71,12 +348,47 @@ pub(crate) fn format_diagnostic_report(result: &CommandResult) -> FormattedOutpu
         &format!("## {} Loom Diagnostic Report\n\n", icon),
     );
 
-    if !result.stdout.trim().is_empty() {
+    let checks = parse_check_output(&result.stdout);
+    if !checks.is_empty() {
+        let of = |st: CheckStatus| -> Vec<&CheckItem> {
+            checks.iter().filter(|c| c.status == st).collect()
+        };
+        let (passed, warned, failed) = (
+            of(CheckStatus::Pass),
+            of(CheckStatus::Warn),
+            of(CheckStatus::Fail),
+        );
+        text.push_str(&format!(
+            "**{} passed, {} warnings, {} failed**\n\n",
+            passed.len(),
+            warned.len(),
+            failed.len()
+        ));
+
+        for (label, icon, items) in [("Failed", "❌", &failed), ("Warnings", "⚠️", &warned)] {
+            if !items.is_empty() {
+                push_section(
+                    &mut text,
+                    &mut sections,
+                    label,
+                    &render_check_problems(label, icon, items),
+                );
+            }
+        }
+        if !passed.is_empty() {
+            push_section(
+                &mut text,
+                &mut sections,
+                "Passed",
+                &render_check_passes(&passed),
+            );
+        }
+    } else if !result.stdout.trim().is_empty() {
         push_section(
             &mut text,
             &mut sections,
             "Details",
-            &format!("```\n{}\n```\n\n", result.stdout.trim()),
+            &fenced("", result.stdout.trim()),
         );
     }
 
@@ -84,10 +396,10 @@ pub(crate) fn format_diagnostic_report(result: &CommandResult) -> FormattedOutpu
         push_section(
             &mut text,
             &mut sections,
-            "Warnings",
+            "Errors",
             &format!(
-                "### Warnings / Errors\n\n```\n{}\n```\n\n",
-                result.stderr.trim()
+                "### Warnings / Errors\n\n{}",
+                fenced("", result.stderr.trim())
             ),
         );
     }
@@ -97,6 +409,196 @@ pub(crate) fn format_diagnostic_report(result: &CommandResult) -> FormattedOutpu
     FormattedOutput { text, sections }
 }
 
+/// Table of failing or warning checks under a `### <icon> <label> (n)` heading.
+fn render_check_problems(label: &str, icon: &str, items: &[&CheckItem]) -> String {
+    let mut out = format!(
+        "### {} {} ({})\n\n| Category | Check | Details |\n| --- | --- | --- |\n",
+        icon,
+        tr(label),
+        items.len()
+    );
+    for c in items {
+        out.push_str(&format!(
+            "| {} | {} | {} |\n",
+            c.category,
+            c.name,
+            table_cell(&c.message)
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+/// Passing checks, one line per category in first-seen order.
+fn render_check_passes(items: &[&CheckItem]) -> String {
+    let mut out = format!("### ✅ {} ({})\n\n", tr("Passed"), items.len());
+    let mut categories: Vec<&str> = Vec::new();
+    for c in items {
+        if !categories.contains(&c.category.as_str()) {
+            categories.push(&c.category);
+        }
+    }
+    for category in categories {
+        let names: Vec<&str> = items
+            .iter()
+            .filter(|c| c.category == category)
+            .map(|c| c.name.as_str())
+            .collect();
+        out.push_str(&format!("- **{}**: {}\n", category, names.join(", ")));
+    }
+    out.push('\n');
+    out
+}
+
+/// Parse `loom check` output into individual checks.
+///
+/// Accepts `--json` output (an array, or an object with a `checks` array) and falls back to
+/// text lines prefixed with ✓/⚠/✗ or `[PASS]`/`[WARN]`/`[FAIL]` markers. Non-marker lines ending
+/// in `:` start a new category.
+pub(crate) fn parse_check_output(stdout: &str) -> Vec<CheckItem> {
+    let trimmed = stdout.trim();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(trimmed) {
+        let list = value
+            .as_array()
+            .or_else(|| value.get("checks").and_then(|c| c.as_array()));
+        if let Some(list) = list {
+            return list.iter().filter_map(check_item_from_json).collect();
+        }
+    }
+
+    let mut category = "General".to_string();
+    let mut items = Vec::new();
+    for line in trimmed.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match split_check_marker(line) {
+            Some((status, rest)) => {
+                let (name, message) = match rest.split_once(" - ").or_else(|| rest.split_once(": "))
+                {
+                    Some((n, m)) => (n.trim(), m.trim()),
+                    None => (rest.trim(), ""),
+                };
+                items.push(CheckItem {
+                    category: category.clone(),
+                    name: name.to_string(),
+                    status,
+                    message: message.to_string(),
+                });
+            }
+            None if line.ends_with(':') => {
+                category = line.trim_end_matches(':').trim().to_string();
+            }
+            None => {}
+        }
+    }
+    items
+}
+
+/// Extract names from a CLI list command's output.
+///
+/// Accepts JSON (an array of names/objects or `{"<key>": [...]}`), a whitespace-aligned table
+/// with a `NAME`/`TOOL` header, or one name per line. A leading `*` (active-item marker) is
+/// skipped.
+pub(crate) fn parse_name_list(stdout: &str, key: &str) -> BTreeSet<String> {
+    let trimmed = stdout.trim();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(trimmed) {
+        let items = value
+            .get(key)
+            .and_then(|t| t.as_array())
+            .or_else(|| value.as_array());
+        if let Some(items) = items {
+            return items
+                .iter()
+                .filter_map(|item| {
+                    item.as_str()
+                        .or_else(|| {
+                            ["name", "id", "session_id"]
+                                .iter()
+                                .find_map(|k| item.get(*k).and_then(|n| n.as_str()))
+                        })
+                        .map(|s| s.to_string())
+                })
+                .collect();
+        }
+    }
+
+    let mut lines = trimmed.lines().filter(|l| !l.trim().is_empty()).peekable();
+    let has_header = lines
+        .peek()
+        .and_then(|l| l.split_whitespace().next())
+        .map(|first| {
+            matches!(
+                first.to_ascii_uppercase().as_str(),
+                "NAME" | "TOOL" | "PROFILE" | "SERVER" | "NAMESPACE" | "ID" | "SESSION"
+            )
+        })
+        .unwrap_or(false);
+    if has_header {
+        lines.next();
+    }
+    lines
+        .filter(|l| !l.trim_start().starts_with(['-', '=', '#']))
+        .filter_map(|l| l.split_whitespace().find(|tok| *tok != "*"))
+        .map(|s| s.trim_start_matches('*').trim_end_matches(':').to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn check_item_from_json(v: &zed::serde_json::Value) -> Option<CheckItem> {
+    let field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| v.get(*k).and_then(|x| x.as_str()))
+            .map(str::to_string)
+    };
+    let name = field(&["name", "title", "check"])?;
+    let raw = field(&["status", "result", "level"])?;
+    let mut message = field(&["message", "detail", "details"]).unwrap_or_default();
+    let status = match raw.to_ascii_lowercase().as_str() {
+        "pass" | "passed" | "ok" | "success" => CheckStatus::Pass,
+        "warn" | "warning" => CheckStatus::Warn,
+        "fail" | "failed" | "failure" | "error" | "critical" => CheckStatus::Fail,
+        // A status we don't know (`skipped`, `unknown`, ...) isn't a failure; keep it visible.
+        _ => {
+            message = if message.is_empty() {
+                format!("status `{}`", raw)
+            } else {
+                format!("status `{}`: {}", raw, message)
+            };
+            CheckStatus::Warn
+        }
+    };
+    Some(CheckItem {
+        category: field(&["category", "group"]).unwrap_or_else(|| "General".to_string()),
+        name,
+        status,
+        message,
+    })
+}
+
+fn split_check_marker(line: &str) -> Option<(CheckStatus, &str)> {
+    const MARKERS: &[(&str, CheckStatus)] = &[
+        ("✓", CheckStatus::Pass),
+        ("✔", CheckStatus::Pass),
+        ("[PASS]", CheckStatus::Pass),
+        ("[OK]", CheckStatus::Pass),
+        ("PASS ", CheckStatus::Pass),
+        ("⚠️", CheckStatus::Warn),
+        ("⚠", CheckStatus::Warn),
+        ("[WARN]", CheckStatus::Warn),
+        ("WARN ", CheckStatus::Warn),
+        ("✗", CheckStatus::Fail),
+        ("✘", CheckStatus::Fail),
+        ("[FAIL]", CheckStatus::Fail),
+        ("[ERROR]", CheckStatus::Fail),
+        ("FAIL ", CheckStatus::Fail),
+    ];
+    MARKERS
+        .iter()
+        .find_map(|(m, st)| line.strip_prefix(m).map(|rest| (*st, rest.trim())))
+}
+
 /// Format `loom status` output.
 pub(crate) fn format_status_report(result: &CommandResult) -> FormattedOutput {
     let icon = status_icon(result.success());
@@ -115,7 +617,7 @@ pub(crate) fn format_status_report(result: &CommandResult) -> FormattedOutput {
             &mut text,
             &mut sections,
             "Output",
-            &format!("```\n{}\n```\n\n", result.stdout.trim()),
+            &fenced("", result.stdout.trim()),
         );
     }
 
@@ -124,45 +626,370 @@ pub(crate) fn format_status_report(result: &CommandResult) -> FormattedOutput {
             &mut text,
             &mut sections,
             "Errors",
-            &format!("```\n{}\n```\n\n", result.stderr.trim()),
+            &fenced("", result.stderr.trim()),
         );
     }
 
     FormattedOutput { text, sections }
 }
 
+/// Restarts within this many seconds of each other count towards a crash loop.
+const CRASH_LOOP_WINDOW_SECS: u64 = 600;
+
+/// Restarts within [`CRASH_LOOP_WINDOW_SECS`] that make `/loom-status` warn about a crash loop.
+const CRASH_LOOP_RESTARTS: u64 = 3;
+
+/// Daemon restart history as reported by `loom status`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DaemonRestarts {
+    /// Restarts since the supervisor started.
+    pub(crate) total: Option<u64>,
+    /// Restarts within the last [`CRASH_LOOP_WINDOW_SECS`], when reported.
+    pub(crate) recent: Option<u64>,
+    pub(crate) last_crash_reason: Option<String>,
+    pub(crate) last_crash_at: Option<u64>,
+}
+
+impl DaemonRestarts {
+    /// Whether the daemon keeps dying: several recent restarts, or (without a recent count)
+    /// several restarts in total with the last crash inside the window.
+    pub(crate) fn is_crash_looping(&self, now: u64) -> bool {
+        match self.recent {
+            Some(recent) => recent >= CRASH_LOOP_RESTARTS,
+            None => {
+                self.total.unwrap_or(0) >= CRASH_LOOP_RESTARTS
+                    && self
+                        .last_crash_at
+                        .is_some_and(|at| now.saturating_sub(at) < CRASH_LOOP_WINDOW_SECS)
+            }
+        }
+    }
+}
+
+/// Restart count and last crash from `loom status` (JSON at the top level or under `daemon`,
+/// or text lines such as `Restarts: 4 (3 in last 10m)` and `Last crash: panic: ...`).
+pub(crate) fn parse_daemon_restarts(stdout: &str) -> DaemonRestarts {
+    let mut out = DaemonRestarts::default();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) {
+        let daemon = value
+            .get("daemon")
+            .filter(|d| d.is_object())
+            .unwrap_or(&value);
+        let get = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|k| daemon.get(*k).or_else(|| value.get(*k)))
+        };
+        out.total = get(&["restart_count", "restarts"]).and_then(|v| v.as_u64());
+        out.recent = get(&["recent_restarts", "restarts_recent"]).and_then(|v| v.as_u64());
+        let crash = get(&["last_crash"]);
+        out.last_crash_reason = crash
+            .and_then(|c| c.get("reason").or(Some(c)))
+            .and_then(|r| r.as_str())
+            .or_else(|| get(&["last_crash_reason", "last_exit_reason"]).and_then(|v| v.as_str()))
+            .map(str::to_string);
+        out.last_crash_at = crash
+            .and_then(|c| ["at", "time", "timestamp"].iter().find_map(|k| c.get(*k)))
+            .or_else(|| get(&["last_crash_at"]))
+            .and_then(event_time);
+        return out;
+    }
+
+    for line in stdout.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+        if key == "restarts" || key == "restart count" {
+            let mut numbers = value
+                .split(|c: char| !c.is_ascii_digit())
+                .filter(|n| !n.is_empty())
+                .filter_map(|n| n.parse::<u64>().ok());
+            out.total = numbers.next();
+            if value.contains(" in ") {
+                out.recent = numbers.next();
+            }
+        } else if key == "last crash" || key == "last crash reason" {
+            out.last_crash_reason = Some(value.to_string()).filter(|v| !v.is_empty());
+        }
+    }
+    out
+}
+
+/// Prominent `/loom-status` warning for a crash-looping daemon (`None` when it is stable).
+pub(crate) fn crash_loop_warning(restarts: &DaemonRestarts, now: u64) -> Option<String> {
+    if !restarts.is_crash_looping(now) {
+        return None;
+    }
+    let count = match (restarts.recent, restarts.total) {
+        (Some(recent), _) => format!(
+            "{} restarts in the last {} minutes",
+            recent,
+            CRASH_LOOP_WINDOW_SECS / 60
+        ),
+        (None, Some(total)) => format!(
+            "{} restarts, the last one {}",
+            total,
+            restarts
+                .last_crash_at
+                .map(|at| relative_time(at, now))
+                .unwrap_or_default()
+        ),
+        (None, None) => "repeated restarts".into(),
+    };
+    let mut text = format!(
+        "> 🔥 **The Loom daemon is crash-looping** ({}).\n>\n\
+         > It will look intermittently unreachable until the cause is fixed.\n",
+        count
+    );
+    if let Some(reason) = &restarts.last_crash_reason {
+        text.push_str(&format!(
+            ">\n> Last crash: `{}`\n",
+            reason.replace('`', "'")
+        ));
+    }
+    text.push_str(
+        ">\n> Run `/loom-doctor` to diagnose it, and `/loom-events` for what happened before \
+         each crash.\n\n",
+    );
+    Some(text)
+}
+
+/// Daemon process resource usage shown by `/loom-status verbose`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct DaemonResources {
+    pub(crate) pid: Option<u32>,
+    pub(crate) rss_kb: Option<u64>,
+    pub(crate) cpu_percent: Option<f64>,
+    /// Upstream MCP server processes spawned by the daemon.
+    pub(crate) server_processes: Option<usize>,
+}
+
+impl DaemonResources {
+    /// Whether a `ps` fallback could still fill in missing numbers.
+    pub(crate) fn needs_probe(&self) -> bool {
+        self.pid.is_some()
+            && (self.rss_kb.is_none()
+                || self.cpu_percent.is_none()
+                || self.server_processes.is_none())
+    }
+}
+
+/// Pull daemon PID / memory / CPU / server process count out of `loom status --verbose`.
+///
+/// JSON fields may live at the top level or under `daemon`; text output only yields the PID
+/// (`pid: 1234` / `PID=1234`), leaving the rest for a `ps` fallback.
+pub(crate) fn parse_daemon_resources(stdout: &str) -> DaemonResources {
+    let mut res = DaemonResources::default();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) {
+        let daemon = value
+            .get("daemon")
+            .filter(|d| d.is_object())
+            .unwrap_or(&value);
+        let num = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|k| daemon.get(*k).or_else(|| value.get(*k)))
+                .and_then(|v| v.as_f64())
+        };
+        res.pid = num(&["pid"]).map(|p| p as u32);
+        res.rss_kb = num(&["rss_kb", "memory_kb"])
+            .or_else(|| num(&["memory_bytes", "rss_bytes"]).map(|b| b / 1024.0))
+            .map(|kb| kb as u64);
+        res.cpu_percent = num(&["cpu_percent", "cpu"]);
+        res.server_processes = num(&["server_processes"]).map(|n| n as usize).or_else(|| {
+            value
+                .get("servers")
+                .and_then(|s| s.as_array())
+                .map(|servers| {
+                    servers
+                        .iter()
+                        .filter(|srv| srv.get("pid").and_then(|p| p.as_u64()).is_some())
+                        .count()
+                })
+        });
+        return res;
+    }
+
+    res.pid = stdout.lines().find_map(|line| {
+        let (key, val) = line.split_once(':').or_else(|| line.split_once('='))?;
+        let key = key.trim().to_ascii_lowercase();
+        if key == "pid" || key.ends_with(" pid") {
+            val.split_whitespace().next()?.parse().ok()
+        } else {
+            None
+        }
+    });
+    res
+}
+
+/// Parse `ps -o rss=,%cpu= -p <pid>` output into `(rss_kb, cpu_percent)`.
+pub(crate) fn parse_ps_usage(stdout: &str) -> Option<(u64, f64)> {
+    let mut fields = stdout.split_whitespace();
+    let rss = fields.next()?.parse().ok()?;
+    let cpu = fields.next()?.parse().ok()?;
+    Some((rss, cpu))
+}
+
+fn human_kb(kb: u64) -> String {
+    if kb >= 1024 * 1024 {
+        format!("{:.1} GB", kb as f64 / (1024.0 * 1024.0))
+    } else if kb >= 1024 {
+        format!("{:.1} MB", kb as f64 / 1024.0)
+    } else {
+        format!("{} KB", kb)
+    }
+}
+
+/// Format `/loom-status verbose`: the regular status report plus a resource usage table.
+pub(crate) fn format_status_verbose(
+    result: &CommandResult,
+    resources: &DaemonResources,
+) -> FormattedOutput {
+    let mut out = format_status_report(result);
+    let unknown = || "—".to_string();
+    let mut table = String::from("### Daemon resources\n\n| Metric | Value |\n| --- | --- |\n");
+    table.push_str(&format!(
+        "| PID | {} |\n",
+        resources
+            .pid
+            .map(|p| format!("`{}`", p))
+            .unwrap_or_else(unknown)
+    ));
+    table.push_str(&format!(
+        "| Memory (RSS) | {} |\n",
+        resources.rss_kb.map(human_kb).unwrap_or_else(unknown)
+    ));
+    table.push_str(&format!(
+        "| CPU | {} |\n",
+        resources
+            .cpu_percent
+            .map(|c| format!("{:.1}%", c))
+            .unwrap_or_else(unknown)
+    ));
+    table.push_str(&format!(
+        "| Server processes | {} |\n",
+        resources
+            .server_processes
+            .map(|n| n.to_string())
+            .unwrap_or_else(unknown)
+    ));
+    table.push('\n');
+    if resources.pid.is_none() {
+        table.push_str("_The daemon PID was not reported; is the daemon running?_\n\n");
+    }
+    push_section(&mut out.text, &mut out.sections, "Resources", &table);
+    out
+}
+
+/// Format `/loom-daemon env`: the daemon's environment diffed against the extension's.
+///
+/// `source` names where the daemon environment came from (CLI or `/proc`). Values are
+/// passed through [`crate::env::redact_env_value`] so secrets never reach the transcript.
+pub(crate) fn format_daemon_env(source: &str, diff: &EnvDiff) -> FormattedOutput {
+    let cell = |key: &str, value: &str| {
+        let shown = crate::env::redact_env_value(key, value);
+        if shown.is_empty() {
+            "_(empty)_".to_string()
+        } else {
+            format!("`{}`", shown.replace('`', "'").replace('|', "\\|"))
+        }
+    };
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let summary = if diff.is_empty() {
+        format!(
+            "## ✅ Daemon environment matches\n\nRead from {}; all {} variables agree with \
+             the environment the extension passes to loom.\n\n",
+            source, diff.same
+        )
+    } else {
+        format!(
+            "## ⚠️ Daemon environment differs\n\nRead from {}. {} missing from the daemon, \
+             {} only in the daemon, {} with different values, {} identical.\n\n",
+            source,
+            diff.only_extension.len(),
+            diff.only_daemon.len(),
+            diff.changed.len(),
+            diff.same
+        )
+    };
+    push_section(&mut text, &mut sections, "Environment", &summary);
+
+    if !diff.only_extension.is_empty() {
+        let mut table = String::from("| Variable | Extension value |\n| --- | --- |\n");
+        for (key, value) in &diff.only_extension {
+            table.push_str(&format!("| `{}` | {} |\n", key, cell(key, value)));
+        }
+        table.push_str(
+            "\nThe daemon was started without these. Run `/loom-restart` so it inherits the \
+             current environment.\n\n",
+        );
+        push_section(&mut text, &mut sections, "Missing from daemon", &table);
+    }
+    if !diff.changed.is_empty() {
+        let mut table =
+            String::from("| Variable | Daemon value | Extension value |\n| --- | --- | --- |\n");
+        for (key, daemon, ext) in &diff.changed {
+            table.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                key,
+                cell(key, daemon),
+                cell(key, ext)
+            ));
+        }
+        table.push('\n');
+        push_section(&mut text, &mut sections, "Different values", &table);
+    }
+    if !diff.only_daemon.is_empty() {
+        let mut table = String::from("| Variable | Daemon value |\n| --- | --- |\n");
+        for (key, value) in &diff.only_daemon {
+            table.push_str(&format!("| `{}` | {} |\n", key, cell(key, value)));
+        }
+        table.push('\n');
+        push_section(&mut text, &mut sections, "Only in daemon", &table);
+    }
+    FormattedOutput { text, sections }
+}
+
 /// Format `loom sync` output.
 pub(crate) fn format_sync_report(
     result: &CommandResult,
     platform: Option<&str>,
 ) -> FormattedOutput {
     let icon = status_icon(result.success());
-    let mut text = String::new();
-    let mut sections = Vec::new();
-
     let title = match platform {
         Some(p) => format!("## {} Sync: {}\n\n", icon, p),
         None => format!("## {} Sync Status\n\n", icon),
     };
-    push_section(&mut text, &mut sections, "Sync", &title);
+    render_sync_output(result, &title)
+}
+
+/// Title plus the CLI's sync output (as a table when tabular) and any stderr.
+fn render_sync_output(result: &CommandResult, title: &str) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(&mut text, &mut sections, "Sync", title);
 
     if !result.stdout.trim().is_empty() {
         // Try to render sync output as a table if it looks tabular.
         let stdout = result.stdout.trim();
-        if looks_tabular(stdout) {
+        if let Some((diff, counts)) = sync_change_lines(stdout) {
             push_section(
                 &mut text,
                 &mut sections,
                 "Results",
-                &format!("{}\n\n", to_markdown_table(stdout)),
+                &format!("{}\n\n{}", counts, fenced("diff", &diff)),
             );
-        } else {
+        } else if looks_tabular(stdout) {
             push_section(
                 &mut text,
                 &mut sections,
                 "Results",
-                &format!("```\n{}\n```\n\n", stdout),
+                &format!("{}\n\n", to_markdown_table(stdout)),
             );
+        } else {
+            push_section(&mut text, &mut sections, "Results", &fenced("", stdout));
         }
     }
 
@@ -171,134 +998,554 @@ pub(crate) fn format_sync_report(
             &mut text,
             &mut sections,
             "Errors",
-            &format!("```\n{}\n```\n\n", result.stderr.trim()),
+            &fenced("", result.stderr.trim()),
         );
     }
 
     FormattedOutput { text, sections }
 }
 
-/// Format `loom restart` / `loom start` / `loom stop` output.
-pub(crate) fn format_daemon_action(result: &CommandResult, action: &str) -> FormattedOutput {
-    let icon = status_icon(result.success());
+/// Added/removed/changed entries in sync output as `+`/`-`/`~` lines, plus a counts line.
+///
+/// JSON output lists them under `added`, `removed`, and `changed` (or `updated`), as names or
+/// objects with a `name`/`path`. Plain output qualifies when it has at least one `+` or `~`
+/// line, so an ordinary `- item` list isn't mistaken for removals.
+fn sync_change_lines(stdout: &str) -> Option<(String, String)> {
+    let mut counts = [0usize; 3];
+    let diff = match zed::serde_json::from_str::<zed::serde_json::Value>(stdout) {
+        Ok(value) => {
+            let groups = [
+                ('+', &["added", "created"][..]),
+                ('-', &["removed", "deleted"][..]),
+                ('~', &["changed", "updated", "modified"][..]),
+            ];
+            let mut lines = Vec::new();
+            for (i, (prefix, keys)) in groups.iter().enumerate() {
+                let items = keys
+                    .iter()
+                    .find_map(|k| value.get(*k)?.as_array())
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                for item in items {
+                    let name = item.as_str().or_else(|| {
+                        ["name", "path", "server", "file"]
+                            .iter()
+                            .find_map(|k| item.get(*k)?.as_str())
+                    });
+                    if let Some(name) = name {
+                        counts[i] += 1;
+                        lines.push(format!("{} {}", prefix, name));
+                    }
+                }
+            }
+            lines.join("\n")
+        }
+        Err(_) => {
+            for line in stdout.lines() {
+                match line.trim_start().chars().next() {
+                    Some('+') => counts[0] += 1,
+                    Some('-') => counts[1] += 1,
+                    Some('~') => counts[2] += 1,
+                    _ => {}
+                }
+            }
+            if counts[0] + counts[2] == 0 {
+                return None;
+            }
+            stdout.to_string()
+        }
+    };
+    if diff.is_empty() {
+        return None;
+    }
+    let summary = format!(
+        "**{} added · {} removed · {} changed**",
+        counts[0], counts[1], counts[2]
+    );
+    Some((diff, summary))
+}
+
+/// Format the combined result of syncing several platforms in one invocation.
+pub(crate) fn format_sync_multi(parts: &[(&str, &CommandResult)]) -> FormattedOutput {
+    let ok_count = parts.iter().filter(|(_, r)| r.success()).count();
+    let icon = status_icon(ok_count == parts.len());
     let mut text = String::new();
     let mut sections = Vec::new();
 
     push_section(
         &mut text,
         &mut sections,
-        action,
-        &format!("## {} Daemon {}\n\n", icon, capitalize(action),),
+        "Sync",
+        &format!(
+            "## {} Sync: {}/{} platforms succeeded\n\n",
+            icon,
+            ok_count,
+            parts.len()
+        ),
     );
 
-    if !result.stdout.trim().is_empty() {
-        push_section(
-            &mut text,
-            &mut sections,
-            "Output",
-            &format!("```\n{}\n```\n\n", result.stdout.trim()),
-        );
+    let mut table =
+        String::from("| Platform | Result | Exit code | Summary |\n| --- | --- | --- | --- |\n");
+    for (platform, result) in parts {
+        table.push_str(&format!(
+            "| {} | {} | `{}` | {} |\n",
+            platform,
+            status_icon(result.success()),
+            result.exit_code,
+            sync_summary_line(result)
+        ));
     }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Results", &table);
 
-    if !result.stderr.trim().is_empty() && !result.success() {
+    for (platform, result) in parts {
+        let body = if result.stdout.trim().is_empty() {
+            result.stderr.trim()
+        } else {
+            result.stdout.trim()
+        };
+        if body.is_empty() {
+            continue;
+        }
         push_section(
             &mut text,
             &mut sections,
-            "Errors",
-            &format!("```\n{}\n```\n\n", result.stderr.trim()),
+            platform,
+            &format!(
+                "### {} {}\n\n{}",
+                status_icon(result.success()),
+                platform,
+                fenced("", body)
+            ),
         );
     }
 
     FormattedOutput { text, sections }
 }
 
-/// Generic fallback formatter.
-pub(crate) fn format_generic(result: &CommandResult, title: &str) -> FormattedOutput {
-    let icon = status_icon(result.success());
+/// Format `/loom-sync diff`: what `loom sync <platform> --dry-run` would write, per platform.
+///
+/// Platforms with nothing to change are listed in the summary instead of getting a block.
+pub(crate) fn format_sync_diff(parts: &[(&str, &CommandResult)]) -> FormattedOutput {
     let mut text = String::new();
     let mut sections = Vec::new();
 
-    push_section(
-        &mut text,
-        &mut sections,
-        title,
-        &format!("## {} {}\n\n", icon, title),
+    let mut changed: Vec<(&str, String)> = Vec::new();
+    let mut unchanged: Vec<&str> = Vec::new();
+    let mut failed: Vec<(&str, &CommandResult)> = Vec::new();
+    for (platform, result) in parts {
+        if !result.success() {
+            failed.push((platform, result));
+            continue;
+        }
+        match sync_dry_run_diff(&result.stdout) {
+            Some(diff) => changed.push((platform, diff)),
+            None => unchanged.push(platform),
+        }
+    }
+
+    let icon = if !failed.is_empty() {
+        "❌"
+    } else if changed.is_empty() {
+        "✅"
+    } else {
+        "📝"
+    };
+    let mut summary = format!(
+        "## {} Sync Preview: {} of {} platform{} would change\n\n",
+        icon,
+        changed.len(),
+        parts.len(),
+        if parts.len() == 1 { "" } else { "s" }
     );
+    if !unchanged.is_empty() {
+        summary.push_str(&format!("Up to date: {}\n\n", unchanged.join(", ")));
+    }
+    push_section(&mut text, &mut sections, "Sync", &summary);
 
-    if !result.stdout.trim().is_empty() {
+    for (platform, diff) in &changed {
         push_section(
             &mut text,
             &mut sections,
-            "Output",
-            &format!("```\n{}\n```\n\n", result.stdout.trim()),
+            platform,
+            &format!("### {}\n\n{}", platform, fenced("diff", diff)),
         );
     }
-
-    if !result.stderr.trim().is_empty() {
+    for (platform, result) in &failed {
+        let stderr = result.stderr.to_ascii_lowercase();
+        let hint = if stderr.contains("unknown flag")
+            || stderr.contains("flag provided but not defined")
+        {
+            "This loom CLI doesn't support `--dry-run`; run `/loom-update` to upgrade.\n\n"
+        } else {
+            ""
+        };
         push_section(
             &mut text,
             &mut sections,
-            "Errors",
-            &format!("```\n{}\n```\n\n", result.stderr.trim()),
+            platform,
+            &format!(
+                "### ❌ {}\n\n{}{}",
+                platform,
+                fenced("", &sync_summary_line(result)),
+                hint
+            ),
         );
     }
-
-    text.push_str(&format!("**Exit code**: `{}`\n", result.exit_code));
+    if !changed.is_empty() {
+        text.push_str("Apply with `/loom-sync <platform>` (or `/loom-sync all`).\n");
+    }
 
     FormattedOutput { text, sections }
 }
 
-/// Format a Markdown table for tools listing.
-pub(crate) fn format_tools_table(result: &CommandResult) -> FormattedOutput {
-    let icon = status_icon(result.success());
-    let mut text = String::new();
-    let mut sections = Vec::new();
+/// Diff body for one platform's dry run, or `None` when it would write nothing.
+///
+/// JSON output (`{"changes": [{"path": ..., "diff": ...}]}`, or `files`) is flattened into
+/// per-file diffs; text output is used as-is, since loom already prints `+`/`-` lines.
+fn sync_dry_run_diff(stdout: &str) -> Option<String> {
+    let trimmed = stdout.trim();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(trimmed) {
+        let items = ["changes", "files", "diffs"]
+            .iter()
+            .find_map(|k| value.get(*k))
+            .unwrap_or(&value)
+            .as_array()?;
+        let mut out = String::new();
+        for item in items {
+            let text = |keys: &[&str]| {
+                keys.iter()
+                    .find_map(|k| item.get(*k).and_then(|v| v.as_str()))
+                    .unwrap_or_default()
+            };
+            let diff = text(&["diff", "patch"]).trim_end();
+            if diff.is_empty() {
+                continue;
+            }
+            let path = text(&["path", "file"]);
+            if !path.is_empty() && !diff.starts_with("---") {
+                out.push_str(&format!("--- a/{}\n+++ b/{}\n", path, path));
+            }
+            out.push_str(diff);
+            out.push('\n');
+        }
+        let out = out.trim_end().to_string();
+        return (!out.is_empty()).then_some(out);
+    }
 
-    push_section(
-        &mut text,
-        &mut sections,
-        "Tools",
-        &format!("## {} Loom Tools\n\n", icon),
-    );
+    let lower = trimmed.to_ascii_lowercase();
+    let nothing = trimmed.is_empty()
+        || [
+            "no changes",
+            "up to date",
+            "up-to-date",
+            "already in sync",
+            "nothing to",
+        ]
+        .iter()
+        .any(|m| lower.contains(m));
+    (!nothing).then(|| trimmed.to_string())
+}
 
-    if !result.stdout.trim().is_empty() {
-        let stdout = result.stdout.trim();
-        if looks_tabular(stdout) {
-            push_section(
-                &mut text,
-                &mut sections,
-                "Tool List",
-                &format!("{}\n\n", to_markdown_table(stdout)),
-            );
-        } else {
-            push_section(
-                &mut text,
-                &mut sections,
-                "Tool List",
-                &format!("```\n{}\n```\n\n", stdout),
-            );
+/// Longest per-platform summary shown in the `/loom-sync all` table.
+const MAX_SYNC_SUMMARY_CHARS: usize = 80;
+
+/// First line of a platform's sync output for the table: the error on failure, else the
+/// CLI's own summary (`—` when it printed nothing).
+fn sync_summary_line(result: &CommandResult) -> String {
+    let (first, second) = if result.success() {
+        (&result.stdout, &result.stderr)
+    } else {
+        (&result.stderr, &result.stdout)
+    };
+    let line = first
+        .lines()
+        .chain(second.lines())
+        .map(str::trim)
+        .find(|l| !l.is_empty());
+    match line {
+        None => "—".to_string(),
+        Some(line) => {
+            let mut short: String = line.chars().take(MAX_SYNC_SUMMARY_CHARS).collect();
+            if line.chars().count() > MAX_SYNC_SUMMARY_CHARS {
+                short.push('…');
+            }
+            table_cell(&short)
         }
     }
+}
 
-    if !result.stderr.trim().is_empty() {
+/// Platforms that `loom sync status` reports as drifted, in `known` order.
+///
+/// JSON may be `{"platforms": [{"platform": ..., "in_sync": false}]}` (or `name`/`drift`/`status`)
+/// or an object keyed by platform. Text output counts lines that name a platform alongside a
+/// drift marker ("drift", "out of sync", "stale", "pending", ⚠️).
+pub(crate) fn drifted_platforms(stdout: &str, known: &[&str]) -> Vec<String> {
+    let is_drift = |status: &str| {
+        let status = status.to_ascii_lowercase();
+        [
+            "drift",
+            "out of sync",
+            "out-of-sync",
+            "stale",
+            "pending",
+            "⚠",
+        ]
+        .iter()
+        .any(|m| status.contains(m))
+    };
+    let entry_drifted = |v: &zed::serde_json::Value| {
+        v.get("in_sync")
+            .and_then(|x| x.as_bool())
+            .map(|in_sync| !in_sync)
+            .or_else(|| v.get("drift").and_then(|x| x.as_bool()))
+            .or_else(|| {
+                ["status", "state"]
+                    .iter()
+                    .find_map(|k| v.get(*k)?.as_str())
+                    .map(is_drift)
+            })
+            .or_else(|| v.as_str().map(is_drift))
+            .unwrap_or(false)
+    };
+
+    let mut drifted: Vec<String> = Vec::new();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) {
+        let list = value.get("platforms").unwrap_or(&value);
+        match list {
+            zed::serde_json::Value::Array(items) => {
+                for item in items {
+                    let name = ["platform", "name"]
+                        .iter()
+                        .find_map(|k| item.get(*k)?.as_str());
+                    if let Some(name) = name.filter(|_| entry_drifted(item)) {
+                        drifted.push(name.to_ascii_lowercase());
+                    }
+                }
+            }
+            zed::serde_json::Value::Object(map) => {
+                for (name, item) in map {
+                    if entry_drifted(item) {
+                        drifted.push(name.to_ascii_lowercase());
+                    }
+                }
+            }
+            _ => {}
+        }
+    } else {
+        for line in stdout.lines().filter(|l| is_drift(l)) {
+            let lower = line.to_ascii_lowercase();
+            let words: Vec<&str> = lower.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+            if let Some(platform) = known.iter().find(|p| words.contains(p)) {
+                drifted.push(platform.to_string());
+            }
+        }
+    }
+    known
+        .iter()
+        .filter(|p| drifted.iter().any(|d| d == *p))
+        .map(|p| p.to_string())
+        .collect()
+}
+
+/// Section appended after a profile switch when platforms drifted from the new profile.
+pub(crate) fn sync_suggestions(profile: &str, drifted: &[String]) -> String {
+    let mut body = format!(
+        "### ⚠️ Resync needed\n\nSwitching to `{}` left {} platform config{} out of sync:\n\n",
+        profile,
+        drifted.len(),
+        if drifted.len() == 1 { "" } else { "s" }
+    );
+    for platform in drifted {
+        body.push_str(&format!("- `/loom-sync {}`\n", platform));
+    }
+    if drifted.len() > 1 {
+        body.push_str(&format!(
+            "\nOr all at once: `/loom-sync {}`\n",
+            drifted.join(" ")
+        ));
+    }
+    body.push('\n');
+    body
+}
+
+/// Format `loom sync status <platform>`: drift details for a single platform.
+///
+/// JSON output (`files`, `last_synced`, `pending`) gets an overview table, a per-file table, and
+/// a pending-changes list; anything else falls back to the plain status rendering.
+pub(crate) fn format_sync_platform_status(
+    result: &CommandResult,
+    platform: &str,
+) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim())
+        .ok()
+        .filter(|v| v.is_object());
+    let Some(v) = parsed else {
+        let title = format!(
+            "## {} Sync Status: {}\n\n",
+            status_icon(result.success()),
+            platform
+        );
+        return render_sync_output(result, &title);
+    };
+
+    let text_field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| v.get(*k).and_then(|x| x.as_str()))
+            .map(str::to_string)
+    };
+    let list_field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| v.get(*k).and_then(|x| x.as_array()))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let files = list_field(&["files", "targets"]);
+    let pending = list_field(&["pending", "pending_changes", "changes"]);
+    let drift = v
+        .get("in_sync")
+        .and_then(|x| x.as_bool())
+        .map(|in_sync| !in_sync)
+        .or_else(|| v.get("drift").and_then(|x| x.as_bool()))
+        .unwrap_or(!pending.is_empty());
+
+    let icon = if !result.success() {
+        "❌"
+    } else if drift {
+        "⚠️"
+    } else {
+        "✅"
+    };
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Sync",
+        &format!("## {} Sync Status: {}\n\n", icon, platform),
+    );
+
+    let state = if drift {
+        format!("⚠️ Drift detected ({} pending)", pending.len())
+    } else {
+        "✅ In sync".to_string()
+    };
+    let mut overview = format!("| Field | Value |\n| --- | --- |\n| State | {} |\n", state);
+    let last_synced = text_field(&["last_synced", "last_sync", "synced_at"]);
+    overview.push_str(&format!(
+        "| Last synced | {} |\n",
+        last_synced.as_deref().unwrap_or("never")
+    ));
+    if let Some(path) = text_field(&["path", "config_path"]) {
+        overview.push_str(&format!("| Config | `{}` |\n", path));
+    }
+    overview.push('\n');
+    push_section(&mut text, &mut sections, "Overview", &overview);
+
+    if !files.is_empty() {
+        let mut table = String::from("| File | State |\n| --- | --- |\n");
+        for file in &files {
+            let (path, state) = match file {
+                zed::serde_json::Value::String(path) => (path.clone(), String::new()),
+                other => (
+                    ["path", "file", "name"]
+                        .iter()
+                        .find_map(|k| other.get(*k).and_then(|x| x.as_str()))
+                        .unwrap_or("?")
+                        .to_string(),
+                    ["status", "state"]
+                        .iter()
+                        .find_map(|k| other.get(*k).and_then(|x| x.as_str()))
+                        .unwrap_or("")
+                        .to_string(),
+                ),
+            };
+            let state = match state.to_ascii_lowercase().as_str() {
+                "" | "ok" | "synced" | "in_sync" | "up_to_date" => "✅ synced".to_string(),
+                "missing" => "❌ missing".to_string(),
+                other => format!("⚠️ {}", other),
+            };
+            table.push_str(&format!("| `{}` | {} |\n", path, state));
+        }
+        table.push('\n');
+        push_section(&mut text, &mut sections, "Files", &table);
+    }
+
+    if !pending.is_empty() {
+        let mut list = String::from("### Pending changes\n\n");
+        for change in &pending {
+            let line = match change {
+                zed::serde_json::Value::String(s) => s.clone(),
+                other => {
+                    let get = |k: &str| other.get(k).and_then(|x| x.as_str()).unwrap_or("");
+                    let action = get("action");
+                    let target = [get("path"), get("server"), get("name")]
+                        .into_iter()
+                        .find(|s| !s.is_empty())
+                        .unwrap_or("");
+                    let desc = get("description");
+                    match (action.is_empty(), target.is_empty()) {
+                        (false, false) => format!("**{}** `{}`", action, target),
+                        (true, false) => format!("`{}`", target),
+                        _ if !desc.is_empty() => desc.to_string(),
+                        _ => other.to_string(),
+                    }
+                }
+            };
+            list.push_str(&format!("- {}\n", line));
+        }
+        list.push_str(&format!("\nRun `/loom-sync {}` to apply.\n\n", platform));
+        push_section(&mut text, &mut sections, "Pending", &list);
+    }
+
+    if !result.stderr.trim().is_empty() {
         push_section(
             &mut text,
             &mut sections,
             "Errors",
-            &format!("```\n{}\n```\n\n", result.stderr.trim()),
+            &fenced("", result.stderr.trim()),
         );
     }
 
     FormattedOutput { text, sections }
 }
 
-/// Format server listing.
-pub(crate) fn format_servers_list(result: &CommandResult) -> FormattedOutput {
-    format_generic(result, "Loom Servers")
+/// Format `loom restart` / `loom start` / `loom stop` output.
+pub(crate) fn format_daemon_action(result: &CommandResult, action: &str) -> FormattedOutput {
+    let icon = status_icon(result.success());
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        action,
+        &format!("## {} Daemon {}\n\n", icon, capitalize(action),),
+    );
+
+    if !result.stdout.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Output",
+            &fenced("", result.stdout.trim()),
+        );
+    }
+
+    if !result.stderr.trim().is_empty() && !result.success() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", result.stderr.trim()),
+        );
+    }
+
+    FormattedOutput { text, sections }
 }
 
-/// Format health/ping check.
-pub(crate) fn format_ping(result: &CommandResult) -> FormattedOutput {
+/// Generic fallback formatter.
+pub(crate) fn format_generic(result: &CommandResult, title: &str) -> FormattedOutput {
     let icon = status_icon(result.success());
     let mut text = String::new();
     let mut sections = Vec::new();
@@ -306,323 +1553,7283 @@ pub(crate) fn format_ping(result: &CommandResult) -> FormattedOutput {
     push_section(
         &mut text,
         &mut sections,
-        "Health",
-        &format!("## {} Loom Health\n\n", icon),
+        title,
+        &format!("## {} {}\n\n", icon, tr(title)),
     );
 
-    if result.success() {
-        text.push_str("Daemon is **reachable** and responding.\n\n");
-    } else {
-        text.push_str("Daemon is **not reachable**.\n\n");
+    if !result.stdout.trim().is_empty() {
+        push_long_output(&mut text, &mut sections, "Output", &result.stdout, "");
     }
 
-    if !result.stdout.trim().is_empty() {
+    if !result.stderr.trim().is_empty() {
         push_section(
             &mut text,
             &mut sections,
-            "Details",
-            &format!("```\n{}\n```\n\n", result.stdout.trim()),
+            "Errors",
+            &fenced("", result.stderr.trim()),
         );
     }
 
+    text.push_str(&format!(
+        "**{}**: `{}`\n",
+        tr("Exit code"),
+        result.exit_code
+    ));
+
     FormattedOutput { text, sections }
 }
 
-/// Format secrets listing.
-pub(crate) fn format_secrets(result: &CommandResult, sub: &str) -> FormattedOutput {
-    let title = match sub {
-        "validate" => "Secrets Validation",
-        _ => "Secrets",
+/// Pretty-printed per-tool schema documents from `loom tools list --json`.
+///
+/// Each document keeps the tool's name, description, server (when present), and input schema
+/// (`inputSchema`, `input_schema`, or `schema`). Returns `(tool name, JSON text)` pairs sorted by
+/// name, or an error when the output has no schemas to export.
+pub(crate) fn parse_tool_schemas(stdout: &str) -> Result<Vec<(String, String)>, String> {
+    let value: zed::serde_json::Value = zed::serde_json::from_str(stdout.trim())
+        .map_err(|e| format!("`loom tools list --json` did not return JSON: {}", e))?;
+    let tools = value
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .or_else(|| value.as_array())
+        .ok_or("`loom tools list --json` returned no tool list")?;
+
+    let mut out = Vec::new();
+    for tool in tools {
+        let Some(name) = tool.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let Some(schema) = ["inputSchema", "input_schema", "schema"]
+            .iter()
+            .find_map(|k| tool.get(*k))
+        else {
+            continue;
+        };
+        let mut doc = zed::serde_json::json!({ "name": name, "inputSchema": schema });
+        for key in ["description", "server"] {
+            if let Some(v) = tool.get(key) {
+                doc[key] = v.clone();
+            }
+        }
+        let text = zed::serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())?;
+        out.push((name.to_string(), format!("{}\n", text)));
+    }
+    if out.is_empty() {
+        return Err("this loom build does not include input schemas in `tools list --json`".into());
+    }
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(out)
+}
+
+/// Format `/loom-tools describe <tool>`: description and a parameter table from the input schema.
+///
+/// Accepts the tool object itself (optionally under `tool`) or a `tools list --json` listing to
+/// pick `name` from. Nested object properties are listed with dotted names.
+pub(crate) fn format_tool_describe(result: &CommandResult, name: &str) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let tool = parsed.as_ref().and_then(|v| {
+        let listed = v
+            .get("tools")
+            .and_then(|t| t.as_array())
+            .or_else(|| v.as_array());
+        match listed {
+            Some(tools) => tools
+                .iter()
+                .find(|t| t.get("name").and_then(|n| n.as_str()) == Some(name)),
+            None => Some(v.get("tool").unwrap_or(v)),
+        }
+    });
+    let schema = tool.and_then(|t| {
+        ["inputSchema", "input_schema", "schema"]
+            .iter()
+            .find_map(|k| t.get(*k))
+    });
+    let (Some(tool), Some(schema)) = (tool, schema) else {
+        if parsed.is_some() && result.success() {
+            return FormattedOutput::plain(format!(
+                "No input schema found for `{}`. Check the name with `/loom-tools search {}`.\n",
+                name, name
+            ));
+        }
+        return format_generic(result, &format!("Tool {}", name));
     };
-    format_generic(result, title)
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let mut header = format!("## 🔧 `{}`\n\n", name);
+    if let Some(server) = tool.get("server").and_then(|s| s.as_str()) {
+        header.push_str(&format!("**Server**: `{}`\n\n", server));
+    }
+    if let Some(desc) = tool.get("description").and_then(|d| d.as_str()) {
+        header.push_str(&format!("{}\n\n", desc.trim()));
+    }
+    push_section(&mut text, &mut sections, "Tool", &header);
+
+    let mut rows = Vec::new();
+    schema_parameters(schema, "", &mut rows);
+    if rows.is_empty() {
+        text.push_str("This tool takes no arguments.\n\n");
+    } else {
+        let mut table = String::from(
+            "| Parameter | Type | Required | Description |\n| --- | --- | --- | --- |\n",
+        );
+        for row in &rows {
+            table.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                row.name,
+                row.kind,
+                if row.required { "yes" } else { "" },
+                table_cell(&row.description)
+            ));
+        }
+        table.push('\n');
+        push_section(&mut text, &mut sections, "Parameters", &table);
+    }
+
+    let example: zed::serde_json::Map<String, zed::serde_json::Value> = rows
+        .iter()
+        .filter(|r| r.required && !r.name.contains('.'))
+        .map(|r| {
+            (
+                r.name.clone(),
+                zed::serde_json::Value::String(format!("<{}>", r.kind)),
+            )
+        })
+        .collect();
+    text.push_str(&format!(
+        "Try it: `/loom-call {} {}`\n",
+        name,
+        zed::serde_json::Value::Object(example)
+    ));
+    FormattedOutput { text, sections }
 }
 
-/// Format session command output.
-pub(crate) fn format_session(result: &CommandResult, sub: &str) -> FormattedOutput {
-    let title = match sub {
-        "start" => "Session Started",
-        "end" => "Session Ended",
-        "list" => "Sessions",
-        _ => "Session Status",
+/// One row of a `/loom-tools describe` parameter table.
+struct SchemaParam {
+    name: String,
+    kind: String,
+    required: bool,
+    description: String,
+}
+
+/// Flatten a JSON schema's `properties` into rows, recursing into nested objects.
+fn schema_parameters(schema: &zed::serde_json::Value, prefix: &str, rows: &mut Vec<SchemaParam>) {
+    let Some(props) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return;
     };
-    format_generic(result, title)
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    let mut names: Vec<&String> = props.keys().collect();
+    names.sort_by_key(|n| (!required.contains(&n.as_str()), n.as_str()));
+    for key in names {
+        let prop = &props[key];
+        let kind = match prop.get("type") {
+            Some(zed::serde_json::Value::String(t)) if t == "array" => {
+                match prop
+                    .get("items")
+                    .and_then(|i| i.get("type"))
+                    .and_then(|t| t.as_str())
+                {
+                    Some(item) => format!("{}[]", item),
+                    None => "array".to_string(),
+                }
+            }
+            Some(zed::serde_json::Value::String(t)) => t.clone(),
+            Some(zed::serde_json::Value::Array(types)) => types
+                .iter()
+                .filter_map(|t| t.as_str())
+                .collect::<Vec<_>>()
+                .join(" \\| "),
+            _ => "any".to_string(),
+        };
+        let mut description = prop
+            .get("description")
+            .and_then(|d| d.as_str())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        if let Some(values) = prop.get("enum").and_then(|e| e.as_array()) {
+            let values: Vec<String> = values.iter().map(|v| format!("`{}`", v)).collect();
+            description.push_str(&format!(" One of: {}.", values.join(", ")));
+        }
+        if let Some(default) = prop.get("default") {
+            description.push_str(&format!(" Default: `{}`.", default));
+        }
+        let name = format!("{}{}", prefix, key);
+        rows.push(SchemaParam {
+            name: name.clone(),
+            kind,
+            required: required.contains(&key.as_str()),
+            description: description.trim().to_string(),
+        });
+        schema_parameters(prop, &format!("{}.", name), rows);
+    }
 }
 
-/// Format task command output.
-pub(crate) fn format_task(result: &CommandResult, sub: &str) -> FormattedOutput {
-    let title = match sub {
-        "add" => "Task Added",
-        "update" => "Task Updated",
-        _ => "Tasks",
+/// Summarize `/loom-tools schema-export`: where schemas went and which file holds each tool.
+pub(crate) fn format_schema_export(dir: &str, written: &[(String, String)]) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Schema Export",
+        &format!(
+            "## ✅ Exported {} tool schemas to `{}/`\n\n",
+            written.len(),
+            dir
+        ),
+    );
+
+    let mut table = String::from("| Tool | File |\n| --- | --- |\n");
+    for (name, file) in written {
+        table.push_str(&format!("| `{}` | `{}` |\n", name, file));
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Files", &table);
+
+    FormattedOutput { text, sections }
+}
+
+/// Memory-entry and session counts for one namespace (`None` when the hub didn't say).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct NamespaceCounts {
+    pub(crate) memories: Option<u64>,
+    pub(crate) sessions: Option<u64>,
+}
+
+/// Per-namespace counts from `agent_memory_stats`.
+///
+/// Accepts `{"namespaces": {"<ns>": {...}}}`, `{"namespaces": [{"namespace": ..., ...}]}`
+/// (or `by_namespace`), with counts under `memories`/`memory_count`/`entries` and
+/// `sessions`/`session_count`.
+pub(crate) fn parse_namespace_counts(stdout: &str) -> BTreeMap<String, NamespaceCounts> {
+    let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) else {
+        return BTreeMap::new();
     };
-    format_generic(result, title)
+    let Some(list) = value
+        .get("namespaces")
+        .or_else(|| value.get("by_namespace"))
+    else {
+        return BTreeMap::new();
+    };
+    let counts = |item: &zed::serde_json::Value| {
+        let count = |keys: &[&str]| keys.iter().find_map(|k| item.get(*k)?.as_u64());
+        NamespaceCounts {
+            memories: count(&["memories", "memory_count", "entries", "entry_count"]),
+            sessions: count(&["sessions", "session_count"]),
+        }
+    };
+    match list {
+        zed::serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(name, item)| (name.clone(), counts(item)))
+            .collect(),
+        zed::serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|item| {
+                let name = item
+                    .get("namespace")
+                    .or_else(|| item.get("name"))?
+                    .as_str()?;
+                Some((name.to_string(), counts(item)))
+            })
+            .collect(),
+        _ => BTreeMap::new(),
+    }
 }
 
-/// Format recall output.
-pub(crate) fn format_recall(result: &CommandResult) -> FormattedOutput {
+/// Sessions per namespace from `agent session-list --json` (`{"sessions": [...]}` or an array).
+pub(crate) fn count_sessions_by_namespace(stdout: &str) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) else {
+        return counts;
+    };
+    let items = value
+        .get("sessions")
+        .and_then(|s| s.as_array())
+        .or_else(|| value.as_array());
+    for item in items.into_iter().flatten() {
+        if let Some(ns) = item.get("namespace").and_then(|n| n.as_str()) {
+            *counts.entry(ns.to_string()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Format `/loom-namespace list`: one row per namespace with counts, the active one marked.
+///
+/// `names` comes from the namespace listing; namespaces that only appear in `counts` are
+/// included too. Falls back to [`format_generic`] when the listing failed and nothing is known.
+pub(crate) fn format_namespaces(
+    list: &CommandResult,
+    names: &BTreeSet<String>,
+    counts: &BTreeMap<String, NamespaceCounts>,
+    active: Option<&str>,
+) -> FormattedOutput {
+    let all: BTreeSet<&str> = names
+        .iter()
+        .map(String::as_str)
+        .chain(counts.keys().map(String::as_str))
+        .chain(active)
+        .collect();
+    if all.is_empty() && !list.success() {
+        return format_generic(list, "Loom Namespaces");
+    }
+
     let mut text = String::new();
     let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Namespaces",
+        &format!(
+            "## 🗂️ Namespaces\n\n**{} namespaces**{}\n\n",
+            all.len(),
+            active
+                .map(|ns| format!(" · active: `{}`", ns))
+                .unwrap_or_default()
+        ),
+    );
+    if all.is_empty() {
+        text.push_str("No namespaces yet. Start one with `/loom-session start <namespace>`.\n");
+        return FormattedOutput { text, sections };
+    }
 
+    let count = |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_else(|| "—".into());
+    let mut table =
+        String::from("|  | Namespace | Memory entries | Sessions |\n| --- | --- | --- | --- |\n");
+    for name in all {
+        let c = counts.get(name).copied().unwrap_or_default();
+        table.push_str(&format!(
+            "| {} | `{}` | {} | {} |\n",
+            if Some(name) == active { "▶" } else { "" },
+            name,
+            count(c.memories),
+            count(c.sessions)
+        ));
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Counts", &table);
+    FormattedOutput { text, sections }
+}
+
+/// Markdown document `/loom-export` writes: a title and date header over the report body.
+pub(crate) fn export_markdown(report: &str, date: &str, body: &str) -> String {
+    format!(
+        "# Loom {} report\n\n_Exported from `/loom-{}` on {}._\n\n{}\n",
+        report,
+        report,
+        date,
+        body.trim_end()
+    )
+}
+
+/// Confirmation for `/loom-export`: where the report went and what it contains.
+pub(crate) fn format_export(
+    report: &str,
+    rel: &str,
+    written: &str,
+    bytes: usize,
+    exported: &FormattedOutput,
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
     push_section(
         &mut text,
         &mut sections,
-        "Recall",
-        "## 🔍 Context Recall\n\n",
+        "Export",
+        &format!("## ✅ Exported `/loom-{}` to `{}`\n\n", report, rel),
+    );
+    let labels: Vec<&str> = exported.sections.iter().map(|s| s.label.as_str()).collect();
+    push_section(
+        &mut text,
+        &mut sections,
+        "File",
+        &format!(
+            "| Field | Value |\n| --- | --- |\n| Path | `{}` |\n| Size | {} bytes |\n| Sections | {} |\n\n",
+            written,
+            bytes,
+            if labels.is_empty() {
+                "—".to_string()
+            } else {
+                labels.join(", ")
+            }
+        ),
+    );
+    FormattedOutput { text, sections }
+}
+
+/// Format a Markdown table for tools listing.
+pub(crate) fn format_tools_table(result: &CommandResult) -> FormattedOutput {
+    let icon = status_icon(result.success());
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Tools",
+        &format!("## {} Loom Tools\n\n", icon),
     );
 
     if !result.stdout.trim().is_empty() {
+        let stdout = result.stdout.trim();
+        if looks_tabular(stdout) {
+            let count = stdout.lines().filter(|l| !l.trim().is_empty()).count() - 1;
+            push_section(
+                &mut text,
+                &mut sections,
+                &counted_label("Tool List", count),
+                &format!("{}\n\n", to_markdown_table(stdout)),
+            );
+        } else {
+            push_section(&mut text, &mut sections, "Tool List", &fenced("", stdout));
+        }
+    }
+
+    if !result.stderr.trim().is_empty() {
         push_section(
             &mut text,
             &mut sections,
-            "Results",
-            &format!("{}\n\n", result.stdout.trim()),
+            "Errors",
+            &fenced("", result.stderr.trim()),
         );
     }
 
-    if !result.stderr.trim().is_empty() && !result.success() {
-        push_section(
-            &mut text,
-            &mut sections,
-            "Errors",
-            &format!("```\n{}\n```\n\n", result.stderr.trim()),
-        );
+    FormattedOutput { text, sections }
+}
+
+/// Ordering for `/loom-tools list sort:<key>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ToolSort {
+    Name,
+    Server,
+    /// Most recently used first; tools the CLI has no usage time for go last.
+    Recent,
+}
+
+impl ToolSort {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s {
+            "name" => Some(Self::Name),
+            "server" => Some(Self::Server),
+            "recent" => Some(Self::Recent),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Server => "server",
+            Self::Recent => "most recently used",
+        }
+    }
+}
+
+/// `sort:` / `limit:` tokens given to `/loom-tools list`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ToolListOptions {
+    pub(crate) sort: Option<ToolSort>,
+    pub(crate) limit: Option<usize>,
+}
+
+/// One tool parsed from `loom tools list` output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ToolRow {
+    pub(crate) name: String,
+    pub(crate) server: String,
+    pub(crate) description: String,
+    /// Sortable last-use key (RFC 3339 string or zero-padded unix time), if the CLI reports one.
+    pub(crate) last_used: Option<String>,
+}
+
+/// Parse `loom tools list` output (JSON or a `NAME SERVER DESCRIPTION` table) into rows.
+pub(crate) fn parse_tool_rows(stdout: &str) -> Vec<ToolRow> {
+    let trimmed = stdout.trim();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(trimmed) {
+        let items = value
+            .get("tools")
+            .and_then(|t| t.as_array())
+            .or_else(|| value.as_array())
+            .cloned()
+            .unwrap_or_default();
+        return items
+            .iter()
+            .filter_map(|item| {
+                let field = |keys: &[&str]| {
+                    keys.iter()
+                        .find_map(|k| item.get(*k).and_then(|v| v.as_str()))
+                        .unwrap_or("")
+                        .to_string()
+                };
+                let name = item
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| field(&["name"]));
+                if name.is_empty() {
+                    return None;
+                }
+                let last_used = ["last_used", "last_used_at", "last_called_at", "used_at"]
+                    .iter()
+                    .find_map(|k| match item.get(*k)? {
+                        zed::serde_json::Value::Number(n) => {
+                            n.as_u64().map(|secs| format!("{:020}", secs))
+                        }
+                        zed::serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+                        _ => None,
+                    });
+                Some(ToolRow {
+                    name,
+                    server: field(&["server", "server_name", "upstream"]),
+                    description: field(&["description", "desc"]),
+                    last_used,
+                })
+            })
+            .collect();
+    }
+
+    let mut lines = trimmed.lines().filter(|l| !l.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let cols: Vec<String> = header
+        .split_whitespace()
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let Some(name_col) = cols.iter().position(|c| c == "NAME" || c == "TOOL") else {
+        return Vec::new();
+    };
+    let col = |names: &[&str]| cols.iter().position(|c| names.contains(&c.as_str()));
+    let server_col = col(&["SERVER", "UPSTREAM"]);
+    let desc_col = col(&["DESCRIPTION", "DESC"]);
+    lines
+        .filter(|l| !l.trim_start().starts_with(['-', '=']))
+        .map(|line| {
+            let parts = split_columns(line, cols.len());
+            let get = |i: Option<usize>| {
+                i.and_then(|i| parts.get(i))
+                    .copied()
+                    .unwrap_or("")
+                    .to_string()
+            };
+            ToolRow {
+                name: get(Some(name_col)),
+                server: get(server_col),
+                description: get(desc_col),
+                last_used: None,
+            }
+        })
+        .filter(|row| !row.name.is_empty())
+        .collect()
+}
+
+/// Split on runs of whitespace into at most `n` columns; the last column keeps its spaces.
+fn split_columns(line: &str, n: usize) -> Vec<&str> {
+    let mut parts = Vec::with_capacity(n);
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        if parts.len() + 1 == n {
+            parts.push(rest);
+            break;
+        }
+        match rest.find(char::is_whitespace) {
+            Some(end) => {
+                parts.push(&rest[..end]);
+                rest = rest[end..].trim_start();
+            }
+            None => {
+                parts.push(rest);
+                break;
+            }
+        }
+    }
+    parts
+}
+
+/// Table of `pinned` tools (in pin order) looked up in `rows`; pins missing from the listing
+/// are named below it.
+fn pinned_tools_table(pinned: &[String], rows: &[ToolRow]) -> String {
+    let mut table = String::from("| Tool | Server | Description |\n| --- | --- | --- |\n");
+    let mut missing = Vec::new();
+    for name in pinned {
+        match rows.iter().find(|r| &r.name == name) {
+            Some(row) => table.push_str(&format!(
+                "| ⭐ `{}` | {} | {} |\n",
+                row.name,
+                table_cell(&row.server),
+                table_cell(&row.description)
+            )),
+            None => missing.push(format!("`{}`", name)),
+        }
+    }
+    if missing.len() == pinned.len() {
+        table.clear();
+    } else {
+        table.push('\n');
+    }
+    if !missing.is_empty() {
+        table.push_str(&format!(
+            "_Pinned but not in the current listing: {}._\n\n",
+            missing.join(", ")
+        ));
+    }
+    table
+}
+
+/// Format `/loom-tools list` with sorting and a row limit applied to the parsed listing.
+///
+/// `pinned` tools (from `/loom-favorites`) are shown first and left out of the sorted table.
+/// Output we can't parse into rows falls back to [`format_tools_table`], with the pins listed
+/// above it by name.
+pub(crate) fn format_tools_listing(
+    result: &CommandResult,
+    options: &ToolListOptions,
+    pinned: &[String],
+) -> FormattedOutput {
+    let mut rows = parse_tool_rows(&result.stdout);
+    if !result.success() || rows.is_empty() {
+        let mut formatted = format_tools_table(result);
+        if result.success() && !pinned.is_empty() {
+            let names: Vec<String> = pinned.iter().map(|t| format!("`{}`", t)).collect();
+            formatted.prepend_section(
+                &counted_label("Pinned", names.len()),
+                &format!("⭐ Pinned: {}\n\n", names.join(", ")),
+            );
+        }
+        return formatted;
+    }
+    let pinned_table = (!pinned.is_empty()).then(|| pinned_tools_table(pinned, &rows));
+    let total = rows.len();
+    rows.retain(|row| !pinned.contains(&row.name));
+
+    match options.sort {
+        Some(ToolSort::Name) => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+        Some(ToolSort::Server) => {
+            rows.sort_by(|a, b| (&a.server, &a.name).cmp(&(&b.server, &b.name)))
+        }
+        Some(ToolSort::Recent) => rows.sort_by(|a, b| {
+            // `None` sorts before `Some`, so comparing b to a puts unused tools last.
+            b.last_used
+                .cmp(&a.last_used)
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        None => {}
+    }
+    let unpinned = rows.len();
+    rows.truncate(options.limit.unwrap_or(unpinned));
+    let shown = rows.len() + (total - unpinned);
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Tools",
+        &format!("## {} Loom Tools\n\n", status_icon(true)),
+    );
+
+    let mut summary = if shown < total {
+        format!("Showing {} of {} tools", shown, total)
+    } else {
+        format!("{} tools", total)
+    };
+    if let Some(sort) = options.sort {
+        summary.push_str(&format!(", sorted by {}", sort.label()));
+    }
+    text.push_str(&format!("_{}._\n\n", summary));
+    if let Some(pinned_table) = pinned_table {
+        push_section(
+            &mut text,
+            &mut sections,
+            &counted_label("Pinned", total - unpinned),
+            &format!("### ⭐ Pinned\n\n{}", pinned_table),
+        );
+    }
+
+    let mut table = String::from("| Tool | Server | Description |\n| --- | --- | --- |\n");
+    for row in &rows {
+        table.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            row.name,
+            table_cell(&row.server),
+            table_cell(&row.description)
+        ));
+    }
+    table.push('\n');
+    push_section(
+        &mut text,
+        &mut sections,
+        &counted_label("Tool List", rows.len()),
+        &table,
+    );
+
+    if !result.stderr.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", result.stderr.trim()),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-favorites`: the pinned tools with their server and description when `listing`
+/// (`loom tools list`) parses, marking the tool `changed` (`("added" | "removed", tool)`).
+pub(crate) fn format_favorites(
+    pinned: &[String],
+    listing: Option<&CommandResult>,
+    changed: Option<(&str, &str)>,
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let header = match changed {
+        Some((action, tool)) => format!("## ⭐ {} `{}`\n\n", capitalize(action), tool),
+        None => "## ⭐ Favorite Tools\n\n".to_string(),
+    };
+    push_section(&mut text, &mut sections, "Favorites", &header);
+
+    if pinned.is_empty() {
+        text.push_str(
+            "_No tools pinned._ Pin one with `/loom-favorites add <tool>`; pinned tools are \
+             listed first in `/loom-tools`.\n\n",
+        );
+        return FormattedOutput { text, sections };
+    }
+    let rows = listing
+        .filter(|r| r.success())
+        .map(|r| parse_tool_rows(&r.stdout))
+        .unwrap_or_default();
+    let body = if rows.is_empty() {
+        let mut list = String::new();
+        for tool in pinned {
+            list.push_str(&format!("- `{}`\n", tool));
+        }
+        list.push('\n');
+        list
+    } else {
+        pinned_tools_table(pinned, &rows)
+    };
+    push_section(&mut text, &mut sections, "Pinned", &body);
+    FormattedOutput { text, sections }
+}
+
+/// Format server listing.
+///
+/// `changes` holds the servers `(added, removed)` since the previous listing this session; when
+/// present they are shown as a diff above the list.
+pub(crate) fn format_servers_list(
+    result: &CommandResult,
+    changes: Option<(&[String], &[String])>,
+) -> FormattedOutput {
+    let Some((added, removed)) = changes else {
+        return format_listing(result, "Loom Servers", "Servers");
+    };
+
+    let icon = status_icon(result.success());
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Loom Servers",
+        &format!("## {} Loom Servers\n\n", icon),
+    );
+
+    let mut diff = String::new();
+    for name in added {
+        diff.push_str(&format!("+ {} (new)\n", name));
+    }
+    for name in removed {
+        diff.push_str(&format!("- {} (removed)\n", name));
+    }
+    push_section(
+        &mut text,
+        &mut sections,
+        "Changes",
+        &format!(
+            "**Changes since last `/loom-servers`**: +{} / -{}\n\n{}",
+            added.len(),
+            removed.len(),
+            fenced("diff", diff.trim_end())
+        ),
+    );
+
+    if let Some(listing) = parse_listing(&result.stdout) {
+        push_section(
+            &mut text,
+            &mut sections,
+            &counted_label("Servers", listing.count),
+            &format!("{}\n\n", listing.table),
+        );
+    } else if !result.stdout.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Output",
+            &fenced("", result.stdout.trim()),
+        );
+    }
+
+    if !result.stderr.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", result.stderr.trim()),
+        );
+    }
+
+    text.push_str(&format!("**Exit code**: `{}`\n", result.exit_code));
+
+    FormattedOutput { text, sections }
+}
+
+/// Errors shown by `/loom-server` before the rest are summarized as a count.
+const MAX_SERVER_ERRORS: usize = 5;
+
+/// Format `/loom-server <name>`: transport, tool count, health, and recent errors.
+///
+/// Reads `loom servers show <name> --json` (optionally wrapped in `{"server": {...}}`);
+/// non-JSON output falls back to [`format_generic`].
+pub(crate) fn format_server_detail(result: &CommandResult, name: &str) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let Some(server) = parsed
+        .as_ref()
+        .map(|v| v.get("server").unwrap_or(v))
+        .filter(|v| v.is_object() && result.success())
+    else {
+        return format_generic(result, &format!("Loom Server {}", name));
+    };
+    let text_field = |keys: &[&str]| {
+        keys.iter().find_map(|k| match server.get(*k)? {
+            zed::serde_json::Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            zed::serde_json::Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        })
+    };
+    let health = text_field(&["health", "status", "state"]);
+    let healthy = health.as_deref().is_some_and(|h| {
+        matches!(
+            h.to_ascii_lowercase().as_str(),
+            "healthy" | "ok" | "running" | "connected" | "ready" | "true"
+        )
+    });
+    let tool_count = server
+        .get("tool_count")
+        .and_then(|n| n.as_u64())
+        .or_else(|| {
+            server
+                .get("tools")
+                .and_then(|t| t.as_array())
+                .map(|t| t.len() as u64)
+        });
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Server",
+        &format!("## {} Server `{}`\n\n", status_icon(healthy), name),
+    );
+
+    let mut table = String::from("| Field | Value |\n| --- | --- |\n");
+    let mut row = |label: &str, value: Option<String>| {
+        table.push_str(&format!(
+            "| {} | {} |\n",
+            label,
+            value.unwrap_or_else(|| "—".into())
+        ));
+    };
+    row("Transport", text_field(&["transport", "type"]));
+    row(
+        "Endpoint",
+        text_field(&["url", "command", "endpoint"]).map(|s| format!("`{}`", s)),
+    );
+    row("Health", health);
+    row("Tools", tool_count.map(|n| n.to_string()));
+    row(
+        "Last seen",
+        text_field(&["last_seen", "last_heartbeat", "updated_at"]),
+    );
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Details", &table);
+
+    let errors: Vec<String> = ["recent_errors", "errors", "last_errors"]
+        .iter()
+        .find_map(|k| server.get(*k)?.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|e| match e {
+            zed::serde_json::Value::String(s) => Some(s.trim().to_string()),
+            obj => {
+                let message = ["message", "error", "msg"]
+                    .iter()
+                    .find_map(|k| obj.get(*k)?.as_str())?;
+                let when = ["time", "timestamp", "at"]
+                    .iter()
+                    .find_map(|k| obj.get(*k)?.as_str());
+                Some(match when {
+                    Some(when) => format!("{} — {}", when, message.trim()),
+                    None => message.trim().to_string(),
+                })
+            }
+        })
+        .collect();
+    if errors.is_empty() {
+        text.push_str("No recent errors.\n");
+    } else {
+        let mut body = format!("### Recent errors ({})\n\n", errors.len());
+        for error in errors.iter().rev().take(MAX_SERVER_ERRORS) {
+            body.push_str(&format!("- {}\n", error));
+        }
+        if errors.len() > MAX_SERVER_ERRORS {
+            body.push_str(&format!(
+                "- …and {} older\n",
+                errors.len() - MAX_SERVER_ERRORS
+            ));
+        }
+        body.push('\n');
+        push_section(&mut text, &mut sections, "Errors", &body);
+    }
+    FormattedOutput { text, sections }
+}
+
+/// A server's state after `/loom-servers enable|disable|restart`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ServerState {
+    pub(crate) enabled: Option<bool>,
+    pub(crate) status: Option<String>,
+    pub(crate) tool_count: Option<u64>,
+}
+
+/// Read enabled flag, status, and tool count from `loom servers show <name> --json`.
+///
+/// Plain `key: value` output is read too; returns `None` when nothing recognizable is found.
+pub(crate) fn parse_server_state(stdout: &str) -> Option<ServerState> {
+    let state = match zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) {
+        Ok(value) => {
+            let server = value.get("server").unwrap_or(&value);
+            ServerState {
+                enabled: server
+                    .get("enabled")
+                    .and_then(|v| v.as_bool())
+                    .or_else(|| server.get("disabled")?.as_bool().map(|d| !d)),
+                status: ["status", "health", "state"]
+                    .iter()
+                    .find_map(|k| server.get(*k)?.as_str())
+                    .map(str::to_string),
+                tool_count: server
+                    .get("tool_count")
+                    .and_then(|n| n.as_u64())
+                    .or_else(|| {
+                        server
+                            .get("tools")
+                            .and_then(|t| t.as_array())
+                            .map(|t| t.len() as u64)
+                    }),
+            }
+        }
+        Err(_) => {
+            let field = |key: &str| {
+                stdout.lines().find_map(|line| {
+                    let (k, v) = line.split_once(':')?;
+                    k.trim()
+                        .eq_ignore_ascii_case(key)
+                        .then(|| v.trim().to_string())
+                })
+            };
+            ServerState {
+                enabled: field("enabled").map(|v| matches!(v.as_str(), "true" | "yes")),
+                status: field("status").or_else(|| field("state")),
+                tool_count: field("tools").and_then(|v| v.parse().ok()),
+            }
+        }
+    };
+    (state != ServerState::default()).then_some(state)
+}
+
+/// Format `/loom-servers enable|disable|restart <name>`: the outcome plus the server's new state.
+pub(crate) fn format_server_action(
+    result: &CommandResult,
+    action: &str,
+    name: &str,
+    state: Option<&ServerState>,
+) -> FormattedOutput {
+    let past = match action {
+        "enable" => "enabled",
+        "disable" => "disabled",
+        _ => "restarted",
+    };
+    if !result.success() {
+        return format_generic(result, &format!("Failed to {} `{}`", action, name));
+    }
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Server",
+        &format!("## ✅ Server `{}` {}\n\n", name, past),
+    );
+    match state {
+        Some(state) => {
+            let mut table = String::from("| Field | Value |\n| --- | --- |\n");
+            if let Some(enabled) = state.enabled {
+                table.push_str(&format!(
+                    "| Enabled | {} |\n",
+                    if enabled { "yes" } else { "no" }
+                ));
+            }
+            if let Some(status) = &state.status {
+                table.push_str(&format!("| Status | {} |\n", status));
+            }
+            if let Some(n) = state.tool_count {
+                table.push_str(&format!("| Tools | {} |\n", n));
+            }
+            table.push('\n');
+            push_section(&mut text, &mut sections, "State", &table);
+        }
+        None => text.push_str(&format!(
+            "The new state isn't available yet; run `/loom-server {}` to check.\n\n",
+            name
+        )),
+    }
+    if !result.stdout.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Output",
+            &fenced("", result.stdout.trim()),
+        );
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Table of tools contributed by each server, flagging servers that contribute none.
+///
+/// Rows cover every listed server plus any server that only appears in the tool listing.
+pub(crate) fn server_tool_counts_table(
+    servers: &BTreeSet<String>,
+    counts: &BTreeMap<String, ServerTools>,
+) -> String {
+    let names: BTreeSet<&str> = servers
+        .iter()
+        .chain(counts.keys())
+        .map(String::as_str)
+        .collect();
+    let mut table = String::from("| Server | Tools | Enabled |\n| --- | --- | --- |\n");
+    let mut idle = 0;
+    for name in names {
+        let c = counts.get(name).copied().unwrap_or_default();
+        let flag = if c.enabled == 0 {
+            idle += 1;
+            " ⚠️"
+        } else {
+            ""
+        };
+        table.push_str(&format!(
+            "| `{}`{} | {} | {} |\n",
+            name, flag, c.total, c.enabled
+        ));
+    }
+    table.push('\n');
+    if idle > 0 {
+        table.push_str(&format!(
+            "⚠️ {} server{} contribute{} no enabled tools.\n\n",
+            idle,
+            if idle == 1 { "" } else { "s" },
+            if idle == 1 { "s" } else { "" }
+        ));
+    }
+    table
+}
+
+/// Format health/ping check.
+pub(crate) fn format_ping(result: &CommandResult) -> FormattedOutput {
+    let icon = status_icon(result.success());
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Health",
+        &format!("## {} Loom Health\n\n", icon),
+    );
+
+    if result.success() {
+        text.push_str("Daemon is **reachable** and responding.\n\n");
+    } else {
+        text.push_str("Daemon is **not reachable**.\n\n");
+    }
+
+    if !result.stdout.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Details",
+            &fenced("", result.stdout.trim()),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Report for an unexpected error inside the extension, with what a maintainer needs to
+/// find it in Zed's log.
+pub(crate) fn format_extension_error(
+    command: &str,
+    args: &[String],
+    error: &str,
+    correlation_id: &str,
+    program: &str,
+    log_lines: &[String],
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Extension Error",
+        &format!(
+            "## ❌ Extension Error\n\n`/{}` failed inside the extension:\n\n{}",
+            command,
+            fenced("", error.trim())
+        ),
+    );
+    let invocation = if args.is_empty() {
+        format!("/{}", command)
+    } else {
+        format!("/{} {}", command, args.join(" "))
+    };
+    push_section(
+        &mut text,
+        &mut sections,
+        "Details",
+        &format!(
+            "| Field | Value |\n| --- | --- |\n| Command | `{}` |\n| Correlation ID | `{}` |\n\
+             | Binary | `{}` |\n| Extension | v{} |\n\n",
+            invocation.replace('|', "\\|"),
+            correlation_id,
+            program,
+            env!("CARGO_PKG_VERSION")
+        ),
+    );
+    if !log_lines.is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Recent Log",
+            &fenced("", &log_lines.join("\n")),
+        );
+    }
+    text.push_str(&format!(
+        "If this keeps happening, include this report in an issue; Zed's log (`zed: open log`) \
+         has every line tagged `{}`.\n",
+        correlation_id
+    ));
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-benchmark`: min/median/max latency over the successful calls plus each sample.
+pub(crate) fn format_benchmark(
+    tool: &str,
+    runs: u32,
+    samples_ms: &[u64],
+    failure: Option<&CommandResult>,
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Benchmark",
+        &format!(
+            "## {} Benchmark: `{}`\n\n",
+            status_icon(failure.is_none()),
+            tool
+        ),
+    );
+
+    match LatencyStats::from_samples(samples_ms) {
+        Some(stats) => {
+            let list: Vec<String> = samples_ms.iter().map(|ms| format!("{} ms", ms)).collect();
+            push_section(
+                &mut text,
+                &mut sections,
+                "Latency",
+                &format!(
+                    "| Min | Median | Max |\n| --- | --- | --- |\n| {} ms | {} ms | {} ms |\n\n\
+                     {} of {} calls succeeded: {}\n\n\
+                     Times are wall-clock round trips of `loom tools call`, including CLI startup.\n\n",
+                    stats.min_ms,
+                    stats.median_ms,
+                    stats.max_ms,
+                    samples_ms.len(),
+                    runs,
+                    list.join(", ")
+                ),
+            );
+        }
+        None => text.push_str("No call succeeded, so there is nothing to measure.\n\n"),
+    }
+
+    if let Some(failed) = failure {
+        let error = if failed.stderr.trim().is_empty() {
+            failed.stdout.trim()
+        } else {
+            failed.stderr.trim()
+        };
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &format!(
+                "Call {} failed (exit code {}); stopped there.\n\n{}\
+                 Pick a cheap, read-only tool with `/loom-benchmark <tool>` or \
+                 `settings.commands.benchmark_tool`.\n\n",
+                samples_ms.len() + 1,
+                failed.exit_code,
+                fenced("", error)
+            ),
+        );
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-env`: which binary slash commands run and why, the `PATH` they get, and the
+/// env overrides from `context_servers.loom.command.env` (secrets redacted).
+pub(crate) fn format_env_report(
+    resolved: &ResolvedBinary,
+    candidates: &[BinaryCandidate],
+    version: Option<&str>,
+    overrides: &[(String, String)],
+    path_sep: &str,
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Environment",
+        "## 🧭 Loom Environment\n\n",
+    );
+
+    let origin = if resolved.from_shell_env {
+        format!(
+            "worktree shell environment ({} variables)",
+            resolved.env.len()
+        )
+    } else {
+        "Zed's own `PATH` only (no worktree shell environment)".to_string()
+    };
+    push_section(
+        &mut text,
+        &mut sections,
+        "Binary",
+        &format!(
+            "| Field | Value |\n| --- | --- |\n| Binary | `{}` |\n| Source | {} — {} |\n\
+             | Version | {} |\n| Environment | {} |\n\n",
+            resolved.program,
+            resolved.source.label(),
+            resolved.source.explanation(),
+            version.unwrap_or("unknown (`loom version` failed)"),
+            origin
+        ),
+    );
+
+    let mut table = String::from("| # | Source | Found | Used |\n| --- | --- | --- | --- |\n");
+    for (i, source) in BinarySource::PRECEDENCE.iter().enumerate() {
+        let found: Vec<&str> = candidates
+            .iter()
+            .filter(|c| c.source == *source)
+            .map(|c| c.path.as_str())
+            .collect();
+        let found = if found.is_empty() {
+            "—".to_string()
+        } else {
+            found
+                .iter()
+                .map(|p| format!("`{}`", p))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            i + 1,
+            source.label(),
+            found,
+            if *source == resolved.source {
+                "✅"
+            } else {
+                ""
+            }
+        ));
+    }
+    table.push_str("\nThe first source that finds a binary wins.\n\n");
+    push_section(&mut text, &mut sections, "Resolution Order", &table);
+
+    let path = resolved
+        .env
+        .iter()
+        .find(|(k, _)| k == "PATH")
+        .map(|(_, v)| v.as_str())
+        .unwrap_or_default();
+    let program_dir = std::path::Path::new(&resolved.program).parent();
+    let mut list = String::new();
+    for (i, dir) in path.split(path_sep).filter(|d| !d.is_empty()).enumerate() {
+        let mut notes = Vec::new();
+        if resolved.path_prefix.as_deref() == Some(dir) {
+            notes.push("added by the extension");
+        }
+        if program_dir.is_some_and(|p| p == std::path::Path::new(dir)) {
+            notes.push("binary is here");
+        }
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!(" ← {}", notes.join(", "))
+        };
+        list.push_str(&format!("{}. `{}`{}\n", i + 1, dir, notes));
+    }
+    if list.is_empty() {
+        list.push_str("_`PATH` is empty._\n");
+    }
+    list.push('\n');
+    push_section(&mut text, &mut sections, "PATH", &list);
+
+    let overrides_text = if overrides.is_empty() {
+        "None (`context_servers.loom.command.env` is not set).\n\n".to_string()
+    } else {
+        let mut table = String::from("| Variable | Value |\n| --- | --- |\n");
+        for (key, value) in overrides {
+            table.push_str(&format!(
+                "| `{}` | `{}` |\n",
+                key,
+                crate::env::redact_env_value(key, value).replace('|', "\\|")
+            ));
+        }
+        table.push('\n');
+        table
+    };
+    push_section(&mut text, &mut sections, "Overrides", &overrides_text);
+
+    text.push_str(
+        "If `loom` works in your terminal but not here, compare `which loom` and `echo $PATH` \
+         there with the above. Zed only sees the worktree shell's environment, so tools like \
+         direnv or version managers must be active for the project directory.\n",
+    );
+    FormattedOutput { text, sections }
+}
+
+/// One server's result in `/loom-health`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ServerProbe {
+    pub(crate) name: String,
+    pub(crate) reachable: bool,
+    /// Round trip of `loom servers ping`, including CLI startup.
+    pub(crate) latency_ms: u64,
+    /// First line of the failure, when unreachable.
+    pub(crate) error: Option<String>,
+}
+
+/// Format `/loom-health`: daemon reachability plus a per-server ✅/❌ table with latencies.
+pub(crate) fn format_health(
+    daemon: &CommandResult,
+    daemon_ms: u64,
+    listing: Option<&CommandResult>,
+    probes: &[ServerProbe],
+) -> FormattedOutput {
+    let down = probes.iter().filter(|p| !p.reachable).count();
+    let healthy = daemon.success() && down == 0 && listing.is_none_or(|l| l.success());
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Health",
+        &format!("## {} Loom Health\n\n", status_icon(healthy)),
+    );
+    if daemon.success() {
+        text.push_str(&format!("Daemon is **reachable** ({} ms).\n\n", daemon_ms));
+    } else {
+        text.push_str("Daemon is **not reachable**; server checks were skipped.\n\n");
+    }
+
+    match listing {
+        Some(listing) if !listing.success() => {
+            let error = if listing.stderr.trim().is_empty() {
+                listing.stdout.trim()
+            } else {
+                listing.stderr.trim()
+            };
+            push_section(
+                &mut text,
+                &mut sections,
+                "Servers",
+                &format!("Couldn't list servers:\n\n{}", fenced("", error)),
+            );
+        }
+        Some(_) if probes.is_empty() => push_section(
+            &mut text,
+            &mut sections,
+            "Servers",
+            "No servers are registered.\n\n",
+        ),
+        Some(_) => {
+            let mut table = String::from(
+                "| Server | Reachable | Latency | Error |\n| --- | --- | --- | --- |\n",
+            );
+            for probe in probes {
+                table.push_str(&format!(
+                    "| `{}` | {} | {} ms | {} |\n",
+                    probe.name,
+                    status_icon(probe.reachable),
+                    probe.latency_ms,
+                    probe
+                        .error
+                        .as_deref()
+                        .map(table_cell)
+                        .unwrap_or_else(|| "—".into())
+                ));
+            }
+            table.push('\n');
+            table.push_str(&format!(
+                "{} of {} servers reachable.\n\n",
+                probes.len() - down,
+                probes.len()
+            ));
+            push_section(&mut text, &mut sections, "Servers", &table);
+        }
+        None => {}
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Format `loom agent heartbeat-status`: last heartbeat time, agent, and auto-heartbeat cadence.
+pub(crate) fn format_heartbeat_status(result: &CommandResult) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim())
+        .ok()
+        .filter(|v| v.is_object());
+    let Some(v) = parsed else {
+        return format_generic(result, "Heartbeat Status");
+    };
+    let field = |keys: &[&str]| {
+        keys.iter().find_map(|k| match v.get(*k)? {
+            zed::serde_json::Value::String(s) => Some(s.clone()),
+            zed::serde_json::Value::Null => None,
+            other => Some(other.to_string()),
+        })
+    };
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Heartbeat Status",
+        &format!("## {} Heartbeat Status\n\n", status_icon(result.success())),
+    );
+
+    let interval = match field(&["interval_secs", "interval"]).as_deref() {
+        None | Some("0") => "off".to_string(),
+        Some(secs) => format!("every {}s", secs),
+    };
+    let table = format!(
+        "| Field | Value |\n| --- | --- |\n| Last heartbeat | {} |\n| From agent | `{}` |\n| Agent status | {} |\n| Auto-heartbeat | {} |\n\n",
+        field(&["last_heartbeat", "last_seen", "timestamp"]).unwrap_or_else(|| "never".into()),
+        field(&["agent_id", "agent"]).unwrap_or_else(|| "—".into()),
+        field(&["status"]).unwrap_or_else(|| "—".into()),
+        interval
+    );
+    push_section(&mut text, &mut sections, "Heartbeat", &table);
+
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-agents`: registered agents with their last heartbeat and status.
+///
+/// `own_agent` (this editor's agent ID) is marked in the table. Non-JSON output falls back to
+/// [`format_generic`].
+pub(crate) fn format_agents(result: &CommandResult, own_agent: &str) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let agents = parsed
+        .as_ref()
+        .and_then(|v| v.get("agents").or(Some(v)))
+        .and_then(|v| v.as_array());
+    let Some(agents) = agents.filter(|_| result.success()) else {
+        return format_generic(result, "Loom Agents");
+    };
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Agents",
+        &format!("## {} Loom Agents\n\n", status_icon(true)),
+    );
+    if agents.is_empty() {
+        text.push_str("No agents are registered with the daemon.\n");
+        return FormattedOutput { text, sections };
+    }
+
+    let mut active = 0;
+    let mut table = String::from("| Agent | Last heartbeat | Status |\n| --- | --- | --- |\n");
+    for agent in agents {
+        let field = |keys: &[&str]| {
+            keys.iter().find_map(|k| match agent.get(*k)? {
+                zed::serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+                zed::serde_json::Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+        };
+        let id = field(&["agent_id", "id", "name"]).unwrap_or_else(|| "?".into());
+        let status = field(&["status", "state"]).unwrap_or_else(|| "—".into());
+        let lower = status.to_ascii_lowercase();
+        let is_active = ["active", "online", "running", "healthy"]
+            .iter()
+            .any(|s| lower.contains(s));
+        if is_active {
+            active += 1;
+        }
+        table.push_str(&format!(
+            "| `{}`{} | {} | {} {} |\n",
+            id,
+            if id == own_agent {
+                " (this editor)"
+            } else {
+                ""
+            },
+            field(&["last_heartbeat", "last_seen", "heartbeat_at"])
+                .unwrap_or_else(|| "never".into()),
+            if is_active { "🟢" } else { "⚪" },
+            status
+        ));
+    }
+    text.push_str(&format!(
+        "**{} agents, {} active**\n\n",
+        agents.len(),
+        active
+    ));
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Agent List", &table);
+
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-metrics`: overall request/error counts plus a per-server latency table.
+///
+/// Accepts `servers` as a list of objects (with `name`) or a map keyed by server name. Counts
+/// and latencies are read from the usual key spellings; missing values show as `—`.
+pub(crate) fn format_metrics(result: &CommandResult) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim())
+        .ok()
+        .filter(|v| v.is_object() && result.success());
+    let Some(v) = parsed else {
+        return format_generic(result, "Loom Metrics");
+    };
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Metrics",
+        "## 📊 Loom Metrics\n\n",
+    );
+
+    let (requests, errors) = metric_counts(&v);
+    let mut overview = format!(
+        "| Metric | Value |\n| --- | --- |\n| Requests | {} |\n| Errors | {} |\n| Error rate | {} |\n",
+        requests.map(|n| n.to_string()).unwrap_or_else(|| "—".into()),
+        errors.map(|n| n.to_string()).unwrap_or_else(|| "—".into()),
+        error_rate(requests, errors)
+    );
+    if let Some(uptime) = metric_number(&v, &["uptime_secs", "uptime_seconds"]) {
+        overview.push_str(&format!("| Uptime | {} |\n", human_duration(uptime as u64)));
+    }
+    overview.push('\n');
+    push_section(&mut text, &mut sections, "Overview", &overview);
+
+    let servers: Vec<(String, &zed::serde_json::Value)> = match v.get("servers") {
+        Some(zed::serde_json::Value::Array(items)) => items
+            .iter()
+            .map(|s| {
+                let name = s.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+                (name.to_string(), s)
+            })
+            .collect(),
+        Some(zed::serde_json::Value::Object(map)) => {
+            map.iter().map(|(name, s)| (name.clone(), s)).collect()
+        }
+        _ => Vec::new(),
+    };
+    if !servers.is_empty() {
+        let ms = |s: &zed::serde_json::Value, keys: &[&str]| {
+            metric_number(s, keys)
+                .map(|n| format!("{:.0} ms", n))
+                .unwrap_or_else(|| "—".into())
+        };
+        let mut table = String::from(
+            "### Per-server\n\n| Server | Requests | Errors | Error rate | p50 | p95 |\n| --- | --- | --- | --- | --- | --- |\n",
+        );
+        for (name, server) in &servers {
+            let (requests, errors) = metric_counts(server);
+            table.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                name,
+                requests
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(|| "—".into()),
+                errors.map(|n| n.to_string()).unwrap_or_else(|| "—".into()),
+                error_rate(requests, errors),
+                ms(
+                    server,
+                    &["p50_ms", "latency_p50_ms", "avg_latency_ms", "latency_ms"]
+                ),
+                ms(server, &["p95_ms", "latency_p95_ms"]),
+            ));
+        }
+        table.push('\n');
+        push_section(&mut text, &mut sections, "Per-server", &table);
+    }
+
+    FormattedOutput { text, sections }
+}
+
+fn metric_number(v: &zed::serde_json::Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|k| v.get(*k)?.as_f64())
+}
+
+/// `(requests, errors)` from the common key spellings.
+fn metric_counts(v: &zed::serde_json::Value) -> (Option<u64>, Option<u64>) {
+    let count = |keys: &[&str]| keys.iter().find_map(|k| v.get(*k)?.as_u64());
+    (
+        count(&["requests", "requests_total", "request_count", "calls"]),
+        count(&["errors", "errors_total", "error_count", "failures"]),
+    )
+}
+
+fn error_rate(requests: Option<u64>, errors: Option<u64>) -> String {
+    match (requests, errors) {
+        (Some(0), _) => "0%".into(),
+        (Some(total), Some(failed)) => {
+            let rate = failed as f64 * 100.0 / total as f64;
+            let icon = if rate >= 5.0 { " ⚠️" } else { "" };
+            format!("{:.1}%{}", rate, icon)
+        }
+        _ => "—".into(),
+    }
+}
+
+fn human_duration(secs: u64) -> String {
+    match secs {
+        s if s >= 86_400 => format!("{}d {}h", s / 86_400, (s % 86_400) / 3600),
+        s if s >= 3600 => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+        s if s >= 60 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Share of a provider's quota (percent) at which `/loom-quota` warns.
+const QUOTA_WARN_PERCENT: u64 = 90;
+
+/// One provider's usage as reported by the `provider_usage` tool.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct ProviderQuota {
+    name: String,
+    used: Option<u64>,
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    /// Unix seconds when the window resets.
+    resets_at: Option<u64>,
+}
+
+impl ProviderQuota {
+    /// Percent of the limit used, when the limit is known and non-zero.
+    fn used_percent(&self) -> Option<u64> {
+        let limit = self.limit.filter(|l| *l > 0)?;
+        Some(self.used? * 100 / limit)
+    }
+
+    fn icon(&self) -> &'static str {
+        match (self.remaining, self.used_percent()) {
+            (Some(0), _) => "🔴",
+            (_, Some(p)) if p >= QUOTA_WARN_PERCENT => "🟠",
+            (_, Some(_)) => "🟢",
+            _ => "⚪",
+        }
+    }
+}
+
+/// Parse `provider_usage` output: `{"providers": [...]}`, a bare array, or an object keyed by
+/// provider. Missing `used`/`remaining` are derived from the limit; resets may be absolute
+/// timestamps or seconds from `now`.
+fn parse_quotas(stdout: &str, now: u64) -> Option<Vec<ProviderQuota>> {
+    let v: zed::serde_json::Value = zed::serde_json::from_str(stdout.trim()).ok()?;
+    let root = v.get("providers").unwrap_or(&v);
+    let items: Vec<(String, &zed::serde_json::Value)> = match root {
+        zed::serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                let name = ["provider", "name", "id"]
+                    .iter()
+                    .find_map(|k| item.get(*k)?.as_str())
+                    .unwrap_or("?");
+                (name.to_string(), item)
+            })
+            .collect(),
+        zed::serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(name, item)| (name.clone(), item))
+            .collect(),
+        _ => return None,
+    };
+    let quotas = items
+        .into_iter()
+        .filter(|(_, item)| item.is_object())
+        .map(|(name, item)| {
+            let count = |keys: &[&str]| keys.iter().find_map(|k| item.get(*k)?.as_u64());
+            let limit = count(&["limit", "quota", "max_requests", "request_limit"]);
+            let mut used = count(&["used", "requests", "request_count", "calls"]);
+            let mut remaining = count(&["remaining", "requests_remaining", "left"]);
+            if let (Some(limit), None) = (limit, remaining) {
+                remaining = used.map(|u| limit.saturating_sub(u));
+            }
+            if let (Some(limit), None) = (limit, used) {
+                used = remaining.map(|r| limit.saturating_sub(r));
+            }
+            let resets_at = ["reset_at", "resets_at", "reset"]
+                .iter()
+                .find_map(|k| event_time(item.get(*k)?))
+                .or_else(|| {
+                    count(&["reset_in_secs", "reset_after_secs", "retry_after"]).map(|s| now + s)
+                });
+            ProviderQuota {
+                name,
+                used,
+                limit,
+                remaining,
+                resets_at,
+            }
+        })
+        .collect();
+    Some(quotas)
+}
+
+/// Whether a failed `loom mcp call` says the hub has no such tool, as opposed to the tool failing.
+pub(crate) fn is_missing_tool(result: &CommandResult) -> bool {
+    let output = format!("{}\n{}", result.stdout, result.stderr).to_ascii_lowercase();
+    !result.success()
+        && ["unknown tool", "tool not found", "no such tool"]
+            .iter()
+            .any(|m| output.contains(m))
+}
+
+/// Format `/loom-quota`: per-provider requests, limit, remaining quota, and reset time, with
+/// providers at or past [`QUOTA_WARN_PERCENT`] called out above the table.
+pub(crate) fn format_quota(result: &CommandResult, now: u64) -> FormattedOutput {
+    let quotas = result
+        .success()
+        .then(|| parse_quotas(&result.stdout, now))
+        .flatten();
+    let Some(quotas) = quotas else {
+        let mut out = format_generic(result, "Provider Quotas");
+        if is_missing_tool(result) {
+            out.append_section(
+                "Remediation",
+                "This hub has no `provider_usage` tool; upgrade loom-core (`/loom-update`) or \
+                 enable the server that provides it.\n\n",
+            );
+        }
+        return out;
+    };
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Quota",
+        "## 🚦 Provider Quotas\n\n",
+    );
+    if quotas.is_empty() {
+        text.push_str("_No providers reported usage._\n\n");
+        return FormattedOutput { text, sections };
+    }
+
+    let reset = |q: &ProviderQuota| match q.resets_at {
+        Some(at) if at > now => format!("in {}", human_duration(at - now)),
+        Some(_) => "now".to_string(),
+        None => "—".to_string(),
+    };
+    let warnings: Vec<String> = quotas
+        .iter()
+        .filter(|q| matches!(q.icon(), "🔴" | "🟠"))
+        .map(|q| match q.remaining {
+            Some(0) => format!(
+                "- 🔴 **{}** is out of quota (resets {})\n",
+                q.name,
+                reset(q)
+            ),
+            _ => format!(
+                "- 🟠 **{}** has used {}% of its quota (resets {})\n",
+                q.name,
+                q.used_percent().unwrap_or_default(),
+                reset(q)
+            ),
+        })
+        .collect();
+    if !warnings.is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Warnings",
+            &format!("{}\n", warnings.concat()),
+        );
+    }
+
+    let num = |n: Option<u64>| n.map_or("—".to_string(), |n| n.to_string());
+    let mut table = String::from(
+        "| | Provider | Requests | Limit | Remaining | Used | Resets |\n| --- | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for q in &quotas {
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} |\n",
+            q.icon(),
+            table_cell(&q.name),
+            num(q.used),
+            num(q.limit),
+            num(q.remaining),
+            q.used_percent()
+                .map_or("—".to_string(), |p| format!("{}%", p)),
+            reset(q)
+        ));
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Providers", &table);
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-events`: a newest-first timeline of daemon events with relative timestamps.
+///
+/// Accepts `{"events": [...]}` or a bare array; each event's kind, detail, and time are read
+/// from the usual key spellings. Timestamps may be RFC 3339 strings or Unix seconds/millis.
+/// Non-JSON output falls back to [`format_generic`].
+pub(crate) fn format_events(result: &CommandResult, now: u64) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let events = parsed
+        .as_ref()
+        .and_then(|v| v.get("events").or(Some(v)))
+        .and_then(|v| v.as_array());
+    let Some(events) = events.filter(|_| result.success()) else {
+        return format_generic(result, "Loom Events");
+    };
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Events",
+        "## 🕒 Loom Events
+
+",
+    );
+    if events.is_empty() {
+        text.push_str(
+            "No recent daemon events.
+",
+        );
+        return FormattedOutput { text, sections };
+    }
+
+    let mut rows: Vec<(Option<u64>, String)> = events
+        .iter()
+        .map(|event| {
+            let field = |keys: &[&str]| {
+                keys.iter().find_map(|k| {
+                    event
+                        .get(*k)?
+                        .as_str()
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string)
+                })
+            };
+            let kind = field(&["type", "kind", "event", "name"]).unwrap_or_else(|| "event".into());
+            let detail = field(&["message", "summary", "detail", "description"]);
+            let subject = field(&["server", "tool", "platform", "agent_id"]);
+            let at = ["timestamp", "time", "ts", "at", "created_at"]
+                .iter()
+                .find_map(|k| event_time(event.get(*k)?));
+
+            let mut line = format!(
+                "- **{}** {} {}",
+                at.map(|t| relative_time(t, now))
+                    .unwrap_or_else(|| "unknown time".into()),
+                event_icon(&kind),
+                kind.replace(['_', '.'], " ")
+            );
+            if let Some(subject) = subject {
+                line.push_str(&format!(" `{}`", subject));
+            }
+            if let Some(detail) = detail {
+                line.push_str(&format!(" — {}", detail));
+            }
+            line.push('\n');
+            (at, line)
+        })
+        .collect();
+    // Newest first; events without a timestamp keep their CLI order at the end.
+    rows.sort_by(|a, b| b.0.is_some().cmp(&a.0.is_some()).then(b.0.cmp(&a.0)));
+
+    text.push_str(&format!("**{} events**\n\n", rows.len()));
+    let timeline: String = rows.into_iter().map(|(_, line)| line).collect();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Timeline",
+        &format!("{}\n", timeline),
+    );
+
+    FormattedOutput { text, sections }
+}
+
+/// Format `loom audit`: who did what, to what, and when, newest first.
+///
+/// Accepts `{"entries": [...]}` (or `audit`/`events`) or a bare array. `keyword` keeps entries
+/// whose fields contain it (case-insensitive); text output is filtered line by line.
+pub(crate) fn format_audit(
+    result: &CommandResult,
+    keyword: Option<&str>,
+    now: u64,
+) -> FormattedOutput {
+    let needle = keyword.map(str::to_lowercase);
+    let matches = |text: &str| {
+        needle
+            .as_deref()
+            .is_none_or(|n| text.to_lowercase().contains(n))
+    };
+    let title = match keyword {
+        Some(k) => format!("## 🛡️ Audit Log: \"{}\"\n\n", k),
+        None => "## 🛡️ Audit Log\n\n".to_string(),
+    };
+
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let entries = parsed.as_ref().and_then(|v| {
+        ["entries", "audit", "events"]
+            .iter()
+            .find_map(|k| v.get(*k))
+            .unwrap_or(v)
+            .as_array()
+    });
+    let Some(entries) = entries.filter(|_| result.success()) else {
+        if !result.success() || result.stdout.trim().is_empty() {
+            return format_generic(result, "Audit Log");
+        }
+        let lines: Vec<&str> = result.stdout.lines().filter(|l| matches(l)).collect();
+        let mut out = FormattedOutput::plain(String::new());
+        out.append_section("Audit", &title);
+        out.append_section(
+            "Results",
+            &if lines.is_empty() {
+                "No audit entries match.\n".to_string()
+            } else {
+                fenced("", &lines.join("\n"))
+            },
+        );
+        return out;
+    };
+
+    let mut rows: Vec<(Option<u64>, String)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let field = |keys: &[&str]| {
+                keys.iter().find_map(|k| {
+                    entry
+                        .get(*k)?
+                        .as_str()
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string)
+                })
+            };
+            let who = field(&["actor", "agent_id", "user", "who", "client"]);
+            let action = field(&["action", "type", "kind", "event"]);
+            let target = field(&["target", "tool", "secret", "resource", "path", "server"]);
+            let outcome = field(&["result", "status", "outcome"]);
+            let detail = field(&["detail", "message", "summary"]);
+            let haystack = [&who, &action, &target, &outcome, &detail]
+                .iter()
+                .filter_map(|f| f.as_deref())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !matches(&haystack) {
+                return None;
+            }
+            let at = ["timestamp", "time", "ts", "at", "created_at"]
+                .iter()
+                .find_map(|k| event_time(entry.get(*k)?));
+            let cell = |v: Option<String>| v.map(|s| table_cell(&s)).unwrap_or_else(|| "—".into());
+            let target = match (target, detail) {
+                (Some(t), Some(d)) => Some(format!("`{}` — {}", t, d)),
+                (Some(t), None) => Some(format!("`{}`", t)),
+                (None, d) => d,
+            };
+            let row = format!(
+                "| {} | {} | {} | {} | {} |\n",
+                at.map(|t| relative_time(t, now))
+                    .unwrap_or_else(|| "—".into()),
+                cell(who),
+                cell(action),
+                cell(target),
+                cell(outcome)
+            );
+            Some((at, row))
+        })
+        .collect();
+    rows.sort_by(|a, b| b.0.is_some().cmp(&a.0.is_some()).then(b.0.cmp(&a.0)));
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(&mut text, &mut sections, "Audit", &title);
+    if rows.is_empty() {
+        text.push_str(if keyword.is_some() {
+            "No audit entries match.\n"
+        } else {
+            "The audit log is empty.\n"
+        });
+        return FormattedOutput { text, sections };
+    }
+    let shown = if keyword.is_some() {
+        format!("**{} of {} entries match**\n\n", rows.len(), entries.len())
+    } else {
+        format!("**{} entries**\n\n", rows.len())
+    };
+    text.push_str(&shown);
+    let mut table = String::from(
+        "| When | Who | Action | Target | Result |\n| --- | --- | --- | --- | --- |\n",
+    );
+    for (_, row) in rows {
+        table.push_str(&row);
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Results", &table);
+    FormattedOutput { text, sections }
+}
+
+/// A message from `agent_inbox`, addressed to this editor's agent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct InboxMessage {
+    pub(crate) id: String,
+    pub(crate) from: String,
+    pub(crate) subject: String,
+    pub(crate) body: String,
+    pub(crate) namespace: Option<String>,
+    pub(crate) sent_at: Option<u64>,
+    pub(crate) read: bool,
+}
+
+/// Parse inbox messages from `{"messages": [...]}`, `{"message": {...}}`, or a bare array/object.
+///
+/// Returns `None` when the output isn't JSON. Messages come back oldest first.
+pub(crate) fn parse_inbox(stdout: &str) -> Option<Vec<InboxMessage>> {
+    let value = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()).ok()?;
+    let items: Vec<&zed::serde_json::Value> = match value
+        .get("messages")
+        .or_else(|| value.get("inbox"))
+        .or_else(|| value.get("message"))
+        .unwrap_or(&value)
+    {
+        zed::serde_json::Value::Array(items) => items.iter().collect(),
+        obj @ zed::serde_json::Value::Object(_) => vec![obj],
+        _ => return None,
+    };
+    let mut messages: Vec<InboxMessage> = items
+        .into_iter()
+        .map(|item| {
+            let field = |keys: &[&str]| {
+                keys.iter().find_map(|k| match item.get(*k)? {
+                    zed::serde_json::Value::String(s) if !s.trim().is_empty() => {
+                        Some(s.trim().to_string())
+                    }
+                    zed::serde_json::Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+            };
+            let body = field(&["body", "content", "message", "summary"]).unwrap_or_default();
+            InboxMessage {
+                id: field(&["id", "message_id"]).unwrap_or_else(|| "?".into()),
+                from: field(&["from_agent", "from", "sender"]).unwrap_or_else(|| "?".into()),
+                subject: field(&["subject", "title"])
+                    .unwrap_or_else(|| body.lines().next().unwrap_or_default().to_string()),
+                namespace: field(&["namespace"]),
+                sent_at: ["sent_at", "created_at", "timestamp", "time"]
+                    .iter()
+                    .find_map(|k| event_time(item.get(*k)?)),
+                read: item.get("read").and_then(|v| v.as_bool()).unwrap_or(false),
+                body,
+            }
+        })
+        .collect();
+    messages.sort_by_key(|m| m.sent_at);
+    Some(messages)
+}
+
+/// Format `/loom-inbox`: messages for `agent_id`, oldest first, unread ones marked.
+///
+/// Non-JSON output falls back to [`format_generic`].
+pub(crate) fn format_inbox(result: &CommandResult, agent_id: &str, now: u64) -> FormattedOutput {
+    let Some(messages) = parse_inbox(&result.stdout).filter(|_| result.success()) else {
+        return format_generic(result, "Loom Inbox");
+    };
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let unread = messages.iter().filter(|m| !m.read).count();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Inbox",
+        &format!(
+            "## 📬 Inbox for `{}`\n\n**{} messages, {} unread**\n\n",
+            agent_id,
+            messages.len(),
+            unread
+        ),
+    );
+    if messages.is_empty() {
+        text.push_str("No messages. Other agents reach you with `/loom-handoff`.\n");
+        return FormattedOutput { text, sections };
+    }
+
+    let mut table =
+        String::from("|  | ID | From | Sent | Subject |\n| --- | --- | --- | --- | --- |\n");
+    for m in &messages {
+        table.push_str(&format!(
+            "| {} | `{}` | `{}` | {} | {} |\n",
+            if m.read { "" } else { "🔵" },
+            m.id,
+            m.from,
+            m.sent_at
+                .map(|t| relative_time(t, now))
+                .unwrap_or_else(|| "—".into()),
+            table_cell(&m.subject)
+        ));
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Messages", &table);
+    text.push_str("Use `/loom-inbox read <id>` to open a message.\n");
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-inbox read <id>`: one message in full.
+pub(crate) fn format_inbox_message(result: &CommandResult, id: &str, now: u64) -> FormattedOutput {
+    let message = parse_inbox(&result.stdout)
+        .filter(|_| result.success())
+        .and_then(|messages| messages.into_iter().find(|m| m.id == id || m.id == "?"));
+    let Some(m) = message else {
+        return format_generic(result, &format!("Inbox Message {}", id));
+    };
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Message",
+        &format!(
+            "## ✉️ {}\n\n**From**: `{}` · **Sent**: {}{}\n\n",
+            if m.subject.is_empty() { id } else { &m.subject },
+            m.from,
+            m.sent_at
+                .map(|t| relative_time(t, now))
+                .unwrap_or_else(|| "unknown".into()),
+            m.namespace
+                .as_deref()
+                .map(|ns| format!(" · **Namespace**: `{}`", ns))
+                .unwrap_or_default()
+        ),
+    );
+    push_section(
+        &mut text,
+        &mut sections,
+        "Body",
+        &format!("{}\n\n", m.body.trim()),
+    );
+    FormattedOutput { text, sections }
+}
+
+fn event_icon(kind: &str) -> &'static str {
+    let lower = kind.to_ascii_lowercase();
+    if ["error", "fail", "crash"].iter().any(|k| lower.contains(k)) {
+        "❌"
+    } else if lower.contains("disconnect") || lower.contains("stop") {
+        "🔌"
+    } else if lower.contains("connect") || lower.contains("start") {
+        "🟢"
+    } else if lower.contains("tool") {
+        "🧰"
+    } else if lower.contains("sync") {
+        "🔄"
+    } else {
+        "•"
+    }
+}
+
+/// Unix seconds from a JSON timestamp: numbers (seconds or millis) or RFC 3339 strings.
+fn event_time(v: &zed::serde_json::Value) -> Option<u64> {
+    let secs = match v {
+        zed::serde_json::Value::Number(n) => n.as_u64()?,
+        zed::serde_json::Value::String(s) => match s.parse::<u64>() {
+            Ok(n) => n,
+            Err(_) => return parse_rfc3339(s),
+        },
+        _ => return None,
+    };
+    // Anything past year ~5000 in seconds is really milliseconds.
+    Some(if secs > 100_000_000_000 {
+        secs / 1000
+    } else {
+        secs
+    })
+}
+
+/// Parse `YYYY-MM-DDTHH:MM:SS[.frac](Z|±HH:MM)` into Unix seconds.
+fn parse_rfc3339(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let num = |range: std::ops::Range<usize>| s.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, min, sec) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !matches!(s.as_bytes().get(10), Some(b'T' | b't' | b' ')) {
+        return None;
+    }
+
+    let rest = s.get(19..)?;
+    let zone = rest.trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+    let offset = match zone {
+        "" | "Z" | "z" => 0,
+        _ => {
+            let sign = match zone.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let hh = zone.get(1..3)?.parse::<i64>().ok()?;
+            let mm = zone.get(4..6)?.parse::<i64>().ok()?;
+            sign * (hh * 3600 + mm * 60)
+        }
+    };
+
+    // Days-from-civil (Howard Hinnant), the inverse of `worktree::utc_date`.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    let secs = days * 86_400 + hour * 3600 + min * 60 + sec - offset;
+    u64::try_from(secs).ok()
+}
+
+/// `"just now"`, `"42s ago"`, `"5m ago"`, `"3h ago"`, `"2d ago"`.
+fn relative_time(then: u64, now: u64) -> String {
+    match now.saturating_sub(then) {
+        s if s < 5 => "just now".into(),
+        s if s < 60 => format!("{}s ago", s),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86_400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86_400),
+    }
+}
+
+/// Format `/loom-history`: recent invocations (given oldest first), newest first.
+///
+/// `total` is how many the session has recorded, so the header can say what was cut off.
+pub(crate) fn format_history(entries: &[&HistoryEntry], total: usize, now: u64) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "History",
+        &format!(
+            "## 🕘 Command History\n\n**Showing {} of {} commands this session**\n\n",
+            entries.len(),
+            total
+        ),
+    );
+    if entries.is_empty() {
+        text.push_str("No slash commands have run yet in this session.\n");
+        return FormattedOutput { text, sections };
+    }
+
+    let mut table =
+        String::from("|  | Command | Exit | Duration | When |\n| --- | --- | --- | --- | --- |\n");
+    let mut errors = String::new();
+    for entry in entries.iter().rev() {
+        let mut command = format!("/{}", entry.command);
+        let args = entry.args.join(" ");
+        if !args.is_empty() {
+            command.push(' ');
+            command.extend(args.chars().take(60));
+            if args.chars().count() > 60 {
+                command.push('…');
+            }
+        }
+        let command = command.replace('`', "'");
+        let ok = entry.error.is_none() && entry.exit_code.as_deref().is_none_or(|c| c == "0");
+        table.push_str(&format!(
+            "| {} | `{}` | {} | {} | {} |\n",
+            status_icon(ok),
+            command.replace('|', "\\|"),
+            entry.exit_code.as_deref().unwrap_or("—"),
+            match entry.duration_ms {
+                ms if ms < 1000 => format!("{}ms", ms),
+                ms => format!("{:.1}s", ms as f64 / 1000.0),
+            },
+            relative_time(entry.started_at, now)
+        ));
+        if let Some(error) = &entry.error {
+            errors.push_str(&format!("- `{}`: {}\n", command, error));
+        }
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Commands", &table);
+    if !errors.is_empty() {
+        errors.push('\n');
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &format!("### Errors\n\n{}", errors),
+        );
+    }
+    text.push_str("Use `/loom-last <command>` for the raw CLI output of a command's latest run.\n");
+    FormattedOutput { text, sections }
+}
+
+/// Name prefix the MCP wrapper gives every prompt recipe.
+pub(crate) const PROMPT_PREFIX: &str = "loom_zed__";
+
+/// A prompt recipe as served by the MCP wrapper (`--list-prompt-recipes`).
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct PromptRecipe {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: String,
+    #[serde(default)]
+    pub(crate) arguments: Vec<PromptArgument>,
+    #[serde(default)]
+    pub(crate) template: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct PromptArgument {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: String,
+    #[serde(default)]
+    pub(crate) required: bool,
+}
+
+impl PromptRecipe {
+    /// Name without the wrapper prefix, as typed after `/loom-prompts`.
+    pub(crate) fn short_name(&self) -> &str {
+        self.name.strip_prefix(PROMPT_PREFIX).unwrap_or(&self.name)
+    }
+}
+
+/// Parse the wrapper's `{"prompts": [...]}` recipe dump.
+pub(crate) fn parse_prompt_recipes(stdout: &str) -> Result<Vec<PromptRecipe>, String> {
+    #[derive(Deserialize)]
+    struct Dump {
+        prompts: Vec<PromptRecipe>,
+    }
+    zed::serde_json::from_str::<Dump>(stdout.trim())
+        .map(|dump| dump.prompts)
+        .map_err(|e| format!("could not parse prompt recipes from the MCP wrapper: {}", e))
+}
+
+/// Format `/loom-prompts`: every recipe with its description and arguments.
+///
+/// `warning` is wrapper stderr (e.g. a `recipes_file` that failed to load), shown as a note.
+pub(crate) fn format_prompts_list(
+    recipes: &[PromptRecipe],
+    warning: Option<&str>,
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Prompts",
+        "## 📝 Loom Prompt Recipes\n\n",
+    );
+    if let Some(warning) = warning.map(str::trim).filter(|w| !w.is_empty()) {
+        text.push_str(&format!("> ⚠️ {}\n\n", warning.replace('\n', "\n> ")));
+    }
+    if recipes.is_empty() {
+        text.push_str("No prompt recipes are available.\n");
+        return FormattedOutput { text, sections };
+    }
+
+    let mut table = String::from("| Recipe | Description | Arguments |\n| --- | --- | --- |\n");
+    for recipe in recipes {
+        let args: Vec<String> = recipe
+            .arguments
+            .iter()
+            .map(|a| {
+                if a.required {
+                    format!("`{}`*", a.name)
+                } else {
+                    format!("`{}`", a.name)
+                }
+            })
+            .collect();
+        table.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            recipe.short_name(),
+            table_cell(&recipe.description),
+            if args.is_empty() {
+                "—".to_string()
+            } else {
+                args.join(", ")
+            }
+        ));
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Recipe List", &table);
+    text.push_str(
+        "Use `/loom-prompts <recipe>` to preview the prompt text. Pick recipes from the Agent \
+         panel's prompt picker to use them.\n",
+    );
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-prompts <recipe>`: the prompt text the Agent receives, plus its arguments.
+pub(crate) fn format_prompt_preview(recipe: &PromptRecipe) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Prompt",
+        &format!(
+            "## 📝 `{}`\n\n{}\n\n",
+            recipe.short_name(),
+            recipe.description
+        ),
+    );
+    push_section(
+        &mut text,
+        &mut sections,
+        "Prompt Text",
+        &format!("### Prompt text\n\n{}", fence(&recipe.template, "markdown")),
+    );
+    if !recipe.arguments.is_empty() {
+        let mut args = String::from(
+            "### Arguments\n\nFilled-in arguments are appended under \"Additional context:\".\n\n\
+             | Argument | Required | Description |\n| --- | --- | --- |\n",
+        );
+        for arg in &recipe.arguments {
+            args.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                arg.name,
+                if arg.required { "yes" } else { "no" },
+                table_cell(&arg.description)
+            ));
+        }
+        args.push('\n');
+        push_section(&mut text, &mut sections, "Arguments", &args);
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Format secrets listing.
+pub(crate) fn format_secrets(result: &CommandResult, sub: &str) -> FormattedOutput {
+    let title = match sub {
+        "validate" => "Secrets Validation",
+        "set" => "Secret Set",
+        _ => "Secrets",
+    };
+    format_generic(result, title)
+}
+
+/// Format `/loom-secrets validate`, adding where to define each missing env-backed secret.
+pub(crate) fn format_secrets_validation(
+    result: &CommandResult,
+    hints: &[SecretHint],
+    shell: Option<&str>,
+) -> FormattedOutput {
+    let mut out = format_secrets(result, "validate");
+    if hints.is_empty() {
+        return out;
+    }
+    let mut fixes = String::from("### 🔑 Fix missing secrets\n\n");
+    for hint in hints {
+        fixes.push_str(&format!("- {}\n", hint.advice(shell)));
+    }
+    fixes.push('\n');
+    push_section(
+        &mut out.text,
+        &mut out.sections,
+        "Fix Missing Secrets",
+        &fixes,
+    );
+    out
+}
+
+/// Format session command output.
+pub(crate) fn format_session(result: &CommandResult, sub: &str) -> FormattedOutput {
+    let title = match sub {
+        "start" => "Session Started",
+        "end" => "Session Ended",
+        "resume" => "Session Resumed",
+        "list" => return format_listing(result, "Sessions", "Sessions"),
+        _ => "Session Status",
+    };
+    format_generic(result, title)
+}
+
+/// Format `/loom-summary`: the generated session summary as Markdown, with the session's
+/// namespace and age and any highlight/next-step lists the CLI returns.
+///
+/// `unsupported` means the CLI has no `session-summary`; `restarted` means the summary came
+/// from ending the session, which was then started again.
+pub(crate) fn format_session_summary(
+    result: &CommandResult,
+    unsupported: bool,
+    restarted: bool,
+    now: u64,
+) -> FormattedOutput {
+    if unsupported {
+        let mut formatted = format_generic(result, "Session Summary");
+        formatted.text.push_str(
+            "\nThis loom-core has no `agent session-summary`. Run `/loom-summary restart` to \
+             summarize by ending the session and starting a new one in the same namespace.\n",
+        );
+        return formatted;
+    }
+    if !result.success() {
+        return format_generic(result, "Session Summary");
+    }
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Summary",
+        "## 📝 Session Summary\n\n",
+    );
+
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim())
+        .ok()
+        .filter(|v| v.is_object());
+    let Some(v) = parsed else {
+        text.push_str(&format!("{}\n\n", result.stdout.trim()));
+        if restarted {
+            text.push_str("_The session was ended to summarize it and a new one was started._\n");
+        }
+        return FormattedOutput { text, sections };
+    };
+    let v = v.get("session").filter(|s| s.is_object()).unwrap_or(&v);
+
+    let mut meta = Vec::new();
+    if let Some(ns) = v.get("namespace").and_then(|n| n.as_str()) {
+        meta.push(format!("namespace `{}`", ns));
+    }
+    if let Some(started) = ["started_at", "start", "created_at"]
+        .iter()
+        .find_map(|k| v.get(*k))
+        .and_then(event_time)
+    {
+        meta.push(format!(
+            "started {} ({})",
+            relative_time(started, now),
+            human_duration(now.saturating_sub(started))
+        ));
+    }
+    for (key, label) in [
+        ("memories", "memories"),
+        ("tasks_completed", "tasks completed"),
+        ("tool_calls", "tool calls"),
+    ] {
+        let count = v
+            .get(key)
+            .and_then(|n| n.as_u64().or_else(|| n.as_array().map(|a| a.len() as u64)));
+        if let Some(n) = count {
+            meta.push(format!("{} {}", n, label));
+        }
+    }
+    if !meta.is_empty() {
+        text.push_str(&format!("_{}_\n\n", meta.join(" · ")));
+    }
+
+    let summary = ["summary", "session_summary", "text"]
+        .iter()
+        .find_map(|k| v.get(*k)?.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    text.push_str(&format!(
+        "{}\n\n",
+        summary.unwrap_or("_No summary was generated for this session yet._")
+    ));
+
+    for (key, heading) in [
+        ("highlights", "Highlights"),
+        ("decisions", "Decisions"),
+        ("next_steps", "Next steps"),
+    ] {
+        let items: Vec<&str> = v
+            .get(key)
+            .and_then(|a| a.as_array())
+            .map(|a| a.iter().filter_map(|i| i.as_str()).collect())
+            .unwrap_or_default();
+        if !items.is_empty() {
+            text.push_str(&format!("### {}\n\n", heading));
+            for item in items {
+                text.push_str(&format!("- {}\n", item.trim()));
+            }
+            text.push('\n');
+        }
+    }
+    if restarted {
+        text.push_str("_The session was ended to summarize it and a new one was started._\n");
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Format task command output.
+pub(crate) fn format_task(result: &CommandResult, sub: &str) -> FormattedOutput {
+    let title = match sub {
+        "add" => "Task Added",
+        "update" => "Task Updated",
+        "done" => "Task Completed",
+        "remove" => "Task Removed",
+        _ => return format_listing(result, "Tasks", "Tasks"),
+    };
+    format_generic(result, title)
+}
+
+/// Items of `agent_context_recall_enhanced` JSON output (`{"results": [...]}` or a bare array).
+fn recall_items(stdout: &str) -> Option<Vec<zed::serde_json::Value>> {
+    zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim())
+        .ok()
+        .and_then(|v| {
+            v.get("results")
+                .and_then(|r| r.as_array())
+                .or_else(|| v.as_array())
+                .cloned()
+        })
+}
+
+/// Format `/loom-context`: recalled memories as plain Markdown for the model to read, one
+/// heading per memory and no code fences around the content.
+pub(crate) fn format_context(
+    result: &CommandResult,
+    project: Option<&str>,
+    namespace: Option<&str>,
+) -> FormattedOutput {
+    if !result.success() {
+        return format_generic(result, "Project Context");
+    }
+    let mut doc = format!(
+        "# Project context: {}\n\n",
+        project.unwrap_or("Loom memory")
+    );
+    let scope: Vec<String> = [
+        project.map(|p| format!("project `{}`", p)),
+        namespace.map(|ns| format!("namespace `{}`", ns)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    doc.push_str("Recalled from Loom agent memory");
+    if !scope.is_empty() {
+        doc.push_str(&format!(" for {}", scope.join(", ")));
+    }
+    doc.push_str(". Use it as background for this conversation.\n\n");
+
+    match recall_items(&result.stdout) {
+        Some(items) if items.is_empty() => doc.push_str("No memories matched.\n"),
+        Some(items) => {
+            for item in &items {
+                let get = |k: &str| item.get(k).and_then(|x| x.as_str());
+                let title = get("title").or(get("source")).unwrap_or("Memory");
+                doc.push_str(&format!("## {}\n\n", title));
+                if let Some(source) = get("source").filter(|s| Some(*s) != get("title")) {
+                    doc.push_str(&format!("Source: {}\n\n", source));
+                }
+                let body = get("content")
+                    .or(get("text"))
+                    .map(str::to_string)
+                    .unwrap_or_else(|| item.to_string());
+                doc.push_str(body.trim());
+                doc.push_str("\n\n");
+            }
+        }
+        None if result.stdout.trim().is_empty() => doc.push_str("No memories matched.\n"),
+        None => {
+            doc.push_str(result.stdout.trim());
+            doc.push('\n');
+        }
+    }
+    let doc = format!("{}\n\n", doc.trim_end());
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(&mut text, &mut sections, "Project Context", &doc);
+    FormattedOutput { text, sections }
+}
+
+/// Characters of a memory's content shown in the ranked `/loom-recall` list.
+const RECALL_PREVIEW_CHARS: usize = 240;
+
+/// Recalled memories as a numbered list, best match first: relevance score, namespace, and age,
+/// then a one-line preview of the content.
+fn recall_ranking(items: &[zed::serde_json::Value], now: u64) -> String {
+    let score = |item: &zed::serde_json::Value| {
+        ["score", "relevance", "similarity"]
+            .iter()
+            .find_map(|k| item.get(*k)?.as_f64())
+    };
+    let mut ranked: Vec<&zed::serde_json::Value> = items.iter().collect();
+    // Stable, so unscored items keep the hub's order after the scored ones.
+    ranked.sort_by(|a, b| {
+        score(b)
+            .partial_cmp(&score(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut out = String::new();
+    for (i, item) in ranked.iter().enumerate() {
+        let get = |k: &str| {
+            item.get(k)
+                .and_then(|x| x.as_str())
+                .filter(|s| !s.is_empty())
+        };
+        let mut meta = vec![score(item)
+            .map(|s| format!("**{:.2}**", s))
+            .unwrap_or_else(|| "**—**".into())];
+        if let Some(ns) = get("namespace") {
+            meta.push(format!("`{}`", ns));
+        }
+        let at = ["created_at", "timestamp", "updated_at", "stored_at"]
+            .iter()
+            .find_map(|k| event_time(item.get(*k)?));
+        if let Some(at) = at {
+            meta.push(relative_time(at, now));
+        }
+        let title = get("title").or(get("source"));
+        let content = get("content")
+            .or(get("text"))
+            .map(str::to_string)
+            .unwrap_or_else(|| item.to_string());
+        let mut preview: String = content
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(RECALL_PREVIEW_CHARS)
+            .collect();
+        if content.chars().count() > RECALL_PREVIEW_CHARS {
+            preview.push('…');
+        }
+        out.push_str(&format!("{}. {}", i + 1, meta.join(" · ")));
+        if let Some(title) = title {
+            out.push_str(&format!(" — {}", title));
+        }
+        out.push_str(&format!("\n   {}\n", preview));
+    }
+    out.push('\n');
+    out
+}
+
+/// Format recall output.
+///
+/// JSON results become a ranked list (see [`recall_ranking`]); anything else is shown as-is.
+/// `saved_to` is the worktree-relative path the results were pinned to (`--save`), if any.
+pub(crate) fn format_recall(
+    result: &CommandResult,
+    saved_to: Option<&str>,
+    now: u64,
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Recall",
+        "## 🔍 Context Recall\n\n",
+    );
+
+    match recall_items(&result.stdout).filter(|_| result.success()) {
+        Some(items) if items.is_empty() => text.push_str("_No memories matched._\n\n"),
+        Some(items) => push_section(
+            &mut text,
+            &mut sections,
+            &counted_label("Results", items.len()),
+            &recall_ranking(&items, now),
+        ),
+        None if !result.stdout.trim().is_empty() => push_section(
+            &mut text,
+            &mut sections,
+            "Results",
+            &format!("{}\n\n", result.stdout.trim()),
+        ),
+        None => {}
+    }
+
+    if let Some(path) = saved_to {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Saved",
+            &format!(
+                "📌 Saved to `{}` — commit it to share this context with teammates.\n\n",
+                path
+            ),
+        );
+    }
+
+    if !result.stderr.trim().is_empty() && !result.success() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", result.stderr.trim()),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Pull the stored entry ID out of an `agent_memory_store` response.
+///
+/// Handles JSON (`{"id": ...}`, `{"entry_id": ...}`, nested under `"entry"`) and text such as
+/// `Stored memory entry mem_123` or `id: mem_123`.
+pub(crate) fn parse_memory_id(stdout: &str) -> Option<String> {
+    let trimmed = stdout.trim();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(trimmed) {
+        let obj = value.get("entry").unwrap_or(&value);
+        return ["id", "entry_id", "memory_id"]
+            .iter()
+            .find_map(|k| match obj.get(*k)? {
+                zed::serde_json::Value::String(s) => Some(s.clone()),
+                zed::serde_json::Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            });
+    }
+    trimmed.lines().find_map(|line| {
+        let lower = line.to_ascii_lowercase();
+        let idx = lower.find("id:").map(|i| i + 3).or_else(|| {
+            (lower.contains("stored") || lower.contains("saved"))
+                .then(|| line.trim_end().rfind(char::is_whitespace).map(|i| i + 1))
+                .flatten()
+        })?;
+        let id = line[idx..]
+            .split_whitespace()
+            .next()?
+            .trim_matches(|c: char| c == '`' || c == '"' || c == '.' || c == ',');
+        (!id.is_empty()).then(|| id.to_string())
+    })
+}
+
+/// Namespace and summary of an agent session, from `loom agent session` / `session-end` output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SessionSnapshot {
+    pub(crate) namespace: Option<String>,
+    pub(crate) summary: Option<String>,
+}
+
+/// Read a [`SessionSnapshot`] from JSON (optionally nested under `"session"`) or `key: value`
+/// text lines.
+pub(crate) fn parse_session_snapshot(stdout: &str) -> SessionSnapshot {
+    let trimmed = stdout.trim();
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(trimmed) {
+        let obj = value.get("session").unwrap_or(&value);
+        let field = |keys: &[&str]| {
+            keys.iter().find_map(|k| {
+                obj.get(*k)?
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+            })
+        };
+        return SessionSnapshot {
+            namespace: field(&["namespace", "ns"]),
+            summary: field(&["summary", "session_summary"]),
+        };
+    }
+    let field = |key: &str| {
+        trimmed.lines().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim().eq_ignore_ascii_case(key) && !v.trim().is_empty())
+                .then(|| v.trim().to_string())
+        })
+    };
+    SessionSnapshot {
+        namespace: field("namespace"),
+        summary: field("summary"),
+    }
+}
+
+/// Format `/loom-handoff`: who the note went to, what it carried, and how it was delivered.
+///
+/// `via` names the tool that stored the note; `ended` says whether this editor's session was
+/// closed as part of the handoff.
+pub(crate) fn format_handoff(
+    result: &CommandResult,
+    target: &str,
+    snapshot: &SessionSnapshot,
+    via: &str,
+    ended: bool,
+) -> FormattedOutput {
+    if !result.success() {
+        return format_generic(result, "Loom Handoff");
+    }
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Handoff",
+        &format!("## 🤝 Handed off to `{}`\n\n", target),
+    );
+    push_section(
+        &mut text,
+        &mut sections,
+        "Details",
+        &format!(
+            "| Field | Value |\n| --- | --- |\n| Namespace | {} |\n| Delivered via | `{}` |\n\
+             | This session | {} |\n\n",
+            snapshot
+                .namespace
+                .as_deref()
+                .map(|ns| format!("`{}`", ns))
+                .unwrap_or_else(|| "—".into()),
+            via,
+            if ended { "ended" } else { "still open" }
+        ),
+    );
+    if let Some(summary) = &snapshot.summary {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Summary",
+            &format!("### Summary\n\n{}\n\n", summary),
+        );
+    }
+    text.push_str(&format!(
+        "The `{}` agent sees this note the next time it recalls context{}.\n",
+        target,
+        snapshot
+            .namespace
+            .as_deref()
+            .map(|ns| format!(" (namespace `{}`)", ns))
+            .unwrap_or_default()
+    ));
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-memory`: confirm the stored entry and its ID.
+pub(crate) fn format_memory_store(result: &CommandResult, content: &str) -> FormattedOutput {
+    if !result.success() {
+        return format_generic(result, "Memory Store");
+    }
+    let id = parse_memory_id(&result.stdout);
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Memory",
+        "## 🧠 Memory Stored\n\n",
+    );
+
+    let preview: String = content.chars().take(120).collect();
+    let ellipsis = if content.chars().count() > 120 {
+        "…"
+    } else {
+        ""
+    };
+    push_section(
+        &mut text,
+        &mut sections,
+        "Entry",
+        &format!(
+            "| Field | Value |\n| --- | --- |\n| ID | {} |\n| Content | {}{} |\n\n",
+            id.as_deref()
+                .map(|id| format!("`{}`", id))
+                .unwrap_or_else(|| "unknown (see output below)".into()),
+            table_cell(&preview),
+            ellipsis
+        ),
+    );
+
+    if id.is_none() && !result.stdout.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Output",
+            &fenced("", result.stdout.trim()),
+        );
+    }
+    text.push_str("Recall it later with `/loom-recall <query>`.\n");
+
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-forget`: which memory entries `agent_memory_delete` removed.
+///
+/// JSON responses may report `deleted` as a count or a list of IDs (optionally alongside `ids`);
+/// anything else is shown verbatim.
+pub(crate) fn format_memory_delete(result: &CommandResult, target: &str) -> FormattedOutput {
+    if !result.success() {
+        return format_generic(result, "Memory Delete");
+    }
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Memory",
+        &format!("## 🗑️ Memory Deleted: {}\n\n", target),
+    );
+
+    let value = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let id_of = |v: &zed::serde_json::Value| {
+        v.as_str()
+            .map(str::to_string)
+            .or_else(|| v.as_u64().map(|n| n.to_string()))
+    };
+    let ids: Vec<String> = value
+        .as_ref()
+        .and_then(|v| v.get("ids").or_else(|| v.get("deleted")))
+        .and_then(|d| d.as_array())
+        .map(|items| items.iter().filter_map(id_of).collect())
+        .unwrap_or_default();
+    let count = value
+        .as_ref()
+        .and_then(|v| v.get("deleted").or_else(|| v.get("count")))
+        .and_then(|d| d.as_u64())
+        .map(|n| n as usize)
+        .or_else(|| (!ids.is_empty()).then_some(ids.len()));
+
+    match count {
+        Some(0) => text.push_str("No matching entries were found; nothing was deleted.\n"),
+        Some(n) => {
+            let mut body = format!(
+                "Deleted **{}** {}.\n\n",
+                n,
+                if n == 1 { "entry" } else { "entries" }
+            );
+            for id in &ids {
+                body.push_str(&format!("- `{}`\n", id));
+            }
+            if !ids.is_empty() {
+                body.push('\n');
+            }
+            push_section(&mut text, &mut sections, "Deleted", &body);
+        }
+        None if !result.stdout.trim().is_empty() => push_section(
+            &mut text,
+            &mut sections,
+            "Output",
+            &fenced("", result.stdout.trim()),
+        ),
+        None => text.push_str("Deleted.\n"),
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Markdown document for `/loom-recall --save`.
+///
+/// JSON results (`[...]` or `{"results": [...]}`) become one heading per item with its source
+/// and score; anything else is kept verbatim.
+pub(crate) fn recall_markdown(query: &str, stdout: &str, date: &str) -> String {
+    let mut doc = format!(
+        "# Recall: {}\n\n_Recalled from Loom agent memory on {}._\n\n",
+        query, date
+    );
+    let Some(items) = recall_items(stdout) else {
+        doc.push_str(stdout.trim());
+        doc.push('\n');
+        return doc;
+    };
+    for (i, item) in items.iter().enumerate() {
+        let get = |k: &str| item.get(k).and_then(|x| x.as_str());
+        let title = get("title").or(get("source")).unwrap_or("Result");
+        doc.push_str(&format!("## {}. {}\n\n", i + 1, title));
+        let mut meta = Vec::new();
+        if let Some(source) = get("source").filter(|s| Some(*s) != get("title")) {
+            meta.push(format!("source: `{}`", source));
+        }
+        if let Some(score) = item.get("score").and_then(|x| x.as_f64()) {
+            meta.push(format!("score: {:.2}", score));
+        }
+        if !meta.is_empty() {
+            doc.push_str(&format!("_{}_\n\n", meta.join(" · ")));
+        }
+        let body = get("content")
+            .or(get("text"))
+            .map(str::to_string)
+            .unwrap_or_else(|| item.to_string());
+        doc.push_str(body.trim());
+        doc.push_str("\n\n");
+    }
+    doc
+}
+
+/// Format skills listing.
+pub(crate) fn format_skills(result: &CommandResult) -> FormattedOutput {
+    format_generic(result, "Loom Skills")
+}
+
+/// Format `/loom-skills show <name>` (`skills_get`): description, steps, and examples as
+/// Markdown. `{"skill": {...}}` and bare objects are accepted; steps and examples may be strings
+/// or objects. Non-JSON output is assumed to be the skill's own Markdown and shown as-is.
+pub(crate) fn format_skill_detail(result: &CommandResult, name: &str) -> FormattedOutput {
+    if !result.success() {
+        return format_generic(result, &format!("Skill `{}`", name));
+    }
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let skill = parsed
+        .as_ref()
+        .map(|v| v.get("skill").unwrap_or(v))
+        .filter(|v| v.is_object());
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let Some(skill) = skill else {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Skill",
+            &format!("## 🧩 Skill: {}\n\n{}\n\n", name, result.stdout.trim()),
+        );
+        return FormattedOutput { text, sections };
+    };
+
+    let field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| skill.get(*k)?.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+    let title = field(&["title", "name"]).unwrap_or(name);
+    let mut header = format!("## 🧩 Skill: {}\n\n", title);
+    let mut meta = Vec::new();
+    if let Some(category) = field(&["category"]) {
+        meta.push(format!("category `{}`", category));
+    }
+    let tags: Vec<String> = skill
+        .get("tags")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.as_str())
+        .map(|t| format!("`{}`", t))
+        .collect();
+    if !tags.is_empty() {
+        meta.push(format!("tags {}", tags.join(" ")));
+    }
+    if !meta.is_empty() {
+        header.push_str(&format!("_{}_\n\n", meta.join(" · ")));
+    }
+    if let Some(description) = field(&["description", "summary"]) {
+        header.push_str(&format!("{}\n\n", description));
+    }
+    push_section(&mut text, &mut sections, "Skill", &header);
+
+    let entry_text = |item: &zed::serde_json::Value, keys: &[&str]| -> Option<String> {
+        item.as_str().map(str::to_string).or_else(|| {
+            keys.iter()
+                .find_map(|k| item.get(*k)?.as_str())
+                .map(str::to_string)
+        })
+    };
+    let steps: Vec<String> = skill
+        .get("steps")
+        .and_then(|s| s.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|step| {
+            let body = entry_text(step, &["description", "instruction", "text"]);
+            match (step.get("title").and_then(|t| t.as_str()), body) {
+                (Some(title), Some(body)) => Some(format!("**{}** — {}", title, body)),
+                (Some(title), None) => Some(format!("**{}**", title)),
+                (None, body) => body,
+            }
+        })
+        .collect();
+    if !steps.is_empty() {
+        let mut body = String::from("### Steps\n\n");
+        for (i, step) in steps.iter().enumerate() {
+            body.push_str(&format!("{}. {}\n", i + 1, step.trim()));
+        }
+        body.push('\n');
+        push_section(&mut text, &mut sections, "Steps", &body);
+    }
+
+    let examples: Vec<&zed::serde_json::Value> = skill
+        .get("examples")
+        .and_then(|e| e.as_array())
+        .map(|e| e.iter().collect())
+        .unwrap_or_default();
+    if !examples.is_empty() {
+        let mut body = String::from("### Examples\n\n");
+        for example in examples {
+            if let Some(title) = example.get("title").and_then(|t| t.as_str()) {
+                body.push_str(&format!("**{}**\n\n", title));
+            }
+            if let Some(code) = entry_text(example, &["code", "input", "prompt", "example"]) {
+                body.push_str(&fenced("", code.trim()));
+            }
+            if let Some(output) = example.get("output").and_then(|o| o.as_str()) {
+                body.push_str(&format!("→ {}\n\n", output.trim()));
+            }
+        }
+        push_section(&mut text, &mut sections, "Examples", &body);
+    }
+
+    if let Some(content) = field(&["content", "body", "instructions", "markdown"]) {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Contents",
+            &format!("{}\n\n", content),
+        );
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Format search results.
+pub(crate) fn format_search(result: &CommandResult) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Search",
+        "## 🔍 Search Results\n\n",
+    );
+
+    if !result.stdout.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Results",
+            &format!("{}\n\n", result.stdout.trim()),
+        );
+    }
+
+    if !result.stderr.trim().is_empty() && !result.success() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", result.stderr.trim()),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Format profile command output.
+pub(crate) fn format_profile(result: &CommandResult, sub: &str) -> FormattedOutput {
+    let title = match sub {
+        "list" => "Profiles",
+        "switch" => "Profile Switched",
+        "create" => "Profile Created",
+        "delete" => "Profile Deleted",
+        _ => "Current Profile",
+    };
+    format_generic(result, title)
+}
+
+/// Format `loom profile show <name>`: a profile's servers, tools, and settings.
+///
+/// Falls back to the generic rendering when the CLI doesn't return a JSON object.
+pub(crate) fn format_profile_show(result: &CommandResult, name: &str) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim())
+        .ok()
+        .filter(|v| v.is_object());
+    let Some(v) = parsed else {
+        return format_generic(result, &format!("Profile: {}", name));
+    };
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let active = v.get("active").and_then(|a| a.as_bool()).unwrap_or(false);
+    push_section(
+        &mut text,
+        &mut sections,
+        "Profile",
+        &format!(
+            "## {} Profile: {}{}\n\n",
+            status_icon(result.success()),
+            name,
+            if active { " (active)" } else { "" }
+        ),
+    );
+    if let Some(desc) = v.get("description").and_then(|d| d.as_str()) {
+        text.push_str(&format!("{}\n\n", desc));
+    }
+
+    let servers = v
+        .get("servers")
+        .and_then(|s| s.as_array())
+        .cloned()
+        .unwrap_or_default();
+    if !servers.is_empty() {
+        let mut table = format!(
+            "### Servers ({})\n\n| Server | Enabled |\n| --- | --- |\n",
+            servers.len()
+        );
+        for server in &servers {
+            let (server_name, enabled) = match server {
+                zed::serde_json::Value::String(s) => (s.as_str(), true),
+                other => (
+                    other.get("name").and_then(|n| n.as_str()).unwrap_or("?"),
+                    other
+                        .get("enabled")
+                        .and_then(|e| e.as_bool())
+                        .unwrap_or(true),
+                ),
+            };
+            table.push_str(&format!(
+                "| {} | {} |\n",
+                server_name,
+                if enabled { "✅" } else { "—" }
+            ));
+        }
+        table.push('\n');
+        push_section(&mut text, &mut sections, "Servers", &table);
+    }
+
+    let tools: Vec<String> = v
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|t| {
+                    t.as_str()
+                        .or_else(|| t.get("name").and_then(|n| n.as_str()))
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if !tools.is_empty() {
+        const MAX_TOOLS: usize = 40;
+        let shown: Vec<String> = tools
+            .iter()
+            .take(MAX_TOOLS)
+            .map(|t| format!("`{}`", t))
+            .collect();
+        let mut body = format!("### Tools ({})\n\n{}", tools.len(), shown.join(", "));
+        if tools.len() > MAX_TOOLS {
+            body.push_str(&format!(", …and {} more", tools.len() - MAX_TOOLS));
+        }
+        body.push_str("\n\n");
+        push_section(&mut text, &mut sections, "Tools", &body);
+    }
+
+    if let Some(settings) = v.get("settings").and_then(|s| s.as_object()) {
+        if !settings.is_empty() {
+            let mut table = String::from("### Settings\n\n| Setting | Value |\n| --- | --- |\n");
+            for (key, value) in settings {
+                let value = match value {
+                    zed::serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                table.push_str(&format!("| {} | `{}` |\n", key, value));
+            }
+            table.push('\n');
+            push_section(&mut text, &mut sections, "Settings", &table);
+        }
+    }
+
+    if !active {
+        text.push_str(&format!(
+            "Run `/loom-profile switch {}` to activate this profile.\n",
+            name
+        ));
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Format a `/loom-call` payload rejected by the pre-flight schema check (nothing was sent).
+pub(crate) fn format_schema_violations(
+    tool_name: &str,
+    violations: &[SchemaViolation],
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        tool_name,
+        &format!(
+            "## ❌ Invalid arguments for `{}`\n\n{} problem{} found against the tool's input \
+             schema; the call was not sent.\n\n",
+            tool_name,
+            violations.len(),
+            if violations.len() == 1 { "" } else { "s" }
+        ),
+    );
+    let mut table = String::from("| Field | Problem |\n| --- | --- |\n");
+    for v in violations {
+        let field = if v.path.is_empty() {
+            "_(arguments)_".to_string()
+        } else {
+            format!("`{}`", v.path)
+        };
+        table.push_str(&format!("| {} | {} |\n", field, table_cell(&v.message)));
+    }
+    table.push_str(&format!(
+        "\nSee `/loom-tools describe {}` for the expected parameters.\n",
+        tool_name
+    ));
+    push_section(&mut text, &mut sections, "Arguments", &table);
+    FormattedOutput { text, sections }
+}
+
+/// What `/loom-retry` did before re-running the failed command.
+pub(crate) enum RetryRemediation<'a> {
+    /// Nothing recognizable in the failure; the command is simply re-run.
+    None,
+    /// A known problem without an automatic fix; re-run anyway, with the manual remedy shown.
+    Manual(&'a Finding),
+    /// An automatic fix was applied (`Err` with the reason if it failed).
+    Applied {
+        finding: &'a Finding,
+        action: &'a str,
+        outcome: Result<String, String>,
+    },
+}
+
+/// Format `/loom-retry`: the remediation step, then the re-run command's own output.
+///
+/// `retried` is `None` when the remediation failed and the command was not re-run.
+pub(crate) fn format_retry(
+    command: &str,
+    args: &[String],
+    remediation: &RetryRemediation,
+    retried: Option<Result<FormattedOutput, String>>,
+) -> FormattedOutput {
+    let invocation = if args.is_empty() {
+        format!("/{}", command)
+    } else {
+        format!("/{} {}", command, args.join(" "))
+    };
+    let mut out = FormattedOutput::plain(String::new());
+    out.append_section("Retry", &format!("## 🔁 Retry `{}`\n\n", invocation));
+
+    let step = match remediation {
+        RetryRemediation::None => {
+            "**1. Remediation**: no known failure signature; re-running as-is.\n\n".to_string()
+        }
+        RetryRemediation::Manual(finding) => format!(
+            "**1. Remediation**: diagnosed **{}**, which has no automatic fix; re-running \
+             as-is.\n\n> {}\n\n",
+            finding.title, finding.remedy
+        ),
+        RetryRemediation::Applied {
+            finding,
+            action,
+            outcome,
+        } => {
+            let mut text = format!(
+                "**1. Remediation**: diagnosed **{}**, so the extension tried to {}.\n\n",
+                finding.title, action
+            );
+            if let Some(evidence) = &finding.evidence {
+                text.push_str(&format!("Evidence: `{}`\n\n", evidence));
+            }
+            match outcome {
+                Ok(detail) => text.push_str(&format!("{} {}\n\n", status_icon(true), detail)),
+                Err(err) => text.push_str(&format!(
+                    "{} {}\n\nThe command was not re-run. {}\n\n",
+                    status_icon(false),
+                    err,
+                    finding.remedy
+                )),
+            }
+            text
+        }
+    };
+    out.append_section("Remediation", &step);
+
+    match retried {
+        None => {}
+        Some(Ok(formatted)) => {
+            out.append_section("Result", "**2. Re-run**:\n\n");
+            out.append_output(formatted);
+        }
+        Some(Err(err)) => out.append_section(
+            "Result",
+            &format!(
+                "**2. Re-run**: {} still failing: {}\n\nRun `/loom-doctor` for a full diagnosis.\n",
+                status_icon(false),
+                err
+            ),
+        ),
+    }
+    out
+}
+
+/// Fenced block for a piece of tool output: pretty JSON when it parses, plain text otherwise.
+fn tool_output_block(raw: &str) -> String {
+    let raw = raw.trim();
+    match zed::serde_json::from_str::<zed::serde_json::Value>(raw) {
+        Ok(value) if value.is_object() || value.is_array() => fenced(
+            "json",
+            &zed::serde_json::to_string_pretty(&value).unwrap_or_else(|_| raw.to_string()),
+        ),
+        _ => fenced("", raw),
+    }
+}
+
+/// Size of base64 `data` (images, audio, blobs), e.g. `12 KB`.
+fn base64_size(data: &str) -> String {
+    human_kb((data.len() as u64 * 3 / 4).div_ceil(1024))
+}
+
+/// Section label and body for one block of an MCP result's `content` array.
+///
+/// Text is shown as-is, resources as links (plus embedded text), and binary data only by type
+/// and size. Unknown block types fall back to their JSON.
+fn tool_content_block(block: &zed::serde_json::Value) -> (&'static str, String) {
+    let field = |value: &zed::serde_json::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let link = |value: &zed::serde_json::Value| {
+        let uri = field(value, "uri")?;
+        let name = field(value, "title")
+            .or_else(|| field(value, "name"))
+            .unwrap_or_else(|| uri.clone());
+        let mut line = format!("🔗 [{}]({})", name, uri);
+        if let Some(mime) = field(value, "mimeType") {
+            line.push_str(&format!(" · `{}`", mime));
+        }
+        if let Some(description) = field(value, "description") {
+            line.push_str(&format!(" — {}", description));
+        }
+        Some(line + "\n\n")
+    };
+    let kind = block.get("type").and_then(|t| t.as_str()).unwrap_or("");
+    let rendered = match kind {
+        "text" => field(block, "text").map(|t| ("Text", tool_output_block(&t))),
+        "image" | "audio" => {
+            let mime = field(block, "mimeType").unwrap_or_else(|| kind.to_string());
+            let size = field(block, "data").map(|d| base64_size(&d));
+            let label = if kind == "image" { "Image" } else { "Audio" };
+            Some((
+                label,
+                format!(
+                    "{} `{}`{} (not shown)\n\n",
+                    if kind == "image" { "🖼️" } else { "🔊" },
+                    mime,
+                    size.map(|s| format!(", {}", s)).unwrap_or_default()
+                ),
+            ))
+        }
+        "resource_link" => link(block).map(|l| ("Resource", l)),
+        "resource" => block.get("resource").map(|resource| {
+            let mut body = link(resource).unwrap_or_default();
+            if let Some(text) = field(resource, "text") {
+                body.push_str(&tool_output_block(&text));
+            } else if let Some(blob) = field(resource, "blob") {
+                body.push_str(&format!(
+                    "Binary contents, {} (not shown)\n\n",
+                    base64_size(&blob)
+                ));
+            }
+            ("Resource", body)
+        }),
+        _ => None,
+    };
+    rendered.unwrap_or_else(|| ("Content", tool_output_block(&block.to_string())))
+}
+
+/// Format `/loom-call` output.
+///
+/// An MCP-style result (`{"content": [...], "isError": ...}`) gets a section per content block
+/// and uses `isError` for the status; any other JSON is pretty-printed.
+pub(crate) fn format_tool_call(result: &CommandResult, tool_name: &str) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let is_error = parsed
+        .as_ref()
+        .and_then(|v| v.get("isError")?.as_bool())
+        .unwrap_or(false);
+    let ok = result.success() && !is_error;
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        tool_name,
+        &format!("## {} Tool: `{}`\n\n", status_icon(ok), tool_name),
+    );
+
+    let blocks: Vec<_> = parsed
+        .as_ref()
+        .and_then(|v| v.get("content")?.as_array())
+        .map(|blocks| blocks.iter().map(tool_content_block).collect())
+        .unwrap_or_default();
+    if !blocks.is_empty() {
+        let numbered = blocks.len() > 1;
+        for (i, (kind, body)) in blocks.iter().enumerate() {
+            let label = if numbered {
+                format!("{} {}", tr(kind), i + 1)
+            } else {
+                kind.to_string()
+            };
+            push_section(&mut text, &mut sections, &label, body);
+        }
+    } else if !result.stdout.trim().is_empty() {
+        match parsed.filter(|v| v.is_object() || v.is_array()) {
+            Some(value) => push_long_output(
+                &mut text,
+                &mut sections,
+                "Output",
+                &zed::serde_json::to_string_pretty(&value).unwrap_or_default(),
+                "json",
+            ),
+            None => push_long_output(&mut text, &mut sections, "Output", &result.stdout, ""),
+        }
+    }
+
+    if !result.stderr.trim().is_empty() && !ok {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", result.stderr.trim()),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// One hop of a `loom tools call --trace` trace (hub, server, tool, ...).
+#[derive(Clone, Debug, PartialEq)]
+struct TraceSpan {
+    name: String,
+    duration_ms: Option<f64>,
+    ok: bool,
+    error: Option<String>,
+    children: Vec<TraceSpan>,
+}
+
+fn parse_trace_span(value: &zed::serde_json::Value) -> Option<TraceSpan> {
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| value.get(*k)?.as_str())
+            .map(str::to_string)
+    };
+    let base = text(&["name", "hop", "span", "operation"])?;
+    let name = match text(&["kind", "type", "layer"]) {
+        Some(kind) if !base.eq_ignore_ascii_case(&kind) => format!("{} `{}`", kind, base),
+        _ => format!("`{}`", base),
+    };
+    let error = text(&["error", "err"]);
+    let status = text(&["status", "outcome"])
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let ok = error.is_none()
+        && value.get("ok").and_then(|v| v.as_bool()) != Some(false)
+        && !["error", "fail", "timeout"]
+            .iter()
+            .any(|s| status.contains(s));
+    let duration_ms = value
+        .get("duration_ms")
+        .and_then(|v| v.as_f64())
+        .or_else(|| value.get("duration_us")?.as_f64().map(|us| us / 1000.0));
+    let children = ["children", "spans", "hops"]
+        .iter()
+        .find_map(|k| value.get(*k)?.as_array())
+        .map(|items| items.iter().filter_map(parse_trace_span).collect())
+        .unwrap_or_default();
+    Some(TraceSpan {
+        name,
+        duration_ms,
+        ok,
+        error,
+        children,
+    })
+}
+
+/// Nested Markdown list for a span tree; each duration also shows its share of `total_ms`.
+fn render_trace(span: &TraceSpan, depth: usize, total_ms: Option<f64>, out: &mut String) {
+    let mut line = format!(
+        "{}- {} {}",
+        "  ".repeat(depth),
+        status_icon(span.ok),
+        span.name
+    );
+    if let Some(ms) = span.duration_ms {
+        line.push_str(&format!(" — {:.1} ms", ms));
+        if let Some(total) = total_ms.filter(|t| *t > 0.0 && depth > 0) {
+            line.push_str(&format!(" ({:.0}%)", ms / total * 100.0));
+        }
+    }
+    if let Some(error) = &span.error {
+        line.push_str(&format!(": `{}`", error.replace('`', "'")));
+    }
+    out.push_str(&line);
+    out.push('\n');
+    for child in &span.children {
+        render_trace(child, depth + 1, total_ms, out);
+    }
+}
+
+/// Deepest-first search for the innermost failing hop, else the slowest leaf.
+fn trace_hotspot(span: &TraceSpan) -> (&TraceSpan, bool) {
+    fn leaves<'a>(span: &'a TraceSpan, out: &mut Vec<&'a TraceSpan>) {
+        if span.children.is_empty() {
+            out.push(span);
+        }
+        for child in &span.children {
+            leaves(child, out);
+        }
+    }
+    fn failing(span: &TraceSpan) -> Option<&TraceSpan> {
+        span.children
+            .iter()
+            .find_map(failing)
+            .or_else(|| (!span.ok).then_some(span))
+    }
+    if let Some(failed) = failing(span) {
+        return (failed, true);
+    }
+    let mut all = Vec::new();
+    leaves(span, &mut all);
+    let slowest = all
+        .into_iter()
+        .max_by(|a, b| {
+            a.duration_ms
+                .unwrap_or(0.0)
+                .total_cmp(&b.duration_ms.unwrap_or(0.0))
+        })
+        .unwrap_or(span);
+    (slowest, false)
+}
+
+/// Format `/loom-trace`: the hop-by-hop trace as a tree, where time went (or what failed),
+/// and the tool's result.
+///
+/// The trace is read from JSON under `trace` (an object, or an array of top-level spans) in
+/// stdout or stderr; anything else is shown verbatim.
+pub(crate) fn format_trace(result: &CommandResult, tool_name: &str) -> FormattedOutput {
+    let parse = |s: &str| zed::serde_json::from_str::<zed::serde_json::Value>(s.trim()).ok();
+    let documents = [parse(&result.stdout), parse(&result.stderr)];
+    let trace = documents.iter().flatten().find_map(|doc| {
+        let trace = doc.get("trace")?;
+        let spans: Vec<TraceSpan> = match trace.as_array() {
+            Some(items) => items.iter().filter_map(parse_trace_span).collect(),
+            None => parse_trace_span(trace).into_iter().collect(),
+        };
+        Some((doc, spans)).filter(|(_, spans)| !spans.is_empty())
+    });
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let ok = result.success() && trace.as_ref().is_none_or(|(_, s)| s.iter().all(|s| s.ok));
+    push_section(
+        &mut text,
+        &mut sections,
+        "Trace",
+        &format!("## {} Trace: `{}`\n\n", status_icon(ok), tool_name),
+    );
+
+    match &trace {
+        Some((doc, spans)) => {
+            let total = spans
+                .iter()
+                .filter_map(|s| s.duration_ms)
+                .reduce(|a, b| a + b);
+            let mut tree = String::new();
+            for span in spans {
+                render_trace(span, 0, total, &mut tree);
+            }
+            tree.push('\n');
+            let root = TraceSpan {
+                name: String::new(),
+                duration_ms: total,
+                ok: true,
+                error: None,
+                children: spans.clone(),
+            };
+            let (hot, failed) = trace_hotspot(&root);
+            if failed {
+                tree.push_str(&format!("**Failed at** {}.\n\n", hot.name));
+            } else if let Some(ms) = hot.duration_ms {
+                tree.push_str(&format!(
+                    "**Slowest hop:** {} ({:.1} ms).\n\n",
+                    hot.name, ms
+                ));
+            }
+            push_section(&mut text, &mut sections, "Hops", &tree);
+
+            let output = doc
+                .get("result")
+                .or_else(|| doc.get("output"))
+                .map(|v| zed::serde_json::to_string_pretty(v).unwrap_or_default());
+            if let Some(output) = output.filter(|o| !o.is_empty() && o != "null") {
+                push_section(&mut text, &mut sections, "Output", &fenced("json", &output));
+            }
+        }
+        None => {
+            text.push_str(
+                "No structured trace in the CLI output; it may not support `--trace`, or \
+                 printed the trace as text below.\n\n",
+            );
+            for (label, body) in [("Output", &result.stdout), ("Errors", &result.stderr)] {
+                if !body.trim().is_empty() {
+                    push_section(&mut text, &mut sections, label, &fenced("", body.trim()));
+                }
+            }
+        }
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Format composite dashboard output from multiple command results.
+pub(crate) fn format_dashboard(parts: &[(&str, &CommandResult)]) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    let failing: Vec<&str> = parts
+        .iter()
+        .filter(|(_, r)| !r.success())
+        .map(|(label, _)| *label)
+        .collect();
+    let mut header = format!(
+        "## 📊 Loom Dashboard\n\n**{} {}/{} components healthy**",
+        status_icon(failing.is_empty()),
+        parts.len() - failing.len(),
+        parts.len()
+    );
+    if !failing.is_empty() {
+        header.push_str(&format!(" — failing: {}", failing.join(", ")));
+    }
+    header.push_str("\n\n");
+    push_section(&mut text, &mut sections, "Dashboard", &header);
+
+    // Failing components first, so problems are visible without scrolling.
+    let mut ordered: Vec<&(&str, &CommandResult)> = parts.iter().collect();
+    ordered.sort_by_key(|(_, r)| r.success());
+    for (label, result) in ordered {
+        let icon = status_icon(result.success());
+        push_section(
+            &mut text,
+            &mut sections,
+            label,
+            &format!(
+                "### {} {}\n\n{}",
+                icon,
+                label,
+                fenced(
+                    "",
+                    if result.stdout.trim().is_empty() {
+                        result.stderr.trim()
+                    } else {
+                        result.stdout.trim()
+                    }
+                ),
+            ),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Format daemon job queue output (`loom queue list` / `loom queue cancel`).
+pub(crate) fn format_queue(result: &CommandResult, sub: &str) -> FormattedOutput {
+    let icon = status_icon(result.success());
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    let title = match sub {
+        "cancel" => "Job Cancelled",
+        _ => "Daemon Queue",
+    };
+    push_section(
+        &mut text,
+        &mut sections,
+        title,
+        &format!("## {} {}\n\n", icon, title),
+    );
+
+    let stdout = result.stdout.trim();
+    if looks_tabular(stdout) {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Jobs",
+            &format!("{}\n\n", to_markdown_table(stdout)),
+        );
+    } else if !stdout.is_empty() {
+        push_section(&mut text, &mut sections, "Jobs", &fenced("", stdout));
+    } else if result.success() && sub != "cancel" {
+        text.push_str("No queued or running jobs.\n\n");
+    }
+
+    if !result.stderr.trim().is_empty() && !result.success() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", result.stderr.trim()),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Tool permissions from `loom permissions list`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ToolPermissions {
+    pub(crate) allowed: Vec<String>,
+    pub(crate) denied: Vec<String>,
+    /// Decision for tools in neither list (`allow`, `deny`, `ask`), when reported.
+    pub(crate) default: Option<String>,
+}
+
+/// Read permissions from JSON (`{"allowed": [...], "denied": [...]}` or a `permissions` list of
+/// `{tool, permission|decision|allowed}`) or `allow <tool>` / `deny <tool>` text lines.
+pub(crate) fn parse_permissions(stdout: &str) -> Option<ToolPermissions> {
+    let mut out = ToolPermissions::default();
+    if let Ok(v) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout.trim()) {
+        let names = |keys: &[&str]| -> Vec<String> {
+            keys.iter()
+                .find_map(|k| v.get(*k)?.as_array())
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|t| t.as_str().or_else(|| t.get("tool")?.as_str()))
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        out.allowed = names(&["allowed", "allow"]);
+        out.denied = names(&["denied", "deny"]);
+        let entries = v
+            .get("permissions")
+            .and_then(|p| p.as_array())
+            .or_else(|| v.as_array());
+        for entry in entries.into_iter().flatten() {
+            let Some(tool) = ["tool", "name"]
+                .iter()
+                .find_map(|k| entry.get(*k)?.as_str())
+            else {
+                continue;
+            };
+            let allowed = match entry.get("allowed").and_then(|a| a.as_bool()) {
+                Some(allowed) => allowed,
+                None => ["permission", "decision", "action"]
+                    .iter()
+                    .find_map(|k| entry.get(*k)?.as_str())
+                    .is_some_and(|d| d.eq_ignore_ascii_case("allow")),
+            };
+            let list = if allowed {
+                &mut out.allowed
+            } else {
+                &mut out.denied
+            };
+            list.push(tool.to_string());
+        }
+        out.default = ["default", "default_permission"]
+            .iter()
+            .find_map(|k| v.get(*k)?.as_str())
+            .map(str::to_string);
+    } else {
+        for line in stdout.lines() {
+            let mut words = line.split_whitespace();
+            let (Some(decision), Some(tool)) = (words.next(), words.next()) else {
+                continue;
+            };
+            match decision.trim_end_matches(':').to_ascii_lowercase().as_str() {
+                "allow" | "allowed" => out.allowed.push(tool.to_string()),
+                "deny" | "denied" => out.denied.push(tool.to_string()),
+                "default" => out.default = Some(tool.to_string()),
+                _ => {}
+            }
+        }
+    }
+    out.allowed.sort();
+    out.denied.sort();
+    (out != ToolPermissions::default()).then_some(out)
+}
+
+/// Format `/loom-permissions`: allowed and denied tools in separate groups.
+///
+/// `changed` is `(action, tool)` after `allow`/`deny`, whose result is `result`; `listing` is
+/// the permissions afterwards (or the only run, for `list`).
+pub(crate) fn format_permissions(
+    result: &CommandResult,
+    changed: Option<(&str, &str)>,
+    listing: Option<&CommandResult>,
+) -> FormattedOutput {
+    if !result.success() {
+        let title = match changed {
+            Some((action, tool)) => format!("Failed to {} `{}`", action, tool),
+            None => "Tool Permissions".to_string(),
+        };
+        return format_generic(result, &title);
+    }
+    let listing = listing.unwrap_or(result);
+    let permissions = listing
+        .success()
+        .then(|| parse_permissions(&listing.stdout))
+        .flatten();
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let header = match changed {
+        Some((action, tool)) => format!(
+            "## ✅ `{}` is now {}\n\n",
+            tool,
+            if action == "allow" {
+                "allowed"
+            } else {
+                "denied"
+            }
+        ),
+        None => "## 🔐 Tool Permissions\n\n".to_string(),
+    };
+    push_section(&mut text, &mut sections, "Permissions", &header);
+
+    let Some(permissions) = permissions else {
+        let body = listing.stdout.trim();
+        if !body.is_empty() {
+            push_section(&mut text, &mut sections, "Output", &fenced("", body));
+        }
+        return FormattedOutput { text, sections };
+    };
+    if let Some(default) = &permissions.default {
+        text.push_str(&format!("Tools not listed below: **{}**.\n\n", default));
+    }
+    for (label, icon, tools) in [
+        ("Allowed", "✅", &permissions.allowed),
+        ("Denied", "🚫", &permissions.denied),
+    ] {
+        let mut body = format!("### {} {} ({})\n\n", icon, label, tools.len());
+        if tools.is_empty() {
+            body.push_str("_None._\n\n");
+        } else {
+            for tool in tools.iter() {
+                let marker = match changed {
+                    Some((_, t)) if t == tool => " ← changed",
+                    _ => "",
+                };
+                body.push_str(&format!("- `{}`{}\n", tool, marker));
+            }
+            body.push('\n');
+        }
+        push_section(&mut text, &mut sections, label, &body);
+    }
+    FormattedOutput { text, sections }
+}
+
+/// What a `/loom-alias` run changed, for the header above the alias table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AliasChange {
+    /// `shadowed` when `settings.commands.aliases` defines the same name and keeps winning.
+    Saved {
+        name: String,
+        shadowed: bool,
+    },
+    Removed(String),
+}
+
+/// `/loom-alias list` (and the listing shown after defining or removing an alias).
+pub(crate) fn format_aliases(aliases: &[Alias], changed: Option<&AliasChange>) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let mut header = match changed {
+        Some(AliasChange::Saved { name, .. }) => format!("## ✅ Saved alias `{}`\n\n", name),
+        Some(AliasChange::Removed(name)) => format!("## 🗑️ Removed alias `{}`\n\n", name),
+        None => "## 🔖 Command Aliases\n\n".to_string(),
+    };
+    if let Some(AliasChange::Saved {
+        name,
+        shadowed: true,
+    }) = changed
+    {
+        header.push_str(&format!(
+            "> ⚠️ `settings.commands.aliases` also defines `{}`, and settings take precedence.\n\n",
+            name
+        ));
+    }
+    push_section(&mut text, &mut sections, "Aliases", &header);
+
+    let body = if aliases.is_empty() {
+        "_No aliases yet._ Define one with `/loom-alias st = status verbose`, or add \
+         `settings.commands.aliases`.\n\n"
+            .to_string()
+    } else {
+        let mut table = "| Alias | Runs | Source |\n|---|---|---|\n".to_string();
+        for alias in aliases {
+            let source = match alias.source {
+                AliasSource::Settings => "settings",
+                AliasSource::Saved => "saved",
+            };
+            table.push_str(&format!(
+                "| `{}` | `/loom-{}` | {} |\n",
+                alias.name, alias.target, source
+            ));
+        }
+        table.push_str(
+            "\nRun one with `/loom-alias <name> [args...]`; extra args are appended.\n\n",
+        );
+        table
+    };
+    push_section(&mut text, &mut sections, "Commands", &body);
+    FormattedOutput { text, sections }
+}
+
+/// Format `loom workflows list`: one row per workflow with its step count and description.
+///
+/// Accepts `{"workflows": [...]}` or a bare array; `steps` may be a count or the step list.
+/// Text output is shown as a table when tabular, else verbatim.
+pub(crate) fn format_workflows(result: &CommandResult) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim()).ok();
+    let workflows = parsed.as_ref().and_then(|v| {
+        v.get("workflows")
+            .and_then(|w| w.as_array())
+            .or_else(|| v.as_array())
+    });
+    let Some(workflows) = workflows.filter(|_| result.success()) else {
+        let stdout = result.stdout.trim();
+        if result.success() && looks_tabular(stdout) {
+            let mut out = FormattedOutput::plain(String::new());
+            out.append_section("Workflows", "## ✅ Workflows\n\n");
+            out.append_section("Results", &format!("{}\n\n", to_markdown_table(stdout)));
+            return out;
+        }
+        return format_generic(result, "Workflows");
+    };
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Workflows",
+        &format!("## ✅ Workflows ({})\n\n", workflows.len()),
+    );
+    if workflows.is_empty() {
+        text.push_str("No workflows defined.\n");
+        return FormattedOutput { text, sections };
+    }
+    let mut table = String::from("| Workflow | Steps | Description |\n| --- | --- | --- |\n");
+    for wf in workflows {
+        let name = wf
+            .get("name")
+            .and_then(|n| n.as_str())
+            .or_else(|| wf.as_str())
+            .unwrap_or("?");
+        let steps = match wf.get("steps") {
+            Some(zed::serde_json::Value::Array(steps)) => steps.len().to_string(),
+            Some(n) if n.is_u64() => n.to_string(),
+            _ => "—".into(),
+        };
+        let desc = wf.get("description").and_then(|d| d.as_str()).unwrap_or("");
+        table.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            name,
+            steps,
+            table_cell(desc)
+        ));
+    }
+    table.push_str("\nRun one with `/loom-workflows run <name>`.\n");
+    push_section(&mut text, &mut sections, "Results", &table);
+    FormattedOutput { text, sections }
+}
+
+/// Format `loom workflows run <name>`: overall outcome plus per-step progress.
+///
+/// JSON output lists `steps` with `name`, `status`, `duration_ms`, and `error`/`output`; the
+/// first failed step's error is repeated in an Errors section. Text output (the CLI's own
+/// progress lines) is shown verbatim.
+pub(crate) fn format_workflow_run(result: &CommandResult, name: &str) -> FormattedOutput {
+    let parsed = zed::serde_json::from_str::<zed::serde_json::Value>(result.stdout.trim())
+        .ok()
+        .filter(|v| v.is_object());
+    let Some(run) = parsed else {
+        let mut out = format_generic(result, &format!("Workflow: {}", name));
+        if result.success() {
+            out.prepend_section(
+                "Workflows",
+                &format!("## ✅ Workflow `{}` finished\n\n", name),
+            );
+        }
+        return out;
+    };
+
+    let text_of = |v: &zed::serde_json::Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| v.get(*k).and_then(|s| s.as_str()))
+            .map(str::to_string)
+    };
+    let status = text_of(&run, &["status", "state"]).unwrap_or_else(|| {
+        if result.success() {
+            "succeeded".into()
+        } else {
+            "failed".into()
+        }
+    });
+    let ok = result.success()
+        && !matches!(
+            status.to_ascii_lowercase().as_str(),
+            "failed" | "error" | "cancelled" | "canceled"
+        );
+    let steps = run
+        .get("steps")
+        .and_then(|s| s.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let done = steps
+        .iter()
+        .filter(|s| {
+            text_of(s, &["status", "state"])
+                .is_some_and(|st| matches!(st.as_str(), "ok" | "succeeded" | "success" | "done"))
+        })
+        .count();
+    let mut header = format!("## {} Workflow `{}`: {}\n\n", status_icon(ok), name, status);
+    if !steps.is_empty() {
+        header.push_str(&format!("{}/{} steps completed\n\n", done, steps.len()));
+    }
+    push_section(&mut text, &mut sections, "Workflows", &header);
+
+    let mut first_error = None;
+    if !steps.is_empty() {
+        let mut table =
+            String::from("| # | Step | Status | Duration |\n| --- | --- | --- | --- |\n");
+        for (i, step) in steps.iter().enumerate() {
+            let step_status = text_of(step, &["status", "state"]).unwrap_or_else(|| "—".into());
+            let icon = match step_status.as_str() {
+                "ok" | "succeeded" | "success" | "done" => "✅",
+                "failed" | "error" => "❌",
+                "skipped" => "⏭️",
+                "running" => "⏳",
+                _ => "•",
+            };
+            let duration = match step.get("duration_ms").and_then(|d| d.as_u64()) {
+                Some(ms) if ms < 1000 => format!("{}ms", ms),
+                Some(ms) => format!("{:.1}s", ms as f64 / 1000.0),
+                None => "—".into(),
+            };
+            table.push_str(&format!(
+                "| {} | {} | {} {} | {} |\n",
+                i + 1,
+                text_of(step, &["name", "id"]).unwrap_or_else(|| "?".into()),
+                icon,
+                step_status,
+                duration
+            ));
+            if icon == "❌" && first_error.is_none() {
+                first_error = text_of(step, &["error", "output"]);
+            }
+        }
+        table.push('\n');
+        push_section(&mut text, &mut sections, "Steps", &table);
+    }
+
+    if let Some(output) = run.get("output").or_else(|| run.get("result")) {
+        let body = match output {
+            zed::serde_json::Value::String(s) => s.trim().to_string(),
+            other => zed::serde_json::to_string_pretty(other).unwrap_or_default(),
+        };
+        if !body.is_empty() {
+            push_section(&mut text, &mut sections, "Output", &fenced("", &body));
+        }
+    }
+    let error = first_error
+        .or_else(|| text_of(&run, &["error"]))
+        .or_else(|| (!ok && !result.stderr.trim().is_empty()).then(|| result.stderr.trim().into()));
+    if let Some(error) = error {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", error.trim()),
+        );
+    }
+    FormattedOutput { text, sections }
+}
+
+/// State of one server in `loom servers list` output (JSON or one row per server).
+pub(crate) fn server_state(listing: &str, server: &str) -> Option<String> {
+    if let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(listing.trim()) {
+        let servers = value
+            .get("servers")
+            .and_then(|s| s.as_array())
+            .or_else(|| value.as_array())?;
+        let entry = servers
+            .iter()
+            .find(|s| s.get("name").and_then(|n| n.as_str()) == Some(server))?;
+        return ["status", "state"]
+            .iter()
+            .find_map(|k| entry.get(*k).and_then(|v| v.as_str()))
+            .map(str::to_string);
+    }
+    listing.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        (fields.next()? == server).then(|| fields.collect::<Vec<_>>().join(" "))
+    })
+}
+
+/// Format `/loom-kill`: the kill result plus whether the daemon respawned the server.
+pub(crate) fn format_kill(
+    result: &CommandResult,
+    server: &str,
+    listing: Option<&CommandResult>,
+) -> FormattedOutput {
+    let icon = status_icon(result.success());
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Kill",
+        &format!("## {} Kill Server: {}\n\n", icon, server),
+    );
+
+    if result.success() {
+        let state = listing
+            .filter(|l| l.success())
+            .and_then(|l| server_state(&l.stdout, server));
+        let respawn = match state.as_deref() {
+            Some(state) => {
+                let lower = state.to_ascii_lowercase();
+                let running = ["running", "ready", "connected", "healthy", "up"]
+                    .iter()
+                    .any(|s| lower.contains(s));
+                if running {
+                    format!("✅ Respawned (`{}`)", state.trim())
+                } else {
+                    format!("⚠️ Not running (`{}`)", state.trim())
+                }
+            }
+            None => "❔ Unknown (server not in `loom servers list`)".to_string(),
+        };
+        push_section(
+            &mut text,
+            &mut sections,
+            "Respawn",
+            &format!(
+                "| Step | Result |\n| --- | --- |\n| Terminate | ✅ |\n| Respawn | {} |\n\n",
+                respawn
+            ),
+        );
+    }
+
+    let body = if result.stdout.trim().is_empty() {
+        result.stderr.trim()
+    } else {
+        result.stdout.trim()
+    };
+    if !body.is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            if result.success() { "Output" } else { "Errors" },
+            &fenced("", body),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-config`: the config file location plus its contents as a highlighted block.
+pub(crate) fn format_config(show: &CommandResult, path: Option<&str>) -> FormattedOutput {
+    if !show.success() {
+        return format_generic(show, "Loom Configuration");
+    }
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    let mut header = format!("## {} Loom Configuration\n\n", status_icon(true));
+    match path {
+        Some(path) => header.push_str(&format!("**File**: `{}`\n\n", path)),
+        None => header.push_str("**File**: unknown (`loom config path` failed)\n\n"),
+    }
+    push_section(&mut text, &mut sections, "Configuration", &header);
+
+    let body = show.stdout.trim();
+    let contents = if body.is_empty() {
+        "_The effective configuration is empty._\n\n".to_string()
+    } else {
+        fenced(config_language(path, body), body)
+    };
+    push_section(&mut text, &mut sections, "Contents", &contents);
+
+    if !show.stderr.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Warnings",
+            &format!("### Warnings\n\n{}", fenced("", show.stderr.trim())),
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// How `/loom-backup` and `/loom-restore` moved the configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BackupMethod {
+    /// `loom config export` / `loom config import`.
+    Export,
+    /// `tar` over the config file and `profiles/`, for CLIs without export/import.
+    Tarball,
+}
+
+impl BackupMethod {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Export => "`loom config export`",
+            Self::Tarball => {
+                "tarball of the config file and profiles (this CLI has no `loom config export`)"
+            }
+        }
+    }
+}
+
+/// Format `/loom-backup`: where the snapshot went and how to restore it.
+///
+/// `gitignored` is false for a custom path outside `.loom/backups/`, which gets a warning since
+/// the snapshot can hold tokens.
+pub(crate) fn format_backup(
+    result: &CommandResult,
+    rel: &str,
+    written: &str,
+    bytes: Option<u64>,
+    method: BackupMethod,
+    gitignored: bool,
+) -> FormattedOutput {
+    if !result.success() {
+        return format_generic(result, "Configuration Backup Failed");
+    }
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Backup",
+        &format!("## ✅ Backed up loom configuration to `{}`\n\n", rel),
+    );
+    let size = bytes.map_or("unknown".to_string(), |b| human_kb(b.div_ceil(1024)));
+    push_section(
+        &mut text,
+        &mut sections,
+        "File",
+        &format!(
+            "| Field | Value |\n| --- | --- |\n| Path | `{}` |\n| Size | {} |\n| Method | {} |\n\n\
+             Restore it with `/loom-restore {}`.\n\n",
+            written,
+            size,
+            method.describe(),
+            rel
+        ),
+    );
+    if gitignored {
+        text.push_str(
+            "_`.loom/backups/` is ignored by git (its own `.gitignore`), so backups stay out of \
+             commits._\n\n",
+        );
+    } else {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Warnings",
+            &format!(
+                "> ⚠️ `{}` can contain tokens from your config and is not git-ignored. Keep it out \
+                 of version control, or back up to `.loom/backups/` instead.\n\n",
+                rel
+            ),
+        );
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-restore` without a file: the backups found under `dir`, newest first.
+pub(crate) fn format_backup_list(backups: &[(String, u64)], dir: &str) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Backups",
+        "## 🗄️ Configuration Backups\n\n",
+    );
+    if backups.is_empty() {
+        text.push_str(&format!(
+            "_No backups in `{}`._ Take one with `/loom-backup`.\n\n",
+            dir
+        ));
+        return FormattedOutput { text, sections };
+    }
+    let mut table = String::from("| Backup | Size |\n| --- | --- |\n");
+    for (rel, bytes) in backups {
+        table.push_str(&format!(
+            "| `{}` | {} |\n",
+            rel,
+            human_kb(bytes.div_ceil(1024))
+        ));
+    }
+    table.push_str("\nRestore one with `/loom-restore <path>`.\n\n");
+    push_section(&mut text, &mut sections, "Files", &table);
+    FormattedOutput { text, sections }
+}
+
+/// Format a confirmed `/loom-restore`.
+pub(crate) fn format_restore(
+    result: &CommandResult,
+    rel: &str,
+    method: BackupMethod,
+) -> FormattedOutput {
+    if !result.success() {
+        return format_generic(result, "Configuration Restore Failed");
+    }
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Restore",
+        &format!("## ✅ Restored loom configuration from `{}`\n\n", rel),
+    );
+    text.push_str(&format!(
+        "Restored with {}. Run `/loom-restart` so the daemon picks up the restored \
+         configuration.\n\n",
+        method.describe()
+    ));
+    let output = result.stdout.trim();
+    if !output.is_empty() {
+        push_section(&mut text, &mut sections, "Output", &fenced("", output));
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Code fence language for a config file, from its extension or (failing that) its contents.
+fn config_language(path: Option<&str>, body: &str) -> &'static str {
+    let ext = path
+        .and_then(|p| p.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("yaml" | "yml") => "yaml",
+        Some("json") => "json",
+        Some("toml") => "toml",
+        _ if body.starts_with('{') || body.starts_with('[') && !body.contains(" = ") => "json",
+        _ if body.contains(" = ") => "toml",
+        _ => "yaml",
+    }
+}
+
+/// The "Hint" section added below a command whose CLI run failed in a recognized way.
+pub(crate) fn format_hint(finding: &Finding) -> String {
+    format!(
+        "### 💡 {}\n\n**{}** — {}\n\n",
+        tr("Hint"),
+        finding.title,
+        finding.remedy
+    )
+}
+
+/// Format `/loom-doctor`: a probe summary table followed by findings and their next steps.
+pub(crate) fn format_doctor(
+    program: &str,
+    version: Option<&str>,
+    probes: &[Probe],
+    findings: &[Finding],
+) -> FormattedOutput {
+    let healthy = findings.is_empty();
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Doctor",
+        &format!(
+            "## {} Loom Doctor\n\n**Binary**: `{}`\n**Version**: {}\n\n",
+            status_icon(healthy),
+            program,
+            version
+                .and_then(|v| v.lines().next())
+                .map(|v| format!("`{}`", v.trim()))
+                .unwrap_or_else(|| "unknown".into())
+        ),
+    );
+
+    let mut table = String::from("| Probe | Result |\n| --- | --- |\n");
+    for probe in probes {
+        let result = match &probe.outcome {
+            Ok(r) if r.success() => "✅ ok".to_string(),
+            Ok(r) => format!("❌ exit `{}`", r.exit_code),
+            Err(_) => "❌ could not run".to_string(),
+        };
+        table.push_str(&format!("| `{}` | {} |\n", probe.label, result));
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Probes", &table);
+
+    if healthy {
+        text.push_str("No known problems detected.\n");
+        return FormattedOutput { text, sections };
+    }
+
+    let mut body = format!("### Findings ({})\n\n", findings.len());
+    for (i, finding) in findings.iter().enumerate() {
+        let icon = match finding.severity {
+            Severity::Critical => "❌",
+            Severity::Warning => "⚠️",
+        };
+        body.push_str(&format!("{}. {} **{}**\n", i + 1, icon, finding.title));
+        if let Some(evidence) = &finding.evidence {
+            body.push_str(&format!("   - Seen: `{}`\n", evidence.replace('`', "'")));
+        }
+        body.push_str(&format!("   - Next step: {}\n", finding.remedy));
+    }
+    body.push('\n');
+    push_section(&mut text, &mut sections, "Findings", &body);
+
+    FormattedOutput { text, sections }
+}
+
+/// `/loom-info` provenance section: which release asset the running binary came from and
+/// whether it still matches the checksum recorded at install time.
+///
+/// `managed_version` is `None` when the binary isn't a managed install (then there is nothing
+/// recorded to show); `current_sha` is the binary's checksum now.
+pub(crate) fn provenance_markdown(
+    managed_version: Option<&str>,
+    manifest: Option<&InstallManifest>,
+    current_sha: Option<&str>,
+) -> String {
+    let mut text = String::from("### Provenance\n\n");
+    let Some(version) = managed_version else {
+        text.push_str(
+            "Not a managed install (from `command.path` or `PATH`); the extension has no \
+             download record for it.\n\n",
+        );
+        return text;
+    };
+    let Some(manifest) = manifest else {
+        text.push_str(&format!(
+            "Managed release `{}` has no install manifest (installed by an older extension \
+             version). Run `/loom-update` after the next release to record one.\n\n",
+            version
+        ));
+        return text;
+    };
+
+    let verify = match (manifest.sha256.as_deref(), current_sha) {
+        (Some(recorded), Some(now)) if recorded == now => "✅ matches install".to_string(),
+        (Some(_), Some(now)) => format!("⚠️ modified since install (now `{}`)", now),
+        (Some(_), None) => "⚠️ binary could not be read".to_string(),
+        (None, _) => "— (no checksum recorded)".to_string(),
+    };
+    text.push_str(&format!(
+        "| Field | Value |\n| --- | --- |\n| Release | {} |\n| Asset | `{}` |\n| Source | {} |\n\
+         | SHA-256 | `{}` |\n| Verify | {} |\n| Installed | {} |\n\n",
+        manifest.release_version,
+        manifest.asset_name,
+        manifest.download_url,
+        manifest.sha256.as_deref().unwrap_or("—"),
+        verify,
+        crate::worktree::utc_date(manifest.installed_at_unix_secs),
+    ));
+    text
+}
+
+/// One row of `/loom-paths`.
+pub(crate) struct PathEntry {
+    pub(crate) label: String,
+    /// `None` when the artifact doesn't exist in this build.
+    pub(crate) path: Option<String>,
+    pub(crate) note: String,
+}
+
+/// Format `/loom-paths`: where the extension keeps its files on disk.
+pub(crate) fn format_paths(entries: &[PathEntry]) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Paths",
+        "## 📁 Loom Extension Paths\n\n",
+    );
+    let mut table = String::from("| Item | Path | Notes |\n| --- | --- | --- |\n");
+    for entry in entries {
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            entry.label,
+            entry
+                .path
+                .as_deref()
+                .map(|p| format!("`{}`", p))
+                .unwrap_or_else(|| "—".into()),
+            table_cell(&entry.note)
+        ));
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Locations", &table);
+    FormattedOutput { text, sections }
+}
+
+/// What `/loom-cleanup` did (or would do) with one installed release.
+pub(crate) enum CleanupAction {
+    Keep(&'static str),
+    /// Preview only: would be deleted with `confirm`.
+    Remove,
+    Removed,
+    Failed(String),
+}
+
+pub(crate) struct CleanupRow {
+    pub(crate) version: String,
+    pub(crate) bytes: u64,
+    pub(crate) action: CleanupAction,
+}
+
+/// Format `/loom-cleanup`: installed releases with their size and fate, plus reclaimed space.
+pub(crate) fn format_cleanup(rows: &[CleanupRow]) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let sum = |pred: fn(&CleanupAction) -> bool| -> (usize, u64) {
+        rows.iter()
+            .filter(|r| pred(&r.action))
+            .fold((0, 0), |(n, b), r| (n + 1, b + r.bytes))
+    };
+    let (pending, pending_bytes) = sum(|a| matches!(a, CleanupAction::Remove));
+    let (removed, removed_bytes) = sum(|a| matches!(a, CleanupAction::Removed));
+    let (failed, _) = sum(|a| matches!(a, CleanupAction::Failed(_)));
+    let size = |bytes: u64| human_kb(bytes.div_ceil(1024));
+
+    let header = if pending > 0 {
+        "## 🧹 loom-core cleanup (preview)\n\n".to_string()
+    } else if removed > 0 || failed > 0 {
+        format!(
+            "## {} Removed {} old release{}, reclaimed {}\n\n",
+            status_icon(failed == 0),
+            removed,
+            if removed == 1 { "" } else { "s" },
+            size(removed_bytes)
+        )
+    } else {
+        "## ✅ Nothing to clean up\n\n".to_string()
+    };
+    push_section(&mut text, &mut sections, "Cleanup", &header);
+    if rows.is_empty() {
+        text.push_str("No managed loom-core releases are installed.\n");
+        return FormattedOutput { text, sections };
+    }
+
+    let mut table = String::from("| Release | Size | Action |\n| --- | --- | --- |\n");
+    for row in rows {
+        let action = match &row.action {
+            CleanupAction::Keep(why) => format!("kept ({})", why),
+            CleanupAction::Remove => "🗑️ will be removed".to_string(),
+            CleanupAction::Removed => "🗑️ removed".to_string(),
+            CleanupAction::Failed(e) => format!("⚠️ {}", table_cell(e)),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            row.version,
+            size(row.bytes),
+            action
+        ));
+    }
+    table.push('\n');
+    push_section(&mut text, &mut sections, "Releases", &table);
+
+    if pending > 0 {
+        text.push_str(&format!(
+            "Run `/loom-cleanup confirm` to delete {} release{} and reclaim {}.\n",
+            pending,
+            if pending == 1 { "" } else { "s" },
+            size(pending_bytes)
+        ));
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-update`: old → new version, or why nothing changed.
+///
+/// `program` is the binary slash commands currently run; `managed_in_use` says whether that is
+/// the managed install (otherwise the update won't affect them until the override is removed).
+pub(crate) fn format_update(
+    outcome: &UpdateOutcome,
+    program: &str,
+    managed_in_use: bool,
+    release_notes: Option<&str>,
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    match outcome {
+        UpdateOutcome::Available { from, version } => {
+            push_section(
+                &mut text,
+                &mut sections,
+                "Update",
+                &format!(
+                    "## ⬆️ loom-core update available: {} → {}\n\n",
+                    from.as_deref().unwrap_or("(not installed)"),
+                    version
+                ),
+            );
+            push_section(
+                &mut text,
+                &mut sections,
+                "Release Notes",
+                &match release_notes {
+                    Some(notes) => format!("{}\n\n", notes.trim()),
+                    None => "Release notes couldn't be fetched from GitHub.\n\n".to_string(),
+                },
+            );
+            let mut formatted = FormattedOutput { text, sections };
+            formatted.append_output(format_confirmation_required(
+                &format!(
+                    "download loom-core {} and make it the managed install",
+                    version
+                ),
+                "/loom-update confirm",
+            ));
+            return formatted;
+        }
+        UpdateOutcome::Updated { from, install } => {
+            push_section(
+                &mut text,
+                &mut sections,
+                "Update",
+                &format!(
+                    "## ✅ loom-core updated: {} → {}\n\n",
+                    from.as_deref().unwrap_or("(not installed)"),
+                    install.release_version
+                ),
+            );
+            push_section(
+                &mut text,
+                &mut sections,
+                "Install",
+                &install_details(install),
+            );
+            text.push_str(
+                "Run `/loom-restart` so the daemon picks up the new binary. The context server \
+                 switches over the next time Zed restarts it.\n",
+            );
+            if !managed_in_use {
+                text.push_str(&format!(
+                    "\n> ⚠️ Slash commands currently run `{}`, not the managed install. Remove \
+                     `command.path` (or the other binary from `PATH`) to use the update.\n",
+                    program
+                ));
+            }
+        }
+        UpdateOutcome::UpToDate(version) => {
+            push_section(
+                &mut text,
+                &mut sections,
+                "Update",
+                &format!("## ✅ loom-core is up to date ({})\n\n", version),
+            );
+        }
+        UpdateOutcome::Pinned(tag) => {
+            push_section(
+                &mut text,
+                &mut sections,
+                "Update",
+                &format!("## ℹ️ loom-core is pinned to {}\n\n", tag),
+            );
+            text.push_str(
+                "`settings.download.tag` pins the release, so `/loom-update` won't replace it. \
+                 Clear the tag (or change it) to move to another version.\n",
+            );
+        }
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Format `/loom-install`: where the fresh install landed and whether commands will use it.
+///
+/// `configured_tag` is `settings.download.tag`; `selected` says whether the installed release is
+/// the one those settings resolve to, and `managed_in_use` whether slash commands run the
+/// managed install at all (rather than `command.path` or a binary on `PATH`).
+pub(crate) fn format_install(
+    install: &LoomInstall,
+    program: &str,
+    configured_tag: Option<&str>,
+    selected: bool,
+    managed_in_use: bool,
+) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        "Install",
+        &format!("## ✅ loom-core {} installed\n\n", install.release_version),
+    );
+    push_section(
+        &mut text,
+        &mut sections,
+        "Details",
+        &install_details(install),
+    );
+    if let Some(report) = &install.startup_report {
+        text.push_str(report);
+        text.push('\n');
+    }
+
+    if !selected {
+        text.push_str(&format!(
+            "> ⚠️ `settings.download.tag` selects {}, so commands keep using that release. Set \
+             it to `\"{}\"` to switch.\n",
+            configured_tag
+                .map(|t| format!("`{}`", t))
+                .unwrap_or_else(|| "the latest release".into()),
+            install.release_version
+        ));
+    } else if !managed_in_use {
+        text.push_str(&format!(
+            "> ⚠️ Slash commands currently run `{}`, not the managed install. Remove \
+             `command.path` (or the other binary from `PATH`) to use it.\n",
+            program
+        ));
+    } else {
+        text.push_str(
+            "Run `/loom-restart` so the daemon picks up the reinstalled binary. The context \
+             server switches over the next time Zed restarts it.\n",
+        );
+    }
+    FormattedOutput { text, sections }
+}
+
+/// `| Field | Value |` table describing a managed install.
+fn install_details(install: &LoomInstall) -> String {
+    let mut details = format!(
+        "| Field | Value |\n| --- | --- |\n| Binary | `{}` |\n",
+        install.loom_path
+    );
+    if let Some(loomd) = &install.loomd_path {
+        details.push_str(&format!("| Daemon | `{}` |\n", loomd));
+    }
+    details.push_str(&format!("| Bin dir | `{}` |\n", install.bin_dir));
+    details.push_str(&format!("| Asset | `{}` |\n", install.asset_name));
+    if let Some(sha) = &install.checksum {
+        details.push_str(&format!("| SHA-256 | `{}` |\n", sha));
+    }
+    details.push('\n');
+    details
+}
+
+/// Explain that a destructive action needs an explicit `confirm` token, without running it.
+pub(crate) fn format_confirmation_required(action: &str, confirm_cmd: &str) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Confirmation Required",
+        &format!("## ⚠️ {}\n\n", tr("Confirmation Required")),
+    );
+    text.push_str(&format!(
+        "{}\n\n{}\n\n```\n{}\n```\n",
+        tr("This will {action}. Nothing has been changed yet.").replace("{action}", action),
+        tr("To proceed, run:"),
+        confirm_cmd
+    ));
+
+    FormattedOutput { text, sections }
+}
+
+/// One argument slot in an argument wizard template.
+pub(crate) struct WizardSlot {
+    /// Placeholder shown in the template (`<id>`, `[json_args]`, ...).
+    pub(crate) name: &'static str,
+    pub(crate) hint: &'static str,
+    /// Known values for this slot (may be empty).
+    pub(crate) candidates: Vec<String>,
+}
+
+/// "Fill in the blanks" output for a command invoked with missing arguments.
+///
+/// `invocation` is what the user already typed (e.g. `/loom-task update 42`); each missing
+/// slot is appended as a placeholder and listed with suggestions.
+pub(crate) fn format_argument_wizard(invocation: &str, slots: &[WizardSlot]) -> FormattedOutput {
+    const MAX_CANDIDATES: usize = 15;
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        "Arguments Needed",
+        &format!("## 🧩 Fill in the blanks: `{}`\n\n", invocation),
+    );
+
+    let placeholders: Vec<&str> = slots.iter().map(|slot| slot.name).collect();
+    let mut body = format!(
+        "Next invocation:\n\n```\n{} {}\n```\n\n| Argument | What to enter | Suggestions |\n| --- | --- | --- |\n",
+        invocation,
+        placeholders.join(" ")
+    );
+    for slot in slots {
+        let suggestions = if slot.candidates.is_empty() {
+            "—".to_string()
+        } else {
+            let mut shown: Vec<String> = slot
+                .candidates
+                .iter()
+                .take(MAX_CANDIDATES)
+                .map(|c| format!("`{}`", c))
+                .collect();
+            if slot.candidates.len() > MAX_CANDIDATES {
+                shown.push(format!("…{} more", slot.candidates.len() - MAX_CANDIDATES));
+            }
+            shown.join(", ")
+        };
+        body.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            slot.name, slot.hint, suggestions
+        ));
+    }
+    body.push('\n');
+    push_section(&mut text, &mut sections, "Template", &body);
+
+    FormattedOutput { text, sections }
+}
+
+/// Dump recorded CLI runs verbatim (exit code + full stdout/stderr), one section per run.
+pub(crate) fn format_raw_runs(command_name: &str, runs: &[RecordedRun]) -> FormattedOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+
+    push_section(
+        &mut text,
+        &mut sections,
+        command_name,
+        &format!("## Raw output: `/{}`\n\n", command_name),
+    );
+
+    for run in runs {
+        let mut body = format!(
+            "### `loom {}`\n\n**Exit code**: `{}`\n\n",
+            run.args.join(" "),
+            run.exit_code
+        );
+        body.push_str("**stdout**\n\n");
+        body.push_str(&fence(&run.stdout, ""));
+        body.push_str("**stderr**\n\n");
+        body.push_str(&fence(&run.stderr, ""));
+        push_section(
+            &mut text,
+            &mut sections,
+            &format!(
+                "loom {}",
+                run.args.first().map(|s| s.as_str()).unwrap_or("")
+            ),
+            &body,
+        );
+    }
+
+    FormattedOutput { text, sections }
+}
+
+/// Format a command's runs as one machine-readable JSON document (`--format json`).
+///
+/// Each run's stdout is embedded as parsed JSON when it parses, else as a string. `error` is the
+/// dispatch error the prose output would have shown; `ok` is false for it or any failing run.
+pub(crate) fn format_json_result(
+    command_name: &str,
+    args: &[String],
+    runs: &[RecordedRun],
+    duration_ms: u64,
+    error: Option<&str>,
+) -> FormattedOutput {
+    let runs_json: Vec<zed::serde_json::Value> = runs
+        .iter()
+        .map(|run| {
+            let payload = zed::serde_json::from_str::<zed::serde_json::Value>(run.stdout.trim())
+                .unwrap_or_else(|_| zed::serde_json::Value::String(run.stdout.clone()));
+            zed::serde_json::json!({
+                "args": run.args,
+                "exit_code": run.exit_code.parse::<i64>().ok(),
+                "payload": payload,
+                "stderr": run.stderr,
+            })
+        })
+        .collect();
+    let ok = error.is_none() && runs.iter().all(|run| run.exit_code == "0");
+    let doc = zed::serde_json::json!({
+        "command": command_name,
+        "args": args,
+        "ok": ok,
+        "exit_code": runs.last().and_then(|run| run.exit_code.parse::<i64>().ok()),
+        "duration_ms": duration_ms,
+        "error": error,
+        "runs": runs_json,
+    });
+    let json = zed::serde_json::to_string_pretty(&doc).unwrap_or_else(|_| doc.to_string());
+
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        command_name,
+        &fence(&json, "json"),
+    );
+    FormattedOutput { text, sections }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Wrap content in a code fence long enough that backticks inside can't close it early.
+fn fence(content: &str, lang: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        if c == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    let ticks = "`".repeat(longest.max(2) + 1);
+    format!(
+        "{ticks}{lang}\n{}\n{ticks}\n\n",
+        content.trim_end_matches('\n')
+    )
+}
+
+fn capitalize(s: &str) -> String {
+    let mut c = s.chars();
+    match c.next() {
+        None => String::new(),
+        Some(first) => {
+            let upper: String = first.to_uppercase().collect();
+            upper + c.as_str()
+        }
+    }
+}
+
+/// A section label with a row count, e.g. `Tools (42)`.
+fn counted_label(label: &str, count: usize) -> String {
+    format!("{} ({})", tr(label), count)
+}
+
+/// A list command's rows rendered as a Markdown table.
+struct Listing {
+    table: String,
+    count: usize,
+}
+
+/// Parse list output: JSON (an array of objects, or the first array inside an object) or a
+/// whitespace-aligned table with a header line. `None` when it's neither.
+fn parse_listing(stdout: &str) -> Option<Listing> {
+    let stdout = stdout.trim();
+    let Ok(value) = zed::serde_json::from_str::<zed::serde_json::Value>(stdout) else {
+        if !looks_tabular(stdout) {
+            return None;
+        }
+        return Some(Listing {
+            table: to_markdown_table(stdout),
+            count: stdout.lines().filter(|l| !l.trim().is_empty()).count() - 1,
+        });
+    };
+    let items = match &value {
+        zed::serde_json::Value::Array(items) => items,
+        zed::serde_json::Value::Object(map) => map.values().find_map(|v| v.as_array())?,
+        _ => return None,
+    };
+    if items.is_empty() {
+        return Some(Listing {
+            table: String::new(),
+            count: 0,
+        });
+    }
+    // Columns are the scalar fields of every item (serde_json keeps object keys sorted).
+    let mut headers: Vec<&str> = Vec::new();
+    for item in items {
+        for (key, field) in item.as_object()? {
+            if !field.is_array() && !field.is_object() && !headers.contains(&key.as_str()) {
+                headers.push(key);
+            }
+        }
+    }
+    if headers.is_empty() {
+        return None;
+    }
+    let mut table = format!(
+        "| {} |\n|{}\n",
+        headers.join(" | "),
+        " --- |".repeat(headers.len())
+    );
+    for item in items {
+        let cells: Vec<String> = headers
+            .iter()
+            .map(|h| match item.get(*h) {
+                None | Some(zed::serde_json::Value::Null) => "—".to_string(),
+                Some(zed::serde_json::Value::String(s)) => table_cell(s),
+                Some(other) => other.to_string(),
+            })
+            .collect();
+        table.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    Some(Listing {
+        table,
+        count: items.len(),
+    })
+}
+
+/// Format a list command (servers, tasks, sessions) as a table whose section label carries the
+/// row count. Output that doesn't parse as a list falls back to [`format_generic`].
+fn format_listing(result: &CommandResult, title: &str, label: &str) -> FormattedOutput {
+    let listing = parse_listing(&result.stdout).filter(|_| result.success());
+    let Some(listing) = listing else {
+        return format_generic(result, title);
+    };
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    push_section(
+        &mut text,
+        &mut sections,
+        title,
+        &format!("## {} {}\n\n", status_icon(true), tr(title)),
+    );
+    let body = if listing.count == 0 {
+        format!("_No {}._\n\n", tr(label).to_lowercase())
+    } else {
+        format!("{}\n\n", listing.table)
+    };
+    push_section(
+        &mut text,
+        &mut sections,
+        &counted_label(label, listing.count),
+        &body,
+    );
+    if !result.stderr.trim().is_empty() {
+        push_section(
+            &mut text,
+            &mut sections,
+            "Errors",
+            &fenced("", result.stderr.trim()),
+        );
+    }
+    FormattedOutput { text, sections }
+}
+
+/// Heuristic: output looks tabular if most non-empty lines have 2+ whitespace-separated columns.
+fn looks_tabular(s: &str) -> bool {
+    let lines: Vec<&str> = s.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return false;
+    }
+    let multi_col = lines
+        .iter()
+        .filter(|l| l.split_whitespace().count() >= 2)
+        .count();
+    multi_col * 2 >= lines.len()
+}
+
+/// Split whitespace-aligned CLI output into cells, header row first.
+///
+/// Tab-separated output splits on tabs. When the header separates columns with runs of 2+
+/// spaces (as `loom servers list` does), columns start where a header word follows such a run
+/// and every line is blank just before it, so values like `Last Seen` or `My Server` stay in one
+/// cell. Otherwise words are split on whitespace, the last column taking the rest of the line.
+fn table_rows(lines: &[&str]) -> Vec<Vec<String>> {
+    let header = lines[0];
+    if header.contains('\t') {
+        return lines
+            .iter()
+            .map(|line| {
+                line.split('\t')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .collect();
+    }
+
+    let chars: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+    let blank = |line: &[char], i: usize| line.get(i).is_none_or(|c| c.is_whitespace());
+    let head = &chars[0];
+    let mut starts: Vec<usize> = Vec::new();
+    for i in 0..head.len() {
+        let word_start = !blank(head, i) && (i == 0 || blank(head, i - 1));
+        let after_gap = i >= 2 && blank(head, i - 2);
+        if word_start && (i == 0 || (after_gap && chars.iter().all(|l| blank(l, i - 1)))) {
+            starts.push(i);
+        }
+    }
+    if starts.len() < 2 {
+        let ncols = header.split_whitespace().count();
+        return lines
+            .iter()
+            .enumerate()
+            .map(|(n, line)| {
+                if n == 0 {
+                    line.split_whitespace().map(str::to_string).collect()
+                } else {
+                    line.trim_start()
+                        .splitn(ncols, char::is_whitespace)
+                        .map(|c| c.trim().to_string())
+                        .collect()
+                }
+            })
+            .collect();
+    }
+    // The first column also covers anything indented before the header's first word.
+    starts[0] = 0;
+    chars
+        .iter()
+        .map(|line| {
+            starts
+                .iter()
+                .enumerate()
+                .map(|(k, &from)| {
+                    let to = starts.get(k + 1).copied().unwrap_or(usize::MAX);
+                    let cell: String = line
+                        .iter()
+                        .skip(from)
+                        .take(to.saturating_sub(from))
+                        .collect();
+                    cell.trim().to_string()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Best-effort conversion of whitespace-aligned CLI output to a Markdown table.
+fn to_markdown_table(s: &str) -> String {
+    let lines: Vec<&str> = s.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.is_empty() {
+        return String::new();
+    }
+    let rows = table_rows(&lines);
+    let ncols = rows[0].len();
+    if ncols == 0 {
+        return fenced("", s).trim_end().to_string();
+    }
+
+    let mut table = String::new();
+    for (n, row) in rows.iter().enumerate() {
+        // Pad to ncols if needed.
+        let cells: Vec<String> = (0..ncols)
+            .map(|i| table_cell(row.get(i).map(String::as_str).unwrap_or("")))
+            .collect();
+        table.push_str(&format!("| {} |\n", cells.join(" | ")));
+        if n == 0 {
+            table.push_str(&format!("|{}\n", " --- |".repeat(ncols)));
+        }
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_result(exit_code: &str, stdout: &str, stderr: &str) -> CommandResult {
+        CommandResult {
+            exit_code: exit_code.to_string(),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    #[test]
+    fn diagnostic_report_success() {
+        let r = mock_result("0", "all checks passed", "");
+        let out = format_diagnostic_report(&r);
+        assert!(out.text.contains("✅"));
+        assert!(out.text.contains("all checks passed"));
+        assert!(!out.sections.is_empty());
+    }
+
+    #[test]
+    fn diagnostic_report_failure() {
+        let r = mock_result("1", "", "connection refused");
+        let out = format_diagnostic_report(&r);
+        assert!(out.text.contains("❌"));
+        assert!(out.text.contains("connection refused"));
+    }
+
+    #[test]
+    fn diagnostic_report_groups_text_checks() {
+        let stdout = "Daemon:\n✓ socket reachable\n✗ version - too old\nSecrets:\n⚠ GITHUB_TOKEN: not set\n✓ vault";
+        let r = mock_result("1", stdout, "");
+        let out = format_diagnostic_report(&r);
+        assert!(out.text.contains("2 passed, 1 warnings, 1 failed"));
+        assert!(out.text.contains("### ❌ Failed (1)"));
+        assert!(out.text.contains("| Daemon | version | too old |"));
+        assert!(out.text.contains("| Secrets | GITHUB_TOKEN | not set |"));
+        assert!(out.text.contains(
+            "### ✅ Passed (2)\n\n- **Daemon**: socket reachable\n- **Secrets**: vault\n"
+        ));
+        let labels: Vec<&str> = out.sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["Diagnostic Report", "Failed", "Warnings", "Passed"]
+        );
+    }
+
+    #[test]
+    fn parse_check_output_json() {
+        let stdout = r#"{"checks":[{"name":"daemon","category":"Runtime","status":"ok"},{"name":"token","status":"warning","message":"expires soon"}]}"#;
+        let items = parse_check_output(stdout);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].category, "Runtime");
+        assert_eq!(items[0].status, CheckStatus::Pass);
+        assert_eq!(items[1].category, "General");
+        assert_eq!(items[1].status, CheckStatus::Warn);
+        assert_eq!(items[1].message, "expires soon");
+
+        let stdout = r#"[{"name":"gpu","status":"skipped","message":"no device"},{"name":"lint","status":"Error"},{"name":"cache","status":"unknown"}]"#;
+        let items = parse_check_output(stdout);
+        assert_eq!(items[0].status, CheckStatus::Warn);
+        assert_eq!(items[0].message, "status `skipped`: no device");
+        assert_eq!(items[1].status, CheckStatus::Fail);
+        assert_eq!(items[1].message, "");
+        assert_eq!(items[2].status, CheckStatus::Warn);
+        assert_eq!(items[2].message, "status `unknown`");
+    }
+
+    #[test]
+    fn status_report_sections() {
+        let r = mock_result("0", "daemon running\nservers: 3", "");
+        let out = format_status_report(&r);
+        assert!(out.sections.len() >= 2);
+        assert_eq!(out.sections[0].label, "Status");
+    }
+
+    #[test]
+    fn tool_call_renders_mcp_content_and_is_error() {
+        let r = mock_result(
+            "0",
+            r#"{"content":[{"type":"text","text":"{\"count\":2}"},{"type":"text","text":"done"}],"isError":true}"#,
+            "",
+        );
+        let out = format_tool_call(&r, "agent_memory_stats");
+        assert!(out.text.contains("## ❌ Tool: `agent_memory_stats`"));
+        assert!(out.text.contains("```json\n{\n  \"count\": 2\n}\n```"));
+        assert!(out.text.contains("```\ndone\n```"));
+        assert_eq!(out.sections[1].label, "Text 1");
+        assert_eq!(out.sections[2].label, "Text 2");
+
+        let r = mock_result(
+            "0",
+            r#"{"content":[
+                {"type":"resource_link","uri":"file:///a.md","name":"a.md","mimeType":"text/markdown"},
+                {"type":"resource","resource":{"uri":"file:///b.txt","text":"hello"}},
+                {"type":"image","data":"AAAA","mimeType":"image/png"}
+            ]}"#,
+            "",
+        );
+        let out = format_tool_call(&r, "docs_fetch");
+        let labels: Vec<_> = out.sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["docs_fetch", "Resource 1", "Resource 2", "Image 3"]
+        );
+        assert!(out
+            .text
+            .contains("🔗 [a.md](file:///a.md) · `text/markdown`"));
+        assert!(out
+            .text
+            .contains("🔗 [file:///b.txt](file:///b.txt)\n\n```\nhello\n```"));
+        assert!(out.text.contains("🖼️ `image/png`, 1 KB (not shown)"));
+
+        let r = mock_result("0", r#"{"ok":true}"#, "");
+        let out = format_tool_call(&r, "ping");
+        assert!(out.text.contains("## ✅ Tool: `ping`"));
+        assert!(out.text.contains("\"ok\": true"));
+    }
+
+    #[test]
+    fn trace_tree_shows_hops_and_failure() {
+        let stdout = r#"{"trace": {"name": "hub", "duration_ms": 200, "children": [
+            {"name": "github", "kind": "server", "duration_ms": 180, "children": [
+                {"name": "search_code", "kind": "tool", "duration_ms": 150, "status": "error",
+                 "error": "rate limited"}
+            ]}
+        ]}, "result": null}"#;
+        let out = format_trace(&mock_result("1", stdout, ""), "search_code");
+        assert!(out.text.starts_with("## ❌ Trace: `search_code`"));
+        assert!(out.text.contains("- ✅ `hub` — 200.0 ms\n"));
+        assert!(out
+            .text
+            .contains("  - ✅ server `github` — 180.0 ms (90%)\n"));
+        assert!(out
+            .text
+            .contains("    - ❌ tool `search_code` — 150.0 ms (75%): `rate limited`\n"));
+        assert!(out.text.contains("**Failed at** tool `search_code`."));
+        assert!(!out.text.contains("```json"));
+
+        let ok = r#"{"trace": [{"name": "hub", "duration_ms": 5},
+                               {"name": "tool", "duration_ms": 40}], "result": {"n": 1}}"#;
+        let out = format_trace(&mock_result("0", ok, ""), "t");
+        assert!(out.text.contains("**Slowest hop:** `tool` (40.0 ms)."));
+        assert!(out.text.contains("\"n\": 1"));
+
+        let plain = format_trace(&mock_result("0", "done", "hub 3ms"), "t");
+        assert!(plain.text.contains("No structured trace"));
+    }
+
+    #[test]
+    fn extension_error_report_fields() {
+        let out = format_extension_error(
+            "loom-servers",
+            &["restart".into(), "github".into()],
+            "session state mutex poisoned",
+            "65000000-3",
+            "/usr/local/bin/loom",
+            &["[loom-zed 1 INFO 65000000-3] slash command: loom-servers".into()],
+        );
+        assert_eq!(out.sections[0].label, "Extension Error");
+        assert!(out
+            .text
+            .contains("| Command | `/loom-servers restart github` |"));
+        assert!(out.text.contains("| Correlation ID | `65000000-3` |"));
+        assert!(out.text.contains("| Binary | `/usr/local/bin/loom` |"));
+        assert!(out.text.contains("slash command: loom-servers"));
+    }
+
+    #[test]
+    fn benchmark_reports_stats_and_failure() {
+        let out = format_benchmark("agent_memory_stats", 3, &[120, 80, 100], None);
+        assert!(out.text.contains("| 80 ms | 100 ms | 120 ms |"));
+        assert!(out
+            .text
+            .contains("3 of 3 calls succeeded: 120 ms, 80 ms, 100 ms"));
+
+        let failed = mock_result("1", "", "unknown tool: nope");
+        let out = format_benchmark("nope", 5, &[], Some(&failed));
+        assert!(out.text.starts_with("## ❌ Benchmark: `nope`"));
+        assert!(out.text.contains("No call succeeded"));
+        assert!(out.text.contains("Call 1 failed (exit code 1)"));
+    }
+
+    #[test]
+    fn env_report_explains_resolution() {
+        let resolved = ResolvedBinary {
+            program: "loom-core/v0.9.1/bin/loom".into(),
+            source: BinarySource::ManagedInstall,
+            env: vec![(
+                "PATH".into(),
+                "loom-core/v0.9.1/bin:/usr/bin:/opt/homebrew/bin".into(),
+            )],
+            from_shell_env: true,
+            path_prefix: Some("loom-core/v0.9.1/bin".into()),
+            notices: Vec::new(),
+        };
+        let candidates = vec![BinaryCandidate {
+            source: BinarySource::ManagedInstall,
+            path: "loom-core/v0.9.1/bin/loom".into(),
+        }];
+        let overrides = vec![
+            ("LOOM_LOG".to_string(), "debug".to_string()),
+            ("GITHUB_TOKEN".to_string(), "ghp_secret".to_string()),
+        ];
+        let out = format_env_report(&resolved, &candidates, Some("0.9.1"), &overrides, ":");
+        assert!(out
+            .text
+            .contains("| Source | managed install — downloaded by the extension"));
+        assert!(out
+            .text
+            .contains("| Environment | worktree shell environment (1 variables) |"));
+        assert!(out
+            .text
+            .contains("| 4 | managed install | `loom-core/v0.9.1/bin/loom` | ✅ |"));
+        assert!(out.text.contains("| 1 | command.path | — |  |"));
+        assert!(out.text.contains(
+            "1. `loom-core/v0.9.1/bin` ← added by the extension, binary is here\n2. `/usr/bin`\n"
+        ));
+        assert!(out.text.contains("| `LOOM_LOG` | `debug` |"));
+        assert!(!out.text.contains("ghp_secret"));
+    }
+
+    #[test]
+    fn health_table_lists_each_server() {
+        let probes = vec![
+            ServerProbe {
+                name: "github".into(),
+                reachable: true,
+                latency_ms: 42,
+                error: None,
+            },
+            ServerProbe {
+                name: "tavily".into(),
+                reachable: false,
+                latency_ms: 5003,
+                error: Some("timeout | no response".into()),
+            },
+        ];
+        let listing = mock_result("0", "", "");
+        let out = format_health(&mock_result("0", "", ""), 12, Some(&listing), &probes);
+        assert!(out.text.starts_with("## ❌ Loom Health"));
+        assert!(out.text.contains("reachable** (12 ms)"));
+        assert!(out.text.contains("| `github` | ✅ | 42 ms | — |"));
+        assert!(out
+            .text
+            .contains("| `tavily` | ❌ | 5003 ms | timeout \\| no response |"));
+        assert!(out.text.contains("1 of 2 servers reachable."));
+
+        let down = format_health(&mock_result("1", "", "refused"), 3, None, &[]);
+        assert!(down.text.contains("server checks were skipped"));
+    }
+
+    #[test]
+    fn crash_loop_detected_from_restart_history() {
+        let text = parse_daemon_restarts(
+            "daemon: running\nRestarts: 7 (4 in last 10m)\nLast crash: panic: socket closed\n",
+        );
+        assert_eq!(text.total, Some(7));
+        assert_eq!(text.recent, Some(4));
+        let warning = crash_loop_warning(&text, 0).unwrap();
+        assert!(warning.contains("4 restarts in the last 10 minutes"));
+        assert!(warning.contains("`panic: socket closed`"));
+
+        let json =
+            r#"{"daemon": {"restart_count": 3, "last_crash": {"reason": "OOM", "at": 1000}}}"#;
+        let parsed = parse_daemon_restarts(json);
+        assert_eq!(parsed.last_crash_reason.as_deref(), Some("OOM"));
+        assert!(parsed.is_crash_looping(1200));
+        assert!(!parsed.is_crash_looping(5000));
+        assert!(crash_loop_warning(&parse_daemon_restarts("Restarts: 1"), 0).is_none());
+    }
+
+    #[test]
+    fn sync_report_with_platform() {
+        let r = mock_result("0", "synced 5 servers", "");
+        let out = format_sync_report(&r, Some("zed"));
+        assert!(out.text.contains("Sync: zed"));
+    }
+
+    #[test]
+    fn sync_report_no_platform() {
+        let r = mock_result("0", "all in sync", "");
+        let out = format_sync_report(&r, None);
+        assert!(out.text.contains("Sync Status"));
+    }
+
+    #[test]
+    fn sync_report_highlights_changes_as_diff() {
+        let r = mock_result("0", "+ github\n- jira\n~ memory (args)", "");
+        let out = format_sync_report(&r, Some("zed"));
+        assert!(out.text.contains(
+            "**1 added · 1 removed · 1 changed**\n\n```diff\n+ github\n- jira\n~ memory (args)\n```"
+        ));
+
+        let r = mock_result(
+            "0",
+            r#"{"added":["github"],"changed":[{"name":"memory"}],"removed":[]}"#,
+            "",
+        );
+        let out = format_sync_report(&r, Some("zed"));
+        assert!(out.text.contains("```diff\n+ github\n~ memory\n```"));
+
+        let r = mock_result("0", "Wrote:\n- ~/.config/zed/settings.json", "");
+        let out = format_sync_report(&r, Some("zed"));
+        assert!(!out.text.contains("```diff"));
+    }
+
+    #[test]
+    fn sync_multi_summarizes_platforms() {
+        let ok = mock_result("0", "synced 5 servers", "");
+        let failed = mock_result("1", "", "config not found");
+        let parts: Vec<(&str, &CommandResult)> = vec![("zed", &ok), ("vscode", &failed)];
+        let out = format_sync_multi(&parts);
+        assert!(out.text.contains("1/2 platforms succeeded"));
+        assert!(out.text.contains("| zed | ✅ | `0` | synced 5 servers |"));
+        assert!(out
+            .text
+            .contains("| vscode | ❌ | `1` | config not found |"));
+        assert!(out.text.contains("config not found"));
+
+        let quiet = mock_result("0", "", "");
+        assert_eq!(sync_summary_line(&quiet), "—");
+        let noisy = mock_result("1", "wrote a|b", "\n  error: permission denied\nmore");
+        assert_eq!(sync_summary_line(&noisy), "error: permission denied");
+    }
+
+    #[test]
+    fn sync_diff_renders_changes_per_platform() {
+        let zed = mock_result(
+            "0",
+            "--- a/settings.json\n+++ b/settings.json\n@@ -1 +1,2 @@\n+  \"github\": {}\n",
+            "",
+        );
+        let vscode = mock_result("0", "vscode: no changes\n", "");
+        let claude = mock_result("1", "", "unknown flag: --dry-run");
+        let json = mock_result(
+            "0",
+            r#"{"changes": [{"path": "~/.codex/config.toml", "diff": "+[mcp]"}, {"path": "x", "diff": ""}]}"#,
+            "",
+        );
+        let parts: Vec<(&str, &CommandResult)> = vec![
+            ("zed", &zed),
+            ("vscode", &vscode),
+            ("claude", &claude),
+            ("codex", &json),
+        ];
+        let out = format_sync_diff(&parts);
+        assert!(out
+            .text
+            .contains("## ❌ Sync Preview: 2 of 4 platforms would change"));
+        assert!(out.text.contains("Up to date: vscode"));
+        assert!(out.text.contains("```diff\n--- a/settings.json"));
+        assert!(out
+            .text
+            .contains("--- a/~/.codex/config.toml\n+++ b/~/.codex/config.toml\n+[mcp]"));
+        assert!(out.text.contains("doesn't support `--dry-run`"));
+        assert!(out.text.contains("/loom-sync <platform>"));
+
+        let clean = mock_result("0", "", "");
+        let out = format_sync_diff(&[("zed", &clean)]);
+        assert!(out
+            .text
+            .contains("## ✅ Sync Preview: 0 of 1 platform would change"));
+        assert!(!out.text.contains("```diff"));
+    }
+
+    #[test]
+    fn sync_platform_status_json_details() {
+        let stdout = r#"{"in_sync": false, "last_synced": "2026-01-02T03:04:05Z",
+            "files": [{"path": "~/.config/zed/settings.json", "status": "modified"}, "~/.zed/tasks.json"],
+            "pending": [{"action": "add", "server": "github"}, "remove stale entry"]}"#;
+        let r = mock_result("0", stdout, "");
+        let out = format_sync_platform_status(&r, "zed");
+        assert!(out.text.contains("## ⚠️ Sync Status: zed"));
+        assert!(out.text.contains("Drift detected (2 pending)"));
+        assert!(out.text.contains("| Last synced | 2026-01-02T03:04:05Z |"));
+        assert!(out
+            .text
+            .contains("| `~/.config/zed/settings.json` | ⚠️ modified |"));
+        assert!(out.text.contains("| `~/.zed/tasks.json` | ✅ synced |"));
+        assert!(out.text.contains("- **add** `github`"));
+        assert!(out.text.contains("- remove stale entry"));
+        assert!(out.text.contains("/loom-sync zed"));
+    }
+
+    #[test]
+    fn sync_platform_status_plain_fallback() {
+        let r = mock_result("0", "zed: in sync", "");
+        let out = format_sync_platform_status(&r, "zed");
+        assert!(out.text.starts_with("## ✅ Sync Status: zed"));
+        assert!(out.text.contains("zed: in sync"));
+        assert_eq!(
+            out.sections[0].range.end as usize,
+            out.text.find("```").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_name_list_skips_active_marker() {
+        let stdout = "PROFILE   SERVERS\n* default  5\n  work     3\n*minimal 1\n";
+        let names: Vec<String> = parse_name_list(stdout, "profiles").into_iter().collect();
+        assert_eq!(names, vec!["default", "minimal", "work"]);
+        let json = r#"{"profiles": [{"name": "a"}, "b"]}"#;
+        assert_eq!(parse_name_list(json, "profiles").len(), 2);
+        let json = r#"{"sessions": [{"session_id": "s-1", "namespace": "work"}]}"#;
+        let names: Vec<String> = parse_name_list(json, "sessions").into_iter().collect();
+        assert_eq!(names, vec!["s-1"]);
+    }
+
+    #[test]
+    fn profile_show_json() {
+        let stdout = r#"{"name": "work", "description": "Day job",
+            "servers": [{"name": "github", "enabled": true}, {"name": "k8s", "enabled": false}],
+            "tools": ["gh_search", {"name": "k8s_pods"}],
+            "settings": {"log_level": "debug", "hub": true}}"#;
+        let r = mock_result("0", stdout, "");
+        let out = format_profile_show(&r, "work");
+        assert!(out.text.contains("## ✅ Profile: work\n"));
+        assert!(out.text.contains("Day job"));
+        assert!(out.text.contains("| github | ✅ |"));
+        assert!(out.text.contains("| k8s | — |"));
+        assert!(out.text.contains("### Tools (2)"));
+        assert!(out.text.contains("| log_level | `debug` |"));
+        assert!(out.text.contains("/loom-profile switch work"));
+    }
+
+    #[test]
+    fn profile_show_plain_fallback() {
+        let r = mock_result("0", "servers: github, k8s", "");
+        let out = format_profile_show(&r, "work");
+        assert!(out.text.contains("Profile: work"));
+        assert!(out.text.contains("servers: github, k8s"));
+    }
+
+    #[test]
+    fn argument_wizard_template() {
+        let slots = vec![
+            WizardSlot {
+                name: "<id>",
+                hint: "Task ID",
+                candidates: Vec::new(),
+            },
+            WizardSlot {
+                name: "<status>",
+                hint: "New status",
+                candidates: vec!["pending".into(), "completed".into()],
+            },
+        ];
+        let out = format_argument_wizard("/loom-task update", &slots);
+        assert!(out
+            .text
+            .contains("```\n/loom-task update <id> <status>\n```"));
+        assert!(out.text.contains("| `<id>` | Task ID | — |"));
+        assert!(out
+            .text
+            .contains("| `<status>` | New status | `pending`, `completed` |"));
+        assert_eq!(out.sections.len(), 2);
+    }
+
+    #[test]
+    fn daemon_resources_from_json() {
+        let stdout = r#"{"daemon": {"pid": 4242, "memory_bytes": 52428800, "cpu_percent": 3.5},
+            "servers": [{"name": "github", "pid": 100}, {"name": "remote"}]}"#;
+        let res = parse_daemon_resources(stdout);
+        assert_eq!(res.pid, Some(4242));
+        assert_eq!(res.rss_kb, Some(51200));
+        assert_eq!(res.cpu_percent, Some(3.5));
+        assert_eq!(res.server_processes, Some(1));
+        assert!(!res.needs_probe());
+
+        let out = format_status_verbose(&mock_result("0", stdout, ""), &res);
+        assert!(out.text.contains("| PID | `4242` |"));
+        assert!(out.text.contains("| Memory (RSS) | 50.0 MB |"));
+        assert!(out.text.contains("| CPU | 3.5% |"));
+        assert!(out.text.contains("| Server processes | 1 |"));
+    }
+
+    #[test]
+    fn daemon_resources_from_text_and_ps() {
+        let res = parse_daemon_resources("Daemon: running\nPID: 777\nServers: 4\n");
+        assert_eq!(res.pid, Some(777));
+        assert!(res.needs_probe());
+        assert_eq!(parse_ps_usage("  20480  12.5\n"), Some((20480, 12.5)));
+        assert_eq!(parse_ps_usage(""), None);
+
+        let out = format_status_verbose(
+            &mock_result("1", "", "daemon not running"),
+            &DaemonResources::default(),
+        );
+        assert!(out.text.contains("| PID | — |"));
+        assert!(out.text.contains("is the daemon running?"));
+    }
+
+    #[test]
+    fn daemon_env_redacts_and_groups() {
+        let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+        let diff = crate::env::diff_env(
+            &[pair("PATH", "/bin"), pair("HOME", "/h")],
+            &[
+                pair("PATH", "/usr/bin|/bin"),
+                pair("HOME", "/h"),
+                pair("GITHUB_TOKEN", "ghp_secret"),
+            ],
+        );
+        let out = format_daemon_env("`loom daemon env`", &diff);
+        assert!(out.text.contains("1 missing from the daemon"));
+        assert!(out
+            .text
+            .contains("| `GITHUB_TOKEN` | `<redacted, 10 chars>` |"));
+        assert!(!out.text.contains("ghp_secret"));
+        assert!(out.text.contains("`/usr/bin\\|/bin`"));
+        assert!(out.text.contains("/loom-restart"));
+        assert_eq!(out.sections.len(), 3);
+
+        let same = crate::env::diff_env(&[pair("A", "1")], &[pair("A", "1")]);
+        let out = format_daemon_env("`/proc/1/environ`", &same);
+        assert!(out.text.contains("Daemon environment matches"));
+        assert_eq!(out.sections.len(), 1);
+    }
+
+    #[test]
+    fn kill_reports_respawn_state() {
+        let killed = mock_result("0", "terminated github (pid 123)", "");
+        let listing = mock_result(
+            "0",
+            "NAME    STATUS   TOOLS\ngithub  running  12\nk8s     stopped  0\n",
+            "",
+        );
+        let out = format_kill(&killed, "github", Some(&listing));
+        assert!(out.text.contains("## ✅ Kill Server: github"));
+        assert!(out.text.contains("✅ Respawned (`running 12`)"));
+
+        let out = format_kill(&killed, "k8s", Some(&listing));
+        assert!(out.text.contains("⚠️ Not running (`stopped 0`)"));
+
+        let json = mock_result(
+            "0",
+            r#"{"servers": [{"name": "github", "status": "starting"}]}"#,
+            "",
+        );
+        assert_eq!(
+            server_state(&json.stdout, "github").as_deref(),
+            Some("starting")
+        );
+
+        let failed = mock_result("1", "", "no such server: nope");
+        let out = format_kill(&failed, "nope", None);
+        assert!(out.text.contains("❌"));
+        assert!(out.text.contains("no such server"));
+        assert!(!out.text.contains("Respawn"));
+    }
+
+    #[test]
+    fn tool_schemas_from_json_list() {
+        let stdout = r#"{"tools": [
+            {"name": "zeta", "server": "k8s", "inputSchema": {"type": "object"}},
+            {"name": "alpha", "description": "A", "input_schema": {"type": "object", "properties": {}}},
+            {"name": "no_schema"}
+        ]}"#;
+        let schemas = parse_tool_schemas(stdout).unwrap();
+        let names: Vec<&str> = schemas.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "zeta"]);
+        assert!(schemas[0].1.contains("\"description\": \"A\""));
+        assert!(schemas[1].1.contains("\"server\": \"k8s\""));
+        assert!(schemas[1].1.ends_with("}\n"));
+
+        assert!(parse_tool_schemas("NAME\nalpha").is_err());
+        assert!(parse_tool_schemas(r#"[{"name": "a"}]"#).is_err());
+
+        let written = vec![("alpha".to_string(), ".loom/tools/alpha.json".to_string())];
+        let out = format_schema_export(".loom/tools", &written);
+        assert!(out
+            .text
+            .contains("Exported 1 tool schemas to `.loom/tools/`"));
+        assert!(out.text.contains("| `alpha` | `.loom/tools/alpha.json` |"));
+    }
+
+    #[test]
+    fn servers_list_shows_changes() {
+        let r = mock_result("0", "NAME STATUS\ngithub-mcp running\n", "");
+        let plain = format_servers_list(&r, None);
+        assert!(!plain.text.contains("Changes since"));
+
+        let added = vec!["github-mcp".to_string()];
+        let removed = vec!["gitlab".to_string()];
+        let out = format_servers_list(&r, Some((&added, &removed)));
+        assert!(out.text.contains("+1 / -1"));
+        assert!(out
+            .text
+            .contains("```diff\n+ github-mcp (new)\n- gitlab (removed)\n```"));
+        assert!(out.text.contains("| github-mcp | running |"));
+        assert_eq!(out.sections[2].label, "Servers (1)");
+    }
+
+    #[test]
+    fn listings_count_rows_in_section_labels() {
+        let r = mock_result(
+            "0",
+            r#"{"tasks":[{"id":"t1","title":"Fix CI","status":"open","tags":["ci"]},{"id":"t2","title":"A | B","status":null}]}"#,
+            "",
+        );
+        let out = format_task(&r, "list");
+        assert_eq!(out.sections[1].label, "Tasks (2)");
+        assert!(out.text.contains(
+            "| id | status | title |\n| --- | --- | --- |\n| t1 | open | Fix CI |\n| t2 | — | A \\| B |\n"
+        ));
+
+        let out = format_session(&mock_result("0", r#"{"sessions":[]}"#, ""), "list");
+        assert_eq!(out.sections[1].label, "Sessions (0)");
+        assert!(out.text.contains("_No sessions._"));
+
+        let out = format_servers_list(&mock_result("0", "NAME STATUS\na up\nb down\n", ""), None);
+        assert_eq!(out.sections[1].label, "Servers (2)");
+
+        let out = format_task(&mock_result("0", "no tasks yet", ""), "list");
+        assert_eq!(out.sections[1].label, "Output");
+    }
+
+    #[test]
+    fn heartbeat_status_table() {
+        let stdout = r#"{"agent_id": "zed-loom", "last_heartbeat": "2026-03-01T10:00:00Z", "status": "active", "interval_secs": 60}"#;
+        let out = format_heartbeat_status(&mock_result("0", stdout, ""));
+        assert!(out
+            .text
+            .contains("| Last heartbeat | 2026-03-01T10:00:00Z |"));
+        assert!(out.text.contains("| From agent | `zed-loom` |"));
+        assert!(out.text.contains("| Auto-heartbeat | every 60s |"));
+
+        let out = format_heartbeat_status(&mock_result("0", r#"{"interval_secs": 0}"#, ""));
+        assert!(out.text.contains("| Last heartbeat | never |"));
+        assert!(out.text.contains("| Auto-heartbeat | off |"));
+    }
+
+    #[test]
+    fn recall_markdown_renders_items() {
+        let stdout = r#"{"results": [{"title": "Auth design", "source": "docs/auth.md", "score": 0.91, "content": "Tokens rotate daily."}, {"text": "plain note"}]}"#;
+        let doc = recall_markdown("auth", stdout, "2026-01-01");
+        assert!(
+            doc.starts_with("# Recall: auth\n\n_Recalled from Loom agent memory on 2026-01-01._")
+        );
+        assert!(doc.contains(
+            "## 1. Auth design\n\n_source: `docs/auth.md` · score: 0.91_\n\nTokens rotate daily."
+        ));
+        assert!(doc.contains("## 2. Result\n\nplain note"));
+
+        let doc = recall_markdown("auth", "free text", "2026-01-01");
+        assert!(doc.ends_with("free text\n"));
+
+        let out = format_recall(
+            &mock_result("0", "hit", ""),
+            Some(".loom/context/auth.md"),
+            0,
+        );
+        assert!(out.text.contains("Saved to `.loom/context/auth.md`"));
+    }
+
+    #[test]
+    fn recall_results_are_ranked_by_score() {
+        let json = r#"{"results": [
+            {"content": "Tokens rotate weekly.", "score": 0.41, "namespace": "ops"},
+            {"content": "Auth uses  OAuth\ndevice flow.", "score": 0.93, "namespace": "zed",
+             "created_at": 1000, "title": "auth.md"},
+            {"content": "Unscored note"}
+        ]}"#;
+        let out = format_recall(&mock_result("0", json, ""), None, 1000 + 7200);
+        assert_eq!(out.sections[1].label, "Results (3)");
+        let first = out
+            .text
+            .find("1. **0.93** · `zed` · 2h ago — auth.md\n   Auth uses OAuth device flow.\n")
+            .unwrap();
+        let second = out.text.find("2. **0.41** · `ops`\n").unwrap();
+        let third = out.text.find("3. **—**\n   Unscored note\n").unwrap();
+        assert!(first < second && second < third);
+
+        let out = format_recall(&mock_result("0", "[]", ""), None, 0);
+        assert!(out.text.contains("_No memories matched._"));
+    }
+
+    #[test]
+    fn permissions_group_allowed_and_denied() {
+        let json = r#"{"default": "ask", "permissions": [
+            {"tool": "github__delete_repo", "permission": "deny"},
+            {"tool": "github__search_code", "allowed": true},
+            {"tool": "agent_memory_store", "decision": "allow"}
+        ]}"#;
+        let parsed = parse_permissions(json).unwrap();
+        assert_eq!(
+            parsed.allowed,
+            vec!["agent_memory_store", "github__search_code"]
+        );
+        assert_eq!(parsed.denied, vec!["github__delete_repo"]);
+        assert_eq!(
+            parse_permissions("allow a\ndeny b\n").unwrap().denied,
+            vec!["b"]
+        );
+        assert_eq!(parse_permissions("nothing here"), None);
+
+        let listing = mock_result("0", json, "");
+        let out = format_permissions(
+            &mock_result("0", "ok", ""),
+            Some(("deny", "github__delete_repo")),
+            Some(&listing),
+        );
+        assert!(out
+            .text
+            .starts_with("## ✅ `github__delete_repo` is now denied"));
+        assert!(out.text.contains("Tools not listed below: **ask**."));
+        assert!(out.text.contains("### ✅ Allowed (2)"));
+        assert!(out
+            .text
+            .contains("### 🚫 Denied (1)\n\n- `github__delete_repo` ← changed\n"));
+    }
+
+    #[test]
+    fn aliases_table_and_shadow_warning() {
+        let aliases = vec![
+            Alias {
+                name: "h".into(),
+                target: "health".into(),
+                source: AliasSource::Saved,
+            },
+            Alias {
+                name: "st".into(),
+                target: "status verbose".into(),
+                source: AliasSource::Settings,
+            },
+        ];
+        let out = format_aliases(&aliases, None);
+        assert!(out.text.starts_with("## 🔖 Command Aliases"));
+        assert!(out
+            .text
+            .contains("| `st` | `/loom-status verbose` | settings |\n"));
+
+        let change = AliasChange::Saved {
+            name: "st".into(),
+            shadowed: true,
+        };
+        let out = format_aliases(&aliases, Some(&change));
+        assert!(out.text.starts_with("## ✅ Saved alias `st`"));
+        assert!(out.text.contains("settings take precedence"));
+        assert!(format_aliases(&[], None).text.contains("_No aliases yet._"));
+    }
+
+    #[test]
+    fn session_summary_renders_digest() {
+        let stdout = r#"{"namespace": "loom-zed/main", "started_at": 1000, "tasks_completed": 3,
+            "summary": "Reworked **update** flow.", "next_steps": ["Ship v0.9"]}"#;
+        let out = format_session_summary(&mock_result("0", stdout, ""), false, false, 8200);
+        assert!(out.text.starts_with("## 📝 Session Summary"));
+        assert!(out
+            .text
+            .contains("_namespace `loom-zed/main` · started 2h ago (2h 0m) · 3 tasks completed_"));
+        assert!(out.text.contains("Reworked **update** flow."));
+        assert!(out.text.contains("### Next steps\n\n- Ship v0.9\n"));
+        assert!(!out.text.contains("new one was started"));
+
+        let out = format_session_summary(&mock_result("0", "Did things.", ""), false, true, 0);
+        assert!(out.text.contains("Did things.\n\n_The session was ended"));
+
+        let missing = mock_result("1", "", "unknown command \"session-summary\"");
+        let out = format_session_summary(&missing, true, false, 0);
+        assert!(out.text.contains("/loom-summary restart"));
+    }
+
+    #[test]
+    fn context_is_plain_markdown_per_memory() {
+        let stdout = r#"{"results": [
+            {"title": "Auth design", "source": "docs/auth.md", "content": "Tokens rotate daily."},
+            {"text": "Use `cargo xtask` for releases."}
+        ]}"#;
+        let out = format_context(
+            &mock_result("0", stdout, ""),
+            Some("loom-zed"),
+            Some("work"),
+        );
+        assert_eq!(out.sections.len(), 1);
+        assert!(out.text.starts_with("# Project context: loom-zed\n\n"));
+        assert!(out
+            .text
+            .contains("for project `loom-zed`, namespace `work`."));
+        assert!(out
+            .text
+            .contains("## Auth design\n\nSource: docs/auth.md\n\nTokens rotate daily."));
+        assert!(out
+            .text
+            .contains("## Memory\n\nUse `cargo xtask` for releases."));
+        assert!(!out.text.contains("```"));
+
+        let empty = format_context(&mock_result("0", "[]", ""), None, None);
+        assert!(empty.text.contains("No memories matched."));
+    }
+
+    #[test]
+    fn skill_detail_renders_steps_and_examples() {
+        let stdout = r#"{"skill": {
+            "name": "pr-review", "category": "git", "tags": ["review"],
+            "description": "Review a pull request.",
+            "steps": ["Fetch the diff", {"title": "Check tests", "description": "Run them."}],
+            "examples": [{"title": "Basic", "input": "review #12"}, "review HEAD"]
+        }}"#;
+        let out = format_skill_detail(&mock_result("0", stdout, ""), "pr-review");
+        assert!(out.text.starts_with(
+            "## 🧩 Skill: pr-review\n\n_category `git` · tags `review`_\n\nReview a pull request."
+        ));
+        assert!(out
+            .text
+            .contains("### Steps\n\n1. Fetch the diff\n2. **Check tests** — Run them.\n"));
+        assert!(out.text.contains("**Basic**\n\n```\nreview #12\n```"));
+        assert!(out.text.contains("```\nreview HEAD\n```"));
+        assert!(!out.text.contains("\"steps\""));
+
+        let plain = format_skill_detail(&mock_result("0", "# PR review\n\nDo it.", ""), "x");
+        assert!(plain.text.contains("# PR review\n\nDo it."));
+    }
+
+    #[test]
+    fn quota_table_flags_providers_near_limit() {
+        let stdout = r#"{"providers": [
+            {"provider": "openai", "used": 950, "limit": 1000, "reset_in_secs": 600},
+            {"provider": "github", "remaining": 0, "limit": 5000, "reset_at": 1000},
+            {"provider": "tavily", "requests": 12}
+        ]}"#;
+        let out = format_quota(&mock_result("0", stdout, ""), 400);
+        assert!(out
+            .text
+            .contains("- 🟠 **openai** has used 95% of its quota (resets in 10m 0s)\n"));
+        assert!(out
+            .text
+            .contains("- 🔴 **github** is out of quota (resets in 10m 0s)\n"));
+        assert!(out
+            .text
+            .contains("| 🔴 | github | 5000 | 5000 | 0 | 100% | in 10m 0s |\n"));
+        assert!(out.text.contains("| ⚪ | tavily | 12 | — | — | — | — |\n"));
+
+        let missing = mock_result("1", "", "unknown tool: provider_usage");
+        assert!(format_quota(&missing, 0)
+            .text
+            .contains("no `provider_usage` tool"));
+    }
+
+    #[test]
+    fn missing_tool_needs_a_failed_call_naming_the_tool() {
+        assert!(is_missing_tool(&mock_result(
+            "1",
+            "",
+            "Error: tool not found: agent_handoff"
+        )));
+        assert!(is_missing_tool(&mock_result(
+            "2",
+            "unknown tool `agent_inbox`",
+            ""
+        )));
+        assert!(!is_missing_tool(&mock_result(
+            "127",
+            "",
+            "sh: loom: command not found"
+        )));
+        assert!(!is_missing_tool(&mock_result(
+            "1",
+            "",
+            "agent not found: reviewer"
+        )));
+        assert!(!is_missing_tool(&mock_result(
+            "0",
+            "unknown tool mentioned in note",
+            ""
+        )));
+    }
+
+    #[test]
+    fn backup_and_restore_reports() {
+        let out = format_backup(
+            &mock_result("0", "", ""),
+            ".loom/backups/loom-config-20261016-120000.tar.gz",
+            "/w/.loom/backups/loom-config-20261016-120000.tar.gz",
+            Some(3000),
+            BackupMethod::Tarball,
+            true,
+        );
+        assert!(out
+            .text
+            .starts_with("## ✅ Backed up loom configuration to `.loom/backups/"));
+        assert!(out.text.contains("| Size | 3 KB |"));
+        assert!(out.text.contains("no `loom config export`"));
+        assert!(out
+            .text
+            .contains("`/loom-restore .loom/backups/loom-config-20261016-120000.tar.gz`"));
+        assert!(out.text.contains("ignored by git"));
+        assert!(!out.text.contains("⚠️"));
+
+        let custom = format_backup(
+            &mock_result("0", "", ""),
+            "snapshots/loom.yaml",
+            "/w/snapshots/loom.yaml",
+            Some(10),
+            BackupMethod::Export,
+            false,
+        );
+        assert!(custom
+            .text
+            .contains("> ⚠️ `snapshots/loom.yaml` can contain tokens"));
+
+        let failed = format_backup(
+            &mock_result("1", "", "permission denied"),
+            "b.tar.gz",
+            "/w/b.tar.gz",
+            None,
+            BackupMethod::Export,
+            true,
+        );
+        assert!(failed.text.contains("permission denied"));
+
+        let list = format_backup_list(&[("a.tar.gz".into(), 10)], ".loom/backups");
+        assert!(list.text.contains("| `a.tar.gz` | 1 KB |"));
+        assert!(format_backup_list(&[], ".loom/backups")
+            .text
+            .contains("_No backups in `.loom/backups`._"));
+
+        let out = format_restore(&mock_result("0", "", ""), "a.tar.gz", BackupMethod::Export);
+        assert!(out.text.contains("`/loom-restart`"));
+    }
+
+    #[test]
+    fn config_shows_path_and_highlighted_contents() {
+        let show = mock_result("0", "servers:\n  github:\n    enabled: true\n", "");
+        let out = format_config(&show, Some("/home/me/.config/loom/config.yaml"));
+        assert!(out
+            .text
+            .contains("**File**: `/home/me/.config/loom/config.yaml`"));
+        assert!(out.text.contains("```yaml\nservers:"));
+
+        let out = format_config(&mock_result("0", "{\"a\": 1}", ""), None);
+        assert!(out.text.contains("unknown (`loom config path` failed)"));
+        assert!(out.text.contains("```json\n{\"a\": 1}"));
+
+        let toml = mock_result("0", "[servers.github]\nenabled = true\n", "");
+        assert!(format_config(&toml, None).text.contains("```toml"));
+
+        let out = format_config(&mock_result("1", "", "no config found"), None);
+        assert!(out.text.contains("no config found"));
+    }
+
+    #[test]
+    fn tool_rows_from_json_and_table() {
+        let json = r#"{"tools": [
+            {"name": "k8s_pods", "server": "k8s", "description": "List pods", "last_used": 20},
+            {"name": "gh_search", "server": "github", "last_used_at": ""}
+        ]}"#;
+        let rows = parse_tool_rows(json);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].server, "k8s");
+        assert_eq!(rows[0].last_used.as_deref(), Some("00000000000000000020"));
+        assert_eq!(rows[1].last_used, None);
+
+        let table = "NAME       SERVER  DESCRIPTION\nk8s_pods   k8s     List all pods\n";
+        let rows = parse_tool_rows(table);
+        assert_eq!(rows[0].name, "k8s_pods");
+        assert_eq!(rows[0].description, "List all pods");
+        assert!(parse_tool_rows("just some text").is_empty());
+    }
+
+    #[test]
+    fn tools_listing_sorts_and_limits() {
+        let json = r#"[
+            {"name": "c", "server": "a", "last_used": 5},
+            {"name": "a", "server": "b"},
+            {"name": "b", "server": "a", "last_used": 9}
+        ]"#;
+        let result = mock_result("0", json, "");
+        let names = |opts: ToolListOptions| {
+            let text = format_tools_listing(&result, &opts, &[]).text;
+            ["`a`", "`b`", "`c`"]
+                .iter()
+                .filter_map(|n| text.find(n).map(|i| (i, *n)))
+                .collect::<std::collections::BTreeMap<_, _>>()
+                .into_values()
+                .collect::<Vec<_>>()
+        };
+        let opts = |sort, limit| ToolListOptions { sort, limit };
+        assert_eq!(
+            names(opts(Some(ToolSort::Name), None)),
+            ["`a`", "`b`", "`c`"]
+        );
+        assert_eq!(
+            names(opts(Some(ToolSort::Server), None)),
+            ["`b`", "`c`", "`a`"]
+        );
+        assert_eq!(names(opts(Some(ToolSort::Recent), Some(2))), ["`b`", "`c`"]);
+
+        let out = format_tools_listing(&result, &opts(Some(ToolSort::Recent), Some(2)), &[]);
+        assert!(out
+            .text
+            .contains("_Showing 2 of 3 tools, sorted by most recently used._"));
+
+        let raw = mock_result("0", "no tools here", "");
+        assert!(format_tools_listing(&raw, &ToolListOptions::default(), &[])
+            .text
+            .contains("no tools here"));
+    }
+
+    #[test]
+    fn tools_listing_from_json_fields() {
+        let json = r#"{"tools": [
+            {"name": "github__search", "server": "github", "description": "Search code | issues\nacross repos"},
+            {"name": "agent_memory_stats", "server_name": "loom", "desc": "Memory usage"}
+        ]}"#;
+        let out = format_tools_listing(
+            &mock_result("0", json, ""),
+            &ToolListOptions::default(),
+            &[],
+        );
+        assert!(out.text.contains("_2 tools._"));
+        assert!(out
+            .text
+            .contains("| `github__search` | github | Search code \\| issues across repos |\n"));
+        assert!(out
+            .text
+            .contains("| `agent_memory_stats` | loom | Memory usage |\n"));
+    }
+
+    #[test]
+    fn pinned_tools_listed_first() {
+        let json = r#"[
+            {"name": "a_tool", "server": "x", "description": "A"},
+            {"name": "b_tool", "server": "y", "description": "B"},
+            {"name": "c_tool", "server": "z", "description": "C"}
+        ]"#;
+        let result = mock_result("0", json, "");
+        let pinned = vec!["c_tool".to_string(), "gone_tool".to_string()];
+        let out = format_tools_listing(&result, &ToolListOptions::default(), &pinned);
+        assert!(out.text.contains(
+            "### ⭐ Pinned\n\n| Tool | Server | Description |\n| --- | --- | --- |\n\
+             | ⭐ `c_tool` | z | C |\n\n_Pinned but not in the current listing: `gone_tool`._"
+        ));
+        assert_eq!(out.text.matches("`c_tool`").count(), 1);
+        assert!(out.text.contains("_3 tools._"));
+
+        let out = format_favorites(&pinned, Some(&result), Some(("added", "c_tool")));
+        assert!(out.text.starts_with("## ⭐ Added `c_tool`"));
+        assert!(format_favorites(&[], None, None)
+            .text
+            .contains("_No tools pinned._"));
+    }
+
+    #[test]
+    fn memory_id_parsing() {
+        assert_eq!(
+            parse_memory_id(r#"{"id": "mem_1"}"#).as_deref(),
+            Some("mem_1")
+        );
+        assert_eq!(
+            parse_memory_id(r#"{"entry": {"entry_id": 42}}"#).as_deref(),
+            Some("42")
+        );
+        assert_eq!(
+            parse_memory_id("Stored memory entry mem_abc.").as_deref(),
+            Some("mem_abc")
+        );
+        assert_eq!(parse_memory_id("ok\nID: `m-9`\n").as_deref(), Some("m-9"));
+        assert_eq!(parse_memory_id("done"), None);
+    }
+
+    #[test]
+    fn memory_store_confirms_id() {
+        let out = format_memory_store(
+            &mock_result("0", r#"{"id": "mem_7"}"#, ""),
+            "Auth uses | pipes",
+        );
+        assert!(out.text.contains("## 🧠 Memory Stored"));
+        assert!(out.text.contains("| ID | `mem_7` |"));
+        assert!(out.text.contains("Auth uses \\| pipes"));
+
+        let out = format_memory_store(&mock_result("0", "ok", ""), "x");
+        assert!(out.text.contains("unknown (see output below)"));
+        assert!(out.text.contains("```\nok\n```"));
+    }
+
+    #[test]
+    fn paths_table() {
+        let out = format_paths(&[
+            PathEntry {
+                label: "Extension work dir".into(),
+                path: Some("/data/loom-zed".into()),
+                note: String::new(),
+            },
+            PathEntry {
+                label: "Log file".into(),
+                path: None,
+                note: "a | b".into(),
+            },
+        ]);
+        assert!(out
+            .text
+            .contains("| Extension work dir | `/data/loom-zed` |  |"));
+        assert!(out.text.contains("| Log file | — | a \\| b |"));
+        assert_eq!(out.sections.len(), 2);
+    }
+
+    #[test]
+    fn memory_delete_reports_entries() {
+        let out = format_memory_delete(
+            &mock_result("0", r#"{"deleted": ["m1", "m2"]}"#, ""),
+            "auth",
+        );
+        assert!(out.text.contains("Deleted **2** entries."));
+        assert!(out.text.contains("- `m2`"));
+
+        let out = format_memory_delete(&mock_result("0", r#"{"deleted": 1}"#, ""), "m1");
+        assert!(out.text.contains("Deleted **1** entry."));
+
+        let out = format_memory_delete(&mock_result("0", r#"{"count": 0}"#, ""), "nope");
+        assert!(out.text.contains("nothing was deleted"));
+
+        let out = format_memory_delete(&mock_result("0", "removed m1", ""), "m1");
+        assert!(out.text.contains("```\nremoved m1\n```"));
+    }
+
+    #[test]
+    fn agents_table_marks_own_agent() {
+        let stdout = r#"{"agents": [
+            {"agent_id": "zed-loom", "last_heartbeat": "2026-03-01T10:00:00Z", "status": "active"},
+            {"id": "vscode", "status": "stale"}
+        ]}"#;
+        let out = format_agents(&mock_result("0", stdout, ""), "zed-loom");
+        assert!(out.text.contains("**2 agents, 1 active**"));
+        assert!(out
+            .text
+            .contains("| `zed-loom` (this editor) | 2026-03-01T10:00:00Z | 🟢 active |"));
+        assert!(out.text.contains("| `vscode` | never | ⚪ stale |"));
+
+        let out = format_agents(&mock_result("0", "[]", ""), "zed-loom");
+        assert!(out.text.contains("No agents are registered"));
+
+        let out = format_agents(&mock_result("0", "ID  STATUS\nzed-loom  active", ""), "x");
+        assert!(out.text.contains("zed-loom"));
+    }
+
+    #[test]
+    fn metrics_tables() {
+        let stdout = r#"{
+            "requests_total": 200, "errors_total": 4, "uptime_secs": 3725,
+            "servers": [
+                {"name": "github", "requests": 150, "errors": 0, "p50_ms": 42.4, "p95_ms": 180},
+                {"name": "k8s", "requests": 50, "errors": 4}
+            ]
+        }"#;
+        let out = format_metrics(&mock_result("0", stdout, ""));
+        assert!(out.text.contains("| Requests | 200 |"));
+        assert!(out.text.contains("| Error rate | 2.0% |"));
+        assert!(out.text.contains("| Uptime | 1h 2m |"));
+        assert!(out
+            .text
+            .contains("| github | 150 | 0 | 0.0% | 42 ms | 180 ms |"));
+        assert!(out.text.contains("| k8s | 50 | 4 | 8.0% ⚠️ | — | — |"));
+
+        let map = r#"{"servers": {"slack": {"calls": 0}}}"#;
+        let out = format_metrics(&mock_result("0", map, ""));
+        assert!(out.text.contains("| slack | 0 | — | 0% |"));
+        assert!(out.text.contains("| Requests | — |"));
+
+        let out = format_metrics(&mock_result("0", "requests: 5", ""));
+        assert!(out.text.contains("requests: 5"));
+    }
+
+    #[test]
+    fn events_timeline_newest_first() {
+        let now = 1_767_225_600 + 3600;
+        let stdout = r#"{"events": [
+            {"type": "server_connected", "server": "github", "timestamp": "2026-01-01T00:00:00Z"},
+            {"type": "sync.completed", "platform": "zed", "message": "3 files", "ts": 1767228600},
+            {"type": "tool_registered", "tool": "k8s_pods", "time": "2026-01-01T02:00:00+02:00"},
+            {"type": "server_error", "message": "exit 1"}
+        ]}"#;
+        let out = format_events(&mock_result("0", stdout, ""), now);
+        assert!(out.text.contains("**4 events**"));
+        let sync = out
+            .text
+            .find("- **10m ago** 🔄 sync completed `zed` — 3 files")
+            .unwrap();
+        let connected = out
+            .text
+            .find("- **1h ago** 🟢 server connected `github`")
+            .unwrap();
+        let tool = out
+            .text
+            .find("- **1h ago** 🧰 tool registered `k8s_pods`")
+            .unwrap();
+        let unknown = out
+            .text
+            .find("- **unknown time** ❌ server error — exit 1")
+            .unwrap();
+        assert!(sync < connected && connected < tool && tool < unknown);
+
+        let out = format_events(&mock_result("0", "[]", ""), now);
+        assert!(out.text.contains("No recent daemon events."));
+        let out = format_events(&mock_result("0", "12:00 server connected", ""), now);
+        assert!(out.text.contains("server connected"));
+    }
+
+    #[test]
+    fn audit_table_filters_by_keyword() {
+        let now = 1_767_225_600;
+        let stdout = r#"{"entries": [
+            {"timestamp": 1767225540, "actor": "zed-loom", "action": "tool.call", "target": "github_search", "result": "ok"},
+            {"timestamp": 1767225300, "actor": "cli", "action": "secret.read", "secret": "GITHUB_TOKEN", "status": "denied"},
+            {"timestamp": 1767225590, "user": "ops", "action": "config.change", "detail": "profile a|b"}
+        ]}"#;
+        let out = format_audit(&mock_result("0", stdout, ""), None, now);
+        assert!(out.text.contains("**3 entries**"));
+        let config = out
+            .text
+            .find("| ops | config.change | profile a\\|b | — |")
+            .unwrap();
+        let call = out
+            .text
+            .find("| zed-loom | tool.call | `github_search` | ok |")
+            .unwrap();
+        assert!(config < call);
+
+        let out = format_audit(&mock_result("0", stdout, ""), Some("SECRET"), now);
+        assert!(out.text.contains("## 🛡️ Audit Log: \"SECRET\""));
+        assert!(out.text.contains("**1 of 3 entries match**"));
+        assert!(out
+            .text
+            .contains("| cli | secret.read | `GITHUB_TOKEN` | denied |"));
+
+        let out = format_audit(&mock_result("0", stdout, ""), Some("nothing"), now);
+        assert!(out.text.contains("No audit entries match."));
+
+        let text = "10:00 cli secret.read GITHUB_TOKEN\n10:01 zed tool.call x\n";
+        let out = format_audit(&mock_result("0", text, ""), Some("tool"), now);
+        assert!(out.text.contains("10:01 zed tool.call x"));
+        assert!(!out.text.contains("GITHUB_TOKEN"));
     }
 
-    FormattedOutput { text, sections }
-}
+    #[test]
+    fn inbox_lists_oldest_first() {
+        let now = 1_767_225_600;
+        let stdout = r#"{"messages": [
+            {"id": "m2", "from_agent": "cli", "body": "Second\nmore", "sent_at": 1767225500, "read": true},
+            {"id": "m1", "from": "vscode-loom", "subject": "Auth | retries",
+             "body": "Pick up the retry work", "namespace": "repo/x", "created_at": "2025-12-31T23:00:00Z"}
+        ]}"#;
+        let out = format_inbox(&mock_result("0", stdout, ""), "zed-loom", now);
+        assert!(out.text.contains("**2 messages, 1 unread**"));
+        let first = out
+            .text
+            .find("| 🔵 | `m1` | `vscode-loom` | 1h ago | Auth \\| retries |")
+            .unwrap();
+        let second = out
+            .text
+            .find("|  | `m2` | `cli` | 1m ago | Second |")
+            .unwrap();
+        assert!(first < second);
 
-/// Format skills listing.
-pub(crate) fn format_skills(result: &CommandResult) -> FormattedOutput {
-    format_generic(result, "Loom Skills")
-}
+        let out = format_inbox(&mock_result("0", "[]", ""), "zed-loom", now);
+        assert!(out.text.contains("No messages."));
 
-/// Format search results.
-pub(crate) fn format_search(result: &CommandResult) -> FormattedOutput {
-    let mut text = String::new();
-    let mut sections = Vec::new();
+        let one = r#"{"message": {"id": "m1", "from": "cli", "body": "Do X", "namespace": "ns"}}"#;
+        let out = format_inbox_message(&mock_result("0", one, ""), "m1", now);
+        assert!(out.text.contains("## ✉️ Do X"));
+        assert!(out.text.contains("**Namespace**: `ns`"));
+        let out = format_inbox_message(&mock_result("1", "", "no such message"), "m9", now);
+        assert!(out.text.contains("no such message"));
+    }
 
-    push_section(
-        &mut text,
-        &mut sections,
-        "Search",
-        "## 🔍 Search Results\n\n",
-    );
+    #[test]
+    fn drift_after_profile_switch() {
+        let known = ["zed", "vscode", "claude", "codex"];
+        let json = r#"{"platforms": [{"platform": "zed", "in_sync": true},
+            {"platform": "Claude", "status": "drift"}, {"name": "vscode", "drift": true}]}"#;
+        assert_eq!(drifted_platforms(json, &known), vec!["vscode", "claude"]);
+        let map = r#"{"codex": "pending", "zed": "ok"}"#;
+        assert_eq!(drifted_platforms(map, &known), vec!["codex"]);
+        let text = "zed       in sync\nvscode    ⚠ out of sync (2 pending)\nclaude  ok\n";
+        assert_eq!(drifted_platforms(text, &known), vec!["vscode"]);
 
-    if !result.stdout.trim().is_empty() {
-        push_section(
-            &mut text,
-            &mut sections,
-            "Results",
-            &format!("{}\n\n", result.stdout.trim()),
+        let mut out = format_profile(&mock_result("0", "switched", ""), "switch");
+        out.append_section(
+            "Sync",
+            &sync_suggestions("work", &["vscode".into(), "claude".into()]),
         );
+        assert!(out.text.contains("left 2 platform configs out of sync"));
+        assert!(out
+            .text
+            .contains("- `/loom-sync vscode`\n- `/loom-sync claude`"));
+        assert!(out
+            .text
+            .contains("Or all at once: `/loom-sync vscode claude`"));
+        assert_eq!(out.sections.last().unwrap().label, "Sync");
     }
 
-    if !result.stderr.trim().is_empty() && !result.success() {
-        push_section(
-            &mut text,
-            &mut sections,
-            "Errors",
-            &format!("```\n{}\n```\n\n", result.stderr.trim()),
-        );
+    #[test]
+    fn localized_labels() {
+        crate::i18n::configure("de", &Default::default());
+        let out = format_generic(&mock_result("1", "out", "err"), "Loom Inbox");
+        crate::i18n::configure("en", &Default::default());
+        let labels: Vec<&str> = out.sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["Loom Inbox", "Ausgabe", "Fehler"]);
+        assert!(out.text.contains("**Exit-Code**: `1`"));
     }
 
-    FormattedOutput { text, sections }
-}
+    #[test]
+    fn json_result_embeds_parsed_payloads() {
+        let runs = vec![
+            RecordedRun {
+                args: vec!["status".into()],
+                exit_code: "0".into(),
+                stdout: r#"{"daemon": "running"}"#.into(),
+                stderr: String::new(),
+            },
+            RecordedRun {
+                args: vec!["servers".into(), "list".into()],
+                exit_code: "1".into(),
+                stdout: "plain text".into(),
+                stderr: "boom".into(),
+            },
+        ];
+        let out = format_json_result("loom-dashboard", &[], &runs, 42, None);
+        assert!(out.text.starts_with("```json\n"));
+        let body = out
+            .text
+            .trim()
+            .trim_start_matches("```json")
+            .trim_end_matches("```");
+        let doc: zed::serde_json::Value = zed::serde_json::from_str(body).unwrap();
+        assert_eq!(doc["ok"], false);
+        assert_eq!(doc["exit_code"], 1);
+        assert_eq!(doc["duration_ms"], 42);
+        assert_eq!(doc["runs"][0]["payload"]["daemon"], "running");
+        assert_eq!(doc["runs"][1]["payload"], "plain text");
+        assert!(doc["error"].is_null());
 
-/// Format profile command output.
-pub(crate) fn format_profile(result: &CommandResult, sub: &str) -> FormattedOutput {
-    let title = match sub {
-        "list" => "Profiles",
-        "switch" => "Profile Switched",
-        _ => "Current Profile",
-    };
-    format_generic(result, title)
-}
+        let out = format_json_result("loom-call", &["x".into()], &[], 0, Some("bad args"));
+        assert!(out.text.contains("\"error\": \"bad args\""));
+        assert!(out.text.contains("\"exit_code\": null"));
+    }
 
-/// Format generic tool call output.
-pub(crate) fn format_tool_call(result: &CommandResult, tool_name: &str) -> FormattedOutput {
-    let icon = status_icon(result.success());
-    let mut text = String::new();
-    let mut sections = Vec::new();
+    #[test]
+    fn retry_reports_fix_and_rerun() {
+        let finding = Finding {
+            id: "daemon-down",
+            title: "Daemon not running".into(),
+            severity: Severity::Critical,
+            evidence: Some("connection refused".into()),
+            remedy: "Run `/loom-start` to start the daemon.".into(),
+        };
+        let mut rerun = FormattedOutput::plain(String::new());
+        rerun.append_section("Status", "## ✅ Loom Status\n\n");
+        let applied = RetryRemediation::Applied {
+            finding: &finding,
+            action: "start the daemon (`loom start`)",
+            outcome: Ok("`loom start`: daemon started".into()),
+        };
+        let args = vec!["verbose".to_string()];
+        let out = format_retry("loom-status", &args, &applied, Some(Ok(rerun)));
+        assert!(out.text.contains("## 🔁 Retry `/loom-status verbose`"));
+        assert!(out.text.contains("diagnosed **Daemon not running**"));
+        assert!(out.text.contains("Evidence: `connection refused`"));
+        let last = out.sections.last().unwrap();
+        assert_eq!(
+            &out.text[last.range.start as usize..last.range.end as usize],
+            "## ✅ Loom Status\n\n"
+        );
 
-    push_section(
-        &mut text,
-        &mut sections,
-        tool_name,
-        &format!("## {} Tool: `{}`\n\n", icon, tool_name),
-    );
+        let failed = RetryRemediation::Applied {
+            finding: &finding,
+            action: "start the daemon (`loom start`)",
+            outcome: Err("`loom start` failed (exit 1): port in use".into()),
+        };
+        let out = format_retry("loom-status", &[], &failed, None);
+        assert!(out.text.contains("The command was not re-run."));
+        assert!(!out.text.contains("Re-run**"));
 
-    if !result.stdout.trim().is_empty() {
-        push_section(
-            &mut text,
-            &mut sections,
-            "Output",
-            &format!("```json\n{}\n```\n\n", result.stdout.trim()),
+        let out = format_retry(
+            "loom-call",
+            &[],
+            &RetryRemediation::None,
+            Some(Err("usage".into())),
         );
+        assert!(out.text.contains("no known failure signature"));
+        assert!(out.text.contains("still failing: usage"));
     }
 
-    if !result.stderr.trim().is_empty() && !result.success() {
-        push_section(
-            &mut text,
-            &mut sections,
-            "Errors",
-            &format!("```\n{}\n```\n\n", result.stderr.trim()),
-        );
+    #[test]
+    fn schema_violations_table() {
+        let violations = vec![
+            SchemaViolation {
+                path: "query".into(),
+                message: "required field is missing".into(),
+            },
+            SchemaViolation {
+                path: String::new(),
+                message: "expected object, got array".into(),
+            },
+        ];
+        let out = format_schema_violations("memory_search", &violations);
+        assert!(out
+            .text
+            .contains("## ❌ Invalid arguments for `memory_search`"));
+        assert!(out.text.contains("2 problems found"));
+        assert!(out.text.contains("| `query` | required field is missing |"));
+        assert!(out
+            .text
+            .contains("| _(arguments)_ | expected object, got array |"));
+        assert!(out.text.contains("/loom-tools describe memory_search"));
     }
 
-    FormattedOutput { text, sections }
-}
-
-/// Format composite dashboard output from multiple command results.
-pub(crate) fn format_dashboard(parts: &[(&str, &CommandResult)]) -> FormattedOutput {
-    let mut text = String::new();
-    let mut sections = Vec::new();
+    #[test]
+    fn tool_describe_parameter_table() {
+        let stdout = r#"{"name": "github__search_code", "server": "github",
+            "description": "Search code across repositories.",
+            "inputSchema": {"type": "object", "required": ["query"], "properties": {
+                "sort": {"type": "string", "enum": ["indexed", "best"], "default": "best"},
+                "query": {"type": "string", "description": "Search | query"},
+                "repos": {"type": "array", "items": {"type": "string"}},
+                "page": {"type": "object", "properties": {"size": {"type": ["integer", "null"]}}}
+            }}}"#;
+        let out = format_tool_describe(&mock_result("0", stdout, ""), "github__search_code");
+        assert!(out.text.contains("**Server**: `github`"));
+        let query = out
+            .text
+            .find("| `query` | string | yes | Search \\| query |")
+            .unwrap();
+        let page = out.text.find("| `page` | object |").unwrap();
+        assert!(query < page);
+        assert!(out.text.contains("| `page.size` | integer \\| null |"));
+        assert!(out.text.contains("| `repos` | string[] |"));
+        assert!(out
+            .text
+            .contains("One of: `\"indexed\"`, `\"best\"`. Default: `\"best\"`."));
+        assert!(out
+            .text
+            .contains("Try it: `/loom-call github__search_code {\"query\":\"<string>\"}`"));
 
-    push_section(
-        &mut text,
-        &mut sections,
-        "Dashboard",
-        "## 📊 Loom Dashboard\n\n",
-    );
+        let listing = r#"{"tools": [{"name": "a", "inputSchema": {"type": "object"}}]}"#;
+        let out = format_tool_describe(&mock_result("0", listing, ""), "a");
+        assert!(out.text.contains("This tool takes no arguments."));
+        let out = format_tool_describe(&mock_result("0", listing, ""), "b");
+        assert!(out.text.contains("No input schema found for `b`"));
+    }
 
-    for (label, result) in parts {
-        let icon = status_icon(result.success());
-        push_section(
-            &mut text,
-            &mut sections,
-            label,
-            &format!(
-                "### {} {}\n\n```\n{}\n```\n\n",
-                icon,
-                label,
-                if result.stdout.trim().is_empty() {
-                    result.stderr.trim()
-                } else {
-                    result.stdout.trim()
+    #[test]
+    fn servers_with_tool_counts() {
+        let servers: BTreeSet<String> = ["github", "slack"].iter().map(|s| s.to_string()).collect();
+        let counts = BTreeMap::from([
+            (
+                "github".to_string(),
+                ServerTools {
+                    total: 12,
+                    enabled: 10,
                 },
             ),
-        );
+            (
+                "k8s".to_string(),
+                ServerTools {
+                    total: 3,
+                    enabled: 0,
+                },
+            ),
+        ]);
+        let table = server_tool_counts_table(&servers, &counts);
+        assert!(table.contains("| `github` | 12 | 10 |"));
+        assert!(table.contains("| `k8s` ⚠️ | 3 | 0 |"));
+        assert!(table.contains("| `slack` ⚠️ | 0 | 0 |"));
+        assert!(table.contains("⚠️ 2 servers contribute no enabled tools."));
     }
 
-    FormattedOutput { text, sections }
-}
+    #[test]
+    fn server_action_shows_new_state() {
+        let state = parse_server_state(
+            r#"{"server": {"name": "github", "disabled": true, "status": "stopped", "tools": []}}"#,
+        )
+        .unwrap();
+        assert_eq!(state.enabled, Some(false));
+        let out =
+            format_server_action(&mock_result("0", "", ""), "disable", "github", Some(&state));
+        assert!(out.text.contains("## ✅ Server `github` disabled"));
+        assert!(out
+            .text
+            .contains("| Enabled | no |\n| Status | stopped |\n| Tools | 0 |"));
 
-// ---------------------------------------------------------------------------
-// Helpers
-// ---------------------------------------------------------------------------
+        let state = parse_server_state("name: k8s\nenabled: true\nstatus: running\n").unwrap();
+        assert_eq!(state.status.as_deref(), Some("running"));
+        assert!(parse_server_state("nothing useful").is_none());
 
-fn capitalize(s: &str) -> String {
-    let mut c = s.chars();
-    match c.next() {
-        None => String::new(),
-        Some(first) => {
-            let upper: String = first.to_uppercase().collect();
-            upper + c.as_str()
-        }
+        let out = format_server_action(&mock_result("0", "", ""), "restart", "k8s", None);
+        assert!(out.text.contains("run `/loom-server k8s` to check"));
+        let out =
+            format_server_action(&mock_result("1", "", "no such server"), "enable", "x", None);
+        assert!(out.text.contains("Failed to enable `x`"));
+        assert!(out.text.contains("no such server"));
     }
-}
 
-/// Heuristic: output looks tabular if most non-empty lines have 2+ whitespace-separated columns.
-fn looks_tabular(s: &str) -> bool {
-    let lines: Vec<&str> = s.lines().filter(|l| !l.trim().is_empty()).collect();
-    if lines.len() < 2 {
-        return false;
+    #[test]
+    fn server_detail_table_and_errors() {
+        let stdout = r#"{"server": {"name": "github", "transport": "stdio",
+            "command": "github-mcp", "status": "healthy", "tools": ["a", "b", "c"],
+            "recent_errors": ["e1", "e2", "e3", "e4", "e5",
+                              {"message": "rate limited", "time": "12:00"}]}}"#;
+        let out = format_server_detail(&mock_result("0", stdout, ""), "github");
+        assert!(out.text.contains("## ✅ Server `github`"));
+        assert!(out.text.contains("| Transport | stdio |"));
+        assert!(out.text.contains("| Endpoint | `github-mcp` |"));
+        assert!(out.text.contains("| Tools | 3 |"));
+        assert!(out.text.contains("| Last seen | — |"));
+        assert!(out
+            .text
+            .contains("### Recent errors (6)\n\n- 12:00 — rate limited\n"));
+        assert!(out.text.contains("- …and 1 older"));
+        assert!(!out.text.contains("- e1\n"));
+
+        let stdout = r#"{"name": "k8s", "type": "sse", "health": "degraded", "tool_count": 0}"#;
+        let out = format_server_detail(&mock_result("0", stdout, ""), "k8s");
+        assert!(out.text.contains("## ❌ Server `k8s`"));
+        assert!(out.text.contains("No recent errors."));
+
+        let out = format_server_detail(&mock_result("1", "", "server not found"), "nope");
+        assert!(out.text.contains("server not found"));
     }
-    let multi_col = lines
-        .iter()
-        .filter(|l| l.split_whitespace().count() >= 2)
-        .count();
-    multi_col * 2 >= lines.len()
-}
 
-/// Best-effort conversion of whitespace-aligned CLI output to a Markdown table.
-fn to_markdown_table(s: &str) -> String {
-    let lines: Vec<&str> = s.lines().filter(|l| !l.trim().is_empty()).collect();
-    if lines.is_empty() {
-        return String::new();
+    #[test]
+    fn namespace_table_with_counts() {
+        let stats = r#"{"namespaces": {"repo/a": {"memories": 12, "sessions": 3},
+                                        "repo/b": {"entry_count": 4}}}"#;
+        let mut counts = parse_namespace_counts(stats);
+        assert_eq!(counts["repo/b"].memories, Some(4));
+        let sessions = count_sessions_by_namespace(
+            r#"{"sessions": [{"namespace": "repo/b"}, {"namespace": "repo/b"}, {"id": "x"}]}"#,
+        );
+        assert_eq!(sessions["repo/b"], 2);
+        counts.get_mut("repo/b").unwrap().sessions = Some(sessions["repo/b"]);
+
+        let names: BTreeSet<String> = ["repo/a", "scratch"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let list = mock_result("0", r#"["repo/a", "scratch"]"#, "");
+        let out = format_namespaces(&list, &names, &counts, Some("repo/a"));
+        assert!(out.text.contains("**3 namespaces** · active: `repo/a`"));
+        assert!(out.text.contains("| ▶ | `repo/a` | 12 | 3 |"));
+        assert!(out.text.contains("|  | `repo/b` | 4 | 2 |"));
+        assert!(out.text.contains("|  | `scratch` | — | — |"));
+
+        let failed = mock_result("1", "", "unknown command");
+        let out = format_namespaces(&failed, &BTreeSet::new(), &BTreeMap::new(), None);
+        assert!(out.text.contains("unknown command"));
     }
 
-    // Use the first line as header.
-    let header_cols: Vec<&str> = lines[0].split_whitespace().collect();
-    let ncols = header_cols.len();
-    if ncols == 0 {
-        return format!("```\n{}\n```", s);
+    #[test]
+    fn export_document_and_confirmation() {
+        let doc = export_markdown("status", "2026-01-01", "## Status\n\nok\n\n");
+        assert!(doc
+            .starts_with("# Loom status report\n\n_Exported from `/loom-status` on 2026-01-01._"));
+        assert!(doc.ends_with("ok\n"));
+
+        let report = format_status_report(&mock_result("0", "daemon: running", ""));
+        let out = format_export("status", "loom-report.md", "/w/loom-report.md", 42, &report);
+        assert!(out
+            .text
+            .contains("Exported `/loom-status` to `loom-report.md`"));
+        assert!(out.text.contains("| Path | `/w/loom-report.md` |"));
+        assert!(out.text.contains("| Size | 42 bytes |"));
     }
 
-    let mut table = String::new();
-    table.push_str("| ");
-    table.push_str(&header_cols.join(" | "));
-    table.push_str(" |\n|");
-    for _ in 0..ncols {
-        table.push_str(" --- |");
+    #[test]
+    fn history_newest_first() {
+        let entry =
+            |command: &str, args: &[&str], exit: Option<&str>, error: Option<&str>| HistoryEntry {
+                command: command.into(),
+                args: args.iter().map(|s| s.to_string()).collect(),
+                exit_code: exit.map(Into::into),
+                error: error.map(Into::into),
+                started_at: 1_000,
+                duration_ms: 1_250,
+            };
+        let first = entry("loom-status", &[], Some("0"), None);
+        let second = entry(
+            "loom-call",
+            &["echo", "{\"a\": \"x|y\"}"],
+            Some("1"),
+            Some("boom"),
+        );
+        let third = entry("loom-help", &[], None, None);
+        let out = format_history(&[&first, &second, &third], 7, 1_030);
+        assert!(out
+            .text
+            .contains("**Showing 3 of 7 commands this session**"));
+        let help = out
+            .text
+            .find("| ✅ | `/loom-help` | — | 1.2s | 30s ago |")
+            .unwrap();
+        let call = out
+            .text
+            .find("| ❌ | `/loom-call echo {\"a\": \"x\\|y\"}` | 1 |")
+            .unwrap();
+        let status = out.text.find("| ✅ | `/loom-status` | 0 |").unwrap();
+        assert!(help < call && call < status);
+        assert!(out.text.contains("### Errors"));
+        assert!(out.text.contains(": boom"));
+
+        let out = format_history(&[], 0, 0);
+        assert!(out.text.contains("No slash commands have run yet"));
     }
-    table.push('\n');
 
-    for line in &lines[1..] {
-        let cols: Vec<&str> = line.splitn(ncols, char::is_whitespace).collect();
-        let cols: Vec<&str> = cols.iter().map(|c| c.trim()).collect();
-        table.push_str("| ");
-        // Pad to ncols if needed.
-        let mut row = Vec::with_capacity(ncols);
-        for i in 0..ncols {
-            row.push(cols.get(i).copied().unwrap_or(""));
-        }
-        table.push_str(&row.join(" | "));
-        table.push_str(" |\n");
+    #[test]
+    fn rfc3339_and_relative_times() {
+        assert_eq!(parse_rfc3339("2026-01-01T00:00:00Z"), Some(1_767_225_600));
+        assert_eq!(
+            parse_rfc3339("2026-01-01T00:00:30.123-01:00"),
+            Some(1_767_225_600 + 3630)
+        );
+        assert_eq!(parse_rfc3339("2000-02-29 00:00:00"), Some(951_782_400));
+        assert_eq!(parse_rfc3339("yesterday"), None);
+        assert_eq!(
+            event_time(&zed::serde_json::json!(1_767_225_600_000u64)),
+            Some(1_767_225_600)
+        );
+        assert_eq!(relative_time(100, 102), "just now");
+        assert_eq!(relative_time(100, 145), "45s ago");
+        assert_eq!(relative_time(0, 2 * 86_400), "2d ago");
     }
 
-    table
-}
+    #[test]
+    fn prompt_recipes_list_and_preview() {
+        let stdout = r#"{"prompts": [
+            {"name": "loom_zed__triage_ci", "description": "Triage CI | fix",
+             "arguments": [{"name": "link", "description": "Job link", "required": true},
+                           {"name": "symptoms", "description": "Errors"}],
+             "template": "Help me triage CI failures.\n"},
+            {"name": "custom", "template": "Do it."}
+        ]}"#;
+        let recipes = parse_prompt_recipes(stdout).unwrap();
+        assert_eq!(recipes[0].short_name(), "triage_ci");
+        assert_eq!(recipes[1].short_name(), "custom");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let out = format_prompts_list(&recipes, Some("failed to load prompt recipes file"));
+        assert!(out.text.contains("> ⚠️ failed to load prompt recipes file"));
+        assert!(out
+            .text
+            .contains("| `triage_ci` | Triage CI \\| fix | `link`*, `symptoms` |"));
+        assert!(out.text.contains("| `custom` |  | — |"));
 
-    fn mock_result(exit_code: &str, stdout: &str, stderr: &str) -> CommandResult {
-        CommandResult {
-            exit_code: exit_code.to_string(),
-            stdout: stdout.to_string(),
-            stderr: stderr.to_string(),
-        }
+        let out = format_prompt_preview(&recipes[0]);
+        assert!(out.text.contains("## 📝 `triage_ci`"));
+        assert!(out
+            .text
+            .contains("```markdown\nHelp me triage CI failures.\n```"));
+        assert!(out.text.contains("| `link` | yes | Job link |"));
+        assert!(format_prompt_preview(&recipes[1]).sections.len() == 2);
+
+        assert!(parse_prompt_recipes("not json").is_err());
     }
 
     #[test]
-    fn diagnostic_report_success() {
-        let r = mock_result("0", "all checks passed", "");
-        let out = format_diagnostic_report(&r);
-        assert!(out.text.contains("✅"));
-        assert!(out.text.contains("all checks passed"));
-        assert!(!out.sections.is_empty());
+    fn secrets_validation_appends_fixes() {
+        let result = mock_result("1", "✗ GITHUB_TOKEN (env): not set\n", "");
+        let hints = vec![SecretHint {
+            name: "GITHUB_TOKEN".into(),
+            defined_in: vec![".env"],
+            in_shell: false,
+        }];
+        let out = format_secrets_validation(&result, &hints, Some("/bin/bash"));
+        assert!(out.text.contains("GITHUB_TOKEN (env): not set"));
+        assert!(out.text.contains("### 🔑 Fix missing secrets"));
+        assert!(out
+            .text
+            .contains("`export GITHUB_TOKEN=...` to `~/.bashrc`"));
+        assert_eq!(out.sections.last().unwrap().label, "Fix Missing Secrets");
+
+        let out = format_secrets_validation(&result, &[], None);
+        assert!(!out.text.contains("Fix missing secrets"));
     }
 
     #[test]
-    fn diagnostic_report_failure() {
-        let r = mock_result("1", "", "connection refused");
-        let out = format_diagnostic_report(&r);
-        assert!(out.text.contains("❌"));
-        assert!(out.text.contains("connection refused"));
+    fn session_snapshot_and_handoff() {
+        let json = r#"{"session": {"namespace": "repo/loom-zed", "summary": "Fixed retries"}}"#;
+        let snapshot = parse_session_snapshot(json);
+        assert_eq!(snapshot.namespace.as_deref(), Some("repo/loom-zed"));
+        assert_eq!(snapshot.summary.as_deref(), Some("Fixed retries"));
+        let text = parse_session_snapshot("Session: s_1\nNamespace: work\nSummary:\n");
+        assert_eq!(text.namespace.as_deref(), Some("work"));
+        assert_eq!(text.summary, None);
+
+        let out = format_handoff(
+            &mock_result("0", "{}", ""),
+            "vscode-loom",
+            &snapshot,
+            "agent_handoff",
+            true,
+        );
+        assert!(out.text.contains("## 🤝 Handed off to `vscode-loom`"));
+        assert!(out.text.contains("| Namespace | `repo/loom-zed` |"));
+        assert!(out.text.contains("| This session | ended |"));
+        assert!(out.text.contains("### Summary\n\nFixed retries"));
+
+        let out = format_handoff(
+            &mock_result("1", "", "unknown agent"),
+            "x",
+            &snapshot,
+            "agent_handoff",
+            false,
+        );
+        assert!(out.text.contains("unknown agent"));
     }
 
     #[test]
-    fn status_report_sections() {
-        let r = mock_result("0", "daemon running\nservers: 3", "");
-        let out = format_status_report(&r);
-        assert!(out.sections.len() >= 2);
-        assert_eq!(out.sections[0].label, "Status");
+    fn doctor_report_lists_findings() {
+        let probes = vec![
+            Probe {
+                label: "loom check".into(),
+                outcome: Ok(mock_result("0", "✓ fine", "")),
+            },
+            Probe {
+                label: "loom status".into(),
+                outcome: Ok(mock_result("1", "", "connection refused")),
+            },
+        ];
+        let findings = crate::doctor::analyze(&probes, &crate::version::VersionStatus::Unknown);
+        let out = format_doctor("loom", Some("loom v0.9.1\nbuilt today"), &probes, &findings);
+        assert!(out.text.contains("**Version**: `loom v0.9.1`"));
+        assert!(out.text.contains("## ❌ Loom Doctor"));
+        assert!(out.text.contains("| `loom status` | ❌ exit `1` |"));
+        assert!(out.text.contains("**Daemon not running**"));
+        assert!(out.text.contains("Next step: Run `/loom-start`"));
+
+        let out = format_doctor("loom", None, &probes[..1], &[]);
+        assert!(out.text.contains("## ✅ Loom Doctor"));
+        assert!(out.text.contains("No known problems detected."));
     }
 
     #[test]
-    fn sync_report_with_platform() {
-        let r = mock_result("0", "synced 5 servers", "");
-        let out = format_sync_report(&r, Some("zed"));
-        assert!(out.text.contains("Sync: zed"));
+    fn provenance_verifies_checksum() {
+        let manifest = InstallManifest {
+            release_version: "v0.9.1".into(),
+            asset_name: "loom-core_v0.9.1_linux_amd64.tar.gz".into(),
+            download_url: "https://example.com/a.tar.gz".into(),
+            sha256: Some("aa".into()),
+            installed_at_unix_secs: 1_767_225_600,
+        };
+        let text = provenance_markdown(Some("v0.9.1"), Some(&manifest), Some("aa"));
+        assert!(text.contains("| Asset | `loom-core_v0.9.1_linux_amd64.tar.gz` |"));
+        assert!(text.contains("| Source | https://example.com/a.tar.gz |"));
+        assert!(text.contains("| Verify | ✅ matches install |"));
+        assert!(text.contains("| Installed | 2026-01-01 |"));
+
+        let text = provenance_markdown(Some("v0.9.1"), Some(&manifest), Some("bb"));
+        assert!(text.contains("⚠️ modified since install (now `bb`)"));
+        assert!(provenance_markdown(Some("v0.9.1"), None, None).contains("no install manifest"));
+        assert!(provenance_markdown(None, None, None).contains("Not a managed install"));
     }
 
     #[test]
-    fn sync_report_no_platform() {
-        let r = mock_result("0", "all in sync", "");
-        let out = format_sync_report(&r, None);
-        assert!(out.text.contains("Sync Status"));
+    fn cleanup_preview_and_result() {
+        let row = |version: &str, bytes: u64, action| CleanupRow {
+            version: version.into(),
+            bytes,
+            action,
+        };
+        let preview = [
+            row("v0.8.0", 2 * 1024 * 1024, CleanupAction::Remove),
+            row("v0.9.1", 3 * 1024 * 1024, CleanupAction::Keep("in use")),
+        ];
+        let out = format_cleanup(&preview);
+        assert!(out.text.contains("## 🧹 loom-core cleanup (preview)"));
+        assert!(out
+            .text
+            .contains("| v0.8.0 | 2.0 MB | 🗑️ will be removed |"));
+        assert!(out.text.contains("| v0.9.1 | 3.0 MB | kept (in use) |"));
+        assert!(out
+            .text
+            .contains("`/loom-cleanup confirm` to delete 1 release and reclaim 2.0 MB"));
+
+        let done = [
+            row("v0.7.0", 1024 * 1024, CleanupAction::Removed),
+            row("v0.8.0", 1024 * 1024, CleanupAction::Removed),
+            row("v0.9.1", 1024, CleanupAction::Keep("in use")),
+        ];
+        let out = format_cleanup(&done);
+        assert!(out
+            .text
+            .contains("## ✅ Removed 2 old releases, reclaimed 2.0 MB"));
+        assert!(!out.text.contains("confirm"));
+
+        let out = format_cleanup(&[row("v0.9.1", 10, CleanupAction::Keep("newest"))]);
+        assert!(out.text.contains("## ✅ Nothing to clean up"));
+        assert!(out.text.contains("| v0.9.1 | 1 KB | kept (newest) |"));
+        assert!(format_cleanup(&[])
+            .text
+            .contains("No managed loom-core releases are installed."));
+    }
+
+    #[test]
+    fn update_outcomes() {
+        let install = LoomInstall {
+            release_version: "v0.9.1".into(),
+            loom_path: "loom-core/v0.9.1/loom".into(),
+            loomd_path: None,
+            bin_dir: "loom-core/v0.9.1".into(),
+            resolved_at_unix_secs: None,
+            asset_name: "loom-core_v0.9.1_linux_amd64.tar.gz".into(),
+            download_url: "https://example.com/loom-core_v0.9.1_linux_amd64.tar.gz".into(),
+            checksum: Some("ab".repeat(32)),
+            startup_report: None,
+        };
+        let out = format_install(&install, "loom-core/v0.9.1/loom", None, true, true);
+        assert!(out.text.contains("## ✅ loom-core v0.9.1 installed"));
+        assert!(out.text.contains("| Bin dir | `loom-core/v0.9.1` |"));
+        assert!(out.text.contains("/loom-restart"));
+        let out = format_install(&install, "loom", Some("v0.8.0"), false, true);
+        assert!(out
+            .text
+            .contains("selects `v0.8.0`, so commands keep using that release"));
+        assert!(out.text.contains("`\"v0.9.1\"`"));
+        let out = format_install(&install, "/usr/bin/loom", None, true, false);
+        assert!(out.text.contains("currently run `/usr/bin/loom`"));
+
+        let updated = UpdateOutcome::Updated {
+            from: Some("v0.9.0".into()),
+            install: Box::new(install),
+        };
+        let out = format_update(&updated, "loom-core/v0.9.0/loom", true, None);
+        assert!(out
+            .text
+            .contains("## ✅ loom-core updated: v0.9.0 → v0.9.1"));
+        assert!(out.text.contains("/loom-restart"));
+        assert!(!out.text.contains("not the managed install"));
+
+        let out = format_update(&updated, "/usr/local/bin/loom", false, None);
+        assert!(out
+            .text
+            .contains("Slash commands currently run `/usr/local/bin/loom`"));
+
+        let out = format_update(
+            &UpdateOutcome::UpToDate("v0.9.1".into()),
+            "loom",
+            true,
+            None,
+        );
+        assert!(out.text.contains("up to date (v0.9.1)"));
+        let out = format_update(&UpdateOutcome::Pinned("v0.8.0".into()), "loom", true, None);
+        assert!(out.text.contains("pinned to v0.8.0"));
+
+        let available = UpdateOutcome::Available {
+            from: Some("v0.9.0".into()),
+            version: "v0.9.1".into(),
+        };
+        let out = format_update(&available, "loom", true, Some("- Fix proxy reconnects"));
+        assert!(out
+            .text
+            .contains("## ⬆️ loom-core update available: v0.9.0 → v0.9.1"));
+        assert!(out.text.contains("- Fix proxy reconnects"));
+        assert!(out.text.contains("/loom-update confirm"));
+        let labels: Vec<&str> = out.sections.iter().map(|s| s.label.as_str()).collect();
+        assert!(labels.contains(&"Release Notes"));
+        let out = format_update(&available, "loom", true, None);
+        assert!(out.text.contains("couldn't be fetched"));
     }
 
     #[test]
@@ -641,6 +8848,101 @@ mod tests {
         assert!(out.text.contains("`2`"));
     }
 
+    #[test]
+    fn ansi_sequences_are_stripped() {
+        assert_eq!(
+            strip_ansi("\u{1b}[1;32m✓\u{1b}[0m github \u{1b}[2K\u{1b}(Bhealthy"),
+            "✓ github healthy"
+        );
+        assert_eq!(
+            strip_ansi("see \u{1b}]8;;https://loom.dev\u{7}docs\u{1b}]8;;\u{1b}\\ now"),
+            "see docs now"
+        );
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+
+    #[test]
+    fn icons_become_text_and_sections_follow() {
+        let mut out = FormattedOutput::plain(String::new());
+        out.append_section("Status", "## ✅ Status\n\n");
+        out.append_section("Tools", "### 🧰 Tools\n\n| ⚠️ | `github` |\n");
+        let out = out.without_icons();
+        assert_eq!(
+            out.text,
+            "## [OK] Status\n\n### Tools\n\n| [WARN] | `github` |\n"
+        );
+        let tools = &out.sections[1];
+        assert_eq!(
+            &out.text[tools.range.start as usize..tools.range.end as usize],
+            "### Tools\n\n| [WARN] | `github` |\n"
+        );
+    }
+
+    #[test]
+    fn secrets_are_masked_only_in_rendered_output() {
+        let r = mock_result("0", "GITHUB_TOKEN=ghp_0123456789abcdef\n", "");
+        let mut out = FormattedOutput::plain("## Env\n\n".to_string());
+        out.append_output(format_generic(&r, "Env"));
+        assert!(r.stdout.contains("ghp_0123456789abcdef"));
+        let tail = |out: &FormattedOutput| {
+            let last = out.sections.last().unwrap();
+            out.text[last.range.end as usize..].to_string()
+        };
+        let before = tail(&out);
+        let out = out.redacted();
+        assert!(out.text.contains("GITHUB_TOKEN=[redacted]"));
+        assert!(!out.text.contains("ghp_0123"));
+        assert_eq!(tail(&out), before);
+    }
+
+    #[test]
+    fn raw_output_cannot_break_fences_or_tables() {
+        let r = mock_result("1", "", "see ```rust\nfn x() {}\n```");
+        let out = format_generic(&r, "Check");
+        assert!(out
+            .text
+            .contains("````\nsee ```rust\nfn x() {}\n```\n````\n\n"));
+
+        let table = to_markdown_table("NAME NOTE\nweb a|b </details>");
+        assert!(table.contains("| web | a\\|b &lt;/details> |"));
+        assert_eq!(table_cell("line one\r\nline two"), "line one line two");
+    }
+
+    #[test]
+    fn backticks_in_stdout_keep_other_fences_intact() {
+        let failed = mock_result("1", "", "bad config: ```yaml");
+        let out = format_sync_diff(&[("zed", &failed)]);
+        assert!(out.text.contains("````\nbad config: ```yaml\n````\n\n"));
+
+        let audit = mock_result("0", "deploy ran ```rm -rf```\nother", "");
+        let out = format_audit(&audit, Some("deploy"), 0);
+        assert!(out.text.contains("````\ndeploy ran ```rm -rf```\n````\n\n"));
+    }
+
+    #[test]
+    fn long_output_gets_a_summary_section() {
+        let body: Vec<String> = (1..=300)
+            .map(|i| match i {
+                42 | 250 => format!("line {}: ERROR timed out", i),
+                _ => format!("line {}", i),
+            })
+            .collect();
+        let r = mock_result("0", &body.join("\n"), "");
+        let out = format_generic(&r, "Sync");
+        let labels: Vec<_> = out.sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, ["Sync", "Output Summary", "Output"]);
+        assert!(out.text.contains("> 📜 300 lines · ⚠️ 2 mention errors"));
+        assert!(out
+            .text
+            .contains("```\nline 1\nline 2\nline 3\nline 4\nline 5\n```"));
+        assert!(out
+            .text
+            .contains("```\nline 296\nline 297\nline 298\nline 299\nline 300\n```"));
+
+        let short = format_generic(&mock_result("0", "line 1\nline 2", ""), "Sync");
+        assert_eq!(short.sections.len(), 2);
+    }
+
     #[test]
     fn section_ranges_are_contiguous() {
         let r = mock_result("0", "output here", "warning here");
@@ -661,6 +8963,18 @@ mod tests {
         assert_eq!(out.text, "hello");
     }
 
+    #[test]
+    fn prepend_section_shifts_ranges() {
+        let r = mock_result("0", "running", "");
+        let mut out = format_status_report(&r);
+        let first_end = out.sections[0].range.end;
+        out.prepend_section("Notice", "note\n\n");
+        assert!(out.text.starts_with("note\n\n"));
+        assert_eq!(out.sections[0].label, "Notice");
+        assert_eq!(out.sections[0].range.end, 6);
+        assert_eq!(out.sections[1].range.end, first_end + 6);
+    }
+
     #[test]
     fn looks_tabular_detects_tables() {
         assert!(looks_tabular("NAME  STATUS\nfoo   ok\nbar   fail"));
@@ -676,6 +8990,37 @@ mod tests {
         assert!(table.contains("| foo | ok |"));
     }
 
+    #[test]
+    fn to_markdown_table_aligned_columns() {
+        let input = "NAME       STATUS    LAST SEEN     TOOLS\n\
+                     My Server  running   2 hours ago   12\n\
+                     github     stopped   never         0\n";
+        let table = to_markdown_table(input);
+        assert!(
+            table.starts_with("| NAME | STATUS | LAST SEEN | TOOLS |\n| --- | --- | --- | --- |\n")
+        );
+        assert!(table.contains("| My Server | running | 2 hours ago | 12 |"));
+        assert!(table.contains("| github | stopped | never | 0 |"));
+
+        let tabs = to_markdown_table("NAME\tLAST SEEN\nweb\t1 min ago\n");
+        assert!(tabs.contains("| web | 1 min ago |"));
+    }
+
+    #[test]
+    fn raw_runs_are_verbatim() {
+        let runs = vec![RecordedRun {
+            args: vec!["status".into()],
+            exit_code: "1".into(),
+            stdout: "line with ``` fence".into(),
+            stderr: "boom".into(),
+        }];
+        let out = format_raw_runs("loom-status", &runs);
+        assert!(out.text.contains("`loom status`"));
+        assert!(out.text.contains("````\nline with ``` fence\n````"));
+        assert!(out.text.contains("boom"));
+        assert_eq!(out.sections.len(), 2);
+    }
+
     #[test]
     fn capitalize_works() {
         assert_eq!(capitalize("restart"), "Restart");
@@ -693,6 +9038,11 @@ mod tests {
         assert!(out.text.contains("Status"));
         assert!(out.text.contains("Hub"));
         assert!(out.sections.len() >= 3); // dashboard header + 2 parts
+        assert!(out
+            .text
+            .contains("**❌ 1/2 components healthy** — failing: Hub\n\n"));
+        let labels: Vec<_> = out.sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, ["Dashboard", "Hub", "Status"]);
     }
 
     #[test]
@@ -702,6 +9052,56 @@ mod tests {
         assert!(out.text.contains("reachable"));
     }
 
+    #[test]
+    fn queue_list_renders_table() {
+        let r = mock_result("0", "ID STATUS AGE\njob-1 running 2m\njob-2 queued 5s", "");
+        let out = format_queue(&r, "list");
+        assert!(out.text.contains("Daemon Queue"));
+        assert!(out.text.contains("| job-1 | running | 2m |"));
+    }
+
+    #[test]
+    fn workflows_list_and_run() {
+        let listing = r#"{"workflows": [
+            {"name": "release", "description": "Tag | publish", "steps": [{}, {}, {}]},
+            {"name": "triage", "steps": 2}
+        ]}"#;
+        let out = format_workflows(&mock_result("0", listing, ""));
+        assert!(out.text.contains("## ✅ Workflows (2)"));
+        assert!(out.text.contains("| `release` | 3 | Tag \\| publish |"));
+        assert!(out.text.contains("| `triage` | 2 |  |"));
+
+        let run = r#"{"status": "failed", "steps": [
+            {"name": "build", "status": "succeeded", "duration_ms": 1500},
+            {"name": "publish", "status": "failed", "duration_ms": 20, "error": "401 Unauthorized"},
+            {"name": "notify", "status": "skipped"}
+        ]}"#;
+        let out = format_workflow_run(&mock_result("1", run, ""), "release");
+        assert!(out.text.contains("## ❌ Workflow `release`: failed"));
+        assert!(out.text.contains("1/3 steps completed"));
+        assert!(out.text.contains("| 1 | build | ✅ succeeded | 1.5s |"));
+        assert!(out.text.contains("| 2 | publish | ❌ failed | 20ms |"));
+        assert!(out.text.contains("401 Unauthorized"));
+
+        let out = format_workflow_run(&mock_result("0", "step 1/1 ok\n", ""), "triage");
+        assert!(out.text.starts_with("## ✅ Workflow `triage` finished"));
+        assert!(out.text.contains("step 1/1 ok"));
+    }
+
+    #[test]
+    fn queue_list_empty() {
+        let r = mock_result("0", "", "");
+        let out = format_queue(&r, "list");
+        assert!(out.text.contains("No queued or running jobs"));
+    }
+
+    #[test]
+    fn confirmation_required_shows_command() {
+        let out = format_confirmation_required("cancel job `x`", "/loom-queue cancel x confirm");
+        assert!(out.text.contains("Confirmation Required"));
+        assert!(out.text.contains("/loom-queue cancel x confirm"));
+    }
+
     #[test]
     fn ping_failure() {
         let r = mock_result("1", "", "");
@@ -1,70 +1,166 @@
 use crate::format::FormattedOutput;
+use crate::i18n::tr;
+use crate::settings::CommandsSettings;
 
-pub(crate) fn dispatch_help(args: &[String]) -> FormattedOutput {
+/// `/loom-help [command]`; commands blocked by `policy` are left out of the overview.
+pub(crate) fn dispatch_help(args: &[String], policy: Option<&CommandsSettings>) -> FormattedOutput {
     let sub = args.first().map(|s| s.as_str()).unwrap_or("");
+    let blocked = |name: &str| policy.and_then(|p| p.policy_block(name));
 
     if !sub.is_empty() {
+        let name = sub.strip_prefix("loom-").unwrap_or(sub);
+        if let Some(reason) = blocked(name) {
+            return FormattedOutput::plain(format!("{}\n", reason));
+        }
         return command_help(sub);
     }
 
-    let text = r#"## 📖 Loom Commands
+    let rows: Vec<&str> = COMMAND_TABLE
+        .lines()
+        .filter(|row| {
+            let name = row
+                .trim_start_matches("| `/loom-")
+                .split([' ', '`'])
+                .next()
+                .unwrap_or_default();
+            blocked(name).is_none()
+        })
+        .collect();
 
-| Command | Description |
-| --- | --- |
-| `/loom-check` | Run `loom check` diagnostics |
-| `/loom-status` | Show daemon and server status |
-| `/loom-sync [platform]` | Sync config (status, zed, vscode, claude, gemini, codex, antigravity, kilocode) |
+    let text = format!(
+        "## 📖 {}\n\n| {} | {} |\n| --- | --- |\n{}\n{}\n",
+        tr("Loom Commands"),
+        tr("Command"),
+        tr("Description"),
+        rows.join("\n"),
+        tr("Use `/loom-help <command>` for detailed usage. Append `--raw` to any command to see the\nunformatted CLI output, or `--format json` for a machine-readable result."),
+    );
+
+    FormattedOutput::plain(text)
+}
+
+/// One row per slash command for the `/loom-help` overview.
+const COMMAND_TABLE: &str = r#"| `/loom-check` | Run `loom check` diagnostics |
+| `/loom-status [verbose]` | Show daemon and server status (verbose adds PID, memory, CPU) |
+| `/loom-sync [status [platform]\|diff [platform]\|platform...]` | Sync config, show drift, or preview changes (status, diff, all, zed, vscode, claude, gemini, codex, antigravity, kilocode) |
 | `/loom-restart` | Restart the Loom daemon |
+| `/loom-daemon env` | Diff the daemon's environment against the extension's (secrets redacted) |
 | `/loom-start` | Start the Loom daemon |
 | `/loom-stop` | Stop the Loom daemon |
-| `/loom-tools [list\|search <q>]` | List or search available MCP tools |
-| `/loom-servers` | List registered MCP servers |
+| `/loom-tools [list\|search <q>\|describe <tool>\|schema-export]` | List, search, describe, or export MCP tools |
+| `/loom-servers [list\|enable\|disable\|restart <name>]` | List, enable, disable, or restart MCP servers |
+| `/loom-server <name>` | One server's transport, tools, health, and recent errors |
 | `/loom-ping` | Quick health check |
-| `/loom-secrets [list\|validate]` | Manage secrets |
-| `/loom-session [start\|end\|status\|list]` | Agent session management |
-| `/loom-heartbeat` | Send agent heartbeat |
-| `/loom-task [list\|add\|update]` | Agent task management |
-| `/loom-recall <query>` | Recall context from agent memory |
-| `/loom-skills [list\|search\|categories]` | Browse available skills |
-| `/loom-search <query>` | Deep search across sources |
-| `/loom-profile [current\|list\|switch]` | Profile management |
-| `/loom-call <tool> [json]` | Invoke any MCP tool directly |
+| `/loom-health` | Ping the daemon and every registered server |
+| `/loom-benchmark [tool] [runs]` | Measure tool-call latency (min/median/max) |
+| `/loom-secrets [list\|validate\|set]` | Manage secrets |
+| `/loom-session [start\|end\|status\|list\|resume <id>]` | Agent session management |
+| `/loom-summary [restart]` | End-of-day digest of the current session |
+| `/loom-heartbeat [send\|status\|interval <secs>]` | Agent heartbeat |
+| `/loom-agents` | List registered agents and their heartbeats |
+| `/loom-task [list\|add\|update\|done\|remove]` | Agent task management |
+| `/loom-recall <query> [--namespace <ns>] [--limit <n>] [--save]` | Recall context from agent memory |
+| `/loom-context [query]` | Add this project's memories to the conversation |
+| `/loom-memory <text>` | Store an entry in agent memory |
+| `/loom-forget <id\|query> <target> confirm` | Delete agent memory entries |
+| `/loom-handoff <agent-id> [end] [note]` | Hand off this session's context to another agent |
+| `/loom-namespace [list]` | Agent namespaces with memory/session counts |
+| `/loom-inbox [list\|read <id>]` | Messages and handoffs from other agents |
+| `/loom-skills [list\|search <q>\|show <name>\|categories]` | Browse available skills |
+| `/loom-search <query> [--source <name>] [--limit <n>]` | Deep search across sources |
+| `/loom-profile [current\|list\|show\|switch\|create\|delete]` | Profile management |
+| `/loom-call <tool> [json\|@file]` | Invoke any MCP tool directly |
+| `/loom-permissions [list\|allow <tool>\|deny <tool>]` | Show or change which tools may run |
+| `/loom-favorites [list\|add <tool>\|remove <tool>]` | Pin tools to the top of `/loom-tools` |
+| `/loom-alias [list\|<name> [args]\|<name> = <command>\|remove <name>]` | Define and run command shortcuts |
+| `/loom-trace <tool> [json\|@file]` | Call a tool with tracing; show the hop-by-hop timing tree |
 | `/loom-dashboard` | Composite overview dashboard |
+| `/loom-config` | Show the effective Loom configuration and its file |
+| `/loom-backup [path]` | Snapshot the loom config and profiles into the worktree |
+| `/loom-restore [path [confirm]]` | List config backups or restore one |
+| `/loom-metrics` | Show daemon request counts, latency, and error rates |
+| `/loom-quota [provider]` | Upstream API usage, remaining quota, and reset times |
+| `/loom-prompts [recipe]` | List prompt recipes or preview one |
+| `/loom-events [n]` | Show a timeline of recent daemon events |
+| `/loom-doctor` | Diagnose common problems and suggest next steps |
+| `/loom-paths` | Show where the extension keeps its files |
+| `/loom-env` | Show the resolved binary, `PATH`, and env overrides |
+| `/loom-update [confirm]` | Preview the latest release notes, then upgrade the managed loom-core binary |
+| `/loom-install [tag]` | Force a fresh download of loom-core |
+| `/loom-cleanup [confirm]` | Delete old downloaded loom-core releases |
 | `/loom-info` | Show resolved Loom binary and version |
-| `/loom-help [command]` | Show this help or command details |
-
-Use `/loom-help <command>` for detailed usage.
-"#
-    .to_string();
-
-    FormattedOutput::plain(text)
-}
+| `/loom-queue [list\|cancel <id> confirm]` | Show or cancel daemon background jobs |
+| `/loom-kill <server> confirm` | Force-terminate a stuck upstream server |
+| `/loom-last [command]` | Show raw output of the previous command |
+| `/loom-export <dashboard\|status\|check> [path]` | Write a report to a Markdown file in the worktree |
+| `/loom-history [n]` | Recent slash commands with exit codes and durations |
+| `/loom-audit [n] [keyword]` | Daemon audit log: tool calls, secret accesses, config changes |
+| `/loom-workflows [list\|run <name>]` | List loom workflows or run one, showing per-step results |
+| `/loom-retry` | Apply the fix for the last failed command, then re-run it |
+| `/loom-help [command]` | Show this help or command details |"#;
 
 fn command_help(cmd: &str) -> FormattedOutput {
     let text = match cmd {
         "check" => "## `/loom-check`\n\nRun `loom check` and return a diagnostic report.\n\n**Usage**: `/loom-check`\n\nNo arguments required.\n",
-        "status" => "## `/loom-status`\n\nShow Loom daemon and server status.\n\n**Usage**: `/loom-status`\n\nNo arguments required.\n",
-        "sync" => "## `/loom-sync`\n\nRun Loom config sync.\n\n**Usage**:\n- `/loom-sync` — show sync status\n- `/loom-sync status` — show sync status\n- `/loom-sync <platform>` — sync a specific platform (`--regen`)\n\n**Platforms**: zed, vscode, claude, gemini, codex, antigravity, kilocode\n",
+        "status" => "## `/loom-status`\n\nShow Loom daemon and server status.\n\n**Usage**:\n- `/loom-status` — daemon and server status\n- `/loom-status verbose` — also show daemon PID, memory, CPU, and upstream server process count (falls back to `ps` when the CLI doesn't report them)\n",
+        "sync" => "## `/loom-sync`\n\nRun Loom config sync.\n\n**Usage**:\n- `/loom-sync` — show sync status\n- `/loom-sync status` — show sync status\n- `/loom-sync status <platform>` — drift details for one platform (files, last synced, pending changes)\n- `/loom-sync diff [platform]` — preview what a sync would write (`--dry-run`) as a diff, for one platform or all of them\n- `/loom-sync <platform>` — sync a specific platform (`--regen`)\n- `/loom-sync <platform> <platform>...` — sync several platforms and show a combined table\n- `/loom-sync all` — sync every platform\n\n**Platforms**: zed, vscode, claude, gemini, codex, antigravity, kilocode\n",
         "restart" => "## `/loom-restart`\n\nRestart the Loom daemon.\n\n**Usage**: `/loom-restart`\n",
+        "daemon" => "## `/loom-daemon`\n\nInspect the running Loom daemon.\n\n**Usage**:\n- `/loom-daemon env` — compare the environment the daemon was started with against the one the extension passes to loom; variables missing from the daemon usually mean it predates a shell change and needs `/loom-restart`\n\nValues of secret-looking variables (`*_TOKEN`, `*_KEY`, `*PASSWORD*`, ...) are redacted. Uses `loom daemon env` when available, else `/proc/<pid>/environ` on Linux.\n",
         "start" => "## `/loom-start`\n\nStart the Loom daemon.\n\n**Usage**: `/loom-start`\n",
         "stop" => "## `/loom-stop`\n\nStop the Loom daemon.\n\n**Usage**: `/loom-stop`\n",
-        "tools" => "## `/loom-tools`\n\nList or search available MCP tools.\n\n**Usage**:\n- `/loom-tools` — list all tools\n- `/loom-tools list` — list all tools\n- `/loom-tools search <query>` — search by name or description\n",
-        "servers" => "## `/loom-servers`\n\nList registered MCP servers with status.\n\n**Usage**: `/loom-servers`\n",
+        "tools" => "## `/loom-tools`\n\nList or search available MCP tools.\n\n**Usage**:\n- `/loom-tools` — list all tools\n- `/loom-tools list` — list all tools\n- `/loom-tools list sort:name|server|recent limit:<n>` — sort the parsed listing and cap the number of rows\n- `/loom-tools search <query>` — search by name or description\n- `/loom-tools describe <tool>` — show the tool's parameters (types, required, defaults) from its input schema, with a `/loom-call` template\n- `/loom-tools schema-export` — write every tool's JSON schema to `.loom/tools/<tool>.json` in the worktree\n\nTools pinned with `/loom-favorites` are listed first.\n",
+        "server" => "## `/loom-server`\n\nShow details for one MCP server from `loom servers show`: transport, endpoint, health, tool count, and its most recent errors.\n\n**Usage**: `/loom-server <name>` (server names complete from `/loom-servers`)\n",
+        "servers" => "## `/loom-servers`\n\nList registered MCP servers with status, or change one server's state.\n\n**Usage**:\n- `/loom-servers` or `/loom-servers list` — list servers (with changes since the last listing) and how many tools each contributes (total and enabled; servers with none are flagged)\n- `/loom-servers enable <name>` — enable a server\n- `/loom-servers disable <name>` — disable a server\n- `/loom-servers restart <name>` — restart a server\n\nAfter a change the server's new enabled flag, status, and tool count are shown. Server names complete as you type.\n",
         "ping" => "## `/loom-ping`\n\nQuick daemon + hub reachability check.\n\n**Usage**: `/loom-ping`\n",
-        "secrets" => "## `/loom-secrets`\n\nManage secrets.\n\n**Usage**:\n- `/loom-secrets` — list secret names (never values)\n- `/loom-secrets list` — list secret names\n- `/loom-secrets validate` — validate all secrets are set\n",
-        "session" => "## `/loom-session`\n\nAgent session management.\n\n**Usage**:\n- `/loom-session` — show current session\n- `/loom-session status` — show current session\n- `/loom-session start [namespace]` — start a new session\n- `/loom-session end` — end current session\n- `/loom-session list` — list recent sessions\n",
-        "heartbeat" => "## `/loom-heartbeat`\n\nSend an agent heartbeat signal.\n\n**Usage**: `/loom-heartbeat`\n",
-        "task" => "## `/loom-task`\n\nAgent task management.\n\n**Usage**:\n- `/loom-task` — list tasks\n- `/loom-task list` — list tasks\n- `/loom-task add <description>` — add a new task\n- `/loom-task update <id> <status>` — update task status (pending/in_progress/completed)\n",
-        "recall" => "## `/loom-recall`\n\nRecall context from agent memory.\n\n**Usage**: `/loom-recall <query>`\n\nRequires a search query.\n",
-        "skills" => "## `/loom-skills`\n\nBrowse available skills.\n\n**Usage**:\n- `/loom-skills` — list all skills\n- `/loom-skills list` — list all skills\n- `/loom-skills search <query>` — search by keyword\n- `/loom-skills categories` — show categories\n",
-        "search" => "## `/loom-search`\n\nDeep search across configured sources.\n\n**Usage**: `/loom-search <query>`\n\nRequires a search query.\n",
-        "profile" => "## `/loom-profile`\n\nProfile management.\n\n**Usage**:\n- `/loom-profile` — show current profile\n- `/loom-profile current` — show current profile\n- `/loom-profile list` — list all profiles\n- `/loom-profile switch <name>` — switch profile\n",
-        "call" => "## `/loom-call`\n\nInvoke any MCP tool directly.\n\n**Usage**: `/loom-call <tool_name> [json_args]`\n\nExample: `/loom-call agent_memory_recall {\"query\": \"auth\"}`\n",
+        "benchmark" => "## `/loom-benchmark`\n\nCall a tool several times in a row and report the min, median, and max wall-clock latency, to tell a slow daemon or tool from a slow agent.\n\n**Usage**: `/loom-benchmark [tool] [runs]`\n\n- `tool` defaults to `settings.commands.benchmark_tool` (`agent_memory_stats`); pick something cheap and read-only\n- `runs` defaults to 5 (1-50)\n\nTimes include CLI startup. The run stops at the first failed call.\n",
+        "health" => "## `/loom-health`\n\nCheck the daemon, then ping each server from `loom servers list` and show a table of which ones answered and how long each took. Latencies are round trips of `loom servers ping`, so they include CLI startup.\n\n**Usage**: `/loom-health`\n",
+        "secrets" => "## `/loom-secrets`\n\nManage secrets.\n\n**Usage**:\n- `/loom-secrets` — list secret names (never values)\n- `/loom-secrets list` — list secret names\n- `/loom-secrets validate` — validate all secrets are set\n- `/loom-secrets set <KEY> <value>` — store a secret; the value is piped to `loom secrets set <KEY> --stdin` and is redacted from logs, history, and output\n\nFor missing env-backed secrets, `validate` checks the worktree's `.env`/`.envrc` and your shell environment and says where to add each variable.\n",
+        "session" => "## `/loom-session`\n\nAgent session management.\n\n**Usage**:\n- `/loom-session` — show current session\n- `/loom-session status` — show current session\n- `/loom-session start [namespace]` — start a new session\n- `/loom-session end` — end current session\n- `/loom-session list` — list recent sessions\n- `/loom-session resume <session-id>` — reattach to an earlier session (e.g. after Zed crashed) instead of starting over; IDs complete from `session-list`\n",
+        "heartbeat" => "## `/loom-heartbeat`\n\nAgent heartbeat signal.\n\n**Usage**:\n- `/loom-heartbeat` — send a heartbeat now\n- `/loom-heartbeat status` — when the last heartbeat was sent, from which agent, and the auto-heartbeat cadence\n- `/loom-heartbeat interval <secs>` — configure auto-heartbeat cadence (`0` disables, minimum 10)\n",
+        "task" => "## `/loom-task`\n\nAgent task management.\n\n**Usage**:\n- `/loom-task` — list tasks\n- `/loom-task list` — list tasks\n- `/loom-task add <description>` — add a new task\n- `/loom-task update <id> <status>` — update task status (pending/in_progress/completed)\n- `/loom-task done <id>` — mark a task completed\n- `/loom-task remove <id>` — delete a task (asks for `confirm` first)\n\nMissing arguments produce a fill-in-the-blanks template instead of an error.\n",
+        "recall" => "## `/loom-recall`\n\nRecall context from agent memory.\n\n**Usage**:\n- `/loom-recall <query>` — recall matching context\n- `/loom-recall <query> --save` — also pin the results to `.loom/context/<slug>.md` in the worktree\n- `/loom-recall <query> --save=<slug>` — pin under a custom file name\n- `/loom-recall <query> --namespace <ns>` — only search one namespace (names complete as you type)\n- `/loom-recall <query> --limit <n>` — return at most `n` results (1-100)\n\nRequires a search query. Flags may go anywhere and also take the `--flag=value` form.\n",
+        "skills" => "## `/loom-skills`\n\nBrowse available skills.\n\n**Usage**:\n- `/loom-skills` — list all skills\n- `/loom-skills list` — list all skills\n- `/loom-skills search <query>` — search by keyword\n- `/loom-skills show <name>` — one skill's description, steps, and examples\n- `/loom-skills categories` — show categories\n\nSkill names complete after `show`.\n",
+        "search" => "## `/loom-search`\n\nDeep search across configured sources.\n\n**Usage**:\n- `/loom-search <query>` — search every source\n- `/loom-search <query> --source github,docs` — only search the named sources (names complete after `--source`)\n- `/loom-search <query> --limit <n>` — return at most `n` results (1-50)\n\nRequires a search query. Flags may go anywhere and also take the `--flag=value` form. Narrowing the sources makes deep search faster and keeps the output small.\n",
+        "profile" => "## `/loom-profile`\n\nProfile management.\n\n**Usage**:\n- `/loom-profile` — show current profile\n- `/loom-profile current` — show current profile\n- `/loom-profile list` — list all profiles\n- `/loom-profile show <name>` — inspect a profile's servers, tools, and settings without switching\n- `/loom-profile switch <name>` — switch profile, then check `loom sync status` and suggest `/loom-sync` for any platform that drifted\n- `/loom-profile create <name>` — create a new profile\n- `/loom-profile delete <name> confirm` — delete a profile (without `confirm`, only shows what would happen)\n\nProfile names complete as you type.\n",
+        "favorites" => "## `/loom-favorites`\n\nPin MCP tools you use often so `/loom-tools` lists them first.\n\n**Usage**:\n- `/loom-favorites` or `/loom-favorites list` — pinned tools with their server and description\n- `/loom-favorites add <tool>` — pin a tool (checked against `loom tools list`)\n- `/loom-favorites remove <tool>` — unpin it\n\nPins are saved in the extension work dir and shared by every worktree. A pinned tool that disappears from the daemon stays pinned and is flagged as missing.\n",
+        "alias" => "## `/loom-alias`\n\nShort names for commands you type often, such as `st` for `status verbose`.\n\n**Usage**:\n- `/loom-alias` or `/loom-alias list` — defined aliases and where each comes from\n- `/loom-alias st = status verbose` — save an alias (the command may be written `status`, `loom-status`, or `/loom-status`)\n- `/loom-alias st [args...]` — run it; extra args are appended, so `/loom-alias st --raw` works\n- `/loom-alias remove st` — delete a saved alias\n\nSaved aliases live in the extension work dir. Aliases in `settings.commands.aliases` (e.g. `{\"st\": \"status verbose\"}`) take precedence and are removed by editing settings. An alias runs under the target command's `commands.enabled`/`disabled` policy.\n",
+        "permissions" => "## `/loom-permissions`\n\nShow and change loom's tool permissions: which MCP tools agents may call.\n\n**Usage**:\n- `/loom-permissions` or `/loom-permissions list` — allowed and denied tools, grouped, plus the default for unlisted tools\n- `/loom-permissions allow <tool>` — allow a tool\n- `/loom-permissions deny <tool>` — deny a tool\n\nAfter a change the updated lists are shown with the tool marked. Tool names complete as you type.\n",
+        "trace" => "## `/loom-trace`\n\nCall a tool with `loom tools call --trace` and show each hop (hub → server → tool) as a tree with its duration and share of the total, plus the slowest hop or the one that failed.\n\n**Usage**: `/loom-trace <tool_name> [json_args]` — arguments work as in `/loom-call` (relaxed JSON, `@args.json`, `\"@file:path\"` values). Tool names complete as you type.\n\nIf the CLI prints no structured trace, its raw output is shown instead.\n",
+        "call" => "## `/loom-call`\n\nInvoke any MCP tool directly.\n\n**Usage**:\n- `/loom-call <tool_name> [json_args]`\n- `/loom-call <tool_name> @path/to/args.json` — read the arguments from a file (relative to the worktree root)\n\nExample: `/loom-call agent_memory_recall {\"query\": \"auth\"}`\n\nRelaxed JSON is accepted and normalized before it is sent: single quotes, unquoted keys, trailing commas, and comments (`{query: 'auth', limit: 5,}`).\n\nString values of the form `\"@file:path\"` are replaced with that worktree file's contents (up to 64 KiB), e.g. `{\"content\": \"@file:src/main.rs\"}`. Other values starting with `@` (`\"@types/node\"`) are sent as written; write `@@file:` for a literal `@file:`.\n\nWhen the tool's input schema is cached from a recent `tools list --json`, the arguments are checked first (required fields, types, enum values) and problems are listed per field without calling the daemon.\n\nRun without arguments to get a fill-in-the-blanks template with known tool names.\n",
         "dashboard" => "## `/loom-dashboard`\n\nComposite overview combining status, servers, tools, sync, and session info.\n\n**Usage**: `/loom-dashboard`\n\nNo arguments required.\n",
-        "info" => "## `/loom-info`\n\nShow the resolved Loom binary path and attempt to print its version.\n\n**Usage**: `/loom-info`\n\nNo arguments required.\n",
+        "agents" => "## `/loom-agents`\n\nList the agents registered with the Loom daemon (`loom agent list`) with their last heartbeat and status, so you can see which editors and CLIs are active.\n\n**Usage**: `/loom-agents`\n\nThis editor's agent (`settings.agent.agent_id`) is marked in the table.\n",
+        "context" => "## `/loom-context`\n\nRecall memories for the current worktree with `agent_context_recall_enhanced` and insert them as plain Markdown the Assistant can read directly: one heading per memory, no code fences.\n\n**Usage**:\n- `/loom-context` — recall context for the worktree (queried by its folder name)\n- `/loom-context <query>` — recall context matching the query instead\n\nThe recall is scoped to `settings.agent.default_namespace`, or to the active session's namespace when that isn't set.\n",
+        "summary" => "## `/loom-summary`\n\nShow a Markdown digest of the current agent session from `loom agent session-summary`: the summary, the session's namespace and age, and any highlights or next steps. The session keeps running.\n\n**Usage**:\n- `/loom-summary` — summarize the session\n- `/loom-summary restart` — for CLIs without `session-summary`: end the session with `--summarize`, show that summary, and start a new session in the same namespace\n",
+        "memory" => "## `/loom-memory`\n\nStore an entry in Loom agent memory via `agent_memory_store` and show its ID.\n\n**Usage**: `/loom-memory <text>`\n\nExample: `/loom-memory The staging cluster uses the eu-west-1 vault`\n\nThe entry is stored under `settings.agent.agent_id`; read it back with `/loom-recall`.\n",
+        "forget" => "## `/loom-forget`\n\nDelete agent memory entries via `agent_memory_delete`.\n\n**Usage**:\n- `/loom-forget id <entry_id> confirm` — delete one entry\n- `/loom-forget query <text> confirm` — delete every entry matching the query\n\nWithout the trailing `confirm` nothing is deleted; the command shows what would happen instead.\n",
+        "backup" => "## `/loom-backup`\n\nSnapshot the hub configuration before a risky change. Uses `loom config export`; on CLIs without it, the config file and `profiles/` are packed with `tar`.\n\n**Usage**:\n- `/loom-backup` — write `.loom/backups/loom-config-<YYYYMMDD-HHMMSS>.yaml` in the worktree (`.tar.gz` for tarball backups)\n- `/loom-backup <path>` — write to a path inside the worktree instead\n\nBackups can hold tokens from your config. `.loom/backups/` gets its own `.gitignore` so they aren't committed; a custom path outside it is not ignored and comes with a warning.\n",
+        "restore" => "## `/loom-restore`\n\nRestore a configuration snapshot taken with `/loom-backup`.\n\n**Usage**:\n- `/loom-restore` — list backups in `.loom/backups/`, newest first\n- `/loom-restore <path>` — preview what will be replaced\n- `/loom-restore <path> confirm` — restore it (`loom config import`, or `tar` for tarball backups on older CLIs)\n\nRun `/loom-restart` afterwards so the daemon loads the restored configuration.\n",
+        "quota" => "## `/loom-quota`\n\nShow upstream API usage per provider from the hub's `provider_usage` tool: requests made, the limit, what's left, and when the window resets.\n\n**Usage**:\n- `/loom-quota` — every provider\n- `/loom-quota <provider>` — one provider (e.g. `openai`)\n\nProviders that have used 90% or more of their quota are listed as warnings above the table; 🔴 means the quota is exhausted.\n",
+        "config" => "## `/loom-config`\n\nShow the effective Loom configuration (`loom config show`) with syntax highlighting, plus the file it was loaded from (`loom config path`).\n\n**Usage**: `/loom-config`\n\nUseful when sync or servers behave unexpectedly.\n",
+        "prompts" => "## `/loom-prompts`\n\nList the prompt recipes the MCP wrapper exposes in the Agent panel's prompt picker, or preview one.\n\n**Usage**:\n- `/loom-prompts` — list recipes with their descriptions and arguments\n- `/loom-prompts <recipe>` — show the prompt text the Agent receives (e.g. `onboard_repo`)\n\nIncludes recipes from `settings.mcp.prompts.recipes_file`. Requires python3 (the wrapper serves the recipes).\n",
+        "events" => "## `/loom-events`\n\nShow recent daemon events (server connected, tool registered, sync completed, ...) as a timeline with relative timestamps, newest first.\n\n**Usage**:\n- `/loom-events` — the last 20 events\n- `/loom-events <n>` — the last `n` events (1-500)\n",
+        "metrics" => "## `/loom-metrics`\n\nShow daemon metrics: total requests and errors, plus per-server request counts, p50/p95 latency, and error rates.\n\n**Usage**: `/loom-metrics`\n\nRuns `loom metrics`, falling back to the `hub_metrics` tool on CLIs without that command.\n",
+        "doctor" => "## `/loom-doctor`\n\nRun `loom check`, `loom status`, and a version probe, then map known failure signatures (daemon not running, socket permission denied, missing secrets, unhealthy servers, outdated CLI) to concrete next steps.\n\n**Usage**: `/loom-doctor`\n\nAdd `--raw` to see the unformatted probe output.\n",
+        "env" => "## `/loom-env`\n\nExplain which loom binary slash commands run: its path, which resolution step picked it (`command.path`, worktree `PATH`, host probe, managed install, or bare `loom`), and its version. Also lists every source in precedence order with what it found, the `PATH` commands get (marking the directory the extension adds and the one holding the binary), and the variables set by `context_servers.loom.command.env`, with secrets redacted.\n\n**Usage**: `/loom-env`\n\nUse it when loom works in your terminal but not in Zed.\n",
+        "paths" => "## `/loom-paths`\n\nShow the extension work directory, managed loom-core installs, the binary slash commands use, the MCP wrapper script, and worktree output folders.\n\n**Usage**: `/loom-paths`\n\nHandy when you need to find downloaded artifacts without digging through Zed's support folder.\n",
+        "install" => "## `/loom-install`\n\nForce a fresh download of loom-core, replacing any cached or on-disk copy of the release. Useful after a corrupted download or to fetch another tag.\n\n**Usage**:\n- `/loom-install` — reinstall the latest release\n- `/loom-install <tag>` — install a specific release (e.g. `v0.9.1`)\n\nReports the version, binary, and bin dir. Commands use the new install only when `settings.download.tag` selects that release.\n",
+        "cleanup" => "## `/loom-cleanup`\n\nDelete downloaded loom-core releases that are no longer used and report the disk space reclaimed.\n\n**Usage**:\n- `/loom-cleanup` — preview which releases would be removed and their sizes\n- `/loom-cleanup confirm` — delete them\n\nThe release in use is always kept (or the newest one, when no managed install is active).\n",
+        "handoff" => "## `/loom-handoff`\n\nPost a handoff note for another editor's agent with this session's namespace and summary, so it can pick up where you left off.\n\n**Usage**:\n- `/loom-handoff <agent-id>` — snapshot the current session and hand it off\n- `/loom-handoff <agent-id> end` — also end this editor's session (with a summary)\n- `/loom-handoff <agent-id> [end] <note...>` — add your own note to the handoff\n\nUse `/loom-agents` to see agent IDs. Hubs without the `agent_handoff` tool get the note as an agent memory entry for the target agent.\n",
+        "namespace" => "## `/loom-namespace`\n\nList agent memory namespaces as a table with each one's memory-entry and session counts (from `agent_memory_stats`, or `agent session-list` for sessions). The namespace of this editor's current session is marked ▶.\n\n**Usage**:\n- `/loom-namespace` or `/loom-namespace list`\n\nCounts show `—` when the hub doesn't report them.\n",
+        "inbox" => "## `/loom-inbox`\n\nShow agent-to-agent messages and handoffs addressed to this editor's agent (`agent.id`), oldest first, with unread ones marked 🔵.\n\n**Usage**:\n- `/loom-inbox` or `/loom-inbox list` — list messages\n- `/loom-inbox read <id>` — show one message in full (and mark it read)\n\nOn hubs without the `agent_inbox` tool, the list falls back to recalling the handoff notes `/loom-handoff` stored in agent memory.\n",
+        "update" => "## `/loom-update`\n\nCheck the latest loom-core GitHub release and, if it is newer than the managed install, show its changelog from the release notes. Confirm to download it and report old → new version.\n\n**Usage**:\n- `/loom-update` — check for a newer release and show what it changes\n- `/loom-update confirm` — download and install it\n\nDoes nothing when `settings.download.tag` pins a release. Run `/loom-restart` afterwards so the daemon uses the new binary.\n",
+        "info" => "## `/loom-info`\n\nShow the resolved Loom binary path and attempt to print its version.\n\n**Usage**: `/loom-info`\n\nNo arguments required. For managed installs it also shows provenance: the release asset, the URL it was downloaded from, and whether the binary still matches the SHA-256 recorded at install time.\n",
+        "queue" => "## `/loom-queue`\n\nShow queued and running daemon background jobs (syncs, indexing, scheduled tasks) with their age and status.\n\n**Usage**:\n- `/loom-queue` — list jobs\n- `/loom-queue list` — list jobs\n- `/loom-queue cancel <id> confirm` — cancel a job (without `confirm`, only shows what would happen)\n",
+        "kill" => "## `/loom-kill`\n\nForce-terminate one wedged upstream MCP server process and report whether the daemon respawned it.\n\n**Usage**:\n- `/loom-kill <server>` — show what would happen\n- `/loom-kill <server> confirm` — terminate the server\n\nServer names complete as you type.\n",
+        "export" => "## `/loom-export`\n\nRun `/loom-dashboard`, `/loom-status`, or `/loom-check` and write its Markdown output to a file in the worktree, e.g. to attach to a ticket.\n\n**Usage**:\n- `/loom-export <dashboard|status|check>` — write `loom-report.md` at the worktree root\n- `/loom-export <report> <path>` — write to a worktree-relative path (parent directories are created; existing files are overwritten)\n",
+        "history" => "## `/loom-history`\n\nList the slash commands run in this session, newest first, with their arguments, CLI exit code, duration, and any error.\n\n**Usage**:\n- `/loom-history` — the last 20 commands\n- `/loom-history <n>` — the last `n` (up to 100 are kept)\n\nPair it with `/loom-last <command>` to see a command's raw output.\n",
+        "audit" => "## `/loom-audit`\n\nShow the daemon's audit log (`loom audit`) as a table of who did what, to what, and when, newest first: tool calls, secret accesses, config changes.\n\n**Usage**:\n- `/loom-audit` — the last 50 entries\n- `/loom-audit <n>` — the last `n` entries (1-1000)\n- `/loom-audit [n] <keyword>` — only entries mentioning the keyword (e.g. `secret`, a tool name, an agent ID)\n",
+        "workflows" => "## `/loom-workflows`\n\nList and run loom workflows.\n\n**Usage**:\n- `/loom-workflows` — list workflows with their step counts\n- `/loom-workflows list` — same as above\n- `/loom-workflows run <name>` — run a workflow and show each step's status and duration, plus the first failing step's error\n\nWorkflow names complete as you type.\n",
+        "retry" => "## `/loom-retry`\n\nRe-run the last failed slash command after fixing what made it fail.\n\n**Usage**: `/loom-retry`\n\nThe failure is classified like `/loom-doctor` does: a stopped daemon is started (`loom start`), a socket permission problem or missing secret/token restarts the daemon so it picks up the current environment, and a binary that can't be run is re-downloaded. Problems without an automatic fix (config errors, crashed servers) show the manual remedy and re-run the command as-is. If the fix itself fails, the command is not re-run.\n\nThe failure is forgotten once the same command and arguments succeed.\n",
+        "last" => "## `/loom-last`\n\nShow the raw CLI output (exit code, full stdout/stderr before truncation or formatting) from the previous command.\n\n**Usage**:\n- `/loom-last` — the most recent command\n- `/loom-last <command>` — the last run of a specific command (e.g. `status`)\n",
         "help" => "## `/loom-help`\n\nShow help for all commands or a specific command.\n\n**Usage**:\n- `/loom-help` — list all commands\n- `/loom-help <command>` — show details for one command\n",
         _ => &format!("Unknown command `{}`. Use `/loom-help` to see all commands.\n", cmd),
     };
 
-    FormattedOutput::plain(text.to_string())
+    FormattedOutput::plain(text.replace("**Usage**", &format!("**{}**", tr("Usage"))))
 }
@@ -0,0 +1,231 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Built-in translations of section labels and fixed strings, keyed by the English text.
+///
+/// English is the identity and has no table. Strings missing from a table stay in English.
+const LOCALES: &[(&str, &[(&str, &str)])] = &[("de", DE)];
+
+const DE: &[(&str, &str)] = &[
+    // Shared section labels.
+    ("Errors", "Fehler"),
+    ("Output", "Ausgabe"),
+    ("Output Summary", "Ausgabe-Zusammenfassung"),
+    ("Details", "Details"),
+    ("Results", "Ergebnisse"),
+    ("Warnings", "Warnungen"),
+    ("Overview", "Übersicht"),
+    ("Summary", "Zusammenfassung"),
+    ("Status", "Status"),
+    ("Files", "Dateien"),
+    ("File", "Datei"),
+    ("Changes", "Änderungen"),
+    ("Settings", "Einstellungen"),
+    ("Configuration", "Konfiguration"),
+    ("Contents", "Inhalt"),
+    ("Arguments", "Argumente"),
+    ("Template", "Vorlage"),
+    ("Search", "Suche"),
+    ("Health", "Zustand"),
+    ("Pending", "Ausstehend"),
+    ("State", "Status"),
+    ("Counts", "Anzahl"),
+    // Command-specific section labels.
+    ("Diagnostic Report", "Diagnosebericht"),
+    ("Failed", "Fehlgeschlagen"),
+    ("Passed", "Bestanden"),
+    ("Sync", "Synchronisierung"),
+    ("Tools", "Tools"),
+    ("Tool List", "Tool-Liste"),
+    ("Parameters", "Parameter"),
+    ("Tools per server", "Tools pro Server"),
+    ("Update", "Aktualisierung"),
+    ("Install", "Installation"),
+    ("Memory", "Gedächtnis"),
+    ("Jobs", "Aufträge"),
+    ("Workflows", "Workflows"),
+    ("Permissions", "Berechtigungen"),
+    ("Allowed", "Erlaubt"),
+    ("Denied", "Verweigert"),
+    ("Aliases", "Aliasse"),
+    ("Favorites", "Favoriten"),
+    ("Pinned", "Angeheftet"),
+    ("Steps", "Schritte"),
+    ("Skill", "Skill"),
+    ("Examples", "Beispiele"),
+    ("Schema Export", "Schema-Export"),
+    ("Namespaces", "Namensräume"),
+    ("Export", "Export"),
+    ("Loom Servers", "Loom-Server"),
+    ("Servers", "Server"),
+    ("Tasks", "Aufgaben"),
+    ("Sessions", "Sitzungen"),
+    ("Server", "Server"),
+    ("Heartbeat Status", "Heartbeat-Status"),
+    ("Heartbeat", "Heartbeat"),
+    ("Agents", "Agenten"),
+    ("Agent List", "Agentenliste"),
+    ("Metrics", "Metriken"),
+    ("Per-server", "Pro Server"),
+    ("Events", "Ereignisse"),
+    ("Audit", "Audit"),
+    ("Benchmark", "Benchmark"),
+    ("Latency", "Latenz"),
+    ("Quota", "Kontingent"),
+    ("Providers", "Anbieter"),
+    ("Text", "Text"),
+    ("Resource", "Ressource"),
+    ("Image", "Bild"),
+    ("Audio", "Audio"),
+    ("Content", "Inhalt"),
+    ("Trace", "Ablaufverfolgung"),
+    ("Hops", "Stationen"),
+    ("Timeline", "Zeitleiste"),
+    ("Inbox", "Posteingang"),
+    ("Messages", "Nachrichten"),
+    ("Message", "Nachricht"),
+    ("Body", "Text"),
+    ("History", "Verlauf"),
+    ("Retry", "Wiederholen"),
+    ("Remediation", "Behebung"),
+    ("Hint", "Hinweis"),
+    ("Result", "Ergebnis"),
+    ("Commands", "Befehle"),
+    ("Prompts", "Prompts"),
+    ("Recipe List", "Rezeptliste"),
+    ("Prompt", "Prompt"),
+    ("Prompt Text", "Prompt-Text"),
+    ("Recall", "Abruf"),
+    ("Project Context", "Projektkontext"),
+    ("Saved", "Gespeichert"),
+    ("Handoff", "Übergabe"),
+    ("Entry", "Eintrag"),
+    ("Deleted", "Gelöscht"),
+    ("Profile", "Profil"),
+    ("Dashboard", "Dashboard"),
+    ("Kill", "Beenden"),
+    ("Respawn", "Neustart"),
+    ("Environment", "Umgebung"),
+    ("Missing from daemon", "Fehlt im Daemon"),
+    ("Different values", "Abweichende Werte"),
+    ("Only in daemon", "Nur im Daemon"),
+    ("Doctor", "Diagnose"),
+    ("Probes", "Prüfungen"),
+    ("Findings", "Befunde"),
+    ("Paths", "Pfade"),
+    ("Binary", "Binärdatei"),
+    ("Resolution Order", "Suchreihenfolge"),
+    ("Overrides", "Überschreibungen"),
+    ("Locations", "Speicherorte"),
+    ("Cleanup", "Aufräumen"),
+    ("Backup", "Sicherung"),
+    ("Backups", "Sicherungen"),
+    ("Restore", "Wiederherstellung"),
+    ("Releases", "Releases"),
+    ("Release Notes", "Versionshinweise"),
+    ("Confirmation Required", "Bestätigung erforderlich"),
+    ("Arguments Needed", "Argumente erforderlich"),
+    ("Throttled", "Gedrosselt"),
+    ("Tool Set Changed", "Tool-Set geändert"),
+    ("Version Warning", "Versionswarnung"),
+    ("Crash Loop", "Absturzschleife"),
+    ("Binary Warning", "Binärdatei-Warnung"),
+    ("Extension Error", "Erweiterungsfehler"),
+    ("Recent Log", "Letzte Protokollzeilen"),
+    // Fixed strings.
+    ("Exit code", "Exit-Code"),
+    (
+        "This will {action}. Nothing has been changed yet.",
+        "Dies wird: {action}. Bisher wurde nichts geändert.",
+    ),
+    ("To proceed, run:", "Zum Fortfahren ausführen:"),
+    ("Loom Commands", "Loom-Befehle"),
+    ("Command", "Befehl"),
+    ("Description", "Beschreibung"),
+    ("Usage", "Verwendung"),
+    (
+        "Use `/loom-help <command>` for detailed usage. Append `--raw` to any command to see the\nunformatted CLI output, or `--format json` for a machine-readable result.",
+        "`/loom-help <befehl>` zeigt Details. Mit angehängtem `--raw` liefert jeder Befehl die\nunformatierte CLI-Ausgabe, mit `--format json` ein maschinenlesbares Ergebnis.",
+    ),
+];
+
+#[derive(Default)]
+struct Labels {
+    table: &'static [(&'static str, &'static str)],
+    overrides: HashMap<String, String>,
+}
+
+thread_local! {
+    // Zed runs extensions single-threaded; set once per slash command from `formatter` settings.
+    static LABELS: RefCell<Labels> = RefCell::new(Labels::default());
+}
+
+/// Select the built-in table for `locale` (`de`, `de-CH`, ...) plus per-string `overrides`.
+///
+/// Unknown locales fall back to English; overrides apply on top of either.
+pub(crate) fn configure(locale: &str, overrides: &HashMap<String, String>) {
+    let lang = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let table = LOCALES
+        .iter()
+        .find(|(code, _)| *code == lang)
+        .map(|(_, table)| *table)
+        .unwrap_or_default();
+    LABELS.with(|labels| {
+        *labels.borrow_mut() = Labels {
+            table,
+            overrides: overrides.clone(),
+        }
+    });
+}
+
+/// Localized form of an English label or fixed string (the string itself if untranslated).
+pub(crate) fn tr(text: &str) -> String {
+    LABELS.with(|labels| {
+        let labels = labels.borrow();
+        labels
+            .overrides
+            .get(text)
+            .cloned()
+            .or_else(|| {
+                labels
+                    .table
+                    .iter()
+                    .find(|(en, _)| *en == text)
+                    .map(|(_, local)| local.to_string())
+            })
+            .unwrap_or_else(|| text.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_and_overrides() {
+        configure("en", &HashMap::new());
+        assert_eq!(tr("Errors"), "Errors");
+
+        configure("de-DE", &HashMap::new());
+        assert_eq!(tr("Errors"), "Fehler");
+        assert_eq!(tr("Not in the table"), "Not in the table");
+
+        let overrides = HashMap::from([("Errors".to_string(), "Probleme".to_string())]);
+        configure("xx", &overrides);
+        assert_eq!(tr("Errors"), "Probleme");
+        assert_eq!(tr("Output"), "Output");
+
+        configure("en", &HashMap::new());
+    }
+
+    #[test]
+    fn german_table_has_no_duplicates() {
+        let mut seen = std::collections::HashSet::new();
+        assert!(DE.iter().all(|(en, _)| seen.insert(*en)));
+        assert!(LOCALES.iter().any(|(code, _)| *code == "de"));
+    }
+}
@@ -1,35 +1,61 @@
+mod aliases;
+mod args;
+mod catalog;
+mod checksum;
 mod commands;
 mod completions;
 mod dispatch;
+mod doctor;
 mod download;
 mod env;
+mod favorites;
 mod format;
 mod help;
+mod i18n;
 mod log;
+mod redact;
+mod resolver;
+mod schema;
+mod secrets;
 mod settings;
+mod state;
+mod version;
+mod worktree;
 
-use std::{collections::HashMap, sync::Mutex};
+use std::{collections::HashMap, sync::Mutex, time::Instant};
 use zed_extension_api as zed;
 
-use commands::join_args;
-use completions::complete_argument;
-use dispatch::{dispatch_command, resolve_binary};
-use download::LoomInstall;
+use args::{redact_args, tokenize_args};
+use catalog::{catalog_from_runs, fetch_catalog, wants_tool_notice};
+use commands::{join_args, run_command_capture, take_recorded_runs};
+use completions::{complete_alias, complete_argument, complete_names, name_source, NameSource};
+use dispatch::{dispatch_command, is_usage_error, record_command_event, DispatchContext};
+use download::{unix_now_secs, LoomInstall};
 use env::{current_path_sep, env_map_to_vec, with_path_prefix};
-use log::{log_msg, LogLevel};
+use log::{begin_correlation, install_panic_hook, log_msg, recent_log_lines, LogLevel};
+use resolver::{binary_candidates, binary_skew_warning, resolve_binary, resolve_loom};
 use settings::{
     parse_extension_settings, LoomRuntimeSettings, DEFAULT_SETTINGS, INSTALL_INSTRUCTIONS,
     SETTINGS_SCHEMA,
 };
+use state::{runs_exit_code, HistoryEntry, SessionState};
+use version::{check_version, outdated_warning, parse_version, probe_version, VersionStatus};
+
+/// MCP wrapper script shipped with the extension, relative to its work dir.
+pub(crate) const WRAPPER_SCRIPT: &str = "scripts/loom_mcp_wrapper.py";
 
 #[derive(Default)]
 struct LoomExtension {
     installs: Mutex<HashMap<String, LoomInstall>>,
     runtime_settings: Mutex<Option<LoomRuntimeSettings>>,
+    /// Minimum-version check results, keyed by resolved binary path (probed once per session).
+    version_checks: Mutex<HashMap<String, VersionStatus>>,
+    session: Mutex<SessionState>,
 }
 
 impl zed::Extension for LoomExtension {
     fn new() -> Self {
+        install_panic_hook();
         Self::default()
     }
 
@@ -96,70 +122,37 @@ impl zed::Extension for LoomExtension {
 
         let env = env_from_settings;
 
-        // Determine the loom binary path to run (explicit path, local, or download).
-        let local_path = resolve_loom_path();
-        let have_local = local_path != "loom";
-
-        let explicit_path = settings
-            .command
-            .as_ref()
-            .and_then(|c| c.path.as_ref())
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty());
-
-        // Always try to resolve a local binary first — this avoids blocking
-        // on slow/failing GitHub API calls when loom is already installed.
-        let (loom_cmd, env) = if let Some(p) = explicit_path {
-            (p, env)
-        } else if dl.enabled() && !have_local {
-            log_msg(
-                LogLevel::Info,
-                &format!("downloading loom-core from {}", dl.repo()),
-            );
-            let install = download::ensure_loom_install(&self.installs, &dl)?;
-            log_msg(
-                LogLevel::Info,
-                &format!("using downloaded loom at {}", install.loom_path),
-            );
-            (
-                install.loom_path,
-                with_path_prefix(env, &install.bin_dir, current_path_sep()),
-            )
-        } else {
-            log_msg(LogLevel::Info, &format!("using loom at: {local_path}"));
-            (local_path, env)
+        // Same precedence as slash commands: explicit path > worktree > host > download.
+        let resolution = resolve_loom(
+            &self.installs,
+            None,
+            settings.command.as_ref().and_then(|c| c.path.as_deref()),
+            &dl,
+        )?;
+        log_msg(
+            LogLevel::Info,
+            &format!(
+                "using loom at {} ({})",
+                resolution.program,
+                resolution.source.label()
+            ),
+        );
+        let loom_cmd = resolution.program;
+        let env = match resolution.install {
+            Some(install) => {
+                if let Some(report) = &install.startup_report {
+                    log_msg(LogLevel::Info, report.trim());
+                }
+                with_path_prefix(env, &install.bin_dir, current_path_sep())
+            }
+            None => env,
         };
 
         // Optional MCP wrapper: adds prompt recipes + tool list hot reload.
         // If the wrapper isn't available, run `loom proxy` directly.
         if ext_settings.mcp.wrapper.enabled() {
-            let wrapper_path = std::env::current_dir()
-                .ok()
-                .map(|d| d.join("scripts").join("loom_mcp_wrapper.py"))
-                .filter(|p| p.exists())
-                .and_then(|p| p.to_str().map(|s| s.to_string()))
-                .or_else(|| {
-                    let rel = std::path::Path::new("scripts/loom_mcp_wrapper.py");
-                    rel.exists().then(|| rel.to_string_lossy().to_string())
-                });
-
-            let python = ext_settings
-                .mcp
-                .wrapper
-                .python()
-                .map(|s| s.to_string())
-                .or_else(|| {
-                    for cand in ["python3", "python"] {
-                        if let Ok(output) =
-                            zed::process::Command::new(cand).arg("--version").output()
-                        {
-                            if output.status == Some(0) {
-                                return Some(cand.to_string());
-                            }
-                        }
-                    }
-                    None
-                });
+            let wrapper_path = resolver::wrapper_script_path();
+            let python = resolver::resolve_python(&ext_settings.mcp.wrapper);
 
             if let (Some(wrapper_path), Some(python)) = (wrapper_path, python) {
                 log_msg(LogLevel::Info, "starting loom via MCP wrapper");
@@ -188,6 +181,8 @@ impl zed::Extension for LoomExtension {
                 if ext_settings.mcp.resources.include_diagnostics() {
                     args.push("--resources-include-diagnostics".to_string());
                 }
+                args.push("--resources-memory-limit".to_string());
+                args.push(ext_settings.mcp.resources.memory_limit().to_string());
                 args.push("--".to_string());
                 args.extend(args_from_settings.clone());
 
@@ -227,6 +222,22 @@ impl zed::Extension for LoomExtension {
         command: zed::SlashCommand,
         args: Vec<String>,
     ) -> Result<Vec<zed::SlashCommandArgumentCompletion>, String> {
+        if let Some(source) = name_source(&command.name, &args) {
+            return Ok(complete_names(&self.live_names(source)?, &args));
+        }
+        if command.name == "loom-alias" {
+            let rt = self
+                .runtime_settings
+                .lock()
+                .map_err(|_| "runtime settings mutex poisoned")?;
+            let defined = aliases::merged(
+                &rt.as_ref()
+                    .map(|rt| rt.extension.commands.aliases.clone())
+                    .unwrap_or_default(),
+                &aliases::load_saved(),
+            );
+            return Ok(complete_alias(&args, &defined));
+        }
         Ok(complete_argument(&command.name, &args))
     }
 
@@ -240,54 +251,319 @@ impl zed::Extension for LoomExtension {
             .runtime_settings
             .lock()
             .map_err(|_| "runtime settings mutex poisoned")?;
-        let (program, base_env) = resolve_binary(&self.installs, worktree, rt.as_ref())?;
+        let resolved = resolve_binary(&self.installs, worktree, rt.as_ref())?;
+        let (program, base_env) = (resolved.program, resolved.env);
+        let mut notices = resolved.notices;
+        if let Some(warning) = self.binary_skew_once(&program, &base_env, worktree, rt.as_ref())? {
+            notices.push(("Binary Warning".to_string(), warning));
+        }
+        let args = tokenize_args(&args);
+        // What gets logged and kept in history: `args` minus secret values, including those
+        // passed through an alias.
+        let logged_args = if command.name == "loom-alias" {
+            let defined = aliases::merged(
+                &rt.as_ref()
+                    .map(|rt| rt.extension.commands.aliases.clone())
+                    .unwrap_or_default(),
+                &aliases::load_saved(),
+            );
+            aliases::redact_invocation(&args, &defined)
+        } else {
+            redact_args(&command.name, &args)
+        };
+        if let Some(rt) = rt.as_ref() {
+            let formatter = &rt.extension.formatter;
+            i18n::configure(formatter.locale(), &formatter.labels);
+            redact::configure(&formatter.redact_patterns);
+        }
+        let icons = rt.as_ref().is_none_or(|rt| rt.extension.display.icons());
 
+        let throttle_secs = rt
+            .as_ref()
+            .and_then(|rt| rt.extension.commands.throttle_secs(&command.name));
+        if let Some(window) = throttle_secs {
+            let cached = self
+                .session
+                .lock()
+                .map_err(|_| "session state mutex poisoned")?
+                .throttled_output(&command.name, &args, window, unix_now_secs());
+            if let Some((mut formatted, age)) = cached {
+                formatted.prepend_section(
+                    "Throttled",
+                    &format!(
+                        "> ⏱️ Throttled: showing the result from {}s ago (`commands.throttle` \
+                         allows one run every {}s).\n\n",
+                        age, window
+                    ),
+                );
+                return Ok(slash_output(formatted, icons));
+            }
+        }
+
+        let correlation_id = begin_correlation(unix_now_secs());
         log_msg(
             LogLevel::Info,
-            &format!("slash command: {} {}", command.name, join_args(&args)),
+            &format!(
+                "slash command: {} {}",
+                command.name,
+                join_args(&logged_args)
+            ),
         );
 
-        let formatted = dispatch_command(&command.name, &args, &program, &base_env)?;
+        // Discard runs left over from probes outside a command, then capture this command's.
+        take_recorded_runs();
+        let worktree_root = worktree.map(|wt| wt.root_path());
+        let ctx = DispatchContext {
+            session: &self.session,
+            installs: &self.installs,
+            runtime_settings: rt.as_ref(),
+            worktree_root: worktree_root.as_deref(),
+            worktree,
+        };
+        let started_at = unix_now_secs();
+        let started = Instant::now();
+        let dispatched = dispatch_command(&command.name, &args, &program, &base_env, &ctx);
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let runs = take_recorded_runs();
+        let tool_diff = if dispatched.is_ok() && wants_tool_notice(&command.name) {
+            self.refresh_tool_catalog(&runs, &program, &base_env)?
+        } else {
+            None
+        };
+        {
+            let mut session = self
+                .session
+                .lock()
+                .map_err(|_| "session state mutex poisoned")?;
+            session.record_history(HistoryEntry {
+                command: command.name.clone(),
+                args: logged_args.clone(),
+                exit_code: runs_exit_code(&runs),
+                error: dispatched.as_ref().err().cloned(),
+                started_at,
+                duration_ms,
+            });
+            session.note_outcome(
+                &command.name,
+                &logged_args,
+                &runs,
+                dispatched.as_ref().err().map(String::as_str),
+            );
+            session.record_runs(&command.name, runs);
+        }
 
-        Ok(zed::SlashCommandOutput {
-            text: formatted.text,
-            sections: formatted.sections,
-        })
+        if let Some(agent) = rt.as_ref().map(|rt| &rt.extension.agent) {
+            if agent.log_commands() && command.name != "loom-help" {
+                let outcome = match &dispatched {
+                    Ok(_) => "ok".to_string(),
+                    Err(e) => format!("error: {}", e),
+                };
+                record_command_event(
+                    &program,
+                    &base_env,
+                    agent.agent_id(),
+                    &command.name,
+                    &logged_args,
+                    &outcome,
+                );
+            }
+        }
+
+        let mut formatted = match dispatched {
+            Ok(formatted) => formatted,
+            Err(e) if is_usage_error(&e) => return Err(redact::mask(&e)),
+            Err(e) => {
+                log_msg(
+                    LogLevel::Error,
+                    &format!("/{} failed: {}", command.name, redact::mask(&e)),
+                );
+                let report = format::format_extension_error(
+                    &command.name,
+                    &logged_args,
+                    &e,
+                    &correlation_id,
+                    &program,
+                    &recent_log_lines(20),
+                );
+                return Ok(slash_output(report, icons));
+            }
+        };
+        if throttle_secs.is_some() {
+            self.session
+                .lock()
+                .map_err(|_| "session state mutex poisoned")?
+                .store_throttled(&command.name, &args, &formatted, unix_now_secs());
+        }
+
+        if let Some(diff) = tool_diff {
+            formatted.prepend_section("Tool Set Changed", &diff.notice());
+        }
+
+        for (label, notice) in notices.iter().rev() {
+            formatted.prepend_section(label, notice);
+        }
+
+        if command.name != "loom-help" {
+            if let VersionStatus::Outdated(found) = self.version_status(&program, &base_env)? {
+                formatted.prepend_section("Version Warning", &outdated_warning(&found));
+            }
+        }
+
+        Ok(slash_output(formatted, icons))
     }
 }
 
-/// Resolve the absolute path to the `loom` binary.
+/// Final slash command output: secret-looking values masked, and icons swapped for text if
+/// `display.icons` is off.
 ///
-/// Zed may not search the system PATH when spawning extension-provided context
-/// servers, so we need to return an absolute path.  We try, in order:
-///   1. `which loom` via the host process API
-///   2. Well-known install locations
-///   3. Bare `"loom"` as a last resort
-fn resolve_loom_path() -> String {
-    // Try `which loom` through the host
-    if let Ok(output) = zed::process::Command::new("which").arg("loom").output() {
-        if output.status == Some(0) {
-            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !path.is_empty() {
-                return path;
+/// Masking happens here rather than on captured CLI output, so commands that parse stdout
+/// (`/loom-env` comparing values, JSON listings) still see the real text.
+fn slash_output(formatted: format::FormattedOutput, icons: bool) -> zed::SlashCommandOutput {
+    let formatted = formatted.redacted();
+    let formatted = if icons {
+        formatted
+    } else {
+        formatted.without_icons()
+    };
+    zed::SlashCommandOutput {
+        text: formatted.text,
+        sections: formatted.sections,
+    }
+}
+
+impl LoomExtension {
+    /// Compare every visible loom binary once per session and warn if they disagree.
+    fn binary_skew_once(
+        &self,
+        chosen: &str,
+        base_env: &[(String, String)],
+        worktree: Option<&zed::Worktree>,
+        rt: Option<&LoomRuntimeSettings>,
+    ) -> Result<Option<String>, String> {
+        {
+            let mut session = self
+                .session
+                .lock()
+                .map_err(|_| "session state mutex poisoned")?;
+            if session.binaries_checked {
+                return Ok(None);
             }
+            session.binaries_checked = true;
+        }
+
+        let probed: Vec<_> = binary_candidates(&self.installs, worktree, rt)
+            .into_iter()
+            .map(|candidate| {
+                let version = probe_version(&candidate.path, base_env)
+                    .as_deref()
+                    .and_then(parse_version)
+                    .map(|(major, minor, patch)| format!("{}.{}.{}", major, minor, patch));
+                (candidate, version)
+            })
+            .collect();
+        let warning = binary_skew_warning(&probed, chosen);
+        if warning.is_some() {
+            let paths: Vec<&str> = probed.iter().map(|(c, _)| c.path.as_str()).collect();
+            log_msg(
+                LogLevel::Warn,
+                &format!("multiple loom binaries found: {}", paths.join(", ")),
+            );
         }
+        Ok(warning)
     }
 
-    // Check well-known locations
-    let home = std::env::var("HOME").unwrap_or_default();
-    let candidates = [
-        format!("{home}/.local/bin/loom"),
-        "/usr/local/bin/loom".to_string(),
-        "/opt/homebrew/bin/loom".to_string(),
-    ];
-    for candidate in &candidates {
-        if std::path::Path::new(candidate).exists() {
-            return candidate.clone();
+    /// Names for live argument completions, listed via the CLI when the cache is cold.
+    ///
+    /// Failures yield an empty list: completions should never surface CLI errors.
+    fn live_names(&self, source: NameSource) -> Result<Vec<String>, String> {
+        let now = unix_now_secs();
+        if let Some(names) = self
+            .session
+            .lock()
+            .map_err(|_| "session state mutex poisoned")?
+            .cached_names(source, now)
+        {
+            return Ok(names);
         }
+
+        let rt = self
+            .runtime_settings
+            .lock()
+            .map_err(|_| "runtime settings mutex poisoned")?;
+        let Ok(resolved) = resolve_binary(&self.installs, None, rt.as_ref()) else {
+            return Ok(Vec::new());
+        };
+        let list_args: Vec<String> = source.list_args().iter().map(|s| s.to_string()).collect();
+        let stdout = match run_command_capture(&resolved.program, &list_args, &resolved.env, &[]) {
+            Ok(result) if result.success() => result.stdout,
+            _ => return Ok(Vec::new()),
+        };
+        Ok(self
+            .session
+            .lock()
+            .map_err(|_| "session state mutex poisoned")?
+            .store_names(source, &stdout, now))
     }
 
-    "loom".to_string()
+    /// Update the cached tool set and return the change since the last observation.
+    ///
+    /// Reuses a `tools list` the command already ran; otherwise re-polls once the TTL lapses.
+    fn refresh_tool_catalog(
+        &self,
+        runs: &[commands::RecordedRun],
+        program: &str,
+        base_env: &[(String, String)],
+    ) -> Result<Option<catalog::ToolSetDiff>, String> {
+        let now = unix_now_secs();
+        let next = match catalog_from_runs(runs, now) {
+            Some(catalog) => Some(catalog),
+            None => {
+                let fresh = self
+                    .session
+                    .lock()
+                    .map_err(|_| "session state mutex poisoned")?
+                    .tool_catalog_fresh(now);
+                if fresh {
+                    None
+                } else {
+                    fetch_catalog(program, base_env, now)
+                }
+            }
+        };
+        let Some(next) = next else {
+            return Ok(None);
+        };
+        Ok(self
+            .session
+            .lock()
+            .map_err(|_| "session state mutex poisoned")?
+            .update_tool_catalog(next))
+    }
+
+    /// Compare the resolved CLI against the minimum supported version, once per binary.
+    fn version_status(
+        &self,
+        program: &str,
+        base_env: &[(String, String)],
+    ) -> Result<VersionStatus, String> {
+        let mut checks = self
+            .version_checks
+            .lock()
+            .map_err(|_| "version check mutex poisoned")?;
+        if let Some(status) = checks.get(program) {
+            return Ok(status.clone());
+        }
+
+        let status = check_version(probe_version(program, base_env).as_deref());
+        if let VersionStatus::Outdated(ref found) = status {
+            log_msg(
+                LogLevel::Warn,
+                &format!("loom at {} is outdated (v{})", program, found),
+            );
+        }
+        checks.insert(program.to_string(), status.clone());
+        Ok(status)
+    }
 }
 
 zed::register_extension!(LoomExtension);
@@ -1,5 +1,17 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Log lines kept in memory for extension error reports.
+const RECENT_LOG_CAPACITY: usize = 100;
+
+thread_local! {
+    // Zed runs extensions single-threaded; the log tail and ids are per extension instance.
+    static RECENT_LOG: RefCell<VecDeque<String>> = const { RefCell::new(VecDeque::new()) };
+    static NEXT_CORRELATION: Cell<u32> = const { Cell::new(1) };
+    static CURRENT_CORRELATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[allow(dead_code)]
 pub(crate) enum LogLevel {
@@ -25,7 +37,53 @@ pub(crate) fn log_msg(level: LogLevel, msg: &str) {
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_secs())
         .unwrap_or(0);
-    eprintln!("[loom-zed {} {}] {}", ts, level.label(), msg);
+    let correlation = CURRENT_CORRELATION.with(|c| c.borrow().clone());
+    let line = match correlation {
+        Some(id) => format!("[loom-zed {} {} {}] {}", ts, level.label(), id, msg),
+        None => format!("[loom-zed {} {}] {}", ts, level.label(), msg),
+    };
+    eprintln!("{}", line);
+    RECENT_LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        if log.len() == RECENT_LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(line);
+    });
+}
+
+/// The last `n` lines logged by the extension, oldest first.
+pub(crate) fn recent_log_lines(n: usize) -> Vec<String> {
+    RECENT_LOG.with(|log| {
+        let log = log.borrow();
+        log.iter()
+            .skip(log.len().saturating_sub(n))
+            .cloned()
+            .collect()
+    })
+}
+
+/// Start a new slash command: allocate its correlation id (`<unix secs hex>-<seq>`) and tag
+/// every log line with it until the next call.
+pub(crate) fn begin_correlation(now_secs: u64) -> String {
+    let seq = NEXT_CORRELATION.with(|n| {
+        let seq = n.get();
+        n.set(seq.wrapping_add(1));
+        seq
+    });
+    let id = format!("{:x}-{}", now_secs, seq);
+    CURRENT_CORRELATION.with(|c| *c.borrow_mut() = Some(id.clone()));
+    id
+}
+
+/// Log panics (with the current correlation id) before the WASM runtime aborts.
+///
+/// Extensions are built without unwinding, so a panic can't be turned into a report; this at
+/// least leaves the message and location in Zed's log.
+pub(crate) fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        log_msg(LogLevel::Error, &format!("panic: {}", info));
+    }));
 }
 
 #[cfg(test)]
@@ -39,6 +97,19 @@ mod tests {
         assert!(LogLevel::Warn < LogLevel::Error);
     }
 
+    #[test]
+    fn log_tail_carries_correlation_id() {
+        let id = begin_correlation(0x6500_0000);
+        assert!(id.starts_with("65000000-"));
+        log_msg(LogLevel::Info, "first");
+        log_msg(LogLevel::Warn, "second");
+        let tail = recent_log_lines(2);
+        assert_eq!(tail.len(), 2);
+        assert!(tail[0].ends_with(&format!("INFO {}] first", id)));
+        assert!(tail[1].contains("second"));
+        assert_ne!(begin_correlation(0x6500_0000), id);
+    }
+
     #[test]
     fn level_labels() {
         assert_eq!(LogLevel::Debug.label(), "DEBUG");
@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::download::{self, LoomInstall};
+use crate::env::{current_path_sep, shell_env_to_vec, upsert_env, with_path_prefix};
+use crate::log::{log_msg, LogLevel};
+use crate::settings::{LoomDownloadSettings, LoomRuntimeSettings, McpWrapperSettings};
+use crate::WRAPPER_SCRIPT;
+use zed_extension_api as zed;
+
+/// Locate `loom` through the host (POSIX `which`, Windows `where`) or well-known install dirs.
+///
+/// Zed may not search the system `PATH` when spawning extension-provided context servers, so
+/// this returns an absolute path (or `None`).
+fn host_loom_path() -> Option<String> {
+    for locator in ["which", "where"] {
+        if let Ok(output) = zed::process::Command::new(locator).arg("loom").output() {
+            if output.status == Some(0) {
+                let first_line = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .to_string();
+                if !first_line.is_empty() {
+                    return Some(first_line);
+                }
+            }
+        }
+    }
+
+    let home = std::env::var("HOME").unwrap_or_default();
+    [
+        format!("{home}/.local/bin/loom"),
+        "/usr/local/bin/loom".to_string(),
+        "/opt/homebrew/bin/loom".to_string(),
+    ]
+    .into_iter()
+    .find(|candidate| Path::new(candidate).exists())
+}
+
+/// Absolute path to the bundled MCP wrapper script, if it was shipped with the extension.
+pub(crate) fn wrapper_script_path() -> Option<String> {
+    std::env::current_dir()
+        .ok()
+        .map(|d| d.join(WRAPPER_SCRIPT))
+        .filter(|p| p.exists())
+        .and_then(|p| p.to_str().map(|s| s.to_string()))
+        .or_else(|| {
+            let rel = Path::new(WRAPPER_SCRIPT);
+            rel.exists().then(|| rel.to_string_lossy().to_string())
+        })
+}
+
+/// Python for the wrapper: `settings.mcp.wrapper.python`, else the first of `python3`/`python`
+/// that runs.
+pub(crate) fn resolve_python(settings: &McpWrapperSettings) -> Option<String> {
+    settings.python().map(|s| s.to_string()).or_else(|| {
+        ["python3", "python"].into_iter().find_map(|cand| {
+            let output = zed::process::Command::new(cand)
+                .arg("--version")
+                .output()
+                .ok()?;
+            (output.status == Some(0)).then(|| cand.to_string())
+        })
+    })
+}
+
+/// Where a resolved loom binary came from, in precedence order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BinarySource {
+    /// `context_servers.loom.command.path`.
+    CommandPath,
+    /// `loom` on the worktree's shell `PATH`.
+    WorktreePath,
+    /// `loom` found through the host (`which`/`where` or a well-known dir).
+    HostPath,
+    /// Downloaded by the extension per `settings.download`.
+    ManagedInstall,
+    /// Nothing found and downloads disabled: run bare `loom` and hope it's on `PATH`.
+    Fallback,
+}
+
+impl BinarySource {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::CommandPath => "command.path",
+            Self::WorktreePath => "worktree PATH",
+            Self::HostPath => "host PATH",
+            Self::ManagedInstall => "managed install",
+            Self::Fallback => "bare `loom`",
+        }
+    }
+
+    /// Every source, in the order [`choose_binary`] tries them.
+    pub(crate) const PRECEDENCE: &'static [BinarySource] = &[
+        Self::CommandPath,
+        Self::WorktreePath,
+        Self::HostPath,
+        Self::ManagedInstall,
+        Self::Fallback,
+    ];
+
+    /// One line on why a binary from this source was picked, for `/loom-env`.
+    pub(crate) fn explanation(self) -> &'static str {
+        match self {
+            Self::CommandPath => "set explicitly in `context_servers.loom.command.path`",
+            Self::WorktreePath => "`loom` found on the worktree shell's `PATH`",
+            Self::HostPath => {
+                "`loom` found by probing the host (`which` or a well-known directory)"
+            }
+            Self::ManagedInstall => "downloaded by the extension (`settings.download`)",
+            Self::Fallback => {
+                "nothing found and downloads disabled; runs `loom` and relies on `PATH`"
+            }
+        }
+    }
+}
+
+/// Pick a binary by precedence: explicit path > worktree `which` > host probe > managed
+/// download > bare `loom`.
+///
+/// Probes are lazy so cheaper sources short-circuit the host calls. `ManagedInstall` carries
+/// no path; the caller downloads (or reuses) the install.
+fn choose_binary(
+    explicit: Option<&str>,
+    worktree_which: impl FnOnce() -> Option<String>,
+    host: impl FnOnce() -> Option<String>,
+    download_enabled: bool,
+) -> (BinarySource, Option<String>) {
+    if let Some(path) = explicit.map(str::trim).filter(|p| !p.is_empty()) {
+        return (BinarySource::CommandPath, Some(path.to_string()));
+    }
+    if let Some(path) = worktree_which() {
+        return (BinarySource::WorktreePath, Some(path));
+    }
+    if let Some(path) = host() {
+        return (BinarySource::HostPath, Some(path));
+    }
+    if download_enabled {
+        (BinarySource::ManagedInstall, None)
+    } else {
+        (BinarySource::Fallback, Some("loom".to_string()))
+    }
+}
+
+/// A loom binary picked by [`resolve_loom`].
+pub(crate) struct Resolution {
+    pub(crate) program: String,
+    pub(crate) source: BinarySource,
+    /// Set for [`BinarySource::ManagedInstall`]; its `bin_dir` belongs on `PATH`.
+    pub(crate) install: Option<LoomInstall>,
+}
+
+/// Resolve the loom binary shared by the context server and slash commands.
+pub(crate) fn resolve_loom(
+    installs: &Mutex<HashMap<String, LoomInstall>>,
+    worktree: Option<&zed::Worktree>,
+    explicit_path: Option<&str>,
+    download: &LoomDownloadSettings,
+) -> Result<Resolution, String> {
+    let (source, path) = choose_binary(
+        explicit_path,
+        || worktree.and_then(|wt| wt.which("loom")),
+        host_loom_path,
+        download.enabled(),
+    );
+    if let Some(program) = path {
+        return Ok(Resolution {
+            program,
+            source,
+            install: None,
+        });
+    }
+
+    log_msg(
+        LogLevel::Info,
+        &format!("downloading loom-core from {}", download.repo()),
+    );
+    let install = download::ensure_loom_install(installs, download)?;
+    Ok(Resolution {
+        program: install.loom_path.clone(),
+        source,
+        install: Some(install),
+    })
+}
+
+/// The loom binary chosen for a slash command and the environment to run it with.
+pub(crate) struct ResolvedBinary {
+    pub(crate) program: String,
+    pub(crate) source: BinarySource,
+    pub(crate) env: Vec<(String, String)>,
+    /// Whether `env` started from the worktree's shell environment (else Zed's own `PATH`).
+    pub(crate) from_shell_env: bool,
+    /// Directory put in front of `PATH` (the managed install's `bin_dir`).
+    pub(crate) path_prefix: Option<String>,
+    /// One-off `(label, markdown)` notes about how the binary was obtained.
+    pub(crate) notices: Vec<(String, String)>,
+}
+
+/// Resolve the loom binary for a slash command and build its base environment.
+pub(crate) fn resolve_binary(
+    installs: &Mutex<HashMap<String, LoomInstall>>,
+    worktree: Option<&zed::Worktree>,
+    runtime_settings: Option<&LoomRuntimeSettings>,
+) -> Result<ResolvedBinary, String> {
+    let mut base_env = worktree
+        .map(|wt| shell_env_to_vec(&wt.shell_env()))
+        .unwrap_or_default();
+    let from_shell_env = !base_env.is_empty();
+    if base_env.is_empty() {
+        if let Ok(path) = std::env::var("PATH") {
+            base_env.push(("PATH".to_string(), path));
+        }
+    }
+
+    if let Some(rt) = runtime_settings {
+        for (k, v) in &rt.command_env {
+            upsert_env(&mut base_env, k, v);
+        }
+    }
+
+    let download_settings = runtime_settings
+        .map(|rt| rt.extension.download.clone())
+        .unwrap_or_default();
+    let resolution = resolve_loom(
+        installs,
+        worktree,
+        runtime_settings.and_then(|rt| rt.command_path.as_deref()),
+        &download_settings,
+    )?;
+    let mut resolved = ResolvedBinary {
+        program: resolution.program,
+        source: resolution.source,
+        env: base_env,
+        from_shell_env,
+        path_prefix: None,
+        notices: Vec::new(),
+    };
+    let Some(install) = resolution.install else {
+        return Ok(resolved);
+    };
+    resolved.env = with_path_prefix(resolved.env, &install.bin_dir, current_path_sep());
+    resolved.path_prefix = Some(install.bin_dir.clone());
+    if let Some(report) = install.startup_report {
+        resolved.notices.push(("Install".to_string(), report));
+    }
+    Ok(resolved)
+}
+
+/// A loom binary found by one of the resolution strategies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BinaryCandidate {
+    pub(crate) source: BinarySource,
+    pub(crate) path: String,
+}
+
+/// Every loom binary the resolution strategies can see, without downloading anything.
+pub(crate) fn binary_candidates(
+    installs: &Mutex<HashMap<String, LoomInstall>>,
+    worktree: Option<&zed::Worktree>,
+    runtime_settings: Option<&LoomRuntimeSettings>,
+) -> Vec<BinaryCandidate> {
+    let mut candidates = Vec::new();
+    if let Some(path) = runtime_settings
+        .and_then(|rt| rt.command_path.as_ref())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        candidates.push(BinaryCandidate {
+            source: BinarySource::CommandPath,
+            path,
+        });
+    }
+    if let Some(path) = worktree.and_then(|wt| wt.which("loom")) {
+        candidates.push(BinaryCandidate {
+            source: BinarySource::WorktreePath,
+            path,
+        });
+    }
+    if let Some(path) = host_loom_path() {
+        candidates.push(BinaryCandidate {
+            source: BinarySource::HostPath,
+            path,
+        });
+    }
+    if let Ok(installs) = installs.lock() {
+        for install in installs.values() {
+            if install.is_usable() {
+                candidates.push(BinaryCandidate {
+                    source: BinarySource::ManagedInstall,
+                    path: install.loom_path.clone(),
+                });
+            }
+        }
+    }
+    candidates
+}
+
+/// Markdown warning when the visible loom binaries disagree on path or version.
+///
+/// `candidates` pairs each binary with its probed version (if any); `chosen` is the path the
+/// slash commands will run. Returns `None` when there is only one distinct binary.
+pub(crate) fn binary_skew_warning(
+    candidates: &[(BinaryCandidate, Option<String>)],
+    chosen: &str,
+) -> Option<String> {
+    let mut paths: Vec<&str> = candidates.iter().map(|(c, _)| c.path.as_str()).collect();
+    paths.sort_unstable();
+    paths.dedup();
+    let mut versions: Vec<&str> = candidates
+        .iter()
+        .filter_map(|(_, v)| v.as_deref())
+        .collect();
+    versions.sort_unstable();
+    versions.dedup();
+    if paths.len() < 2 && versions.len() < 2 {
+        return None;
+    }
+
+    let mut text = String::from(
+        "> ⚠️ **Multiple loom binaries found.** Version skew between them can cause confusing \
+         behavior (e.g. the context server and slash commands talking to different builds).\n\n\
+         | Source | Path | Version | Used |\n| --- | --- | --- | --- |\n",
+    );
+    for (candidate, version) in candidates {
+        text.push_str(&format!(
+            "| {} | `{}` | {} | {} |\n",
+            candidate.source.label(),
+            candidate.path,
+            version
+                .as_deref()
+                .map(|v| format!("v{}", v))
+                .unwrap_or_else(|| "?".into()),
+            if candidate.path == chosen { "✅" } else { "" }
+        ));
+    }
+    text.push_str(
+        "\nSet `context_servers.loom.command.path` to pin one binary, or remove the extras.\n\n",
+    );
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn some(path: &str) -> impl FnOnce() -> Option<String> + '_ {
+        move || Some(path.to_string())
+    }
+
+    fn unreachable_probe() -> Option<String> {
+        panic!("lower-precedence source should not be probed")
+    }
+
+    #[test]
+    fn explicit_path_wins() {
+        assert_eq!(
+            choose_binary(
+                Some(" /opt/loom "),
+                unreachable_probe,
+                unreachable_probe,
+                true
+            ),
+            (BinarySource::CommandPath, Some("/opt/loom".into()))
+        );
+    }
+
+    #[test]
+    fn blank_explicit_path_is_ignored() {
+        assert_eq!(
+            choose_binary(Some("  "), some("/wt/loom"), unreachable_probe, true),
+            (BinarySource::WorktreePath, Some("/wt/loom".into()))
+        );
+    }
+
+    #[test]
+    fn worktree_before_host() {
+        assert_eq!(
+            choose_binary(None, some("/wt/loom"), unreachable_probe, false),
+            (BinarySource::WorktreePath, Some("/wt/loom".into()))
+        );
+    }
+
+    #[test]
+    fn host_before_download() {
+        assert_eq!(
+            choose_binary(None, || None, some("/usr/local/bin/loom"), true),
+            (BinarySource::HostPath, Some("/usr/local/bin/loom".into()))
+        );
+    }
+
+    #[test]
+    fn download_when_nothing_local() {
+        assert_eq!(
+            choose_binary(None, || None, || None, true),
+            (BinarySource::ManagedInstall, None)
+        );
+    }
+
+    #[test]
+    fn bare_loom_when_download_disabled() {
+        assert_eq!(
+            choose_binary(None, || None, || None, false),
+            (BinarySource::Fallback, Some("loom".into()))
+        );
+    }
+
+    fn candidate(source: BinarySource, path: &str) -> BinaryCandidate {
+        BinaryCandidate {
+            source,
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn skew_warning_only_when_binaries_disagree() {
+        let same = vec![
+            (
+                candidate(BinarySource::WorktreePath, "/usr/local/bin/loom"),
+                Some("0.9.1".into()),
+            ),
+            (
+                candidate(BinarySource::HostPath, "/usr/local/bin/loom"),
+                Some("0.9.1".into()),
+            ),
+        ];
+        assert!(binary_skew_warning(&same, "/usr/local/bin/loom").is_none());
+        assert!(binary_skew_warning(&[], "loom").is_none());
+
+        let skewed = vec![
+            (
+                candidate(BinarySource::WorktreePath, "/usr/local/bin/loom"),
+                Some("0.8.0".into()),
+            ),
+            (
+                candidate(BinarySource::ManagedInstall, "/ext/loom-core/loom"),
+                Some("0.9.1".into()),
+            ),
+        ];
+        let warning = binary_skew_warning(&skewed, "/usr/local/bin/loom").unwrap();
+        assert!(warning.contains("| worktree PATH | `/usr/local/bin/loom` | v0.8.0 | ✅ |"));
+        assert!(warning.contains("| managed install | `/ext/loom-core/loom` | v0.9.1 |  |"));
+    }
+}
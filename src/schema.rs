@@ -0,0 +1,221 @@
+use zed_extension_api::serde_json::{Map, Value};
+
+/// One way a tool payload breaks its input schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SchemaViolation {
+    /// Dotted path to the offending field (`filters.limit`, `ids[2]`), empty for the root.
+    pub(crate) path: String,
+    pub(crate) message: String,
+}
+
+/// Check `value` against the subset of JSON Schema tool schemas actually use: `type`,
+/// `required`, `properties`, `additionalProperties: false`, `enum`, and `items`.
+///
+/// Anything else (`oneOf`, `$ref`, formats, ranges) is left to the daemon, so an empty result
+/// means "nothing obviously wrong", not "valid".
+pub(crate) fn validate(value: &Value, schema: &Value) -> Vec<SchemaViolation> {
+    let mut out = Vec::new();
+    check(value, schema, "", &mut out);
+    out
+}
+
+fn check(value: &Value, schema: &Value, path: &str, out: &mut Vec<SchemaViolation>) {
+    let mut violation = |message: String| {
+        out.push(SchemaViolation {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| has_type(value, t)) {
+            violation(format!(
+                "expected {}, got {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            let listed: Vec<String> = options.iter().map(Value::to_string).collect();
+            violation(format!("{} is not one of {}", value, listed.join(", ")));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => check_object(fields, schema, path, out),
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items").filter(|s| s.is_object()) {
+                for (i, item) in items.iter().enumerate() {
+                    check(item, item_schema, &format!("{}[{}]", path, i), out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_object(
+    fields: &Map<String, Value>,
+    schema: &Value,
+    path: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    for key in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !fields.contains_key(key) {
+            out.push(SchemaViolation {
+                path: child(key),
+                message: "required field is missing".into(),
+            });
+        }
+    }
+
+    let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+    for (key, field) in fields {
+        match properties.and_then(|p| p.get(key)) {
+            Some(field_schema) => check(field, field_schema, &child(key), out),
+            None if closed => {
+                let known: Vec<&str> = properties
+                    .map(|p| p.keys().map(String::as_str).collect())
+                    .unwrap_or_default();
+                out.push(SchemaViolation {
+                    path: child(key),
+                    message: if known.is_empty() {
+                        "unknown field".into()
+                    } else {
+                        format!("unknown field (expected one of {})", known.join(", "))
+                    },
+                });
+            }
+            None => {}
+        }
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        // Unknown type keywords are not ours to reject.
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed_extension_api::serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["query", "mode"],
+            "additionalProperties": false,
+            "properties": {
+                "query": {"type": "string"},
+                "mode": {"type": "string", "enum": ["fast", "deep"]},
+                "limit": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "filters": {
+                    "type": "object",
+                    "properties": {"since": {"type": ["string", "null"]}}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn valid_payload_passes() {
+        let payload = json!({
+            "query": "auth",
+            "mode": "deep",
+            "limit": 5,
+            "tags": ["a"],
+            "filters": {"since": null}
+        });
+        assert!(validate(&payload, &schema()).is_empty());
+    }
+
+    #[test]
+    fn reports_each_field_problem() {
+        let payload = json!({
+            "mode": "slow",
+            "limit": 2.5,
+            "tags": ["a", 3],
+            "filters": {"since": 7},
+            "extra": true
+        });
+        let found: Vec<(String, String)> = validate(&payload, &schema())
+            .into_iter()
+            .map(|v| (v.path, v.message))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("query".into(), "required field is missing".into()),
+                (
+                    "extra".into(),
+                    "unknown field (expected one of filters, limit, mode, query, tags)".into()
+                ),
+                (
+                    "filters.since".into(),
+                    "expected string or null, got integer".into()
+                ),
+                ("limit".into(), "expected integer, got number".into()),
+                (
+                    "mode".into(),
+                    "\"slow\" is not one of \"fast\", \"deep\"".into()
+                ),
+                ("tags[1]".into(), "expected string, got integer".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn root_type_mismatch() {
+        let found = validate(&json!(["x"]), &schema());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "");
+        assert_eq!(found[0].message, "expected object, got array");
+    }
+}
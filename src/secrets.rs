@@ -0,0 +1,258 @@
+use zed_extension_api::serde_json::Value;
+
+use crate::format::CommandResult;
+
+/// Worktree files that commonly hold environment variables, in the order hints mention them.
+pub(crate) const ENV_FILES: &[&str] = &[".envrc", ".env"];
+
+/// Secret backends that are not environment variables; failures for these get no env hint.
+const NON_ENV_BACKENDS: &[&str] = &[
+    "keychain",
+    "keyring",
+    "vault",
+    "1password",
+    "op://",
+    "file:",
+];
+
+/// Words in validation output that look like env var names but aren't.
+const NOT_VAR_NAMES: &[&str] = &[
+    "ENV", "MISSING", "ERROR", "WARN", "WARNING", "FAIL", "NOT", "SET",
+];
+
+/// Where a missing env-backed secret is (or isn't) defined on the user's side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SecretHint {
+    pub(crate) name: String,
+    /// Worktree env files (see [`ENV_FILES`]) that assign a non-empty value.
+    pub(crate) defined_in: Vec<&'static str>,
+    /// Set in the worktree's shell environment (what `/loom-restart` would pass on).
+    pub(crate) in_shell: bool,
+}
+
+impl SecretHint {
+    /// One Markdown bullet telling the user where to add (or how to pass on) the variable.
+    pub(crate) fn advice(&self, shell: Option<&str>) -> String {
+        let name = &self.name;
+        if self.in_shell {
+            return format!(
+                "`{name}` is set in your shell environment, but the daemon was started without \
+                 it. Run `/loom-restart` so it inherits the variable."
+            );
+        }
+        if self.defined_in.contains(&".envrc") {
+            return format!(
+                "`{name}` is in the worktree's `.envrc` but not in your shell environment, so \
+                 direnv hasn't loaded it. Run `direnv allow` in the worktree, then `/loom-restart`."
+            );
+        }
+        let (profile, line) = shell_profile(shell, name);
+        if self.defined_in.contains(&".env") {
+            return format!(
+                "`{name}` is in the worktree's `.env`, but loom doesn't read `.env` files. Add \
+                 `{line}` to `{profile}` (or `dotenv` to an `.envrc` with direnv), then \
+                 `/loom-restart`."
+            );
+        }
+        format!(
+            "`{name}` isn't set anywhere Zed can see. Add `{line}` to `{profile}` (or to the \
+             worktree's `.envrc` if you use direnv), then `/loom-restart`."
+        )
+    }
+}
+
+/// Profile file for the user's shell (from `$SHELL`) and the line that exports `name` there.
+fn shell_profile(shell: Option<&str>, name: &str) -> (&'static str, String) {
+    let shell = shell.and_then(|s| s.rsplit('/').next()).unwrap_or_default();
+    match shell {
+        "zsh" => ("~/.zshrc", format!("export {name}=...")),
+        "bash" => ("~/.bashrc", format!("export {name}=...")),
+        "fish" => ("~/.config/fish/config.fish", format!("set -gx {name} ...")),
+        _ => ("~/.profile", format!("export {name}=...")),
+    }
+}
+
+/// Cross-check missing env-backed secrets against worktree env files and the shell env.
+///
+/// `env_files` pairs each of [`ENV_FILES`] with its contents (when the file exists).
+pub(crate) fn hints(
+    missing: &[String],
+    env_files: &[(&'static str, String)],
+    shell_env: &[(String, String)],
+) -> Vec<SecretHint> {
+    missing
+        .iter()
+        .map(|name| SecretHint {
+            name: name.clone(),
+            defined_in: env_files
+                .iter()
+                .filter(|(_, text)| env_file_defines(text, name))
+                .map(|(file, _)| *file)
+                .collect(),
+            in_shell: shell_env.iter().any(|(k, v)| k == name && !v.is_empty()),
+        })
+        .collect()
+}
+
+/// Whether a `.env`/`.envrc` assigns `name` a non-empty value (`NAME=x` or `export NAME=x`).
+fn env_file_defines(text: &str, name: &str) -> bool {
+    text.lines().any(|line| {
+        let line = line.trim();
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        line.split_once('=').is_some_and(|(key, value)| {
+            key.trim() == name && !value.trim().trim_matches(['"', '\'']).is_empty()
+        })
+    })
+}
+
+/// Names of env-backed secrets that `loom secrets validate` reports as missing.
+///
+/// Reads JSON (`{"secrets": [...]}` or a bare array) when available, else failing text lines
+/// such as `✗ GITHUB_TOKEN (env): not set`. Secrets from other backends are skipped.
+pub(crate) fn missing_env_secrets(result: &CommandResult) -> Vec<String> {
+    let found: Vec<String> = match json_missing(&result.stdout) {
+        Some(names) => names,
+        None => result
+            .stdout
+            .lines()
+            .chain(result.stderr.lines())
+            .filter_map(text_missing)
+            .collect(),
+    };
+    let mut names: Vec<String> = Vec::new();
+    for name in found {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn json_missing(stdout: &str) -> Option<Vec<String>> {
+    let parsed: Value = zed_extension_api::serde_json::from_str(stdout.trim()).ok()?;
+    let items = parsed
+        .get("secrets")
+        .or_else(|| parsed.get("results"))
+        .unwrap_or(&parsed)
+        .as_array()?;
+    Some(
+        items
+            .iter()
+            .filter_map(|item| {
+                let text = |k: &str| item.get(k).and_then(Value::as_str).unwrap_or_default();
+                let backend = [text("source"), text("backend"), text("type")].join(" ");
+                let env_name = ["env", "env_var", "variable"]
+                    .iter()
+                    .map(|k| text(k))
+                    .find(|v| !v.is_empty());
+                let is_env = env_name.is_some() || backend.to_ascii_lowercase().contains("env");
+                let status = text("status").to_ascii_lowercase();
+                let missing = ["missing", "not set", "unset", "empty", "invalid"]
+                    .iter()
+                    .any(|s| status.contains(s))
+                    || item.get("valid").and_then(Value::as_bool) == Some(false)
+                    || item.get("present").and_then(Value::as_bool) == Some(false);
+                (is_env && missing)
+                    .then(|| env_name.unwrap_or_else(|| text("name")).to_string())
+                    .filter(|n| !n.is_empty())
+            })
+            .collect(),
+    )
+}
+
+fn text_missing(line: &str) -> Option<String> {
+    let lower = line.to_ascii_lowercase();
+    let failed = ['✗', '❌', '⚠'].iter().any(|m| line.contains(*m))
+        || ["missing", "not set", "unset"]
+            .iter()
+            .any(|m| lower.contains(m));
+    if !failed || NON_ENV_BACKENDS.iter().any(|b| lower.contains(b)) {
+        return None;
+    }
+    env_var_in(line)
+}
+
+/// The env var a line talks about: `env:NAME`, `$NAME`/`${NAME}`, or an `UPPER_SNAKE` word.
+fn env_var_in(line: &str) -> Option<String> {
+    let is_var_char = |c: char| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_';
+    for marker in ["env:", "${", "$"] {
+        if let Some(idx) = line.find(marker) {
+            let name: String = line[idx + marker.len()..]
+                .chars()
+                .take_while(|c| is_var_char(*c))
+                .collect();
+            if !name.is_empty() {
+                return Some(name);
+            }
+        }
+    }
+    line.split(|c: char| !is_var_char(c))
+        .find(|word| {
+            word.len() >= 3
+                && word.starts_with(|c: char| c.is_ascii_uppercase())
+                && (word.contains('_') || word.len() >= 4)
+                && !NOT_VAR_NAMES.contains(word)
+        })
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(stdout: &str) -> CommandResult {
+        CommandResult {
+            exit_code: "1".into(),
+            stdout: stdout.into(),
+            stderr: String::new(),
+        }
+    }
+
+    #[test]
+    fn missing_from_text_output() {
+        let out = "✓ SLACK_TOKEN (env)\n✗ GITHUB_TOKEN (env): not set\n\
+                   ✗ vault:kv/tavily: missing\nmissing: env:K8S_TOKEN\n";
+        assert_eq!(
+            missing_env_secrets(&result(out)),
+            vec!["GITHUB_TOKEN", "K8S_TOKEN"]
+        );
+    }
+
+    #[test]
+    fn missing_from_json_output() {
+        let out = r#"{"secrets": [
+            {"name": "github", "source": "env", "env": "GITHUB_TOKEN", "status": "missing"},
+            {"name": "TAVILY_API_KEY", "source": "env", "valid": false},
+            {"name": "vault-token", "source": "vault", "status": "missing"},
+            {"name": "SLACK_TOKEN", "source": "env", "status": "ok"}
+        ]}"#;
+        assert_eq!(
+            missing_env_secrets(&result(out)),
+            vec!["GITHUB_TOKEN", "TAVILY_API_KEY"]
+        );
+    }
+
+    #[test]
+    fn hints_cross_check_files_and_shell() {
+        let files = [
+            (".envrc", "export A_TOKEN=abc\n".to_string()),
+            (".env", "B_TOKEN=\"xyz\"\nC_TOKEN=\n".to_string()),
+        ];
+        let shell = vec![("D_TOKEN".to_string(), "1".to_string())];
+        let missing: Vec<String> = ["A_TOKEN", "B_TOKEN", "C_TOKEN", "D_TOKEN"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let hints = hints(&missing, &files, &shell);
+        assert_eq!(hints[0].defined_in, vec![".envrc"]);
+        assert!(hints[0].advice(None).contains("direnv allow"));
+        assert!(hints[1].advice(Some("/bin/zsh")).contains("`~/.zshrc`"));
+        assert!(hints[1].advice(None).contains("doesn't read `.env`"));
+        assert!(hints[2].defined_in.is_empty());
+        assert!(hints[2]
+            .advice(Some("/usr/bin/fish"))
+            .contains("`set -gx C_TOKEN ...`"));
+        assert!(hints[3].in_shell);
+        assert!(hints[3].advice(None).contains("/loom-restart"));
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::Deserialize;
 use zed_extension_api as zed;
 
@@ -18,10 +20,15 @@ pub(crate) struct LoomExtensionSettings {
     #[serde(default)]
     pub(crate) download: LoomDownloadSettings,
     #[serde(default)]
-    #[allow(dead_code)] // consumed by dispatch_session/heartbeat/task in future
     pub(crate) agent: AgentSettings,
     #[serde(default)]
     pub(crate) mcp: McpSettings,
+    #[serde(default)]
+    pub(crate) commands: CommandsSettings,
+    #[serde(default)]
+    pub(crate) formatter: FormatterSettings,
+    #[serde(default)]
+    pub(crate) display: DisplaySettings,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -35,6 +42,8 @@ pub(crate) struct LoomDownloadSettings {
     pub(crate) tag: Option<String>,
     /// Exact GitHub release asset name to download (advanced override).
     pub(crate) asset: Option<String>,
+    /// If true, run `loom start` after a fresh download when no daemon is running.
+    pub(crate) auto_start_daemon: Option<bool>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -42,8 +51,10 @@ pub(crate) struct LoomDownloadSettings {
 pub(crate) struct AgentSettings {
     /// Agent identifier used for session/heartbeat/task operations.
     pub(crate) agent_id: Option<String>,
-    /// Default namespace for sessions (e.g. "project/branch").
+    /// Default namespace for sessions and `/loom-context` recalls (e.g. "project/branch").
     pub(crate) default_namespace: Option<String>,
+    /// If true, record each slash command as a lightweight session event.
+    pub(crate) log_commands: Option<bool>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -80,6 +91,80 @@ pub(crate) struct McpResourcesSettings {
     pub(crate) enabled: Option<bool>,
     /// If true, expose a (potentially expensive) diagnostics resource that runs `loom check`.
     pub(crate) include_diagnostics: Option<bool>,
+    /// Maximum number of recent agent memory entries exposed as resources (0 disables).
+    pub(crate) memory_limit: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub(crate) struct CommandsSettings {
+    /// Minimum seconds between real runs of a slash command (e.g. `{"loom-dashboard": 10}`).
+    #[serde(default)]
+    pub(crate) throttle: HashMap<String, u64>,
+    /// When set, only these commands (or `@group`s) may run; `/loom-help` always can.
+    pub(crate) enabled: Option<Vec<String>>,
+    /// Commands (or `@group`s) that may not run, applied after `enabled`.
+    #[serde(default)]
+    pub(crate) disabled: Vec<String>,
+    /// Tool `/loom-benchmark` calls when none is given; should be cheap and side-effect free.
+    pub(crate) benchmark_tool: Option<String>,
+    /// Shortcuts run with `/loom-alias <name>` (e.g. `{"st": "status verbose"}`).
+    #[serde(default)]
+    pub(crate) aliases: HashMap<String, String>,
+}
+
+/// Command groups usable as `@group` in `commands.enabled` / `commands.disabled`.
+pub(crate) const COMMAND_GROUPS: &[(&str, &[&str])] = &[
+    ("daemon", &["start", "stop", "restart", "kill", "daemon"]),
+    ("secrets", &["secrets"]),
+    ("install", &["update", "install", "cleanup"]),
+    (
+        "agent",
+        &[
+            "session",
+            "summary",
+            "heartbeat",
+            "agents",
+            "task",
+            "recall",
+            "context",
+            "memory",
+            "forget",
+            "handoff",
+            "namespace",
+            "inbox",
+        ],
+    ),
+    (
+        "tools",
+        &[
+            "tools",
+            "call",
+            "trace",
+            "permissions",
+            "skills",
+            "search",
+            "workflows",
+            "benchmark",
+            "favorites",
+        ],
+    ),
+    (
+        "config",
+        &["sync", "profile", "config", "backup", "restore"],
+    ),
+];
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub(crate) struct FormatterSettings {
+    /// Language for section labels and fixed strings in slash command output (e.g. "de").
+    pub(crate) locale: Option<String>,
+    /// Per-string replacements keyed by the English text (e.g. `{"Errors": "Problèmes"}`).
+    #[serde(default)]
+    pub(crate) labels: HashMap<String, String>,
+    /// Extra secret regexes masked in slash command output, on top of the built-ins
+    /// (e.g. `"acme_([a-z0-9]{16})"`).
+    #[serde(default)]
+    pub(crate) redact_patterns: Vec<String>,
 }
 
 impl Default for AgentSettings {
@@ -87,15 +172,19 @@ impl Default for AgentSettings {
         Self {
             agent_id: Some("zed-loom".to_string()),
             default_namespace: None,
+            log_commands: None,
         }
     }
 }
 
 impl AgentSettings {
-    #[allow(dead_code)] // used by dispatch functions in future phases
     pub(crate) fn agent_id(&self) -> &str {
         self.agent_id.as_deref().unwrap_or("zed-loom")
     }
+
+    pub(crate) fn log_commands(&self) -> bool {
+        self.log_commands.unwrap_or(false)
+    }
 }
 
 impl LoomDownloadSettings {
@@ -109,6 +198,10 @@ impl LoomDownloadSettings {
             .unwrap_or(DEFAULT_LOOM_CORE_REPO)
             .trim()
     }
+
+    pub(crate) fn auto_start_daemon(&self) -> bool {
+        self.auto_start_daemon.unwrap_or(true)
+    }
 }
 
 impl McpWrapperSettings {
@@ -149,6 +242,87 @@ impl McpResourcesSettings {
     pub(crate) fn include_diagnostics(&self) -> bool {
         self.include_diagnostics.unwrap_or(false)
     }
+
+    pub(crate) fn memory_limit(&self) -> u64 {
+        self.memory_limit.unwrap_or(20)
+    }
+}
+
+impl CommandsSettings {
+    pub(crate) fn benchmark_tool(&self) -> &str {
+        self.benchmark_tool
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or("agent_memory_stats")
+    }
+
+    /// Throttle window for `command_name`; keys may omit the `loom-` prefix. 0 disables.
+    pub(crate) fn throttle_secs(&self, command_name: &str) -> Option<u64> {
+        let short = command_name.strip_prefix("loom-").unwrap_or(command_name);
+        self.throttle
+            .get(command_name)
+            .or_else(|| self.throttle.get(short))
+            .copied()
+            .filter(|secs| *secs > 0)
+    }
+
+    /// Policy message when `command_name` may not run (`None` if it may).
+    pub(crate) fn policy_block(&self, command_name: &str) -> Option<String> {
+        let short = command_name.strip_prefix("loom-").unwrap_or(command_name);
+        if short == "help" {
+            return None;
+        }
+        if let Some(enabled) = &self.enabled {
+            if !enabled.iter().any(|e| command_entry_matches(e, short)) {
+                return Some(format!(
+                    "`/loom-{}` is disabled by policy: it is not listed in \
+                     `settings.commands.enabled`.",
+                    short
+                ));
+            }
+        }
+        self.disabled
+            .iter()
+            .find(|e| command_entry_matches(e, short))
+            .map(|entry| {
+                format!(
+                    "`/loom-{}` is disabled by policy: `settings.commands.disabled` contains `{}`.",
+                    short,
+                    entry.trim()
+                )
+            })
+    }
+}
+
+/// Whether a `commands.enabled`/`disabled` entry (`secrets`, `loom-secrets`, `@daemon`) covers
+/// the command `short` (name without the `loom-` prefix).
+fn command_entry_matches(entry: &str, short: &str) -> bool {
+    let entry = entry.trim().trim_start_matches('/');
+    match entry.strip_prefix('@') {
+        Some(group) => COMMAND_GROUPS
+            .iter()
+            .any(|(name, members)| *name == group && members.contains(&short)),
+        None => entry.strip_prefix("loom-").unwrap_or(entry) == short,
+    }
+}
+
+impl FormatterSettings {
+    pub(crate) fn locale(&self) -> &str {
+        self.locale.as_deref().unwrap_or("en")
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub(crate) struct DisplaySettings {
+    /// If false, status icons in slash command output become text (`[OK]`, `[FAIL]`, ...) and
+    /// decorative emoji are dropped.
+    pub(crate) icons: Option<bool>,
+}
+
+impl DisplaySettings {
+    pub(crate) fn icons(&self) -> bool {
+        self.icons.unwrap_or(true)
+    }
 }
 
 pub(crate) fn parse_extension_settings(
@@ -225,6 +399,11 @@ pub(crate) const SETTINGS_SCHEMA: &str = r#"{
           "type": ["string", "null"],
           "default": null,
           "description": "Override the exact asset filename to download."
+        },
+        "auto_start_daemon": {
+          "type": "boolean",
+          "default": true,
+          "description": "After a fresh download, run `loom start` if no daemon is running."
         }
       }
     },
@@ -240,7 +419,12 @@ pub(crate) const SETTINGS_SCHEMA: &str = r#"{
         "default_namespace": {
           "type": ["string", "null"],
           "default": null,
-          "description": "Default namespace for agent sessions."
+          "description": "Default namespace for agent sessions and /loom-context recalls."
+        },
+        "log_commands": {
+          "type": "boolean",
+          "default": false,
+          "description": "Record each slash command (name, args summary, outcome) as a session event so session summaries reflect editor activity."
         }
       }
     },
@@ -300,10 +484,86 @@ pub(crate) const SETTINGS_SCHEMA: &str = r#"{
               "type": "boolean",
               "default": false,
               "description": "Expose a potentially expensive diagnostics resource that runs `loom check`."
+            },
+            "memory_limit": {
+              "type": "integer",
+              "minimum": 0,
+              "maximum": 200,
+              "default": 20,
+              "description": "Expose up to N recent agent memory entries as resources. 0 disables."
             }
           }
         }
       }
+    },
+    "commands": {
+      "type": "object",
+      "description": "Slash command behavior.",
+      "properties": {
+        "throttle": {
+          "type": "object",
+          "default": {},
+          "additionalProperties": { "type": "integer", "minimum": 0 },
+          "description": "Minimum seconds between runs per command (e.g. {\"loom-dashboard\": 10}). Re-running sooner shows the previous result instead of hitting the daemon."
+        },
+        "enabled": {
+          "type": ["array", "null"],
+          "items": { "type": "string" },
+          "default": null,
+          "description": "Allowlist of slash commands that may run: names (\"status\" or \"loom-status\") or groups (@daemon, @secrets, @install, @agent, @tools, @config). null allows all. /loom-help always runs."
+        },
+        "disabled": {
+          "type": "array",
+          "items": { "type": "string" },
+          "default": [],
+          "description": "Slash commands or @groups that are refused with a policy message and left out of /loom-help (e.g. [\"@secrets\", \"@daemon\"])."
+        },
+        "benchmark_tool": {
+          "type": "string",
+          "default": "agent_memory_stats",
+          "description": "Lightweight, read-only tool that /loom-benchmark calls when no tool is given."
+        },
+        "aliases": {
+          "type": "object",
+          "default": {},
+          "additionalProperties": { "type": "string" },
+          "description": "Command shortcuts run with /loom-alias <name> (e.g. {\"st\": \"status verbose\"}). These take precedence over aliases saved with /loom-alias <name> = <command>."
+        }
+      }
+    },
+    "formatter": {
+      "type": "object",
+      "description": "Slash command output presentation.",
+      "properties": {
+        "locale": {
+          "type": "string",
+          "default": "en",
+          "description": "Language for section labels and fixed strings. Built in: en, de. Unknown locales use English."
+        },
+        "labels": {
+          "type": "object",
+          "default": {},
+          "additionalProperties": { "type": "string" },
+          "description": "Replacements for individual labels/strings, keyed by the English text (e.g. {\"Errors\": \"Problèmes\"}). Applied on top of the locale."
+        },
+        "redact_patterns": {
+          "type": "array",
+          "default": [],
+          "items": { "type": "string" },
+          "description": "Extra regexes for secrets masked in slash command output, on top of the built-ins (sk-..., ghp_..., AKIA..., AWS_SECRET...=, Bearer ...). If a pattern has a capture group, only the first group is replaced."
+        }
+      }
+    },
+    "display": {
+      "type": "object",
+      "description": "Slash command output appearance.",
+      "properties": {
+        "icons": {
+          "type": "boolean",
+          "default": true,
+          "description": "Show emoji icons. When false, status icons become [OK]/[FAIL]/[WARN] text and decorative emoji are dropped."
+        }
+      }
     }
   }
 }"#;
@@ -313,11 +573,13 @@ pub(crate) const DEFAULT_SETTINGS: &str = r#"{
     "enabled": true,
     "repo": "crb2nu/loom-core",
     "tag": null,
-    "asset": null
+    "asset": null,
+    "auto_start_daemon": true
   },
   "agent": {
     "agent_id": "zed-loom",
-    "default_namespace": null
+    "default_namespace": null,
+    "log_commands": false
   },
   "mcp": {
     "wrapper": {
@@ -331,8 +593,24 @@ pub(crate) const DEFAULT_SETTINGS: &str = r#"{
     },
     "resources": {
       "enabled": true,
-      "include_diagnostics": false
+      "include_diagnostics": false,
+      "memory_limit": 20
     }
+  },
+  "commands": {
+    "throttle": {},
+    "enabled": null,
+    "disabled": [],
+    "benchmark_tool": "agent_memory_stats",
+    "aliases": {}
+  },
+  "formatter": {
+    "locale": "en",
+    "labels": {},
+    "redact_patterns": []
+  },
+  "display": {
+    "icons": true
   }
 }"#;
 
@@ -365,6 +643,7 @@ mod tests {
             repo: None,
             tag: Some("".to_string()),
             asset: None,
+            auto_start_daemon: None,
         };
         // enabled() still defaults to true.
         assert!(s.enabled());
@@ -381,8 +660,10 @@ mod tests {
             repo: None,
             tag: None,
             asset: None,
+            auto_start_daemon: Some(false),
         };
         assert!(!s.enabled());
+        assert!(!s.auto_start_daemon());
     }
 
     #[test]
@@ -405,6 +686,17 @@ mod tests {
         assert_eq!(s.agent.default_namespace.as_deref(), Some("project/main"));
     }
 
+    #[test]
+    fn log_commands_defaults_off() {
+        let s = parse_extension_settings(None);
+        assert!(!s.agent.log_commands());
+
+        let value = zed::serde_json::json!({ "agent": { "log_commands": true } });
+        let s = parse_extension_settings(Some(&value));
+        assert!(s.agent.log_commands());
+        assert_eq!(s.agent.agent_id(), "zed-loom");
+    }
+
     #[test]
     fn prompts_recipes_file_default_none() {
         let s = parse_extension_settings(None);
@@ -417,6 +709,64 @@ mod tests {
         assert!(!s.mcp.resources.include_diagnostics());
     }
 
+    #[test]
+    fn resources_memory_limit() {
+        let s = parse_extension_settings(None);
+        assert_eq!(s.mcp.resources.memory_limit(), 20);
+
+        let value = zed::serde_json::json!({ "mcp": { "resources": { "memory_limit": 0 } } });
+        let s = parse_extension_settings(Some(&value));
+        assert_eq!(s.mcp.resources.memory_limit(), 0);
+    }
+
+    #[test]
+    fn command_policy() {
+        let value = zed::serde_json::json!({
+            "commands": { "disabled": ["@secrets", "loom-stop", "/kill"] }
+        });
+        let s = parse_extension_settings(Some(&value));
+        assert!(s.commands.policy_block("loom-status").is_none());
+        assert!(s
+            .commands
+            .policy_block("loom-secrets")
+            .unwrap()
+            .contains("`@secrets`"));
+        assert!(s.commands.policy_block("loom-stop").is_some());
+        assert!(s.commands.policy_block("loom-kill").is_some());
+
+        let value = zed::serde_json::json!({
+            "commands": { "enabled": ["status", "@tools"], "disabled": ["call"] }
+        });
+        let s = parse_extension_settings(Some(&value));
+        assert!(s.commands.policy_block("loom-status").is_none());
+        assert!(s.commands.policy_block("loom-tools").is_none());
+        assert!(s.commands.policy_block("loom-help").is_none());
+        assert!(s
+            .commands
+            .policy_block("loom-call")
+            .unwrap()
+            .contains("`call`"));
+        assert!(s
+            .commands
+            .policy_block("loom-sync")
+            .unwrap()
+            .contains("commands.enabled"));
+    }
+
+    #[test]
+    fn command_throttle_lookup() {
+        let s = parse_extension_settings(None);
+        assert_eq!(s.commands.throttle_secs("loom-dashboard"), None);
+
+        let value = zed::serde_json::json!({
+            "commands": { "throttle": { "loom-dashboard": 10, "status": 5, "loom-tools": 0 } }
+        });
+        let s = parse_extension_settings(Some(&value));
+        assert_eq!(s.commands.throttle_secs("loom-dashboard"), Some(10));
+        assert_eq!(s.commands.throttle_secs("loom-status"), Some(5));
+        assert_eq!(s.commands.throttle_secs("loom-tools"), None);
+    }
+
     #[test]
     fn parse_prompts_recipes_file() {
         let value = zed::serde_json::json!({
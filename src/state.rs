@@ -0,0 +1,359 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use crate::catalog::{ToolCatalog, ToolSetDiff};
+use crate::commands::RecordedRun;
+use crate::completions::NameSource;
+use crate::download::unix_now_secs;
+use crate::format::{parse_name_list, FormattedOutput};
+
+/// How long live completion names (profiles, ...) are reused before re-listing.
+pub(crate) const NAME_CACHE_TTL_SECS: u64 = 60;
+
+/// Slash command invocations kept for `/loom-history` (oldest are dropped first).
+pub(crate) const HISTORY_CAPACITY: usize = 100;
+
+/// One dispatched slash command, as listed by `/loom-history`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct HistoryEntry {
+    pub(crate) command: String,
+    pub(crate) args: Vec<String>,
+    /// Exit code of the first failing CLI run (else the last run); `None` if nothing ran.
+    pub(crate) exit_code: Option<String>,
+    /// Dispatch error shown to the user, if any.
+    pub(crate) error: Option<String>,
+    pub(crate) started_at: u64,
+    pub(crate) duration_ms: u64,
+}
+
+/// The most recent slash command that failed, kept for `/loom-retry`.
+#[derive(Clone, Debug)]
+pub(crate) struct FailedInvocation {
+    pub(crate) command: String,
+    pub(crate) args: Vec<String>,
+    pub(crate) runs: Vec<RecordedRun>,
+    pub(crate) error: Option<String>,
+}
+
+/// Per-session state shared by slash commands (lives as long as the extension instance).
+#[derive(Default)]
+pub(crate) struct SessionState {
+    /// Raw CLI runs of the most recent invocation of each command, keyed by command name.
+    pub(crate) last_runs: HashMap<String, Vec<RecordedRun>>,
+    /// Name of the most recently dispatched command that ran the CLI.
+    pub(crate) last_command: Option<String>,
+    /// Last observed tool set, used to report tool additions/removals between commands.
+    pub(crate) tool_catalog: Option<ToolCatalog>,
+    /// Names for argument completions with the time they were listed.
+    pub(crate) names: HashMap<NameSource, (Vec<String>, u64)>,
+    /// Server names from the previous `/loom-servers`, to highlight additions/removals.
+    pub(crate) servers_snapshot: Option<BTreeSet<String>>,
+    /// Whether the visible loom binaries were already compared this session.
+    pub(crate) binaries_checked: bool,
+    /// Last output of throttled commands, keyed by command name + args, with when it ran.
+    pub(crate) throttled: HashMap<String, (FormattedOutput, u64)>,
+    /// Recent slash command invocations, oldest first (at most [`HISTORY_CAPACITY`]).
+    pub(crate) history: VecDeque<HistoryEntry>,
+    /// Last failed invocation, cleared once the same command and args succeed.
+    pub(crate) last_failure: Option<FailedInvocation>,
+}
+
+impl SessionState {
+    /// Remember the raw runs of a finished command (commands that ran nothing are skipped).
+    pub(crate) fn record_runs(&mut self, command_name: &str, runs: Vec<RecordedRun>) {
+        if runs.is_empty() || command_name == "loom-last" {
+            return;
+        }
+        let now = unix_now_secs();
+        for run in runs.iter().filter(|r| r.exit_code == "0") {
+            if let Some(source) = NameSource::from_list_args(&run.args) {
+                self.store_names(source, &run.stdout, now);
+            }
+        }
+        self.last_runs.insert(command_name.to_string(), runs);
+        self.last_command = Some(command_name.to_string());
+    }
+
+    /// Append a finished invocation to the history (`/loom-history` itself is not recorded).
+    pub(crate) fn record_history(&mut self, entry: HistoryEntry) {
+        if entry.command == "loom-history" {
+            return;
+        }
+        if self.history.len() == HISTORY_CAPACITY {
+            self.history.pop_front();
+        }
+        self.history.push_back(entry);
+    }
+
+    /// Track the last failed invocation for `/loom-retry`.
+    ///
+    /// A command failed when dispatch returned an error or its final CLI run exited non-zero
+    /// (earlier non-zero runs are usually fallbacks, e.g. a rejected `--json`).
+    pub(crate) fn note_outcome(
+        &mut self,
+        command: &str,
+        args: &[String],
+        runs: &[RecordedRun],
+        error: Option<&str>,
+    ) {
+        if command == "loom-retry" || command == "loom-history" || command == "loom-last" {
+            return;
+        }
+        let failed = error.is_some() || runs.last().is_some_and(|r| r.exit_code != "0");
+        if failed {
+            self.last_failure = Some(FailedInvocation {
+                command: command.to_string(),
+                args: args.to_vec(),
+                runs: runs.to_vec(),
+                error: error.map(str::to_string),
+            });
+        } else if self
+            .last_failure
+            .as_ref()
+            .is_some_and(|f| f.command == command && f.args == args)
+        {
+            self.last_failure = None;
+        }
+    }
+
+    /// Cached completion names for `source`, if listed within [`NAME_CACHE_TTL_SECS`].
+    pub(crate) fn cached_names(&self, source: NameSource, now: u64) -> Option<Vec<String>> {
+        self.names
+            .get(&source)
+            .filter(|(_, at)| now.saturating_sub(*at) < NAME_CACHE_TTL_SECS)
+            .map(|(names, _)| names.clone())
+    }
+
+    /// Parse a list command's output and cache the names for completions.
+    pub(crate) fn store_names(
+        &mut self,
+        source: NameSource,
+        stdout: &str,
+        now: u64,
+    ) -> Vec<String> {
+        let names: Vec<String> = parse_name_list(stdout, source.json_key())
+            .into_iter()
+            .collect();
+        self.names.insert(source, (names.clone(), now));
+        names
+    }
+
+    /// Previous output of `command_name args` and its age, if it ran less than `window_secs` ago.
+    pub(crate) fn throttled_output(
+        &self,
+        command_name: &str,
+        args: &[String],
+        window_secs: u64,
+        now: u64,
+    ) -> Option<(FormattedOutput, u64)> {
+        self.throttled
+            .get(&throttle_key(command_name, args))
+            .map(|(output, at)| (output, now.saturating_sub(*at)))
+            .filter(|(_, age)| *age < window_secs)
+            .map(|(output, age)| (output.clone(), age))
+    }
+
+    /// Remember a throttled command's output for [`Self::throttled_output`].
+    pub(crate) fn store_throttled(
+        &mut self,
+        command_name: &str,
+        args: &[String],
+        output: &FormattedOutput,
+        now: u64,
+    ) {
+        self.throttled
+            .insert(throttle_key(command_name, args), (output.clone(), now));
+    }
+
+    /// Replace the server snapshot; returns `(added, removed)` if it changed since the last one.
+    pub(crate) fn update_servers_snapshot(
+        &mut self,
+        names: BTreeSet<String>,
+    ) -> Option<(Vec<String>, Vec<String>)> {
+        let changes = self
+            .servers_snapshot
+            .as_ref()
+            .map(|prev| diff_names(prev, &names))
+            .filter(|(added, removed)| !added.is_empty() || !removed.is_empty());
+        self.servers_snapshot = Some(names);
+        changes
+    }
+
+    /// Whether the cached tool set is still within its TTL.
+    pub(crate) fn tool_catalog_fresh(&self, now: u64) -> bool {
+        self.tool_catalog
+            .as_ref()
+            .map(|c| c.is_fresh(now))
+            .unwrap_or(false)
+    }
+
+    /// Store a new tool set and return what changed since the previous one (if any was known).
+    pub(crate) fn update_tool_catalog(&mut self, next: ToolCatalog) -> Option<ToolSetDiff> {
+        let diff = self.tool_catalog.as_ref().and_then(|prev| prev.diff(&next));
+        self.tool_catalog = Some(next);
+        diff
+    }
+}
+
+/// Exit code summarizing a command's CLI runs: the first non-zero one, else the last.
+pub(crate) fn runs_exit_code(runs: &[RecordedRun]) -> Option<String> {
+    runs.iter()
+        .find(|r| r.exit_code != "0")
+        .or_else(|| runs.last())
+        .map(|r| r.exit_code.clone())
+}
+
+fn throttle_key(command_name: &str, args: &[String]) -> String {
+    format!("{} {}", command_name, args.join(" "))
+}
+
+/// Names present only in `next` (added) and only in `prev` (removed), sorted.
+pub(crate) fn diff_names(
+    prev: &BTreeSet<String>,
+    next: &BTreeSet<String>,
+) -> (Vec<String>, Vec<String>) {
+    (
+        next.difference(prev).cloned().collect(),
+        prev.difference(next).cloned().collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> RecordedRun {
+        RecordedRun {
+            args: args.iter().map(|s| s.to_string()).collect(),
+            exit_code: "0".into(),
+            stdout: "out".into(),
+            stderr: String::new(),
+        }
+    }
+
+    #[test]
+    fn record_runs_tracks_last_command() {
+        let mut state = SessionState::default();
+        state.record_runs("loom-status", vec![run(&["status"])]);
+        state.record_runs("loom-help", Vec::new());
+        state.record_runs("loom-last", vec![run(&["status"])]);
+        assert_eq!(state.last_command.as_deref(), Some("loom-status"));
+        assert_eq!(state.last_runs.len(), 1);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut state = SessionState::default();
+        let entry = |command: &str, started_at| HistoryEntry {
+            command: command.into(),
+            args: Vec::new(),
+            exit_code: Some("0".into()),
+            error: None,
+            started_at,
+            duration_ms: 5,
+        };
+        state.record_history(entry("loom-history", 0));
+        for i in 0..HISTORY_CAPACITY as u64 + 3 {
+            state.record_history(entry("loom-status", i));
+        }
+        assert_eq!(state.history.len(), HISTORY_CAPACITY);
+        assert_eq!(state.history.front().unwrap().started_at, 3);
+    }
+
+    #[test]
+    fn last_failure_tracks_and_clears() {
+        let mut state = SessionState::default();
+        let mut failed = run(&["status"]);
+        failed.exit_code = "1".into();
+        let args = vec!["verbose".to_string()];
+
+        state.note_outcome(
+            "loom-status",
+            &args,
+            &[run(&["status", "--json"]), failed],
+            None,
+        );
+        assert_eq!(state.last_failure.as_ref().unwrap().command, "loom-status");
+        // Another command succeeding, or a retry, leaves the failure in place.
+        state.note_outcome("loom-tools", &[], &[run(&["tools", "list"])], None);
+        state.note_outcome("loom-retry", &[], &[], Some("boom"));
+        assert_eq!(state.last_failure.as_ref().unwrap().args, args);
+
+        state.note_outcome("loom-status", &args, &[run(&["status"])], None);
+        assert!(state.last_failure.is_none());
+
+        state.note_outcome("loom-call", &[], &[], Some("usage: /loom-call <tool>"));
+        assert!(state.last_failure.as_ref().unwrap().error.is_some());
+    }
+
+    #[test]
+    fn throttled_output_within_window() {
+        let mut state = SessionState::default();
+        let args = vec!["verbose".to_string()];
+        state.store_throttled(
+            "loom-status",
+            &args,
+            &FormattedOutput::plain("cached".into()),
+            100,
+        );
+        let (output, age) = state
+            .throttled_output("loom-status", &args, 10, 104)
+            .unwrap();
+        assert_eq!(output.text, "cached");
+        assert_eq!(age, 4);
+        assert!(state
+            .throttled_output("loom-status", &args, 10, 110)
+            .is_none());
+        assert!(state
+            .throttled_output("loom-status", &[], 10, 104)
+            .is_none());
+    }
+
+    #[test]
+    fn list_runs_refresh_completion_names() {
+        let mut state = SessionState::default();
+        let mut listing = run(&["profile", "list"]);
+        listing.stdout = "* default\n  work\n".into();
+        state.record_runs("loom-profile", vec![listing]);
+        let now = unix_now_secs();
+        assert_eq!(
+            state.cached_names(NameSource::Profiles, now),
+            Some(vec!["default".to_string(), "work".to_string()])
+        );
+        assert_eq!(
+            state.cached_names(NameSource::Profiles, now + NAME_CACHE_TTL_SECS),
+            None
+        );
+    }
+
+    #[test]
+    fn servers_snapshot_reports_changes() {
+        let mut state = SessionState::default();
+        let set = |names: &[&str]| names.iter().map(|s| s.to_string()).collect();
+        assert!(state
+            .update_servers_snapshot(set(&["github", "k8s"]))
+            .is_none());
+        assert!(state
+            .update_servers_snapshot(set(&["github", "k8s"]))
+            .is_none());
+        let (added, removed) = state
+            .update_servers_snapshot(set(&["github", "tavily"]))
+            .unwrap();
+        assert_eq!(added, vec!["tavily"]);
+        assert_eq!(removed, vec!["k8s"]);
+    }
+
+    #[test]
+    fn tool_catalog_diff_between_updates() {
+        let mut state = SessionState::default();
+        let set = |names: &[&str], now| {
+            ToolCatalog::new(names.iter().map(|s| s.to_string()).collect(), now)
+        };
+        assert!(!state.tool_catalog_fresh(0));
+        assert!(state.update_tool_catalog(set(&["a", "b"], 0)).is_none());
+        assert!(state.tool_catalog_fresh(1));
+        assert!(state.update_tool_catalog(set(&["a", "b"], 1)).is_none());
+        let diff = state.update_tool_catalog(set(&["a", "c"], 2)).unwrap();
+        assert_eq!(diff.added, vec!["c"]);
+        assert_eq!(diff.removed, vec!["b"]);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::commands::run_command_capture;
+
+/// Oldest loom-core release the slash commands and context server are tested against.
+pub(crate) const MIN_LOOM_VERSION: &str = "0.7.0";
+
+/// Commands that rely on loom-core features newer than what older CLIs ship.
+const VERSION_SENSITIVE_COMMANDS: &[(&str, &str)] = &[
+    ("context server", "`loom proxy` was added in v0.7.0"),
+    (
+        "/loom-session, /loom-summary, /loom-heartbeat, /loom-task, /loom-agents, /loom-handoff, /loom-inbox, /loom-namespace",
+        "`loom agent ...` subcommands",
+    ),
+    (
+        "/loom-call, /loom-recall, /loom-context, /loom-memory, /loom-forget, /loom-search, /loom-skills, /loom-quota",
+        "`loom tools call`",
+    ),
+    ("/loom-sync <platform>", "`loom sync <platform> --regen`"),
+    ("/loom-sync diff", "`loom sync <platform> --dry-run`"),
+];
+
+/// Result of comparing the resolved CLI against [`MIN_LOOM_VERSION`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum VersionStatus {
+    Supported,
+    Outdated(String),
+    /// The CLI did not report a parseable version; we don't warn on guesses.
+    Unknown,
+}
+
+/// Run `loom version` (falling back to `loom --version`) and return the raw output.
+pub(crate) fn probe_version(program: &str, base_env: &[(String, String)]) -> Option<String> {
+    for flag in ["version", "--version"] {
+        if let Ok(result) = run_command_capture(program, &[flag.into()], base_env, &[]) {
+            if !result.success() {
+                continue;
+            }
+            let out = if result.stdout.trim().is_empty() {
+                result.stderr.trim()
+            } else {
+                result.stdout.trim()
+            };
+            if !out.is_empty() {
+                return Some(out.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Classify raw version output against the minimum supported version.
+pub(crate) fn check_version(raw: Option<&str>) -> VersionStatus {
+    let Some(found) = raw.and_then(parse_version) else {
+        return VersionStatus::Unknown;
+    };
+    let min = parse_version(MIN_LOOM_VERSION).unwrap_or((0, 0, 0));
+    if found < min {
+        VersionStatus::Outdated(format!("{}.{}.{}", found.0, found.1, found.2))
+    } else {
+        VersionStatus::Supported
+    }
+}
+
+/// Extract the first `MAJOR.MINOR[.PATCH]` triple from free-form version output
+/// (e.g. `loom version v0.9.1 (abc123)`).
+pub(crate) fn parse_version(s: &str) -> Option<(u64, u64, u64)> {
+    s.split(|c: char| c.is_whitespace() || c == ',' || c == '(' || c == ')')
+        .map(|tok| tok.trim_start_matches(['v', 'V']))
+        .find_map(|tok| {
+            let core = tok.split(['-', '+']).next()?;
+            let mut parts = core.split('.');
+            let major = parts.next()?.parse().ok()?;
+            let minor = parts.next()?.parse().ok()?;
+            let patch = parts.next().map(|p| p.parse().ok()).unwrap_or(Some(0))?;
+            Some((major, minor, patch))
+        })
+}
+
+/// Markdown warning prepended to slash command output when the CLI is too old.
+pub(crate) fn outdated_warning(found: &str) -> String {
+    let mut text = format!(
+        "> ⚠️ **loom-core v{} is older than the minimum supported v{}.**\n>\n\
+         > These commands may fail or misbehave:\n",
+        found, MIN_LOOM_VERSION
+    );
+    for (commands, reason) in VERSION_SENSITIVE_COMMANDS {
+        text.push_str(&format!("> - {} — {}\n", commands, reason));
+    }
+    text.push_str(
+        ">\n> To fix: run `/loom-update` to upgrade the managed install, or upgrade loom-core \
+         yourself; if `command.path` points at an old binary, remove it so the extension's \
+         download is used.\n\n",
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_variants() {
+        assert_eq!(parse_version("loom version v0.9.1 (abc)"), Some((0, 9, 1)));
+        assert_eq!(parse_version("0.7.0"), Some((0, 7, 0)));
+        assert_eq!(parse_version("loom 1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("v0.8.0-rc.1"), Some((0, 8, 0)));
+        assert_eq!(parse_version("dev build"), None);
+    }
+
+    #[test]
+    fn check_version_classifies() {
+        assert_eq!(
+            check_version(Some("loom v0.6.2")),
+            VersionStatus::Outdated("0.6.2".into())
+        );
+        assert_eq!(check_version(Some("loom v0.7.0")), VersionStatus::Supported);
+        assert_eq!(check_version(Some("loom v1.0.0")), VersionStatus::Supported);
+        assert_eq!(check_version(Some("unknown")), VersionStatus::Unknown);
+        assert_eq!(check_version(None), VersionStatus::Unknown);
+    }
+
+    #[test]
+    fn outdated_warning_mentions_versions() {
+        let w = outdated_warning("0.6.2");
+        assert!(w.contains("v0.6.2"));
+        assert!(w.contains(MIN_LOOM_VERSION));
+        assert!(w.contains("/loom-session"));
+        assert!(w.contains("/loom-update"));
+    }
+}
@@ -0,0 +1,255 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use zed_extension_api::serde_json::Value;
+
+/// Largest file an `@file:path` value in a `/loom-call` payload may pull in.
+pub(crate) const MAX_FILE_REF_BYTES: usize = 64 * 1024;
+
+/// Marks a payload string as a worktree file reference (`"@file:src/main.rs"`).
+const FILE_REF_PREFIX: &str = "@file:";
+
+/// Write `contents` to `rel` under the worktree root, creating parent directories.
+///
+/// Returns the absolute path written.
+pub(crate) fn write_file(root: &str, rel: &str, contents: &str) -> Result<PathBuf, String> {
+    let path = Path::new(root).join(rel);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&path, contents).map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Replace `"@file:path"` string values anywhere in a tool payload with the worktree file's
+/// contents.
+///
+/// Only the explicit prefix counts, so values like `"@types/node"` or `"@octocat"` are sent as
+/// written, and a missing file is an error rather than a silent literal. `"@@file:..."` escapes a
+/// literal `@file:`. `read` receives the validated relative path. Returns the paths that were
+/// expanded.
+pub(crate) fn expand_file_refs(
+    value: &mut Value,
+    read: &mut dyn FnMut(&str) -> Result<String, String>,
+) -> Result<Vec<String>, String> {
+    let mut expanded = Vec::new();
+    expand_value(value, read, &mut expanded)?;
+    Ok(expanded)
+}
+
+fn expand_value(
+    value: &mut Value,
+    read: &mut dyn FnMut(&str) -> Result<String, String>,
+    expanded: &mut Vec<String>,
+) -> Result<(), String> {
+    match value {
+        Value::String(s) => {
+            if s.starts_with("@@file:") {
+                s.remove(0);
+                return Ok(());
+            }
+            let Some(rel) = s.strip_prefix(FILE_REF_PREFIX) else {
+                return Ok(());
+            };
+            let rel = relative_path(rel)?;
+            let contents = read(&rel)?;
+            if contents.len() > MAX_FILE_REF_BYTES {
+                return Err(format!(
+                    "`@file:{}` is {} KiB; files referenced in a payload are limited to {} KiB",
+                    rel,
+                    contents.len().div_ceil(1024),
+                    MAX_FILE_REF_BYTES / 1024
+                ));
+            }
+            *s = contents;
+            expanded.push(rel);
+        }
+        Value::Array(items) => {
+            for item in items {
+                expand_value(item, read, expanded)?;
+            }
+        }
+        Value::Object(fields) => {
+            for field in fields.values_mut() {
+                expand_value(field, read, expanded)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Validate a user-supplied path so it stays inside the worktree (relative, no `..`).
+pub(crate) fn relative_path(rel: &str) -> Result<String, String> {
+    let rel = rel.trim();
+    let path = Path::new(rel);
+    if rel.is_empty()
+        || path.is_absolute()
+        || rel.starts_with(['/', '\\'])
+        || path
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        return Err(format!(
+            "`{}` must be a relative path inside the worktree",
+            rel
+        ));
+    }
+    Ok(rel.to_string())
+}
+
+/// Turn an arbitrary name (tool, server, ...) into a safe single-segment file stem.
+pub(crate) fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let stem = stem.trim_matches('.').to_string();
+    if stem.is_empty() {
+        "_".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Lowercase, dash-separated slug for file names (`"Auth flow: v2"` → `auth-flow-v2`).
+pub(crate) fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+        if slug.len() >= 60 {
+            break;
+        }
+    }
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+/// `YYYY-MM-DD` (UTC) for a Unix timestamp, for headers in generated files.
+pub(crate) fn utc_date(unix_secs: u64) -> String {
+    // Civil-from-days (Howard Hinnant), valid for all dates after 1970.
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days / 146_097;
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// `YYYYMMDD-HHMMSS` (UTC) for a Unix timestamp, for file names that sort by time.
+pub(crate) fn utc_stamp(unix_secs: u64) -> String {
+    let secs = unix_secs % 86_400;
+    format!(
+        "{}-{:02}{:02}{:02}",
+        utc_date(unix_secs).replace('-', ""),
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_stem_sanitizes() {
+        assert_eq!(file_stem("github__search_code"), "github__search_code");
+        assert_eq!(file_stem("k8s/pods list"), "k8s_pods_list");
+        assert_eq!(file_stem("../etc"), "_etc");
+        assert_eq!(file_stem(""), "_");
+    }
+
+    #[test]
+    fn relative_path_stays_inside() {
+        assert_eq!(relative_path(" docs/report.md ").unwrap(), "docs/report.md");
+        assert!(relative_path("/tmp/report.md").is_err());
+        assert!(relative_path("../report.md").is_err());
+        assert!(relative_path("docs/../../x.md").is_err());
+        assert!(relative_path("").is_err());
+    }
+
+    #[test]
+    fn file_refs_expand_in_nested_values() {
+        use zed_extension_api::serde_json::json;
+
+        let mut payload = json!({
+            "content": "@file:src/main.rs",
+            "files": ["@file:Makefile", "@@file:literal", "@@handle"],
+            "user": "@octocat",
+            "pkg": "@types/node",
+            "login": "@user.name",
+            "note": "email me @ home"
+        });
+        let mut read = |rel: &str| match rel {
+            "src/main.rs" => Ok("fn main() {}".to_string()),
+            "Makefile" => Ok("all:".to_string()),
+            _ => Err(format!("failed to read {}", rel)),
+        };
+        let expanded = expand_file_refs(&mut payload, &mut read).unwrap();
+        assert_eq!(expanded, vec!["src/main.rs", "Makefile"]);
+        assert_eq!(payload["content"], "fn main() {}");
+        assert_eq!(
+            payload["files"],
+            json!(["all:", "@file:literal", "@@handle"])
+        );
+        assert_eq!(payload["user"], "@octocat");
+        assert_eq!(payload["pkg"], "@types/node");
+        assert_eq!(payload["login"], "@user.name");
+
+        let mut typo = json!({"c": "@file:src/mian.rs"});
+        assert!(expand_file_refs(&mut typo, &mut read).is_err());
+        let mut escape = json!({"c": "@file:../secret.txt"});
+        assert!(expand_file_refs(&mut escape, &mut read).is_err());
+        let mut big = |_: &str| Ok("x".repeat(MAX_FILE_REF_BYTES + 1));
+        let err = expand_file_refs(&mut json!("@file:big.log"), &mut big).unwrap_err();
+        assert!(err.contains("limited to 64 KiB"));
+    }
+
+    #[test]
+    fn slugify_queries() {
+        assert_eq!(slugify("Auth flow: v2"), "auth-flow-v2");
+        assert_eq!(slugify("  --  "), "untitled");
+        assert!(slugify(&"x".repeat(200)).len() <= 60);
+    }
+
+    #[test]
+    fn utc_dates() {
+        assert_eq!(utc_date(0), "1970-01-01");
+        assert_eq!(utc_date(951_782_400), "2000-02-29");
+        assert_eq!(utc_date(1_767_225_600), "2026-01-01");
+        assert_eq!(utc_stamp(1_767_225_600 + 3_723), "20260101-010203");
+    }
+
+    #[test]
+    fn write_file_creates_parents() {
+        let root = std::env::temp_dir().join(format!("loom-zed-wt-{}", std::process::id()));
+        let root_str = root.to_string_lossy().to_string();
+        let path = write_file(&root_str, ".loom/tools/a.json", "{}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+        assert_eq!(
+            fs::read_to_string(root.join(".loom/tools/a.json")).unwrap(),
+            "{}"
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+}